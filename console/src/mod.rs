@@ -8,7 +8,6 @@ extern crate blake2_rfc;
 extern crate rand;
 extern crate serde;
 extern crate rabe;
-extern crate deflate;
 extern crate inflate;
 extern crate serde_json;
 extern crate serde_derive;
@@ -16,7 +15,7 @@ extern crate serde_derive;
 extern crate clap;
 extern crate serde_cbor;
 
-use base64::{decode, encode};
+use base64::decode;
 use clap::{App, Arg, ArgMatches, SubCommand};
 use crate::rabe::{
     RabeError,
@@ -27,21 +26,37 @@ use crate::rabe::{
         bsw,
         lsw,
         mke08,
-        yct14
+        yct14,
+        CpAbe,
+        KpAbe,
+        MultiAuthorityAbe,
+        ac17::{Ac17Cp, Ac17Kp},
+        aw11::Aw11,
+        bdabe::Bdabe,
+        bsw::Bsw,
+        lsw::Lsw,
+        mke08::Mke08,
+        yct14::Yct14,
+        SchemeId,
+        registry::{Encoded, Registry}
     },
     utils::{
+        aes::{AeadSuite, stream},
         policy::pest::PolicyLanguage,
-        file::{write_file, read_file, read_raw, write_from_vec, read_to_vec}
+        armor::{armor, dearmor},
+        file::{write_file, read_file, read_raw, write_from_vec, read_to_vec},
+        json::JsonFormat,
+        wire::WireFormat
     }
 };
-use serde::Serialize;
-use serde_cbor::{
-    from_slice,
-    ser::to_vec_packed
-};
+use serde::de::DeserializeOwned;
+use serde_cbor::from_slice;
 use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read},
     process,
-    path::Path
+    path::Path,
+    str::FromStr
 };
 
 // File extensions
@@ -58,6 +73,8 @@ const SCHEME: &'static str = "scheme";
 const JSON: &'static str = "json";
 const LANG: &'static str = "lang";
 const FILE: &'static str = "file";
+const AEAD: &'static str = "aead";
+const STREAM: &'static str = "stream";
 
 // Default file names
 const GP_FILE: &'static str = "gp";
@@ -69,26 +86,6 @@ const PKA_FILE: &'static str = "pka";
 const AU_PK_FILE: &'static str = "pkau";
 const AU_SK_FILE: &'static str = "skau";
 
-// Key file header and footer
-const GP_BEGIN: &'static str = "-----BEGIN GLOBAL PARAMETERS-----\n";
-const GP_END: &'static str = "\n-----END GLOBAL PARAMETERS-----";
-const SK_BEGIN: &'static str = "-----BEGIN SECRET KEY-----\n";
-const SK_END: &'static str = "\n-----END SECRET KEY-----";
-const MSK_BEGIN: &'static str = "-----BEGIN MASTER SECRET KEY-----\n";
-const MSK_END: &'static str = "\n-----END MASTER SECRET KEY-----";
-const PK_BEGIN: &'static str = "-----BEGIN PUBLIC KEY-----\n";
-const PK_END: &'static str = "\n-----END PUBLIC KEY-----";
-const CT_BEGIN: &'static str = "-----BEGIN CIPHERTEXT-----\n";
-const CT_END: &'static str = "\n-----END CIPHERTEXT-----";
-const SKA_BEGIN: &'static str = "-----BEGIN SECRET ATTRIBUTE KEY-----\n";
-const SKA_END: &'static str = "\n-----END SECRET ATTRIBUTE KEY-----";
-const PKA_BEGIN: &'static str = "-----BEGIN PUBLIC ATTRIBUTE-----\n";
-const PKA_END: &'static str = "\n-----END PUBLIC ATTRIBUTE-----";
-const AU_PK_BEGIN: &'static str = "-----BEGIN PUBLIC AUTHORITY-----\n";
-const AU_PK_END: &'static str = "\n-----END PUBLIC AUTHORITY-----";
-const AU_SK_BEGIN: &'static str = "-----BEGIN SECRET AUTHORITY-----\n";
-const AU_SK_END: &'static str = "\n-----END SECRET AUTHORITY-----";
-
 // Application commands
 const CMD_SETUP: &'static str = "setup";
 const CMD_AUTHGEN: &'static str = "authgen";
@@ -98,6 +95,7 @@ const CMD_ENCRYPT: &'static str = "encrypt";
 const CMD_DECRYPT: &'static str = "decrypt";
 const CMD_REQ_ATTR_PK: &'static str = "req-attr-pk";
 const CMD_REQ_ATTR_SK: &'static str = "req-attr-sk";
+const CMD_EXPLAIN: &'static str = "explain";
 
 fn main() {
     arg_enum! {
@@ -132,15 +130,15 @@ fn main() {
 
     let _abe_app = App::new(crate_name!())
         .version(crate_version!())
-        .author(crate_authors!("\n"))
+        .author(env!("CARGO_PKG_AUTHORS"))
         .about(crate_description!())
         .arg(
             Arg::with_name(SCHEME)
                 .long(SCHEME)
-                .required(true)
+                .required(false)
                 .takes_value(true)
                 .possible_values(&Scheme::variants())
-                .help("scheme(s) to use."),
+                .help("scheme(s) to use. decrypt and explain read it from the key if it is not given."),
         )
         .arg(
             Arg::with_name(JSON)
@@ -223,6 +221,7 @@ fn main() {
                 )
                 .arg(
                     Arg::with_name(POLICY)
+                        .long(POLICY)
                         .required(false)
                         .takes_value(true)
                         .help("policy to use."),
@@ -284,6 +283,7 @@ fn main() {
                 )
                 .arg(
                     Arg::with_name(POLICY)
+                        .long(POLICY)
                         .required(false)
                         .takes_value(true)
                         .help("policy to use."),
@@ -351,10 +351,28 @@ fn main() {
                 )
                 .arg(
                     Arg::with_name(POLICY)
+                        .long(POLICY)
                         .required(false)
                         .takes_value(true)
                         .help("the policy to use."),
                 )
+                .arg(
+                    Arg::with_name(AEAD)
+                        .long(AEAD)
+                        .required(false)
+                        .takes_value(true)
+                        .possible_values(&["AES-256-CCM", "AES-256-GCM", "CHACHA20-POLY1305"])
+                        .case_insensitive(true)
+                        .default_value("AES-256-CCM")
+                        .help("the AEAD that encrypts the file."),
+                )
+                .arg(
+                    Arg::with_name(STREAM)
+                        .long(STREAM)
+                        .required(false)
+                        .takes_value(false)
+                        .help("encrypts the file in chunks in constant memory."),
+                )
                 .arg(
                     Arg::with_name(FILE)
                         .required(true)
@@ -462,50 +480,72 @@ fn main() {
                         .help("attribute to use."),
                 ),
         )
+        .subcommand(
+            // Explain
+            SubCommand::with_name(CMD_EXPLAIN)
+                .about("explains why a key can or cannot decrypt a file.")
+                .arg(
+                    Arg::with_name(SK_FILE)
+                        .required(false)
+                        .takes_value(true)
+                        .default_value(&_sk_default)
+                        .help("user key file."),
+                )
+                .arg(
+                    Arg::with_name(FILE)
+                        .required(true)
+                        .takes_value(true)
+                        .help("encrypted file to use."),
+                ),
+        )
         .get_matches();
 
     if let Err(e) = run(_abe_app) {
         println!("Application error: {}", e);
+        if let Some(_policy_error) = e.policy_parse_error() {
+            println!("{}", _policy_error.snippet());
+        }
         process::exit(1);
     }
 
     fn run(argument_matches: ArgMatches) -> Result<(), RabeError> {
-        if let Some(_s) = argument_matches.value_of(SCHEME) {
-            let _scheme = match _s {
-                "AC17CP" => Scheme::AC17CP,
-                "AC17KP" => Scheme::AC17KP,
-                "AW11" => Scheme::AW11,
-                "BDABE" => Scheme::BDABE,
-                "BSW" => Scheme::BSW,
-                "LSW" => Scheme::LSW,
-                "MKE08" => Scheme::MKE08,
-                "YCT14" => Scheme::YCT14,
-                _ => Scheme::BSW // this should not happen at all
+        let _scheme = argument_matches.value_of(SCHEME).map(|_s| match _s {
+            "AC17CP" => Scheme::AC17CP,
+            "AC17KP" => Scheme::AC17KP,
+            "AW11" => Scheme::AW11,
+            "BDABE" => Scheme::BDABE,
+            "BSW" => Scheme::BSW,
+            "LSW" => Scheme::LSW,
+            "MKE08" => Scheme::MKE08,
+            "YCT14" => Scheme::YCT14,
+            _ => Scheme::BSW // this should not happen at all
+        });
+        let mut _lang;
+        if let Some(_l) = argument_matches.value_of(LANG) {
+            _lang = match _l {
+                "json" => PolicyLanguage::JsonPolicy,
+                _ => PolicyLanguage::HumanPolicy,
             };
-            let mut _lang;
-            if let Some(_l) = argument_matches.value_of(LANG) {
-                _lang = match _l {
-                    "json" => PolicyLanguage::JsonPolicy,
-                    _ => PolicyLanguage::HumanPolicy,
-                };
-            } else {
-                _lang = PolicyLanguage::HumanPolicy;
-            }
-            let _json: bool = argument_matches.is_present(JSON);
-            match argument_matches.subcommand() {
-                (CMD_SETUP, Some(arguments)) => run_setup(arguments, _scheme, _json),
-                (CMD_AUTHGEN, Some(arguments)) => run_authgen(arguments, _scheme, _lang, _json),
-                (CMD_KEYGEN, Some(arguments)) => run_keygen(arguments, _scheme, _lang, _json),
-                (CMD_DELEGATE, Some(arguments)) => run_delegate(arguments, _scheme, _lang, _json),
-                (CMD_ENCRYPT, Some(arguments)) => run_encrypt(arguments, _scheme, _lang, _json),
-                (CMD_DECRYPT, Some(arguments)) => run_decrypt(arguments, _scheme, _lang, _json),
-                (CMD_REQ_ATTR_PK, Some(arguments)) => run_req_attr_pk(arguments, _scheme, _lang, _json),
-                (CMD_REQ_ATTR_SK, Some(arguments)) => run_req_attr_sk(arguments, _scheme, _lang, _json),
-                _ => Ok(()),
-            }
         } else {
-            println!("Application error: Scheme missing");
-            process::exit(1);
+            _lang = PolicyLanguage::HumanPolicy;
+        }
+        let _json: bool = argument_matches.is_present(JSON);
+        match (argument_matches.subcommand(), _scheme) {
+            // decrypt and explain read the scheme from the key if it is not given
+            ((CMD_DECRYPT, Some(arguments)), _scheme) => run_decrypt(arguments, _scheme, _lang, _json),
+            ((CMD_EXPLAIN, Some(arguments)), _scheme) => run_explain(arguments, _scheme, _json),
+            (_, None) => {
+                println!("Application error: Scheme missing");
+                process::exit(1);
+            }
+            ((CMD_SETUP, Some(arguments)), Some(_scheme)) => run_setup(arguments, _scheme, _json),
+            ((CMD_AUTHGEN, Some(arguments)), Some(_scheme)) => run_authgen(arguments, _scheme, _lang, _json),
+            ((CMD_KEYGEN, Some(arguments)), Some(_scheme)) => run_keygen(arguments, _scheme, _lang, _json),
+            ((CMD_DELEGATE, Some(arguments)), Some(_scheme)) => run_delegate(arguments, _scheme, _lang, _json),
+            ((CMD_ENCRYPT, Some(arguments)), Some(_scheme)) => run_encrypt(arguments, _scheme, _lang, _json),
+            ((CMD_REQ_ATTR_PK, Some(arguments)), Some(_scheme)) => run_req_attr_pk(arguments, _scheme, _lang, _json),
+            ((CMD_REQ_ATTR_SK, Some(arguments)), Some(_scheme)) => run_req_attr_sk(arguments, _scheme, _lang, _json),
+            _ => Ok(()),
         }
     }
 
@@ -543,20 +583,20 @@ fn main() {
                 if _json {
                     write_file(
                         Path::new(&_msk_file),
-                        serde_json::to_string_pretty(&_msk).unwrap(),
+                        json_enc(&_msk),
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        serde_json::to_string_pretty(&_pk).unwrap(),
+                        json_enc(&_pk),
                     );
                 } else {
                     write_file(
                         Path::new(&_msk_file),
-                        ser_enc(_msk, MSK_BEGIN, MSK_END)
+                        ser_enc(&_msk)
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        ser_enc(_pk, PK_BEGIN, PK_END)
+                        ser_enc(&_pk)
                     );
                 }
             }
@@ -565,12 +605,12 @@ fn main() {
                 if _json {
                     write_file(
                         Path::new(&_gp_file),
-                        serde_json::to_string_pretty(&_gp).unwrap(),
+                        json_enc(&_gp),
                     );
                 } else {
                     write_file(
                         Path::new(&_msk_file),
-                        ser_enc(_gp, GP_BEGIN, GP_END)
+                        ser_enc(&_gp)
                     );
                 }
             }
@@ -579,20 +619,20 @@ fn main() {
                 if _json {
                     write_file(
                         Path::new(&_msk_file),
-                        serde_json::to_string_pretty(&_msk).unwrap(),
+                        json_enc(&_msk),
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        serde_json::to_string_pretty(&_pk).unwrap(),
+                        json_enc(&_pk),
                     );
                 } else {
                     write_file(
                         Path::new(&_msk_file),
-                        ser_enc(_msk, MSK_BEGIN, MSK_END)
+                        ser_enc(&_msk)
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        ser_enc(_pk, PK_BEGIN, PK_END)
+                        ser_enc(&_pk)
                     );
                 }
             }
@@ -601,20 +641,20 @@ fn main() {
                 if _json {
                     write_file(
                         Path::new(&_msk_file),
-                        serde_json::to_string_pretty(&_msk).unwrap(),
+                        json_enc(&_msk),
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        serde_json::to_string_pretty(&_pk).unwrap(),
+                        json_enc(&_pk),
                     );
                 } else {
                     write_file(
                         Path::new(&_msk_file),
-                        ser_enc(_msk, MSK_BEGIN, MSK_END)
+                        ser_enc(&_msk)
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        ser_enc(_pk, PK_BEGIN, PK_END)
+                        ser_enc(&_pk)
                     );
                 }
             }
@@ -623,20 +663,20 @@ fn main() {
                 if _json {
                     write_file(
                         Path::new(&_msk_file),
-                        serde_json::to_string_pretty(&_msk).unwrap(),
+                        json_enc(&_msk),
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        serde_json::to_string_pretty(&_pk).unwrap(),
+                        json_enc(&_pk),
                     );
                 } else {
                     write_file(
                         Path::new(&_msk_file),
-                        ser_enc(_msk, MSK_BEGIN, MSK_END)
+                        ser_enc(&_msk)
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        ser_enc(_pk, PK_BEGIN, PK_END)
+                        ser_enc(&_pk)
                     );
                 }
             }
@@ -645,20 +685,20 @@ fn main() {
                 if _json {
                     write_file(
                         Path::new(&_msk_file),
-                        serde_json::to_string_pretty(&_msk).unwrap(),
+                        json_enc(&_msk),
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        serde_json::to_string_pretty(&_pk).unwrap(),
+                        json_enc(&_pk),
                     );
                 } else {
                     write_file(
                         Path::new(&_msk_file),
-                        ser_enc(_msk, MSK_BEGIN, MSK_END)
+                        ser_enc(&_msk)
                     );
                     write_file(
                         Path::new(&_pk_file),
-                        ser_enc(_pk, PK_BEGIN, PK_END)
+                        ser_enc(&_pk)
                     );
                 }
             },
@@ -676,24 +716,24 @@ fn main() {
                     }
                 }
                 if _attributes.len() > 0 {
-                    let (_pk, _msk) = yct14::setup(_attributes);
+                    let (_pk, _msk) = yct14::try_setup(_attributes)?;
                     if _json {
                         write_file(
                             Path::new(&_msk_file),
-                            serde_json::to_string_pretty(&_msk).unwrap(),
+                            json_enc(&_msk),
                         );
                         write_file(
                             Path::new(&_pk_file),
-                            serde_json::to_string_pretty(&_pk).unwrap(),
+                            json_enc(&_pk),
                         );
                     } else {
                         write_file(
                             Path::new(&_msk_file),
-                            ser_enc(_msk, MSK_BEGIN, MSK_END)
+                            ser_enc(&_msk)
                         );
                         write_file(
                             Path::new(&_pk_file),
-                            ser_enc(_pk, PK_BEGIN, PK_END)
+                            ser_enc(&_pk)
                         );
                     }
                 }
@@ -770,40 +810,32 @@ fn main() {
             Scheme::AW11 => {
                 let mut _gp: aw11::Aw11GlobalKey;
                 if _json {
-                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
+                    _gp = json_dec(&_gp_file)?;
                 } else {
-                    _gp = match ser_dec(&_gp_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _gp = ser_dec(&_gp_file)?;
                 }
                 match aw11::authgen(&_gp, &_attributes) {
-                    None => {
-                        return Err(RabeError::new(
-                            "sorry, could not generate authority. The attribute set empty.",
-                        ));
+                    Err(e) => {
+                        return Err(e);
                     }
-                    Some((_pk, _msk)) => {
+                    Ok((_pk, _msk)) => {
                         if _json {
                             write_file(
                                 Path::new(&_msk_file),
-                                serde_json::to_string_pretty(&_msk).unwrap(),
+                                json_enc(&_msk),
                             );
                             write_file(
                                 Path::new(&_pk_file),
-                                serde_json::to_string_pretty(&_pk).unwrap(),
+                                json_enc(&_pk),
                             );
                         } else {
                             write_file(
                                 Path::new(&_msk_file),
-                                ser_enc(_msk, AU_SK_BEGIN, AU_SK_END)
+                                ser_enc(&_msk)
                             );
                             write_file(
                                 Path::new(&_pk_file),
-                                ser_enc(_pk, AU_PK_BEGIN, AU_PK_END)
+                                ser_enc(&_pk)
                             );
                         }
                     }
@@ -813,35 +845,23 @@ fn main() {
                 let mut _pk: bdabe::BdabePublicKey;
                 let mut _msk: bdabe::BdabeMasterKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_pk_file)?;
+                    _msk = ser_dec(&_msk_file)?;
                 }
                 let _sk: bdabe::BdabeSecretAuthorityKey =
                     bdabe::authgen(&_pk, &_msk, &_name);
                 if _json {
                     write_file(
                         Path::new(&_au_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_au_file),
-                        ser_enc(_sk, AU_SK_BEGIN, AU_SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             }
@@ -849,34 +869,22 @@ fn main() {
                 let mut _pk: mke08::Mke08PublicKey;
                 let mut _msk: mke08::Mke08MasterKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_pk_file)?;
+                    _msk = ser_dec(&_msk_file)?;
                 }
                 let _sk: mke08::Mke08SecretAuthorityKey = mke08::authgen(&_name);
                 if _json {
                     write_file(
                         Path::new(&_au_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_au_file),
-                        ser_enc(_sk, AU_SK_BEGIN, AU_SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             },
@@ -972,53 +980,40 @@ fn main() {
             Scheme::AC17CP => {
                 let mut _msk: ac17::Ac17MasterKey;
                 if _json {
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                }
-                let _sk: ac17::Ac17CpSecretKey = ac17::cp_keygen(&_msk, &_attributes)
-                    .unwrap();
+                    _msk = ser_dec(&_msk_file)?;
+                }
+                let _sk: ac17::Ac17CpSecretKey = ac17::cp_keygen(&_msk, &_attributes)?;
                 if _json {
                     write_file(
                         Path::new(&_sk_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_sk_file),
-                        ser_enc(_sk, SK_BEGIN, SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             }
             Scheme::AC17KP => {
                 let mut _msk: ac17::Ac17MasterKey;
                 if _json {
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                }
-                let _sk: ac17::Ac17KpSecretKey = ac17::kp_keygen(&_msk, &_policy, _lang).unwrap();
+                    _msk = ser_dec(&_msk_file)?;
+                }
+                let _sk: ac17::Ac17KpSecretKey = ac17::kp_keygen(&_msk, &_policy, _lang)?;
                 if _json {
                     write_file(
                         Path::new(&_sk_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_sk_file),
-                        ser_enc(_sk, SK_BEGIN, SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             }
@@ -1026,35 +1021,22 @@ fn main() {
                 let mut _pk: bsw::CpAbePublicKey;
                 let mut _msk: bsw::CpAbeMasterKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                }
-                let _sk: bsw::CpAbeSecretKey = bsw::keygen(&_pk, &_msk, &_attributes)
-                    .unwrap();
+                    _pk = ser_dec(&_pk_file)?;
+                    _msk = ser_dec(&_msk_file)?;
+                }
+                let _sk: bsw::CpAbeSecretKey = bsw::keygen(&_pk, &_msk, &_attributes)?;
                 if _json {
                     write_file(
                         Path::new(&_sk_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_sk_file),
-                        ser_enc(_sk, SK_BEGIN, SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             }
@@ -1062,34 +1044,22 @@ fn main() {
                 let mut _pk: lsw::KpAbePublicKey;
                 let mut _msk: lsw::KpAbeMasterKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                }
-                let _sk: lsw::KpAbeSecretKey = lsw::keygen(&_pk, &_msk, &_policy, _lang).unwrap();
+                    _pk = ser_dec(&_pk_file)?;
+                    _msk = ser_dec(&_msk_file)?;
+                }
+                let _sk: lsw::KpAbeSecretKey = lsw::keygen(&_pk, &_msk, &_policy, _lang)?;
                 if _json {
                     write_file(
                         Path::new(&_sk_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_sk_file),
-                        ser_enc(_sk, SK_BEGIN, SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             }
@@ -1097,35 +1067,23 @@ fn main() {
                 let mut _pk: aw11::Aw11GlobalKey;
                 let mut _msk: aw11::Aw11MasterKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = json_dec(&_gp_file)?;
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _pk = match ser_dec(&_gp_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_gp_file)?;
+                    _msk = ser_dec(&_msk_file)?;
                 }
                 let _sk: aw11::Aw11SecretKey =
                     aw11::keygen(&_pk, &_msk, &_name, &_attributes).unwrap();
                 if _json {
                     write_file(
                         Path::new(&_name_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_name_file),
-                        ser_enc(_sk, SK_BEGIN, SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             }
@@ -1133,34 +1091,22 @@ fn main() {
                 let mut _pk: bdabe::BdabePublicKey;
                 let mut _msk: bdabe::BdabeSecretAuthorityKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_ska_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _msk = json_dec(&_ska_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_ska_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_pk_file)?;
+                    _msk = ser_dec(&_ska_file)?;
                 }
                 let _sk: bdabe::BdabeUserKey = bdabe::keygen(&_pk, &_msk, &_name);
                 if _json {
                     write_file(
                         Path::new(&_name_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_name_file),
-                        ser_enc(_sk, SK_BEGIN, SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             }
@@ -1168,35 +1114,23 @@ fn main() {
                 let mut _pk: mke08::Mke08PublicKey;
                 let mut _msk: mke08::Mke08MasterKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_pk_file)?;
+                    _msk = ser_dec(&_msk_file)?;
                 }
                 if _name != String::from("") {
                     let _sk: mke08::Mke08UserKey = mke08::keygen(&_pk, &_msk, &_name);
                     if _json {
                         write_file(
                             Path::new(&_name_file),
-                            serde_json::to_string_pretty(&_sk).unwrap(),
+                            json_enc(&_sk),
                         );
                     } else {
                         write_file(
                             Path::new(&_name_file),
-                            ser_enc(_sk, SK_BEGIN, SK_END)
+                            ser_enc(&_sk)
                         );
                     }
                 } else {
@@ -1209,34 +1143,22 @@ fn main() {
                 let mut _pk: yct14::Yct14AbePublicKey;
                 let mut _msk: yct14::Yct14AbeMasterKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _msk = json_dec(&_msk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_msk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                }
-                let _sk: yct14::Yct14AbeSecretKey = yct14::keygen(&_pk, &_msk, &_policy, _lang).unwrap();
+                    _pk = ser_dec(&_pk_file)?;
+                    _msk = ser_dec(&_msk_file)?;
+                }
+                let _sk: yct14::Yct14AbeSecretKey = yct14::keygen(&_pk, &_msk, &_policy, _lang)?;
                 if _json {
                     write_file(
                         Path::new(&_sk_file),
-                        serde_json::to_string_pretty(&_sk).unwrap(),
+                        json_enc(&_sk),
                     );
                 } else {
                     write_file(
                         Path::new(&_sk_file),
-                        ser_enc(_sk, SK_BEGIN, SK_END)
+                        ser_enc(&_sk)
                     );
                 }
             }
@@ -1296,42 +1218,28 @@ fn main() {
                 let mut _pk: bsw::CpAbePublicKey;
                 let mut _msk: bsw::CpAbeSecretKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _msk = json_dec(&_sk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _msk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                }
-                let _sk: Option<bsw::CpAbeSecretKey> =
+                    _pk = ser_dec(&_pk_file)?;
+                    _msk = ser_dec(&_sk_file)?;
+                }
+                let _sk: Result<bsw::CpAbeSecretKey, RabeError> =
                     bsw::delegate(&_pk, &_msk, &_attributes);
                 match _sk {
-                    None => {
-                        return Err(RabeError::new(
-                            "sorry, could not delegate attributes. The given attributes are not a subset.",
-                        ));
+                    Err(e) => {
+                        return Err(e);
                     }
-                    Some(_del) => {
+                    Ok(_del) => {
                         if _json {
                             write_file(
                                 Path::new(&_dg_file),
-                                serde_json::to_string_pretty(&_del).unwrap(),
+                                json_enc(&_del),
                             );
                         } else {
                             write_file(
                                 Path::new(&_dg_file),
-                                ser_enc(_del, SK_BEGIN, SK_END)
+                                ser_enc(&_del)
                             );
                         }
                     }
@@ -1396,6 +1304,10 @@ fn main() {
             None => {}
             Some(_pol) => _policy = _pol.to_string(),
         }
+        let _suite: AeadSuite = match arguments.value_of(AEAD) {
+            None => AeadSuite::default(),
+            Some(_aead) => _aead.parse()?,
+        };
         match arguments.value_of(FILE) {
             None => {}
             Some(_file) => {
@@ -1405,6 +1317,9 @@ fn main() {
                 _ct_file.push_str(&CT_EXTENSION);
             }
         }
+        if arguments.is_present(STREAM) {
+            return run_encrypt_stream(_scheme, _lang, _json, _suite, &_gp_file, &_pk_files, &_attributes, &_policy, &_pt_file, &_ct_file);
+        }
         let buffer: Vec<u8> = read_to_vec(Path::new(&_pt_file));
         match _scheme {
             Scheme::AC17CP => {
@@ -1413,26 +1328,20 @@ fn main() {
                 if _pk_files.len() == 1 {
                     _pk_file = _pk_files[0].clone();
                     if _json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
+                        _pk = json_dec(&_pk_file)?;
                     } else {
-                        _pk = match ser_dec(&_pk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pk = ser_dec(&_pk_file)?;
                     }
-                    let _ct = ac17::cp_encrypt(&_pk, &_policy, &buffer, _lang);
+                    let _ct = ac17::cp_encrypt_with_suite(&_pk, &_policy, &buffer, _lang, _suite)?;
                     if _json {
                         write_file(
                             Path::new(&_ct_file),
-                            serde_json::to_string_pretty(&_ct).unwrap(),
+                            json_enc(&_ct),
                         );
                     } else {
                         write_file(
                             Path::new(&_ct_file),
-                            ser_enc(_ct, CT_BEGIN, CT_END)
+                            ser_enc(&_ct)
                         );
                     }
                 } else {
@@ -1447,26 +1356,20 @@ fn main() {
                 if _pk_files.len() == 1 {
                     _pk_file = _pk_files[0].clone();
                     if _json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
+                        _pk = json_dec(&_pk_file)?;
                     } else {
-                        _pk = match ser_dec(&_pk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pk = ser_dec(&_pk_file)?;
                     }
-                    let _ct = ac17::kp_encrypt(&_pk, &_attributes, &buffer);
+                    let _ct = ac17::kp_encrypt_with_suite(&_pk, &_attributes, &buffer, _suite)?;
                     if _json {
                         write_file(
                             Path::new(&_ct_file),
-                            serde_json::to_string_pretty(&_ct).unwrap(),
+                            json_enc(&_ct),
                         );
                     } else {
                         write_file(
                             Path::new(&_ct_file),
-                            ser_enc(_ct, CT_BEGIN, CT_END)
+                            ser_enc(&_ct)
                         );
                     }
                 } else {
@@ -1480,27 +1383,20 @@ fn main() {
                 // only one pk is allowed
                 if _pk_files.len() == 1 {
                     if _json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_files[0].clone())))
-                            .unwrap();
+                        _pk = json_dec(&_pk_files[0].clone())?;
                     } else {
-                        _pk = match ser_dec(&_pk_files[0].clone()) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pk = ser_dec(&_pk_files[0].clone())?;
                     }
-                    let _ct = bsw::encrypt(&_pk, &_policy, &buffer, _lang);
+                    let _ct = bsw::encrypt_with_suite(&_pk, &_policy, &buffer, _lang, _suite)?;
                     if _json {
                         write_file(
                             Path::new(&_ct_file),
-                            serde_json::to_string_pretty(&_ct).unwrap(),
+                            json_enc(&_ct),
                         );
                     } else {
                         write_file(
                             Path::new(&_ct_file),
-                            ser_enc(_ct, CT_BEGIN, CT_END)
+                            ser_enc(&_ct)
                         );
                     }
                 } else {
@@ -1514,27 +1410,20 @@ fn main() {
                 // only one pk is allowed
                 if _pk_files.len() == 1 {
                     if _json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_files[0].clone())))
-                            .unwrap();
+                        _pk = json_dec(&_pk_files[0].clone())?;
                     } else {
-                        _pk = match ser_dec(&_pk_files[0].clone()) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pk = ser_dec(&_pk_files[0].clone())?;
                     }
-                    let _ct = lsw::encrypt(&_pk, &_attributes, &buffer);
+                    let _ct = lsw::encrypt_with_suite(&_pk, &_attributes, &buffer, _suite)?;
                     if _json {
                         write_file(
                             Path::new(&_ct_file),
-                            serde_json::to_string_pretty(&_ct).unwrap(),
+                            json_enc(&_ct),
                         );
                     } else {
                         write_file(
                             Path::new(&_ct_file),
-                            ser_enc(_ct, CT_BEGIN, CT_END)
+                            ser_enc(&_ct)
                         );
                     }
                 } else {
@@ -1546,126 +1435,90 @@ fn main() {
             Scheme::AW11 => {
                 let mut _gp: aw11::Aw11GlobalKey;
                 if _json {
-                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
+                    _gp = json_dec(&_gp_file)?;
                 } else {
-                    _gp = match ser_dec(&_gp_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _gp = ser_dec(&_gp_file)?;
                 }
                 let mut _pks: Vec<aw11::Aw11PublicKey> = Vec::new();
                 for filename in _pk_files {
                     let mut _pka: aw11::Aw11PublicKey;
                     if _json {
-                        _pka = serde_json::from_str(&read_file(Path::new(&filename))).unwrap();
+                        _pka = json_dec(&filename)?;
                     } else {
-                        _pka = match ser_dec(&filename) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pka = ser_dec(&filename)?;
                     }
                     _pks.push(_pka);
                 }
-                let _ct = aw11::encrypt(&_gp, &_pks, &_policy, _lang, &buffer);
+                let _ct = aw11::encrypt_with_suite(&_gp, &_pks, &_policy, _lang, &buffer, _suite)?;
                 if _json {
                     write_file(
                         Path::new(&_ct_file),
-                        serde_json::to_string_pretty(&_ct).unwrap(),
+                        json_enc(&_ct),
                     );
                 } else {
                     write_file(
                         Path::new(&_ct_file),
-                        ser_enc(_ct, CT_BEGIN, CT_END)
+                        ser_enc(&_ct)
                     );
                 }
             }
             Scheme::BDABE => {
                 let mut _pk: bdabe::BdabePublicKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_pk_file)?;
                 }
                 let mut _attr_vec: Vec<bdabe::BdabePublicAttributeKey> = Vec::new();
                 for filename in _pk_files {
                     let mut _pka: bdabe::BdabePublicAttributeKey;
                     if _json {
-                        _pka = serde_json::from_str(&read_file(Path::new(&filename))).unwrap();
+                        _pka = json_dec(&filename)?;
                     } else {
-                        _pka = match ser_dec(&filename) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pka = ser_dec(&filename)?;
                     }
                     _attr_vec.push(_pka);
                 }
-                let _ct = bdabe::encrypt(&_pk, &_attr_vec, &_policy, &buffer, _lang);
+                let _ct = bdabe::encrypt_with_suite(&_pk, &_attr_vec, &_policy, &buffer, _lang, _suite)?;
                 if _json {
                     write_file(
                         Path::new(&_ct_file),
-                        serde_json::to_string_pretty(&_ct).unwrap(),
+                        json_enc(&_ct),
                     );
                 } else {
                     write_file(
                         Path::new(&_ct_file),
-                        ser_enc(_ct, CT_BEGIN, CT_END)
+                        ser_enc(&_ct)
                     );
                 }
             }
             Scheme::MKE08 => {
                 let mut _pk: mke08::Mke08PublicKey;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_pk_file)?;
                 }
                 let mut _attr_vec: Vec<mke08::Mke08PublicAttributeKey> = Vec::new();
                 for filename in _pk_files {
                     let mut _pka: mke08::Mke08PublicAttributeKey;
                     if _json {
-                        _pka = serde_json::from_str(&read_file(Path::new(&filename))).unwrap();
+                        _pka = json_dec(&filename)?;
                     } else {
-                        _pka = match ser_dec(&filename) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pka = ser_dec(&filename)?;
                     }
                     _attr_vec.push(_pka);
                 }
-                let _ct = mke08::encrypt(&_pk, &_attr_vec, &_policy, _lang, &buffer);
+                let _ct = mke08::encrypt_with_suite(&_pk, &_attr_vec, &_policy, _lang, &buffer, _suite)?;
                 if _json {
                     write_file(
                         Path::new(&_ct_file),
-                        serde_json::to_string_pretty(&_ct).unwrap(),
+                        json_enc(&_ct),
                     );
                 } else {
                     write_file(
                         Path::new(&_ct_file),
-                        ser_enc(_ct, CT_BEGIN, CT_END)
+                        ser_enc(&_ct)
                     );
                 }
             }
@@ -1674,27 +1527,20 @@ fn main() {
                 // only one pk is allowed
                 if _pk_files.len() == 1 {
                     if _json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_files[0].clone())))
-                            .unwrap();
+                        _pk = json_dec(&_pk_files[0].clone())?;
                     } else {
-                        _pk = match ser_dec(&_pk_files[0].clone()) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pk = ser_dec(&_pk_files[0].clone())?;
                     }
-                    let _ct = yct14::encrypt(&_pk, &_attributes, &buffer);
+                    let _ct = yct14::encrypt_with_suite(&_pk, &_attributes, &buffer, _suite)?;
                     if _json {
                         write_file(
                             Path::new(&_ct_file),
-                            serde_json::to_string_pretty(&_ct).unwrap(),
+                            json_enc(&_ct),
                         );
                     } else {
                         write_file(
                             Path::new(&_ct_file),
-                            ser_enc(_ct, CT_BEGIN, CT_END)
+                            ser_enc(&_ct)
                         );
                     }
                 } else {
@@ -1709,7 +1555,7 @@ fn main() {
 
     fn run_decrypt(
         arguments: &ArgMatches,
-        _scheme: Scheme,
+        _scheme: Option<Scheme>,
         _lang: PolicyLanguage,
         _json: bool,
     ) -> Result<(), RabeError> {
@@ -1751,28 +1597,27 @@ fn main() {
             None => {}
             Some(x) => _policy = x.to_string(),
         }
+        if is_stream(&_file)? {
+            let _scheme = match _scheme {
+                Some(_scheme) => _scheme,
+                None => Scheme::from_str(read_encoded(&_sk_file)?.scheme()?.name())?,
+            };
+            return run_decrypt_stream(_scheme, _json, &_gp_file, &_pk_file, &_sk_file, &_file);
+        }
+        let _scheme = match _scheme {
+            Some(_scheme) => _scheme,
+            None => return run_decrypt_registry(&_gp_file, &_pk_file, &_sk_file, &_file),
+        };
         match _scheme {
             Scheme::AC17CP => {
                 let mut _sk: ac17::Ac17CpSecretKey;
                 let mut _ct: ac17::Ac17CpCiphertext;
                 if _json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = json_dec(&_sk_file)?;
+                    _ct = json_dec(&_file)?;
                 } else {
-                    _sk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _ct = match ser_dec(&_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _sk = ser_dec(&_sk_file)?;
+                    _ct = ser_dec(&_file)?;
                 }
                 _pt_option = ac17::cp_decrypt(&_sk, &_ct);
             }
@@ -1780,23 +1625,11 @@ fn main() {
                 let mut _sk: ac17::Ac17KpSecretKey;
                 let mut _ct: ac17::Ac17KpCiphertext;
                 if _json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = json_dec(&_sk_file)?;
+                    _ct = json_dec(&_file)?;
                 } else {
-                    _sk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _ct = match ser_dec(&_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _sk = ser_dec(&_sk_file)?;
+                    _ct = ser_dec(&_file)?;
                 }
                 _pt_option = ac17::kp_decrypt(&_sk, &_ct);
             }
@@ -1804,23 +1637,11 @@ fn main() {
                 let mut _sk: bsw::CpAbeSecretKey;
                 let mut _ct: bsw::CpAbeCiphertext;
                 if _json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = json_dec(&_sk_file)?;
+                    _ct = json_dec(&_file)?;
                 } else {
-                    _sk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _ct = match ser_dec(&_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _sk = ser_dec(&_sk_file)?;
+                    _ct = ser_dec(&_file)?;
                 }
                 _pt_option = bsw::decrypt(&_sk, &_ct);
             }
@@ -1828,23 +1649,11 @@ fn main() {
                 let mut _sk: lsw::KpAbeSecretKey;
                 let mut _ct: lsw::KpAbeCiphertext;
                 if _json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = json_dec(&_sk_file)?;
+                    _ct = json_dec(&_file)?;
                 } else {
-                    _sk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _ct = match ser_dec(&_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _sk = ser_dec(&_sk_file)?;
+                    _ct = ser_dec(&_file)?;
                 }
                 _pt_option = lsw::decrypt(&_sk, &_ct);
             }
@@ -1853,31 +1662,13 @@ fn main() {
                 let mut _sk: aw11::Aw11SecretKey;
                 let mut _ct: aw11::Aw11Ciphertext;
                 if _json {
-                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _gp = json_dec(&_gp_file)?;
+                    _sk = json_dec(&_sk_file)?;
+                    _ct = json_dec(&_file)?;
                 } else {
-                    _gp = match ser_dec(&_gp_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _sk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _ct = match ser_dec(&_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _gp = ser_dec(&_gp_file)?;
+                    _sk = ser_dec(&_sk_file)?;
+                    _ct = ser_dec(&_file)?;
                 }
                 _pt_option = aw11::decrypt(&_gp, &_sk, &_ct);
             }
@@ -1886,31 +1677,13 @@ fn main() {
                 let mut _sk: bdabe::BdabeUserKey;
                 let mut _ct: bdabe::BdabeCiphertext;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _pk = json_dec(&_pk_file)?;
+                    _sk = json_dec(&_sk_file)?;
+                    _ct = json_dec(&_file)?;
                 } else {
-                    _pk = match ser_dec(&_pk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _sk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _ct = match ser_dec(&_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_pk_file)?;
+                    _sk = ser_dec(&_sk_file)?;
+                    _ct = ser_dec(&_file)?;
                 }
                 _pt_option = bdabe::decrypt(&_pk, &_sk, &_ct);
             }
@@ -1919,31 +1692,13 @@ fn main() {
                 let mut _sk: mke08::Mke08UserKey;
                 let mut _ct: mke08::Mke08Ciphertext;
                 if _json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _pk = json_dec(&_gp_file)?;
+                    _sk = json_dec(&_sk_file)?;
+                    _ct = json_dec(&_file)?;
                 } else {
-                    _pk = match ser_dec(&_gp_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _sk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _ct = match ser_dec(&_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _pk = ser_dec(&_gp_file)?;
+                    _sk = ser_dec(&_sk_file)?;
+                    _ct = ser_dec(&_file)?;
                 }
                 _pt_option = mke08::decrypt(&_pk, &_sk, &_ct);
             }
@@ -1951,23 +1706,11 @@ fn main() {
                 let mut _sk: yct14::Yct14AbeSecretKey;
                 let mut _ct: yct14::Yct14AbeCiphertext;
                 if _json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = json_dec(&_sk_file)?;
+                    _ct = json_dec(&_file)?;
                 } else {
-                    _sk = match ser_dec(&_sk_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
-                    _ct = match ser_dec(&_file) {
-                        Ok(parsed) => match from_slice(&parsed) {
-                            Ok(parsed_res) => parsed_res,
-                            Err(e) => return Err(e.into())
-                        },
-                        Err(e) => return Err(e)
-                    };
+                    _sk = ser_dec(&_sk_file)?;
+                    _ct = ser_dec(&_file)?;
                 }
                 _pt_option = yct14::decrypt(&_sk, &_ct);
             }
@@ -2034,23 +1777,11 @@ fn main() {
                     let mut _pk: mke08::Mke08PublicKey;
                     let mut _ska: mke08::Mke08SecretAuthorityKey;
                     if _json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                        _ska = serde_json::from_str(&read_file(Path::new(&_au_sk_file))).unwrap();
+                        _pk = json_dec(&_pk_file)?;
+                        _ska = json_dec(&_au_sk_file)?;
                     } else {
-                        _pk = match ser_dec(&_pk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
-                        _ska = match ser_dec(&_au_sk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pk = ser_dec(&_pk_file)?;
+                        _ska = ser_dec(&_au_sk_file)?;
                     }
                     match mke08::request_authority_pk(&_pk, &_attributes[0], &_ska) {
                         None => {}
@@ -2058,12 +1789,12 @@ fn main() {
                             if _json {
                                 write_file(
                                     Path::new(&_pka_file),
-                                    serde_json::to_string_pretty(&_a_pk).unwrap(),
+                                    json_enc(&_a_pk),
                                 );
                             } else {
                                 write_file(
                                     Path::new(&_pka_file),
-                                    ser_enc(_a_pk, PKA_BEGIN, PKA_END)
+                                    ser_enc(&_a_pk)
                                 );
                             }
                         }
@@ -2073,23 +1804,11 @@ fn main() {
                     let mut _pk: bdabe::BdabePublicKey;
                     let mut _ska: bdabe::BdabeSecretAuthorityKey;
                     if _json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                        _ska = serde_json::from_str(&read_file(Path::new(&_au_sk_file))).unwrap();
+                        _pk = json_dec(&_pk_file)?;
+                        _ska = json_dec(&_au_sk_file)?;
                     } else {
-                        _pk = match ser_dec(&_pk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
-                        _ska = match ser_dec(&_au_sk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _pk = ser_dec(&_pk_file)?;
+                        _ska = ser_dec(&_au_sk_file)?;
                     }
                     match bdabe::request_attribute_pk(&_pk, &_ska, &_attributes[0]) {
                         None => {}
@@ -2097,12 +1816,12 @@ fn main() {
                             if _json {
                                 write_file(
                                     Path::new(&_pka_file),
-                                    serde_json::to_string_pretty(&_a_pk).unwrap(),
+                                    json_enc(&_a_pk),
                                 );
                             } else {
                                 write_file(
                                     Path::new(&_pka_file),
-                                    ser_enc(_a_pk, PKA_BEGIN, PKA_END)
+                                    ser_enc(&_a_pk)
                                 );
                             }
                         }
@@ -2174,23 +1893,11 @@ fn main() {
                     let mut _ska: mke08::Mke08SecretAttributeKey;
                     let mut _skau: mke08::Mke08SecretAuthorityKey;
                     if _json {
-                        _usk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                        _skau = serde_json::from_str(&read_file(Path::new(&_au_sk_file))).unwrap();
+                        _usk = json_dec(&_sk_file)?;
+                        _skau = json_dec(&_au_sk_file)?;
                     } else {
-                        _usk = match ser_dec(&_sk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
-                        _skau = match ser_dec(&_au_sk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _usk = ser_dec(&_sk_file)?;
+                        _skau = ser_dec(&_au_sk_file)?;
                     }
                     match mke08::request_authority_sk(
                         &_attributes[0],
@@ -2202,12 +1909,12 @@ fn main() {
                             if _json {
                                 write_file(
                                     Path::new(&_ask_file),
-                                    serde_json::to_string_pretty(&_a_sk).unwrap(),
+                                    json_enc(&_a_sk),
                                 );
                             } else {
                                 write_file(
                                     Path::new(&_ask_file),
-                                    ser_enc(_a_sk, SKA_BEGIN, SKA_END),
+                                    ser_enc(&_a_sk),
                                 );
                             }
                         }
@@ -2218,23 +1925,11 @@ fn main() {
                     let mut _ska: bdabe::BdabeSecretAttributeKey;
                     let mut _skau: bdabe::BdabeSecretAuthorityKey;
                     if _json {
-                        _usk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                        _skau = serde_json::from_str(&read_file(Path::new(&_au_sk_file))).unwrap();
+                        _usk = json_dec(&_sk_file)?;
+                        _skau = json_dec(&_au_sk_file)?;
                     } else {
-                        _usk = match ser_dec(&_sk_file) {
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
-                        _skau = match ser_dec(&_au_sk_file){
-                            Ok(parsed) => match from_slice(&parsed) {
-                                Ok(parsed_res) => parsed_res,
-                                Err(e) => return Err(e.into())
-                            },
-                            Err(e) => return Err(e)
-                        };
+                        _usk = ser_dec(&_sk_file)?;
+                        _skau = ser_dec(&_au_sk_file)?;
                     }
                     match bdabe::request_attribute_sk(
                         &_usk._pk,
@@ -2246,12 +1941,12 @@ fn main() {
                             if _json {
                                 write_file(
                                     Path::new(&_ask_file),
-                                    serde_json::to_string_pretty(&_a_sk).unwrap(),
+                                    json_enc(&_a_sk),
                                 );
                             } else {
                                 write_file(
                                     Path::new(&_ask_file),
-                                    ser_enc(_a_sk, SKA_BEGIN, SKA_END)
+                                    ser_enc(&_a_sk)
                                 );
                             }
                         }
@@ -2270,32 +1965,168 @@ fn main() {
         }
         Ok(())
     }
+
+    fn run_encrypt_stream(
+        _scheme: Scheme,
+        _lang: PolicyLanguage,
+        _json: bool,
+        _suite: AeadSuite,
+        _gp_file: &String,
+        _pk_files: &Vec<String>,
+        _attributes: &Vec<String>,
+        _policy: &String,
+        _pt_file: &String,
+        _ct_file: &String,
+    ) -> Result<(), RabeError> {
+        let _output = BufWriter::new(File::create(_ct_file)?);
+        let _pk_file = _pk_files.first().ok_or_else(|| RabeError::new("no public key file given"))?;
+        let mut _writer = match _scheme {
+            Scheme::AC17CP => Ac17Cp.encrypt_stream(&read_key(_pk_file, _json)?, _policy, _lang, _suite, _output),
+            Scheme::AC17KP => Ac17Kp.encrypt_stream(&read_key(_pk_file, _json)?, _attributes, _suite, _output),
+            Scheme::AW11 => Aw11.encrypt_stream(&read_key(_gp_file, _json)?, &read_keys(_pk_files, _json)?, _policy, _lang, _suite, _output),
+            Scheme::BDABE => Bdabe.encrypt_stream(&read_key(_gp_file, _json)?, &read_keys(_pk_files, _json)?, _policy, _lang, _suite, _output),
+            Scheme::BSW => Bsw.encrypt_stream(&read_key(_pk_file, _json)?, _policy, _lang, _suite, _output),
+            Scheme::LSW => Lsw.encrypt_stream(&read_key(_pk_file, _json)?, _attributes, _suite, _output),
+            Scheme::MKE08 => Mke08.encrypt_stream(&read_key(_gp_file, _json)?, &read_keys(_pk_files, _json)?, _policy, _lang, _suite, _output),
+            Scheme::YCT14 => Yct14::default().encrypt_stream(&read_key(_pk_file, _json)?, _attributes, _suite, _output),
+        }?;
+        io::copy(&mut BufReader::new(File::open(_pt_file)?), &mut _writer)?;
+        _writer.finish()?;
+        println!("successfully wrote to {}", _ct_file);
+        Ok(())
+    }
+
+    fn run_decrypt_stream(
+        _scheme: Scheme,
+        _json: bool,
+        _gp_file: &String,
+        _pk_file: &String,
+        _sk_file: &String,
+        _file: &String,
+    ) -> Result<(), RabeError> {
+        let _input = BufReader::new(File::open(_file)?);
+        let mut _reader = match _scheme {
+            Scheme::AC17CP => Ac17Cp.decrypt_stream(&read_key(_sk_file, _json)?, _input),
+            Scheme::AC17KP => Ac17Kp.decrypt_stream(&read_key(_sk_file, _json)?, _input),
+            Scheme::AW11 => Aw11.decrypt_stream(&read_key(_gp_file, _json)?, &read_key(_sk_file, _json)?, _input),
+            Scheme::BDABE => Bdabe.decrypt_stream(&read_key(_pk_file, _json)?, &read_key(_sk_file, _json)?, _input),
+            Scheme::BSW => Bsw.decrypt_stream(&read_key(_sk_file, _json)?, _input),
+            Scheme::LSW => Lsw.decrypt_stream(&read_key(_sk_file, _json)?, _input),
+            Scheme::MKE08 => Mke08.decrypt_stream(&read_key(_pk_file, _json)?, &read_key(_sk_file, _json)?, _input),
+            Scheme::YCT14 => Yct14::default().decrypt_stream(&read_key(_sk_file, _json)?, _input),
+        }?;
+        // the plaintext of "file.ct" is written to "file"
+        let _pt_file = match _file.strip_suffix(&[DOT, CT_EXTENSION].concat()) {
+            Some(_pt_file) => _pt_file.to_string(),
+            None => [_file.as_str(), DOT, "pt"].concat(),
+        };
+        let mut _output = BufWriter::new(File::create(&_pt_file)?);
+        let _copied = io::copy(&mut _reader, &mut _output);
+        drop(_output);
+        if let Err(e) = _copied {
+            // the plaintext written so far is not authenticated as a whole
+            fs::remove_file(&_pt_file)?;
+            return Err(e.into());
+        }
+        println!("successfully wrote to {}", _pt_file);
+        Ok(())
+    }
+
+    fn run_explain(arguments: &ArgMatches, _scheme: Option<Scheme>, _json: bool) -> Result<(), RabeError> {
+        let _sk_file = arguments.value_of(SK_FILE).unwrap_or(SK_FILE).to_string();
+        let _file = arguments.value_of(FILE).unwrap_or_default().to_string();
+        let _explanation = match _scheme {
+            None => Registry::default().explain(&read_encoded(&_sk_file)?, &read_encoded(&_file)?),
+            Some(Scheme::AC17CP) => Ac17Cp.explain(&read_key(&_sk_file, _json)?, &read_key(&_file, _json)?),
+            Some(Scheme::AC17KP) => Ac17Kp.explain(&read_key(&_sk_file, _json)?, &read_key(&_file, _json)?),
+            Some(Scheme::AW11) => Aw11.explain(&read_key(&_sk_file, _json)?, &read_key(&_file, _json)?),
+            Some(Scheme::BDABE) => Bdabe.explain(&read_key(&_sk_file, _json)?, &read_key(&_file, _json)?),
+            Some(Scheme::BSW) => Bsw.explain(&read_key(&_sk_file, _json)?, &read_key(&_file, _json)?),
+            Some(Scheme::LSW) => Lsw.explain(&read_key(&_sk_file, _json)?, &read_key(&_file, _json)?),
+            Some(Scheme::MKE08) => Mke08.explain(&read_key(&_sk_file, _json)?, &read_key(&_file, _json)?),
+            Some(Scheme::YCT14) => Yct14::default().explain(&read_key(&_sk_file, _json)?, &read_key(&_file, _json)?),
+        }?;
+        println!("{}", _explanation);
+        Ok(())
+    }
+
+    /// Decrypts a file with the scheme recorded in the key, see rabe::schemes::registry
+    fn run_decrypt_registry(
+        _gp_file: &String,
+        _pk_file: &String,
+        _sk_file: &String,
+        _file: &String,
+    ) -> Result<(), RabeError> {
+        let _registry = Registry::default();
+        let _sk = read_encoded(_sk_file)?;
+        let _ct = read_encoded(_file)?;
+        let _gk = match _registry.scheme(&_sk, &_ct)? {
+            SchemeId::Aw11 => Some(read_encoded(_gp_file)?),
+            SchemeId::Bdabe | SchemeId::Mke08 => Some(read_encoded(_pk_file)?),
+            _ => None,
+        };
+        let _pt = _registry.decrypt(_gk.as_ref(), &_sk, &_ct)?;
+        write_from_vec(Path::new(_file), &_pt);
+        Ok(())
+    }
 }
 
-fn ser_enc<T: Serialize>(input: T, head: &str, tail: &str) -> String {
-    use deflate::deflate_bytes;
-    [
-        head,
-        &encode(
-            &deflate_bytes(
-                &to_vec_packed(&input).unwrap()
-            )
-        ),
-        tail
-    ].concat()
+fn read_key<T: DeserializeOwned + JsonFormat>(file_name: &String, _json: bool) -> Result<T, RabeError> {
+    if _json {
+        json_dec(file_name)
+    } else {
+        ser_dec(file_name)
+    }
 }
-fn ser_dec(file_name: &String) -> Result<Vec<u8>, RabeError> {
+
+/// Reads a key or ciphertext of any scheme, armored or in JSON
+fn read_encoded(file_name: &String) -> Result<Encoded, RabeError> {
+    Encoded::from_text(&read_file(Path::new(file_name)))
+}
+
+fn read_keys<T: DeserializeOwned + JsonFormat>(file_names: &Vec<String>, _json: bool) -> Result<Vec<T>, RabeError> {
+    file_names.iter().map(|file_name| read_key(file_name, _json)).collect()
+}
+
+/// Returns true if the file was encrypted with --stream
+fn is_stream(file_name: &String) -> Result<bool, RabeError> {
+    let mut _magic = Vec::new();
+    File::open(file_name)?.take(stream::MAGIC.len() as u64).read_to_end(&mut _magic)?;
+    Ok(_magic == stream::MAGIC)
+}
+
+fn json_enc<T: JsonFormat>(input: &T) -> String {
+    input.to_json_string()
+}
+
+fn json_dec<T: DeserializeOwned + JsonFormat>(file_name: &String) -> Result<T, RabeError> {
+    let _value: serde_json::Value = serde_json::from_str(&read_file(Path::new(file_name)))?;
+    // files written before the JSON representation was versioned hold the serde encoding
+    if _value.get("version").is_some() {
+        T::from_json_value(&_value)
+    } else {
+        Ok(serde_json::from_value(_value)?)
+    }
+}
+
+fn ser_enc<T: WireFormat>(input: &T) -> String {
+    armor(input)
+}
+
+fn ser_dec<T: DeserializeOwned + WireFormat>(file_name: &String) -> Result<T, RabeError> {
+    let _text = read_file(Path::new(file_name));
+    // files written before the armor had headers hold deflated CBOR on a single line
+    match _text.lines().nth(1) {
+        Some(_line) if !_line.starts_with("Version: ") => ser_dec_legacy(&_text),
+        _ => dearmor(&_text),
+    }
+}
+
+fn ser_dec_legacy<T: DeserializeOwned>(_text: &String) -> Result<T, RabeError> {
     use inflate::inflate_bytes;
-    let base64: Vec<u8> = decode(
-        &read_raw(
-            &read_file(
-                Path::new(file_name)
-            )
-        )
-    )?;
+    let base64: Vec<u8> = decode(&read_raw(_text))?;
     match inflate_bytes(&base64) {
-        Ok(bytes) => Ok(bytes),
+        Ok(bytes) => Ok(from_slice(&bytes)?),
         Err(e) => Err(RabeError::new(e.to_string().as_str()))
     }
-
-}
\ No newline at end of file
+}
@@ -14,38 +14,70 @@ extern crate rabe_bn;
 extern crate byteorder;
 extern crate libc;
 extern crate rand;
+extern crate rand_chacha;
 extern crate serde;
 extern crate serde_json;
+extern crate serde_cbor;
+extern crate bincode;
 extern crate pest;
 extern crate ccm;
 extern crate aes;
+extern crate aes_gcm;
+extern crate chacha20poly1305;
 extern crate sha3;
+extern crate hmac;
+extern crate hkdf;
 #[macro_use]
 extern crate pest_derive;
 
+/// various utilities, declared first so that the schemes can use their macros
+#[macro_use]
+pub mod utils;
 /// implemented schemes
 pub mod schemes;
-/// various utilities
-pub mod utils;
 
 use std::{fmt::{
     Display,
     Result,
     Formatter
-}, error::Error, cmp};
-use pest::error::{Error as PestError, LineColLocation};
+}, error::Error};
+use pest::error::Error as PestError;
 use utils::policy::pest::json::Rule as jsonRule;
 use utils::policy::pest::human::Rule as humanRule;
+use utils::policy::pest::{PolicyLanguage, error::PolicyParseError};
+use utils::validate::ValidationError;
+use utils::fingerprint::KeyMismatch;
 use ccm::aead;
 
 #[derive(Debug, Serialize)]
 pub struct RabeError {
     details: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy_error: Option<Box<PolicyParseError>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validation_error: Option<Box<ValidationError>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_mismatch: Option<Box<KeyMismatch>>,
 }
 
 impl RabeError {
     pub fn new(msg: &str) -> RabeError {
-        RabeError { details: msg.to_string() }
+        RabeError { details: msg.to_string(), policy_error: None, validation_error: None, key_mismatch: None }
+    }
+
+    /// Returns the details of a malformed policy, if this error was caused by one
+    pub fn policy_parse_error(&self) -> Option<&PolicyParseError> {
+        self.policy_error.as_deref()
+    }
+
+    /// Returns the reason a deserialized key or ciphertext was rejected, if this error was caused by one
+    pub fn validation_error(&self) -> Option<&ValidationError> {
+        self.validation_error.as_deref()
+    }
+
+    /// Returns the issuers of a secret key and a ciphertext of different public keys, if this error was caused by them
+    pub fn key_mismatch(&self) -> Option<&KeyMismatch> {
+        self.key_mismatch.as_deref()
     }
 }
 
@@ -61,27 +93,33 @@ impl Error for RabeError {
     }
 }
 
+impl From<PolicyParseError> for RabeError {
+    fn from(error: PolicyParseError) -> Self {
+        RabeError { details: error.to_string(), policy_error: Some(Box::new(error)), validation_error: None, key_mismatch: None }
+    }
+}
+
+impl From<ValidationError> for RabeError {
+    fn from(error: ValidationError) -> Self {
+        RabeError { details: error.to_string(), policy_error: None, validation_error: Some(Box::new(error)), key_mismatch: None }
+    }
+}
+
+impl From<KeyMismatch> for RabeError {
+    fn from(error: KeyMismatch) -> Self {
+        RabeError { details: error.to_string(), policy_error: None, validation_error: None, key_mismatch: Some(Box::new(error)) }
+    }
+}
+
 impl From<PestError<jsonRule>> for RabeError {
     fn from(error: PestError<jsonRule>) -> Self {
-        let line = match error.line_col.to_owned() {
-            LineColLocation::Pos((line, _)) => line,
-            LineColLocation::Span((start_line, _), (end_line, _)) => cmp::max(start_line, end_line),
-        };
-        RabeError::new(
-            format!("Json Policy Error in line {}\n", line).as_ref()
-        )
+        PolicyParseError::from_pest(error, PolicyLanguage::JsonPolicy).into()
     }
 }
 
 impl From<PestError<humanRule>> for RabeError {
     fn from(error: PestError<humanRule>) -> Self {
-        let line = match error.line_col.to_owned() {
-            LineColLocation::Pos((line, _)) => line,
-            LineColLocation::Span((start_line, _), (end_line, _)) => cmp::max(start_line, end_line),
-        };
-        RabeError::new(
-            format!("Json Policy Error in line {}\n", line).as_ref()
-        )
+        PolicyParseError::from_pest(error, PolicyLanguage::HumanPolicy).into()
     }
 }
 
@@ -99,6 +137,14 @@ impl From<serde_cbor::Error> for RabeError {
     }
 }
 
+impl From<serde_json::Error> for RabeError {
+    fn from(_error: serde_json::Error) -> Self {
+        RabeError::new(
+            format!("serde_json::Error {}", _error).as_ref()
+        )
+    }
+}
+
 impl From<base64::DecodeError> for RabeError {
     fn from(_error: base64::DecodeError) -> Self {
         RabeError::new(
@@ -106,6 +152,14 @@ impl From<base64::DecodeError> for RabeError {
         )
     }
 }
+impl From<std::io::Error> for RabeError {
+    fn from(_error: std::io::Error) -> Self {
+        RabeError::new(
+            format!("std::io::Error {}", _error).as_ref()
+        )
+    }
+}
+
 impl From<String> for RabeError {
     fn from(_error: String) -> Self {
         RabeError::new(_error.as_str())
@@ -18,7 +18,7 @@
 //! let (pk, msk) = setup();
 //! let plaintext = String::from("our plaintext!").into_bytes();
 //! let policy = String::from(r#""A" and "B""#);
-//! let ct: Ac17KpCiphertext =  kp_encrypt(&pk, &vec![
+//! let ct: Ac17KpCiphertext =  kp_encrypt(&pk, &[
 //!     "A".to_string(),
 //!     "B".to_string()
 //! ], &plaintext).unwrap();
@@ -48,17 +48,28 @@ use std::{
 use rabe_bn::{Group, Gt, G1, G2, Fr, pairing};
 use rand::Rng;
 use utils::{
+    attribute::canonicalize,
     policy::msp::AbePolicy,
     tools::*,
-    secretsharing::*,
     aes::*,
-    hash::blake2b_hash_g1
+    hash::HashVersion
 };
 use utils::policy::pest::{PolicyLanguage, parse, PolicyType};
 use RabeError;
+use utils::validate::{ValidationError, check_g1, check_g2, check_gt, check_fr, check_fr_invertible, check_arity, check_unique, check_policy, check_issuer_length};
+use utils::fingerprint::{Fingerprint, check_issuer};
+use utils::json::POLICY_FIELDS;
+use schemes::{CpAbe, CpAbeCca, KpAbe, KpAbeCca, SchemeId};
+use utils::policy::{ast::Policy, explain::{explain, Explanation}};
+
+/// The JSON field names of the tuples in _c
+const JSON_C: &[&str] = &["attribute", "c"];
+/// The JSON field names of the tuples in _k
+const JSON_K: &[&str] = &["attribute", "k"];
 
 /// An AC17 Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Ac17PublicKey {
     pub _g: G1,
     pub _h_a: Vec<G2>,
@@ -67,6 +78,7 @@ pub struct Ac17PublicKey {
 
 /// An AC17 Public Key (MK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Ac17MasterKey {
     pub _g: G1,
     pub _h: G2,
@@ -75,6 +87,20 @@ pub struct Ac17MasterKey {
     pub _b: Vec<Fr>,
 }
 
+impl Ac17MasterKey {
+    /// The Ac17PublicKey setup() generated together with this master key. Keygen only gets the
+    /// master key, but records the fingerprint of the public key as the issuer.
+    pub fn public_key(&self) -> Ac17PublicKey {
+        let mut _h_a: Vec<G2> = self._a.iter().map(|_a| self._h * *_a).collect();
+        _h_a.push(self._h);
+        // e(g, h)^(k_i a_i + k_n) = e([k_i]_1 a_i + [k_n]_1, h)
+        let _e_gh_ka: Vec<Gt> = (0usize..ASSUMPTION_SIZE)
+            .map(|_i| pairing(self._g_k[_i] * self._a[_i] + self._g_k[ASSUMPTION_SIZE], self._h))
+            .collect();
+        Ac17PublicKey {_g: self._g, _h_a, _e_gh_ka}
+    }
+}
+
 /// An AC17 Ciphertext (CT)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct Ac17Ciphertext {
@@ -82,10 +108,20 @@ pub struct Ac17Ciphertext {
     pub _c: Vec<(String, Vec<G1>)>,
     pub _c_p: Gt,
     pub _ct: Vec<u8>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    #[serde(default)]
+    pub _suite: AeadSuite,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
 }
 
 /// An AC17 CP-ABE Ciphertext (CT), composed of a policy and an Ac17Ciphertext.
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Ac17CpCiphertext {
     pub _policy: (String, PolicyLanguage),
     pub _ct: Ac17Ciphertext,
@@ -93,21 +129,94 @@ pub struct Ac17CpCiphertext {
 
 /// An AC17 KP-ABE Ciphertext (CT), composed of a set of attributes and an Ac17Ciphertext.
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Ac17KpCiphertext {
     pub _attr: Vec<String>,
     pub _ct: Ac17Ciphertext,
 }
 
+/// An AC17 key encapsulation: an Ac17Ciphertext without the symmetrically encrypted data
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct Ac17Encapsulation {
+    pub _c_0: Vec<G2>,
+    pub _c: Vec<(String, Vec<G1>)>,
+    pub _c_p: Gt,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
+}
+
+/// An AC17 CP-ABE key encapsulation, composed of a policy and an Ac17Encapsulation.
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
+pub struct Ac17CpEncapsulation {
+    pub _policy: (String, PolicyLanguage),
+    pub _enc: Ac17Encapsulation,
+}
+
+/// An AC17 KP-ABE key encapsulation, composed of a set of attributes and an Ac17Encapsulation.
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
+pub struct Ac17KpEncapsulation {
+    pub _attr: Vec<String>,
+    pub _enc: Ac17Encapsulation,
+}
+
+impl Ac17Ciphertext {
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> Ac17Encapsulation {
+        Ac17Encapsulation {
+            _c_0: self._c_0.clone(),
+            _c: self._c.clone(),
+            _c_p: self._c_p,
+            _hash: self._hash,
+            _kdf: self._kdf,
+            _issuer: self._issuer.clone(),
+        }
+    }
+}
+
+impl Ac17Encapsulation {
+    /// Attaches the symmetrically encrypted data
+    fn into_ciphertext(self, _ct: Vec<u8>, _suite: AeadSuite) -> Ac17Ciphertext {
+        Ac17Ciphertext {_c_0: self._c_0, _c: self._c, _c_p: self._c_p, _ct, _hash: self._hash, _kdf: self._kdf, _suite, _issuer: self._issuer}
+    }
+}
+
+impl Ac17CpCiphertext {
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> Ac17CpEncapsulation {
+        Ac17CpEncapsulation {_policy: self._policy.clone(), _enc: self._ct.encapsulation()}
+    }
+}
+
+impl Ac17KpCiphertext {
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> Ac17KpEncapsulation {
+        Ac17KpEncapsulation {_attr: self._attr.clone(), _enc: self._ct.encapsulation()}
+    }
+}
+
 /// An AC17 Secret Key (SK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct Ac17SecretKey {
     pub _k_0: Vec<G2>,
     pub _k: Vec<(String, Vec<G1>)>,
     pub _k_p: Vec<G1>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
 }
 
 /// An AC17 KP-ABE Secret Key (SK), composed of a policy and an Ac17Ciphertext.
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Ac17KpSecretKey {
     pub _policy: (String, PolicyLanguage),
     pub _sk: Ac17SecretKey,
@@ -115,6 +224,7 @@ pub struct Ac17KpSecretKey {
 
 /// An AC17 CP-ABE Secret Key (SK), composed of a set of attributes and an Ac17Ciphertext.
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Ac17CpSecretKey {
     pub _attr: Vec<String>,
     pub _sk: Ac17SecretKey,
@@ -123,6 +233,170 @@ pub struct Ac17CpSecretKey {
 /// The assumption size of the pairing in the AC17 scheme.
 const ASSUMPTION_SIZE: usize = 2;
 
+/// Hashes an attribute together with the indices `_l` and `_t`
+fn hash_attribute(_hash: HashVersion, _g: G1, _attr: &str, _l: usize, _t: usize) -> G1 {
+    _hash.hash_g1(_g, "rabe/ac17/attribute", &[_attr.as_bytes(), _l.to_string().as_bytes(), _t.to_string().as_bytes()])
+}
+
+/// Hashes the (1-based) column `_j` of an MSP together with the indices `_l` and `_t`.
+/// The leading "0" field keeps the input of the legacy hashes reproducible.
+fn hash_column(_hash: HashVersion, _g: G1, _j: usize, _l: usize, _t: usize) -> G1 {
+    _hash.hash_g1(_g, "rabe/ac17/column", &[b"0", _j.to_string().as_bytes(), _l.to_string().as_bytes(), _t.to_string().as_bytes()])
+}
+
+/// The public group elements of a ciphertext, authenticated as associated data
+fn header_elements(_enc: &Ac17Encapsulation) -> Vec<u8> {
+    let mut _elements = HeaderEncoder::new();
+    _elements.count(_enc._c_0.len());
+    for _c_0 in _enc._c_0.iter() {
+        _elements.g2(_c_0);
+    }
+    _elements.count(_enc._c.len());
+    for (_attr, _c) in _enc._c.iter() {
+        _elements.str(_attr).count(_c.len());
+        for _c_l in _c.iter() {
+            _elements.g1(_c_l);
+        }
+    }
+    _elements.gt(&_enc._c_p).finish()
+}
+
+/// Checks the per attribute or per row components of a key or ciphertext, each one a vector of
+/// ASSUMPTION_SIZE + 1 points
+fn check_components(_field: &str, _components: &[(String, Vec<G1>)]) -> Result<(), ValidationError> {
+    for (_i, (_, _points)) in _components.iter().enumerate() {
+        let _field = format!("{}[{}]", _field, _i);
+        check_arity(&_field, _points.len(), ASSUMPTION_SIZE + 1)?;
+        for _point in _points.iter() {
+            check_g1(&_field, _point)?;
+        }
+    }
+    Ok(())
+}
+
+impl_wire!(Ac17PublicKey: Ac17Cp, PublicKey { _g, _h_a, _e_gh_ka });
+
+impl_json!(Ac17PublicKey { _g, _h_a, _e_gh_ka });
+
+impl Fingerprint for Ac17PublicKey {}
+
+impl_validate!(Ac17PublicKey {
+    _g: check_g1,
+    _h_a: [check_g2; ASSUMPTION_SIZE + 1],
+    _e_gh_ka: [check_gt; ASSUMPTION_SIZE],
+});
+
+impl_validated_serde!(Ac17PublicKey);
+
+impl_wire!(Ac17MasterKey: Ac17Cp, MasterKey { _g, _h, _g_k, _a, _b });
+
+impl_json!(Ac17MasterKey { _g, _h, _g_k, _a, _b });
+
+impl Fingerprint for Ac17MasterKey {}
+
+impl_validate!(Ac17MasterKey {
+    _g: check_g1,
+    _h: check_g2,
+    _g_k: [check_g1; ASSUMPTION_SIZE + 1],
+    // keygen inverts the elements of _a
+    _a: [check_fr_invertible; ASSUMPTION_SIZE],
+    _b: [check_fr; ASSUMPTION_SIZE],
+});
+
+impl_validated_serde!(Ac17MasterKey);
+
+impl_wire!(Ac17Ciphertext { _c_0, _c, _c_p, _ct, _hash, _kdf, _suite; _issuer });
+
+impl_json!(Ac17Ciphertext { _c_0, _c: tuples(JSON_C), _c_p, _ct, _hash, _kdf, _suite, _issuer });
+
+impl_validate!(Ac17Ciphertext {
+    _c_0: [check_g2; ASSUMPTION_SIZE + 1],
+    _c: check_components,
+    _c_p: check_gt,
+    _issuer: check_issuer_length,
+});
+
+impl_wire!(Ac17CpCiphertext: Ac17Cp, Ciphertext { _policy, _ct });
+
+impl_json!(Ac17CpCiphertext { _policy: tuple(POLICY_FIELDS), _ct });
+
+impl_validate!(Ac17CpCiphertext { _policy: check_policy, _ct: validate });
+
+impl_validated_serde!(Ac17CpCiphertext);
+
+impl_wire!(Ac17KpCiphertext: Ac17Kp, Ciphertext { _attr, _ct });
+
+impl_json!(Ac17KpCiphertext { _attr, _ct });
+
+impl_validate!(Ac17KpCiphertext { _attr: check_unique, _ct: validate });
+
+impl_validated_serde!(Ac17KpCiphertext);
+
+impl_wire!(Ac17Encapsulation { _c_0, _c, _c_p, _hash, _kdf; _issuer });
+
+impl_json!(Ac17Encapsulation { _c_0, _c: tuples(JSON_C), _c_p, _hash, _kdf, _issuer });
+
+impl_validate!(Ac17Encapsulation {
+    _c_0: [check_g2; ASSUMPTION_SIZE + 1],
+    _c: check_components,
+    _c_p: check_gt,
+    _issuer: check_issuer_length,
+});
+
+impl_wire!(Ac17CpEncapsulation: Ac17Cp, Encapsulation { _policy, _enc });
+
+impl_json!(Ac17CpEncapsulation { _policy: tuple(POLICY_FIELDS), _enc });
+
+impl_validate!(Ac17CpEncapsulation { _policy: check_policy, _enc: validate });
+
+impl_validated_serde!(Ac17CpEncapsulation);
+
+impl_wire!(Ac17KpEncapsulation: Ac17Kp, Encapsulation { _attr, _enc });
+
+impl_json!(Ac17KpEncapsulation { _attr, _enc });
+
+impl_validate!(Ac17KpEncapsulation { _attr: check_unique, _enc: validate });
+
+impl_validated_serde!(Ac17KpEncapsulation);
+
+impl_wire!(Ac17SecretKey { _k_0, _k, _k_p, _hash; _issuer });
+
+impl_json!(Ac17SecretKey { _k_0, _k: tuples(JSON_K), _k_p, _hash, _issuer });
+
+impl_validate!(Ac17SecretKey {
+    _k_0: [check_g2; ASSUMPTION_SIZE + 1],
+    _k: check_components,
+    _k_p: [check_g1],
+    _issuer: check_issuer_length,
+});
+
+impl_wire!(Ac17KpSecretKey: Ac17Kp, SecretKey { _policy, _sk });
+
+impl_json!(Ac17KpSecretKey { _policy: tuple(POLICY_FIELDS), _sk });
+
+impl_validate!(Ac17KpSecretKey {
+    _policy: check_policy,
+    _sk: validate,
+} |_self| {
+    // only CP-ABE keys have a _k_p component
+    check_arity("_k_p", _self._sk._k_p.len(), 0)?;
+});
+
+impl_validated_serde!(Ac17KpSecretKey);
+
+impl_wire!(Ac17CpSecretKey: Ac17Cp, SecretKey { _attr, _sk });
+
+impl_json!(Ac17CpSecretKey { _attr, _sk });
+
+impl_validate!(Ac17CpSecretKey {
+    _attr: check_unique,
+    _sk: validate,
+} |_self| {
+    check_arity("_k_p", _self._sk._k_p.len(), ASSUMPTION_SIZE + 1)?;
+});
+
+impl_validated_serde!(Ac17CpSecretKey);
+
 /// The setup algorithm of both AC17CP and AC17KP. Generates an Ac17PublicKey and an Ac17MasterKey.
 pub fn setup() -> (Ac17PublicKey, Ac17MasterKey) {
     // random number generator
@@ -174,12 +448,13 @@ pub fn setup() -> (Ac17PublicKey, Ac17MasterKey) {
 ///	* `msk` - A Master Key (MSK), generated by the function setup()
 ///	* `attributes` - A Vector of String attributes assigned to this user key
 ///
-pub fn cp_keygen(msk: &Ac17MasterKey, attributes: &Vec<String>) -> Option<Ac17CpSecretKey> {
+pub fn cp_keygen(msk: &Ac17MasterKey, attributes: &[String]) -> Result<Ac17CpSecretKey, RabeError> {
     // if no attibutes or an empty policy
     // maybe add empty msk also here
     if attributes.is_empty() {
-        return None;
+        return Err(RabeError::new("Error in ac17/cp_keygen: no attributes given."));
     }
+    let attributes = &canonicalize(attributes)?;
     // random number generator
     let mut _rng = rand::thread_rng();
     // pick randomness
@@ -212,11 +487,7 @@ pub fn cp_keygen(msk: &Ac17MasterKey, attributes: &Vec<String>) -> Option<Ac17Cp
             let mut _prod = G1::zero();
             let _a_t = _a[_t].inverse().unwrap();
             for _l in 0usize..(ASSUMPTION_SIZE + 1) {
-                let mut _hash = String::new();
-                _hash.push_str(&_attr);
-                _hash.push_str(&_l.to_string());
-                _hash.push_str(&_t.to_string());
-                _prod = _prod + (blake2b_hash_g1(msk._g, &_hash) * (_br[_l] * _a_t));
+                _prod = _prod + (hash_attribute(HashVersion::CURRENT, msk._g, _attr, _l, _t) * (_br[_l] * _a_t));
             }
             _prod = _prod + (msk._g * (_sigma_attr * _a_t));
             _key.push(_prod);
@@ -232,19 +503,15 @@ pub fn cp_keygen(msk: &Ac17MasterKey, attributes: &Vec<String>) -> Option<Ac17Cp
         let mut _prod = _g_k[_t];
         let _a_t = _a[_t].inverse().unwrap();
         for _l in 0usize..(ASSUMPTION_SIZE + 1) {
-            let mut _hash = String::new();
-            _hash.push_str(&String::from("01"));
-            _hash.push_str(&_l.to_string());
-            _hash.push_str(&_t.to_string());
-            _prod = _prod + (blake2b_hash_g1(msk._g, &_hash) * (_br[_l] * _a_t));
+            _prod = _prod + (hash_column(HashVersion::CURRENT, msk._g, 1, _l, _t) * (_br[_l] * _a_t));
         }
         _prod = _prod + (msk._g * (_sigma * _a_t));
         _k_p.push(_prod);
     }
     _k_p.push(_g_k[ASSUMPTION_SIZE] + (msk._g * _sigma.neg()));
     let _attr = attributes.clone();
-    let _sk = Ac17SecretKey {_k_0, _k, _k_p};
-    return Some(Ac17CpSecretKey {_attr, _sk});
+    let _sk = Ac17SecretKey {_k_0, _k, _k_p, _hash: HashVersion::CURRENT, _issuer: msk.public_key().fingerprint()};
+    Ok(Ac17CpSecretKey {_attr, _sk})
 }
 
 /// The encrypt algorithm of AC17CP. Generates an Ac17CpCiphertext using an Ac17PublicKey, an access policy given as String and some plaintext data given as [u8].
@@ -261,12 +528,91 @@ pub fn cp_encrypt(
     plaintext: &[u8],
     language: PolicyLanguage,
 ) -> Result<Ac17CpCiphertext, RabeError> {
+    cp_encrypt_with_suite(pk, policy, plaintext, language, AeadSuite::default())
+}
+
+/// Same as cp_encrypt(), but encrypts the plaintext with the given AEAD suite.
+///
+/// # Arguments
+///
+///	* `pk` - A Public Key (PK), generated by the function setup()
+///	* `policy` - An access policy given as JSON String
+///	* `plaintext` - plaintext data given as a Vector of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn cp_encrypt_with_suite(
+    pk: &Ac17PublicKey,
+    policy: &String,
+    plaintext: &[u8],
+    language: PolicyLanguage,
+    _suite: AeadSuite,
+) -> Result<Ac17CpCiphertext, RabeError> {
+    let (_key, _enc) = cp_encapsulate(pk, policy, language)?;
+    cp_seal(&_key, _enc, plaintext, _suite)
+}
+
+/// Encrypts plaintext data under the SymmetricKey of an Ac17CpEncapsulation, see cp_encapsulate().
+///
+/// # Arguments
+///
+///	* `_key` - The SymmetricKey of `_enc`
+///	* `_enc` - An AC17CP Encapsulation
+///	* `plaintext` - plaintext data given as a slice of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn cp_seal(_key: &SymmetricKey, _enc: Ac17CpEncapsulation, plaintext: &[u8], _suite: AeadSuite) -> Result<Ac17CpCiphertext, RabeError> {
+    //Encrypt plaintext using derived key from secret
+    let _ct = encrypt_symmetric_key(_key, SchemeId::Ac17Cp, _suite, &policy_header(&_enc._policy.0, _enc._policy.1), &header_elements(&_enc._enc), plaintext)?;
+    Ok(Ac17CpCiphertext {
+        _policy: _enc._policy,
+        _ct: _enc._enc.into_ciphertext(_ct, _suite),
+    })
+}
+
+/// The encapsulate algorithm of AC17CP. Generates a new random SymmetricKey and its Ac17CpEncapsulation under an access policy.
+///
+/// # Arguments
+///
+///	* `pk` - A Public Key (PK), generated by the function setup()
+///	* `policy` - An access policy given as String
+///	* `language` - The language the policy is written in
+///
+pub fn cp_encapsulate(
+    pk: &Ac17PublicKey,
+    policy: &String,
+    language: PolicyLanguage,
+) -> Result<(SymmetricKey, Ac17CpEncapsulation), RabeError> {
     // random number generator
     let mut _rng = rand::thread_rng();
+    // random msg
+    let _msg: Gt = _rng.gen();
+    let _enc = cp_encapsulate_with(pk, policy, language, &_msg, &mut _rng)?;
+    let _key = SymmetricKey::derive(KdfVersion::CURRENT, &_msg, SchemeId::Ac17Cp, &policy_header(policy, language));
+    Ok((_key, _enc))
+}
+
+/// Encapsulates a given Gt message under an access policy, drawing all randomness from `_rng`.
+///
+/// # Arguments
+///
+///	* `pk` - A Public Key (PK), generated by the function setup()
+///	* `policy` - An access policy given as String
+///	* `language` - The language the policy is written in
+///	* `_msg` - The Gt message the SymmetricKey is derived from
+///	* `_rng` - The random number generator
+///
+pub fn cp_encapsulate_with<R: Rng>(
+    pk: &Ac17PublicKey,
+    policy: &String,
+    language: PolicyLanguage,
+    _msg: &Gt,
+    _rng: &mut R,
+) -> Result<Ac17CpEncapsulation, RabeError> {
     match parse(policy, language) {
         Ok(_policy) => {
+            SchemeId::Ac17Cp.check_policy(&_policy)?;
             // an msp policy from the given String
-            let msp: AbePolicy = AbePolicy::from_policy(&_policy).unwrap();
+            let msp: AbePolicy = AbePolicy::from_policy(&_policy)?;
             let _num_cols = msp._m[0].len();
             let _num_rows = msp._m.len();
             // pick randomness
@@ -289,20 +635,10 @@ pub fn cp_encrypt(
             let mut _hash_table: Vec<Vec<Vec<G1>>> = Vec::new();
             for _j in 0usize.._num_cols {
                 let mut _x: Vec<Vec<G1>> = Vec::new();
-                let mut _hash1 = String::new();
-                _hash1.push_str(&String::from("0"));
-                _hash1.push_str(&(_j + 1).to_string());
                 for _l in 0usize..(ASSUMPTION_SIZE + 1) {
                     let mut _y: Vec<G1> = Vec::new();
-                    let mut _hash2 = String::new();
-                    _hash2.push_str(&_hash1);
-                    _hash2.push_str(&_l.to_string());
                     for _t in 0usize..ASSUMPTION_SIZE {
-                        let mut _hash3 = String::new();
-                        _hash3.push_str(&_hash2);
-                        _hash3.push_str(&_t.to_string());
-                        let _hashed_value = blake2b_hash_g1(pk._g, &_hash3);
-                        _y.push(_hashed_value);
+                        _y.push(hash_column(HashVersion::CURRENT, pk._g, _j + 1, _l, _t));
                     }
                     _x.push(_y)
                 }
@@ -314,16 +650,14 @@ pub fn cp_encrypt(
                 for _l in 0usize..(ASSUMPTION_SIZE + 1) {
                     let mut _prod = G1::zero();
                     for _t in 0usize..ASSUMPTION_SIZE {
-                        let mut _hash = String::new();
-                        _hash.push_str(&msp._pi[_i]);
-                        _hash.push_str(&_l.to_string());
-                        _hash.push_str(&_t.to_string());
-                        let mut _prod1 = blake2b_hash_g1(pk._g, &_hash);
+                        let mut _prod1 = hash_attribute(HashVersion::CURRENT, pk._g, &msp._pi[_i], _l, _t);
                         for _j in 0usize.._num_cols {
                             if msp._m[_i][_j] == 1 {
                                 _prod1 = _prod1 + _hash_table[_j][_l][_t];
                             } else if msp._m[_i][_j] == -1 {
                                 _prod1 = _prod1 - _hash_table[_j][_l][_t];
+                            } else if msp._m[_i][_j] != 0 {
+                                _prod1 = _prod1 + (_hash_table[_j][_l][_t] * i64_to_fr(msp._m[_i][_j]));
                             }
                         }
                         _prod = _prod + (_prod1 * _s[_t]);
@@ -336,14 +670,10 @@ pub fn cp_encrypt(
             for _i in 0usize..ASSUMPTION_SIZE {
                 _c_p = _c_p * (pk._e_gh_ka[_i].pow(_s[_i]));
             }
-            // random msg
-            let _msg: Gt = _rng.gen();
-            let _ct = encrypt_symmetric(&_msg, &plaintext.to_vec()).unwrap();
-            _c_p = _c_p * _msg;
-            //Encrypt plaintext using derived key from secret
-            Ok(Ac17CpCiphertext {
+            _c_p = _c_p * *_msg;
+            Ok(Ac17CpEncapsulation {
                 _policy: (policy.to_string(), language),
-                _ct: Ac17Ciphertext { _c_0, _c, _c_p, _ct},
+                _enc: Ac17Encapsulation { _c_0, _c, _c_p, _hash: HashVersion::CURRENT, _kdf: KdfVersion::CURRENT, _issuer: pk.fingerprint() },
             })
         },
         Err(e) => Err(e)
@@ -358,40 +688,74 @@ pub fn cp_encrypt(
 ///	* `ct` - An AC17CP Ciphertext
 ///
 pub fn cp_decrypt(sk: &Ac17CpSecretKey, ct: &Ac17CpCiphertext) -> Result<Vec<u8>, RabeError> {
-    match parse(ct._policy.0.as_ref(), ct._policy.1) {
+    let _key = cp_decapsulate(sk, &ct.encapsulation())?;
+    cp_open(&_key, ct)
+}
+
+/// Decrypts an Ac17CpCiphertext under the SymmetricKey of its encapsulation, see cp_decapsulate().
+///
+/// # Arguments
+///
+///	* `_key` - The SymmetricKey of the encapsulation of `ct`
+///	* `ct` - An AC17CP Ciphertext
+///
+pub fn cp_open(_key: &SymmetricKey, ct: &Ac17CpCiphertext) -> Result<Vec<u8>, RabeError> {
+    // Decrypt plaintext using derived secret from cp-abe scheme
+    decrypt_symmetric_key(ct._ct._kdf, _key, SchemeId::Ac17Cp, ct._ct._suite, &policy_header(&ct._policy.0, ct._policy.1), &header_elements(&ct._ct.encapsulation()), &ct._ct._ct)
+}
+
+/// The decapsulate algorithm of AC17CP. Recovers the SymmetricKey of an Ac17CpEncapsulation with a matching Ac17CpSecretKey.
+///
+/// # Arguments
+///
+///	* `sk` - A Secret Key (SK), generated by the function cp_keygen()
+///	* `enc` - An AC17CP Encapsulation, generated by the function cp_encapsulate()
+///
+pub fn cp_decapsulate(sk: &Ac17CpSecretKey, enc: &Ac17CpEncapsulation) -> Result<SymmetricKey, RabeError> {
+    let _msg = cp_decapsulate_message(sk, enc)?;
+    Ok(SymmetricKey::derive(enc._enc._kdf, &_msg, SchemeId::Ac17Cp, &policy_header(&enc._policy.0, enc._policy.1)))
+}
+
+/// Recovers the Gt message of an Ac17CpEncapsulation with a matching Ac17CpSecretKey.
+///
+/// # Arguments
+///
+///	* `sk` - A Secret Key (SK), generated by the function cp_keygen()
+///	* `enc` - An AC17CP Encapsulation
+///
+pub fn cp_decapsulate_message(sk: &Ac17CpSecretKey, enc: &Ac17CpEncapsulation) -> Result<Gt, RabeError> {
+    sk._sk._hash.check(enc._enc._hash)?;
+    check_issuer(&sk._sk._issuer, &enc._enc._issuer)?;
+    match parse(enc._policy.0.as_ref(), enc._policy.1) {
         Ok(pol) => {
             return if traverse_policy(&sk._attr, &pol, PolicyType::Leaf) == false {
                 Err(RabeError::new("Error in cp_decrypt: attributes in SK do not match policy in CT."))
             } else {
-                match calc_pruned(&sk._attr, &pol, None) {
-                    Err(e) => Err(e),
-                    Ok((_match, _list)) => {
-                        if _match {
+                // the rows of the ciphertext follow the rows of the msp
+                let msp: AbePolicy = AbePolicy::from_policy(&pol)?;
+                match msp.coefficients(&sk._attr) {
+                    None => Err(RabeError::new("Error: attributes in sk do not match policy in ct.")),
+                    Some(_coeffs) => {
+                        if enc._enc._c.len() == msp._m.len() {
                             let mut _prod1_gt = Gt::one();
                             let mut _prod2_gt = Gt::one();
                             for _i in 0usize..(ASSUMPTION_SIZE + 1) {
                                 let mut _prod_h = G1::zero();
                                 let mut _prod_g = G1::zero();
-                                for _current in _list.iter() {
-                                    for _attr in ct._ct._c.iter() {
-                                        if _attr.0 == _current.to_string() {
-                                            _prod_g = _prod_g + _attr.1[_i];
-                                        }
-                                    }
+                                for (_row, _gamma) in _coeffs.iter() {
+                                    _prod_g = _prod_g + (enc._enc._c[*_row].1[_i] * *_gamma);
                                     for _attr in sk._sk._k.iter() {
-                                        if _attr.0 == _current.to_string() {
-                                            _prod_h = _prod_h + _attr.1[_i];
+                                        if _attr.0 == msp._pi[*_row] {
+                                            _prod_h = _prod_h + (_attr.1[_i] * *_gamma);
                                         }
                                     }
                                 }
-                                _prod1_gt = _prod1_gt * pairing(sk._sk._k_p[_i] + _prod_h, ct._ct._c_0[_i]);
+                                _prod1_gt = _prod1_gt * pairing(sk._sk._k_p[_i] + _prod_h, enc._enc._c_0[_i]);
                                 _prod2_gt = _prod2_gt * pairing(_prod_g, sk._sk._k_0[_i]);
                             }
-                            let _msg = ct._ct._c_p * (_prod2_gt * _prod1_gt.inverse());
-                            // Decrypt plaintext using derived secret from cp-abe scheme
-                            decrypt_symmetric(&_msg, &ct._ct._ct)
+                            Ok(enc._enc._c_p * (_prod2_gt * _prod1_gt.inverse()))
                         } else {
-                            Err(RabeError::new("Error: attributes in sk do not match policy in ct."))
+                            Err(RabeError::new("Error in cp_decrypt: ciphertext does not match its policy."))
                         }
                     }
                 }
@@ -416,8 +780,9 @@ pub fn kp_keygen(
     let mut _rng = rand::thread_rng();
     match parse(policy, lang) {
         Ok(pol) => {
+            SchemeId::Ac17Kp.check_policy(&pol)?;
             // an msp policy from the given String
-            let msp: AbePolicy = AbePolicy::from_policy(&pol).unwrap();
+            let msp: AbePolicy = AbePolicy::from_policy(&pol)?;
             let _num_cols = msp._m[0].len();
             let _num_rows = msp._m.len();
             // pick randomness
@@ -455,36 +820,29 @@ pub fn kp_keygen(
                     let mut _prod = G1::zero();
                     let _a_t = _a[_t].inverse().unwrap();
                     for _l in 0usize..(ASSUMPTION_SIZE + 1) {
-                        let mut _hash = String::new();
-                        _hash.push_str(&msp._pi[_i]);
-                        _hash.push_str(&_l.to_string());
-                        _hash.push_str(&_t.to_string());
-                        _prod = _prod + (blake2b_hash_g1(msk._g, &_hash) * (_br[_l] * _a_t));
+                        _prod = _prod + (hash_attribute(HashVersion::CURRENT, msk._g, &msp._pi[_i], _l, _t) * (_br[_l] * _a_t));
                     }
                     _prod = _prod + (msk._g * (_sigma_attr * _a_t));
                     if msp._m[_i][0] == 1 {
                         _prod = _prod + (msk._g_k[_t]);
                     } else if msp._m[_i][0] == -1 {
                         _prod = _prod - (msk._g_k[_t]);
+                    } else if msp._m[_i][0] != 0 {
+                        _prod = _prod + (msk._g_k[_t] * i64_to_fr(msp._m[_i][0]));
                     }
                     let mut _temp = G1::zero();
                     for _j in 1usize.._num_cols {
                         // sum term of _sk_it
-                        let mut _hash0 = String::new();
-                        _hash0.push_str(&String::from("0"));
-                        _hash0.push_str(&_j.to_string());
                         for _l in 0usize..(ASSUMPTION_SIZE + 1) {
-                            let mut _hash1 = String::new();
-                            _hash1.push_str(&_hash0);
-                            _hash1.push_str(&_l.to_string());
-                            _hash1.push_str(&_t.to_string());
-                            _temp = _temp + (blake2b_hash_g1(msk._g, &_hash1) * (_br[_l] * _a_t));
+                            _temp = _temp + (hash_column(HashVersion::CURRENT, msk._g, _j, _l, _t) * (_br[_l] * _a_t));
                         }
                         _temp = _temp + (msk._g * _sigma_prime[_j - 1].neg());
                         if msp._m[_i][_j] == 1 {
                             _prod = _prod + _temp;
                         } else if msp._m[_i][_j] == -1 {
                             _prod = _prod - _temp;
+                        } else if msp._m[_i][_j] != 0 {
+                            _prod = _prod + (_temp * i64_to_fr(msp._m[_i][_j]));
                         }
                     }
                     _key.push(_prod);
@@ -495,6 +853,8 @@ pub fn kp_keygen(
                     _sk_i3 = _sk_i3 + (msk._g_k[ASSUMPTION_SIZE]);
                 } else if msp._m[_i][0] == -1 {
                     _sk_i3 = _sk_i3 - (msk._g_k[ASSUMPTION_SIZE]);
+                } else if msp._m[_i][0] != 0 {
+                    _sk_i3 = _sk_i3 + (msk._g_k[ASSUMPTION_SIZE] * i64_to_fr(msp._m[_i][0]));
                 }
                 // sum term of _sk_i3
                 for _j in 1usize.._num_cols {
@@ -502,6 +862,8 @@ pub fn kp_keygen(
                         _sk_i3 = _sk_i3 + (msk._g * _sigma_prime[_j - 1].neg());
                     } else if msp._m[_i][_j] == -1 {
                         _sk_i3 = _sk_i3 - (msk._g * _sigma_prime[_j - 1].neg());
+                    } else if msp._m[_i][_j] != 0 {
+                        _sk_i3 = _sk_i3 + (msk._g * (_sigma_prime[_j - 1].neg() * i64_to_fr(msp._m[_i][_j])));
                     }
                 }
                 _key.push(_sk_i3);
@@ -509,7 +871,7 @@ pub fn kp_keygen(
             }
             Ok(Ac17KpSecretKey {
                 _policy: (policy.to_string(), lang),
-                _sk: Ac17SecretKey { _k_0, _k, _k_p: Vec::new()},
+                _sk: Ac17SecretKey { _k_0, _k, _k_p: Vec::new(), _hash: HashVersion::CURRENT, _issuer: msk.public_key().fingerprint() },
             })
         },
         Err(e) => Err(e)
@@ -526,11 +888,85 @@ pub fn kp_keygen(
 ///
 pub fn kp_encrypt(
     pk: &Ac17PublicKey,
-    attributes: &Vec<String>,
+    attributes: &[String],
+    _plaintext: &[u8],
+) -> Result<Ac17KpCiphertext, RabeError> {
+    kp_encrypt_with_suite(pk, attributes, _plaintext, AeadSuite::default())
+}
+
+/// Same as kp_encrypt(), but encrypts the plaintext with the given AEAD suite.
+///
+/// # Arguments
+///
+///	* `pk` - A Public Key (MSK), generated by the function setup()
+///	* `attributes` - A set of attributes given as Vec<String>
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn kp_encrypt_with_suite(
+    pk: &Ac17PublicKey,
+    attributes: &[String],
     _plaintext: &[u8],
-) -> Option<Ac17KpCiphertext> {
+    _suite: AeadSuite,
+) -> Result<Ac17KpCiphertext, RabeError> {
+    let (_key, _enc) = kp_encapsulate(pk, attributes)?;
+    kp_seal(&_key, _enc, _plaintext, _suite)
+}
+
+/// Encrypts plaintext data under the SymmetricKey of an Ac17KpEncapsulation, see kp_encapsulate().
+///
+/// # Arguments
+///
+///	* `_key` - The SymmetricKey of `_enc`
+///	* `_enc` - An AC17KP Encapsulation
+///	* `_plaintext` - plaintext data given as a slice of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn kp_seal(_key: &SymmetricKey, _enc: Ac17KpEncapsulation, _plaintext: &[u8], _suite: AeadSuite) -> Result<Ac17KpCiphertext, RabeError> {
+    //Encrypt plaintext using derived key from secret
+    let _ct = encrypt_symmetric_key(_key, SchemeId::Ac17Kp, _suite, &attribute_header(&_enc._attr), &header_elements(&_enc._enc), _plaintext)?;
+    Ok(Ac17KpCiphertext {
+        _attr: _enc._attr,
+        _ct: _enc._enc.into_ciphertext(_ct, _suite),
+    })
+}
+
+/// The encapsulate algorithm of AC17KP. Generates a new random SymmetricKey and its Ac17KpEncapsulation under a set of attributes.
+///
+/// # Arguments
+///
+///	* `pk` - A Public Key (PK), generated by the function setup()
+///	* `attributes` - A set of attributes given as Vec<String>
+///
+pub fn kp_encapsulate(
+    pk: &Ac17PublicKey,
+    attributes: &[String],
+) -> Result<(SymmetricKey, Ac17KpEncapsulation), RabeError> {
     // random number generator
     let mut _rng = rand::thread_rng();
+    // random msg
+    let _msg: Gt = _rng.gen();
+    let _enc = kp_encapsulate_with(pk, attributes, &_msg, &mut _rng)?;
+    let _key = SymmetricKey::derive(KdfVersion::CURRENT, &_msg, SchemeId::Ac17Kp, &attribute_header(&_enc._attr));
+    Ok((_key, _enc))
+}
+
+/// Encapsulates a given Gt message under a set of attributes, drawing all randomness from `_rng`.
+///
+/// # Arguments
+///
+///	* `pk` - A Public Key (PK), generated by the function setup()
+///	* `attributes` - A set of attributes given as Vec<String>
+///	* `_msg` - The Gt message the SymmetricKey is derived from
+///	* `_rng` - The random number generator
+///
+pub fn kp_encapsulate_with<R: Rng>(
+    pk: &Ac17PublicKey,
+    attributes: &[String],
+    _msg: &Gt,
+    _rng: &mut R,
+) -> Result<Ac17KpEncapsulation, RabeError> {
+    let attributes = &canonicalize(attributes)?;
     // pick randomness
     let mut _s: Vec<Fr> = Vec::new();
     let mut _sum = Fr::zero();
@@ -553,13 +989,7 @@ pub fn kp_encrypt(
         for _l in 0usize..(ASSUMPTION_SIZE + 1) {
             let mut _prod = G1::zero();
             for _t in 0usize..ASSUMPTION_SIZE {
-                let mut _hash = String::new();
-                _hash.push_str(&_attr);
-                _hash.push_str(&_l.to_string());
-                _hash.push_str(&_t.to_string());
-
-                let mut _prod1 = blake2b_hash_g1(pk._g, &_hash);
-                _prod = _prod + (_prod1 * _s[_t]);
+                _prod = _prod + (hash_attribute(HashVersion::CURRENT, pk._g, _attr, _l, _t) * _s[_t]);
             }
             _ct.push(_prod);
         }
@@ -569,15 +999,11 @@ pub fn kp_encrypt(
     for _i in 0usize..ASSUMPTION_SIZE {
         _c_p = _c_p * (pk._e_gh_ka[_i].pow(_s[_i]));
     }
-    // random msg
-    let _msg: Gt = _rng.gen();
-    let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
-    _c_p = _c_p * _msg;
-    //Encrypt plaintext using derived key from secret
-    return Some(Ac17KpCiphertext {
+    _c_p = _c_p * *_msg;
+    Ok(Ac17KpEncapsulation {
         _attr: attributes.clone(),
-        _ct: Ac17Ciphertext {_c_0, _c, _c_p, _ct},
-    });
+        _enc: Ac17Encapsulation {_c_0, _c, _c_p, _hash: HashVersion::CURRENT, _kdf: KdfVersion::CURRENT, _issuer: pk.fingerprint()},
+    })
 }
 
 /// The decrypt algorithm of AC17KP. Reconstructs the original plaintext data as Vec<u8>, given a Ac17KpCiphertext with a matching Ac17KpSecretKey.
@@ -588,45 +1014,74 @@ pub fn kp_encrypt(
 ///	* `ct` - An AC17KP Ciphertext
 ///
 pub fn kp_decrypt(sk: &Ac17KpSecretKey, ct: &Ac17KpCiphertext) -> Result<Vec<u8>, RabeError> {
+    let _key = kp_decapsulate(sk, &ct.encapsulation())?;
+    kp_open(&_key, ct)
+}
+
+/// Decrypts an Ac17KpCiphertext under the SymmetricKey of its encapsulation, see kp_decapsulate().
+///
+/// # Arguments
+///
+///	* `_key` - The SymmetricKey of the encapsulation of `ct`
+///	* `ct` - An AC17KP Ciphertext
+///
+pub fn kp_open(_key: &SymmetricKey, ct: &Ac17KpCiphertext) -> Result<Vec<u8>, RabeError> {
+    // Decrypt plaintext using derived secret from kp-abe scheme
+    decrypt_symmetric_key(ct._ct._kdf, _key, SchemeId::Ac17Kp, ct._ct._suite, &attribute_header(&ct._attr), &header_elements(&ct._ct.encapsulation()), &ct._ct._ct)
+}
+
+/// The decapsulate algorithm of AC17KP. Recovers the SymmetricKey of an Ac17KpEncapsulation with a matching Ac17KpSecretKey.
+///
+/// # Arguments
+///
+///	* `sk` - A Secret Key (SK), generated by the function kp_keygen()
+///	* `enc` - An AC17KP Encapsulation, generated by the function kp_encapsulate()
+///
+pub fn kp_decapsulate(sk: &Ac17KpSecretKey, enc: &Ac17KpEncapsulation) -> Result<SymmetricKey, RabeError> {
+    let _msg = kp_decapsulate_message(sk, enc)?;
+    Ok(SymmetricKey::derive(enc._enc._kdf, &_msg, SchemeId::Ac17Kp, &attribute_header(&enc._attr)))
+}
+
+/// Recovers the Gt message of an Ac17KpEncapsulation with a matching Ac17KpSecretKey.
+///
+/// # Arguments
+///
+///	* `sk` - A Secret Key (SK), generated by the function kp_keygen()
+///	* `enc` - An AC17KP Encapsulation
+///
+pub fn kp_decapsulate_message(sk: &Ac17KpSecretKey, enc: &Ac17KpEncapsulation) -> Result<Gt, RabeError> {
+    sk._sk._hash.check(enc._enc._hash)?;
+    check_issuer(&sk._sk._issuer, &enc._enc._issuer)?;
     match parse(sk._policy.0.as_ref(), sk._policy.1) {
         Ok(pol) => {
-            return if traverse_policy(&ct._attr, &pol, PolicyType::Leaf) == false {
+            if !traverse_policy(&enc._attr, &pol, PolicyType::Leaf) {
                 Err(RabeError::new("Error in kp_decrypt: attributes in ct do not match policy in sk."))
             } else {
-                match calc_pruned(&ct._attr, &pol, None) {
-                    Err(e) => Err(e),
-                    Ok(_p) => {
-                        let (_match, _list) = _p;
-                        if _match {
+                // the rows of the secret key follow the rows of the msp
+                let msp: AbePolicy = AbePolicy::from_policy(&pol)?;
+                match msp.coefficients(&enc._attr) {
+                    None => Err(RabeError::new("Error in kp_decrypt: pruned attributes in sk do not match policy in ct.")),
+                    Some(_coeffs) => {
+                        if sk._sk._k.len() == msp._m.len() {
                             let mut _prod1_gt = Gt::one();
                             let mut _prod2_gt = Gt::one();
                             for _i in 0usize..(ASSUMPTION_SIZE + 1) {
                                 let mut _prod_h = G1::zero();
                                 let mut _prod_g = G1::zero();
-                                for _current in _list.iter() {
-                                    for _attr in ct._ct._c.iter() {
-                                        if _attr.0 == _current.to_string() {
-                                            _prod_g = _prod_g + _attr.1[_i];
-                                        }
-                                    }
-                                    for _attr in sk._sk._k.iter() {
-                                        if _attr.0 == _current.to_string() {
-                                            _prod_h = _prod_h + _attr.1[_i];
+                                for (_row, _gamma) in _coeffs.iter() {
+                                    _prod_h = _prod_h + (sk._sk._k[*_row].1[_i] * *_gamma);
+                                    for _attr in enc._enc._c.iter() {
+                                        if _attr.0 == msp._pi[*_row] {
+                                            _prod_g = _prod_g + (_attr.1[_i] * *_gamma);
                                         }
                                     }
                                 }
-                                // for _j in 0usize..ct._ct._c.len() {
-                                //     _prod_h = _prod_h + sk._sk._k[_j].1[_i];
-                                //     _prod_g = _prod_g + ct._ct._c[_j].1[_i];
-                                // }
-                                _prod1_gt = _prod1_gt * pairing(_prod_h, ct._ct._c_0[_i]);
+                                _prod1_gt = _prod1_gt * pairing(_prod_h, enc._enc._c_0[_i]);
                                 _prod2_gt = _prod2_gt * pairing(_prod_g, sk._sk._k_0[_i]);
                             }
-                            let _msg = ct._ct._c_p * (_prod2_gt * _prod1_gt.inverse());
-                            // Decrypt plaintext using derived secret from cp-abe scheme
-                            decrypt_symmetric(&_msg, &ct._ct._ct)
+                            Ok(enc._enc._c_p * (_prod2_gt * _prod1_gt.inverse()))
                         } else {
-                            Err(RabeError::new("Error in kp_decrypt: pruned attributes in sk do not match policy in ct."))
+                            Err(RabeError::new("Error in kp_decrypt: secret key does not match its policy."))
                         }
                     }
                 }
@@ -636,10 +1091,156 @@ pub fn kp_decrypt(sk: &Ac17KpSecretKey, ct: &Ac17KpCiphertext) -> Result<Vec<u8>
     }
 }
 
+/// The AC17 CP-ABE scheme, for use through the `CpAbe` trait
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ac17Cp;
+
+impl CpAbe for Ac17Cp {
+    const ID: SchemeId = SchemeId::Ac17Cp;
+
+    type PublicKey = Ac17PublicKey;
+    type MasterKey = Ac17MasterKey;
+    type SecretKey = Ac17CpSecretKey;
+    type Ciphertext = Ac17CpCiphertext;
+    type Encapsulation = Ac17CpEncapsulation;
+
+    fn setup(&self) -> (Ac17PublicKey, Ac17MasterKey) {
+        setup()
+    }
+
+    fn keygen(&self, _pk: &Ac17PublicKey, msk: &Ac17MasterKey, attributes: &[String]) -> Result<Ac17CpSecretKey, RabeError> {
+        cp_keygen(msk, attributes)
+    }
+
+    fn encrypt_with_suite(&self, pk: &Ac17PublicKey, policy: &str, language: PolicyLanguage, plaintext: &[u8], suite: AeadSuite) -> Result<Ac17CpCiphertext, RabeError> {
+        cp_encrypt_with_suite(pk, &policy.to_string(), plaintext, language, suite)
+    }
+
+    fn decrypt(&self, sk: &Ac17CpSecretKey, ct: &Ac17CpCiphertext) -> Result<Vec<u8>, RabeError> {
+        cp_decrypt(sk, ct)
+    }
+
+    fn encapsulate(&self, pk: &Ac17PublicKey, policy: &str, language: PolicyLanguage) -> Result<(SymmetricKey, Ac17CpEncapsulation), RabeError> {
+        cp_encapsulate(pk, &policy.to_string(), language)
+    }
+
+    fn decapsulate(&self, sk: &Ac17CpSecretKey, enc: &Ac17CpEncapsulation) -> Result<SymmetricKey, RabeError> {
+        cp_decapsulate(sk, enc)
+    }
+
+    fn explain(&self, sk: &Ac17CpSecretKey, ct: &Ac17CpCiphertext) -> Result<Explanation, RabeError> {
+        explain(&Policy::parse(&ct._policy.0, ct._policy.1)?, &sk._attr)
+    }
+}
+
+impl CpAbeCca for Ac17Cp {
+    fn encapsulate_with<R: Rng>(&self, pk: &Ac17PublicKey, policy: &str, language: PolicyLanguage, msg: &Gt, rng: &mut R) -> Result<Ac17CpEncapsulation, RabeError> {
+        cp_encapsulate_with(pk, &policy.to_string(), language, msg, rng)
+    }
+
+    fn decapsulate_message(&self, sk: &Ac17CpSecretKey, enc: &Ac17CpEncapsulation) -> Result<Gt, RabeError> {
+        cp_decapsulate_message(sk, enc)
+    }
+
+    fn encapsulation_policy(&self, enc: &Ac17CpEncapsulation) -> (String, PolicyLanguage) {
+        enc._policy.clone()
+    }
+
+    fn encapsulation(&self, ct: &Ac17CpCiphertext) -> Ac17CpEncapsulation {
+        ct.encapsulation()
+    }
+
+    fn seal(&self, key: &SymmetricKey, enc: Ac17CpEncapsulation, plaintext: &[u8], suite: AeadSuite) -> Result<Ac17CpCiphertext, RabeError> {
+        cp_seal(key, enc, plaintext, suite)
+    }
+
+    fn open(&self, key: &SymmetricKey, ct: &Ac17CpCiphertext) -> Result<Vec<u8>, RabeError> {
+        cp_open(key, ct)
+    }
+}
+
+/// The AC17 KP-ABE scheme, for use through the `KpAbe` trait
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ac17Kp;
+
+impl KpAbe for Ac17Kp {
+    const ID: SchemeId = SchemeId::Ac17Kp;
+
+    type PublicKey = Ac17PublicKey;
+    type MasterKey = Ac17MasterKey;
+    type SecretKey = Ac17KpSecretKey;
+    type Ciphertext = Ac17KpCiphertext;
+    type Encapsulation = Ac17KpEncapsulation;
+
+    fn setup(&self) -> (Ac17PublicKey, Ac17MasterKey) {
+        setup()
+    }
+
+    fn keygen(&self, _pk: &Ac17PublicKey, msk: &Ac17MasterKey, policy: &str, language: PolicyLanguage) -> Result<Ac17KpSecretKey, RabeError> {
+        kp_keygen(msk, &policy.to_string(), language)
+    }
+
+    fn encrypt_with_suite(&self, pk: &Ac17PublicKey, attributes: &[String], plaintext: &[u8], suite: AeadSuite) -> Result<Ac17KpCiphertext, RabeError> {
+        kp_encrypt_with_suite(pk, attributes, plaintext, suite)
+    }
+
+    fn decrypt(&self, sk: &Ac17KpSecretKey, ct: &Ac17KpCiphertext) -> Result<Vec<u8>, RabeError> {
+        kp_decrypt(sk, ct)
+    }
+
+    fn encapsulate(&self, pk: &Ac17PublicKey, attributes: &[String]) -> Result<(SymmetricKey, Ac17KpEncapsulation), RabeError> {
+        kp_encapsulate(pk, attributes)
+    }
+
+    fn decapsulate(&self, sk: &Ac17KpSecretKey, enc: &Ac17KpEncapsulation) -> Result<SymmetricKey, RabeError> {
+        kp_decapsulate(sk, enc)
+    }
+
+    fn explain(&self, sk: &Ac17KpSecretKey, ct: &Ac17KpCiphertext) -> Result<Explanation, RabeError> {
+        explain(&Policy::parse(&sk._policy.0, sk._policy.1)?, &ct._attr)
+    }
+}
+
+impl KpAbeCca for Ac17Kp {
+    fn encapsulate_with<R: Rng>(&self, pk: &Ac17PublicKey, attributes: &[String], msg: &Gt, rng: &mut R) -> Result<Ac17KpEncapsulation, RabeError> {
+        kp_encapsulate_with(pk, attributes, msg, rng)
+    }
+
+    fn decapsulate_message(&self, sk: &Ac17KpSecretKey, enc: &Ac17KpEncapsulation) -> Result<Gt, RabeError> {
+        kp_decapsulate_message(sk, enc)
+    }
+
+    fn encapsulation_attributes(&self, enc: &Ac17KpEncapsulation) -> Vec<String> {
+        enc._attr.clone()
+    }
+
+    fn encapsulation(&self, ct: &Ac17KpCiphertext) -> Ac17KpEncapsulation {
+        ct.encapsulation()
+    }
+
+    fn seal(&self, key: &SymmetricKey, enc: Ac17KpEncapsulation, plaintext: &[u8], suite: AeadSuite) -> Result<Ac17KpCiphertext, RabeError> {
+        kp_seal(key, enc, plaintext, suite)
+    }
+
+    fn open(&self, key: &SymmetricKey, ct: &Ac17KpCiphertext) -> Result<Vec<u8>, RabeError> {
+        kp_open(key, ct)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use utils::wire::WireFormat;
+    use utils::validate::Validate;
+
+    #[test]
+    fn attribute_and_column_hashes() {
+        let (pk, _msk) = setup();
+        // attribute "01" and column 1 both concatenate to "01" + l + t
+        assert!(hash_attribute(HashVersion::Legacy, pk._g, "01", 0, 1) == hash_column(HashVersion::Legacy, pk._g, 1, 0, 1));
+        assert!(hash_attribute(HashVersion::CURRENT, pk._g, "01", 0, 1) != hash_column(HashVersion::CURRENT, pk._g, 1, 0, 1));
+    }
 
     #[test]
     fn kp_and() {
@@ -652,7 +1253,7 @@ mod tests {
         let policy = String::from(r#"{"name": "and", "children": [{"name": "A"}, {"name": "B"}]}"#);
         // kp-abe ciphertext
         let ct: Ac17KpCiphertext =
-            kp_encrypt(&pk, &vec!["A".to_string(), "B".to_string()], &plaintext).unwrap();
+            kp_encrypt(&pk, &["A".to_string(), "B".to_string()], &plaintext).unwrap();
         // a kp-abe SK key
         let sk: Ac17KpSecretKey = kp_keygen(&msk, &policy, PolicyLanguage::JsonPolicy).unwrap();
         // and now decrypt again
@@ -670,14 +1271,14 @@ mod tests {
         let policy = String::from(r#"{"name":"or", "children": [{"name": "and", "children": [{"name": "A"}, {"name": "B"}]}, {"name": "and", "children": [{"name": "C"}, {"name": "D"}]}]}"#);
         // kp-abe ciphertext
         let ct: Ac17KpCiphertext =
-            kp_encrypt(&pk, &vec!["A".to_string(), "B".to_string()], &plaintext).unwrap();
+            kp_encrypt(&pk, &["A".to_string(), "B".to_string()], &plaintext).unwrap();
         // a kp-abe SK key
         let sk: Ac17KpSecretKey = kp_keygen(&msk, &policy, PolicyLanguage::JsonPolicy).unwrap();
         // and now decrypt again
         assert_eq!(kp_decrypt(&sk, &ct).unwrap(), plaintext);
         // kp-abe ciphertext
         let ct: Ac17KpCiphertext =
-            kp_encrypt(&pk, &vec!["C".to_string(), "D".to_string()], &plaintext).unwrap();
+            kp_encrypt(&pk, &["C".to_string(), "D".to_string()], &plaintext).unwrap();
         // a kp-abe SK key
         let sk: Ac17KpSecretKey = kp_keygen(&msk, &policy, PolicyLanguage::JsonPolicy).unwrap();
         // and now decrypt again
@@ -694,11 +1295,30 @@ mod tests {
         // our policy
         let policy = String::from(r#"{"name": "or", "children": [{"name": "A"}, {"name": "B"}]}"#);
         // kp-abe ciphertext
-        let ct: Ac17KpCiphertext = kp_encrypt(&pk, &vec!["B".to_string()], &plaintext).unwrap();
+        let ct: Ac17KpCiphertext = kp_encrypt(&pk, &["B".to_string()], &plaintext).unwrap();
+        // a kp-abe SK key
+        let sk: Ac17KpSecretKey = kp_keygen(&msk, &policy, PolicyLanguage::JsonPolicy).unwrap();
+        // and now decrypt again
+        assert_eq!(kp_decrypt(&sk, &ct).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn kp_single() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy consists of a single attribute
+        let policy = String::from(r#"{"name": "admin"}"#);
+        // kp-abe ciphertext
+        let ct: Ac17KpCiphertext = kp_encrypt(&pk, &["admin".to_string(), "B".to_string()], &plaintext).unwrap();
+        let ct_no_match: Ac17KpCiphertext = kp_encrypt(&pk, &["B".to_string()], &plaintext).unwrap();
         // a kp-abe SK key
         let sk: Ac17KpSecretKey = kp_keygen(&msk, &policy, PolicyLanguage::JsonPolicy).unwrap();
         // and now decrypt again
         assert_eq!(kp_decrypt(&sk, &ct).unwrap(), plaintext);
+        assert!(kp_decrypt(&sk, &ct_no_match).is_err());
     }
 
     #[test]
@@ -711,7 +1331,7 @@ mod tests {
         // our policy
         let policy = String::from(r#"{"name": "or", "children": [{"name": "A"}, {"name": "B"}]}"#);
         // kp-abe ciphertext
-        let ct: Ac17KpCiphertext = kp_encrypt(&pk, &vec!["C".to_string()], &plaintext).unwrap();
+        let ct: Ac17KpCiphertext = kp_encrypt(&pk, &["C".to_string()], &plaintext).unwrap();
         // a kp-abe SK key
         let sk: Ac17KpSecretKey = kp_keygen(&msk, &policy, PolicyLanguage::JsonPolicy).unwrap();
         // and now decrypt again
@@ -730,11 +1350,49 @@ mod tests {
         // kp-abe ciphertext
         let ct: Ac17CpCiphertext = cp_encrypt(&pk, &policy, &plaintext, PolicyLanguage::JsonPolicy).unwrap();
         // a kp-abe SK key
-        let sk: Ac17CpSecretKey = cp_keygen(&msk, &vec!["A".to_string(), "B".to_string()]).unwrap();
+        let sk: Ac17CpSecretKey = cp_keygen(&msk, &["A".to_string(), "B".to_string()]).unwrap();
         // and now decrypt again
         assert_eq!(cp_decrypt(&sk, &ct).unwrap(), plaintext);
     }
 
+    #[test]
+    fn cp_and3() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy
+        let policy = String::from(r#""A" and "B" and "C""#);
+        // kp-abe ciphertext
+        let ct: Ac17CpCiphertext = cp_encrypt(&pk, &policy, &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        // a kp-abe SK key
+        let sk: Ac17CpSecretKey = cp_keygen(&msk, &["A".to_string(), "B".to_string(), "C".to_string()]).unwrap();
+        let sk_no_match: Ac17CpSecretKey = cp_keygen(&msk, &["A".to_string(), "B".to_string()]).unwrap();
+        // and now decrypt again
+        assert_eq!(cp_decrypt(&sk, &ct).unwrap(), plaintext);
+        assert!(cp_decrypt(&sk_no_match, &ct).is_err());
+    }
+
+    #[test]
+    fn cp_single() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy consists of a single attribute
+        let policy = String::from(r#""admin""#);
+        // cp-abe ciphertext
+        let ct: Ac17CpCiphertext = cp_encrypt(&pk, &policy, &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        // cp-abe SK keys
+        let sk: Ac17CpSecretKey = cp_keygen(&msk, &["admin".to_string(), "B".to_string()]).unwrap();
+        let sk_no_match: Ac17CpSecretKey = cp_keygen(&msk, &["B".to_string()]).unwrap();
+        // and now decrypt again
+        assert_eq!(cp_decrypt(&sk, &ct).unwrap(), plaintext);
+        assert!(cp_decrypt(&sk_no_match, &ct).is_err());
+    }
+
     #[test]
     fn cp_or() {
         // setup scheme
@@ -747,7 +1405,7 @@ mod tests {
         // kp-abe ciphertext
         let ct: Ac17CpCiphertext = cp_encrypt(&pk, &policy, &plaintext, PolicyLanguage::JsonPolicy).unwrap();
         // a matching kp-abe SK key
-        let sk_m1: Ac17CpSecretKey = cp_keygen(&msk, &vec!["A".to_string()]).unwrap();
+        let sk_m1: Ac17CpSecretKey = cp_keygen(&msk, &["A".to_string()]).unwrap();
         let pt = cp_decrypt(&sk_m1, &ct);
         assert_eq!(pt.is_ok(), true);
         assert_eq!(pt.unwrap(), plaintext);
@@ -767,7 +1425,7 @@ mod tests {
         // a kp-abe SK key
         let sk: Ac17CpSecretKey = cp_keygen(
             &msk,
-            &vec![
+            &[
                 "A".to_string(),
                 "B".to_string(),
                 "C".to_string(),
@@ -777,4 +1435,51 @@ mod tests {
         // and now decrypt again
         assert_eq!(cp_decrypt(&sk, &ct).unwrap(), plaintext);
     }
+
+    #[test]
+    fn issuers() {
+        let (pk, msk) = setup();
+        let (other_pk, other_msk) = setup();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        // the master key recomputes the public key, so keygen knows the issuer
+        assert!(msk.public_key() == pk);
+        let cp_sk: Ac17CpSecretKey = cp_keygen(&msk, &["A".to_string()]).unwrap();
+        let kp_sk: Ac17KpSecretKey = kp_keygen(&other_msk, &String::from(r#""A""#), PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(cp_sk._sk._issuer, pk.fingerprint());
+        assert_eq!(kp_sk._sk._issuer, other_pk.fingerprint());
+        let cp_ct: Ac17CpCiphertext = cp_encrypt(&pk, &String::from(r#""A""#), &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        let kp_ct: Ac17KpCiphertext = kp_encrypt(&pk, &["A".to_string()], &plaintext).unwrap();
+        assert_eq!(cp_decrypt(&cp_sk, &cp_ct).unwrap(), plaintext);
+        assert!(kp_decrypt(&kp_sk, &kp_ct).err().unwrap().key_mismatch().is_some());
+    }
+
+    #[test]
+    fn malformed_ciphertexts() {
+        use utils::validate::ValidationError;
+        let (pk, msk) = setup();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        let ct: Ac17CpCiphertext = cp_encrypt(&pk, &String::from(r#""A" and "B""#), &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        assert!(ct.validate().is_ok());
+        // a truncated _c_0 is rejected by validate() and by decoding instead of panicking in decrypt
+        let mut _truncated = ct.clone();
+        _truncated._ct._c_0.pop();
+        let _arity = ValidationError::Arity { _field: "_c_0".to_string(), _expected: ASSUMPTION_SIZE + 1, _found: ASSUMPTION_SIZE };
+        assert_eq!(_truncated.validate().unwrap_err().validation_error(), Some(&_arity));
+        assert_eq!(Ac17CpCiphertext::from_bytes(&_truncated.to_bytes()).err().unwrap().validation_error(), Some(&_arity));
+        assert!(serde_json::from_str::<Ac17CpCiphertext>(&serde_json::to_string(&_truncated).unwrap()).is_err());
+        let mut _identity = ct.clone();
+        _identity._ct._c_p = Gt::one();
+        assert_eq!(_identity.validate().unwrap_err().validation_error(), Some(&ValidationError::Identity { _field: "_c_p".to_string() }));
+        // serde does not check that points are on the curve
+        let mut _off_curve = serde_json::to_value(&ct).unwrap();
+        _off_curve["_ct"]["_c"][0][1][0]["y"] = _off_curve["_ct"]["_c"][0][1][0]["x"].clone();
+        assert!(serde_json::from_value::<Ac17CpCiphertext>(_off_curve).is_err());
+        let mut _policy = ct.clone();
+        _policy._policy.0 = String::from(r#""A" and"#);
+        assert!(_policy.validate().is_err());
+        // a valid ciphertext survives both encodings
+        let _json: Ac17CpCiphertext = serde_json::from_str(&serde_json::to_string(&ct).unwrap()).unwrap();
+        let sk: Ac17CpSecretKey = cp_keygen(&msk, &["A".to_string(), "B".to_string()]).unwrap();
+        assert_eq!(cp_decrypt(&sk, &_json).unwrap(), plaintext);
+    }
 }
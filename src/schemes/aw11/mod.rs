@@ -26,6 +26,7 @@ use std::string::String;
 use rand::Rng;
 use rabe_bn::{Fr, G1, G2, Gt, pairing};
 use utils::{
+    attribute::{canonical_attribute, canonicalize},
     secretsharing::{
         calc_coefficients,
         calc_pruned
@@ -33,14 +34,34 @@ use utils::{
     policy::msp::AbePolicy,
     tools::*,
     aes::*,
-    hash::blake2b_hash_g1
+    hash::HashVersion
 };
 use utils::policy::pest::{PolicyLanguage, parse, PolicyType};
 use utils::secretsharing::gen_shares_policy;
 use RabeError;
+use utils::wire::{WireFormat, WireKind};
+use utils::validate::{Validate, ValidationError, check_g1, check_g2, check_gt, check_fr, check_policy, check_issuer_length};
+use utils::fingerprint::{Fingerprint, check_issuer};
+use utils::json::{Json, JsonReader, JsonWriter, SchemaWriter, POLICY_FIELDS};
+use serde_json::Value;
+use schemes::{MultiAuthorityAbe, SchemeId};
+use utils::policy::{ast::Policy, explain::{explain, Explanation}};
+
+/// The JSON field names of the tuples in _attr of the public key
+const JSON_PK_ATTR: &[&str] = &["attribute", "e_gg_alpha", "g_y"];
+/// The JSON field names of the tuples in _attr of the master key
+const JSON_MK_ATTR: &[&str] = &["attribute", "alpha", "y"];
+/// The JSON field names of the tuples in _c
+const JSON_C: &[&str] = &["attribute", "c_1", "c_2", "c_3"];
+/// The JSON field names of the tuples in _attr of the secret key
+const JSON_SK_ATTR: &[&str] = &["attribute", "k"];
+
+/// The domain of the hash of a user's GID
+const HASH_GID: &str = "rabe/aw11/gid";
 
 /// An AW11 Global Parameters Key (GK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Aw11GlobalKey {
     pub _g1: G1,
     pub _g2: G2,
@@ -48,30 +69,75 @@ pub struct Aw11GlobalKey {
 
 /// An AW11 Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Aw11PublicKey {
     pub _attr: Vec<(String, Gt, G2)>,
 }
 
 /// An AW11 Master Key (MK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Aw11MasterKey {
     pub _attr: Vec<(String, Fr, Fr)>,
 }
 
 /// An AW11 Ciphertext (CT)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Aw11Ciphertext {
     pub _policy: (String, PolicyLanguage),
     pub _c_0: Gt,
     pub _c: Vec<(String, Gt, G2, G2)>,
     pub _ct: Vec<u8>,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    #[serde(default)]
+    pub _suite: AeadSuite,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
+}
+
+/// An AW11 key encapsulation: an Aw11Ciphertext without the symmetrically encrypted data
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
+pub struct Aw11Encapsulation {
+    pub _policy: (String, PolicyLanguage),
+    pub _c_0: Gt,
+    pub _c: Vec<(String, Gt, G2, G2)>,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
+}
+
+impl Aw11Ciphertext {
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> Aw11Encapsulation {
+        Aw11Encapsulation {
+            _policy: self._policy.clone(),
+            _c_0: self._c_0,
+            _c: self._c.clone(),
+            _kdf: self._kdf,
+            _issuer: self._issuer.clone(),
+        }
+    }
 }
 
 /// An AW11 Secret Key (SK)
+///
+/// Only the key depends on the hash of the GID, so ciphertexts carry no HashVersion.
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Aw11SecretKey {
     pub _gid: String,
     pub _attr: Vec<(String, G1)>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
 }
 
 /// A global Context for an AW11 Global Parameters Key (GP)
@@ -80,6 +146,147 @@ pub struct Aw11GlobalContext {
     pub _gk: Aw11GlobalKey,
 }
 
+impl_wire!(Aw11GlobalKey: Aw11, GlobalKey { _g1, _g2 });
+
+impl_json!(Aw11GlobalKey { _g1, _g2 });
+
+impl Fingerprint for Aw11GlobalKey {}
+
+impl_validate!(Aw11GlobalKey { _g1: check_g1, _g2: check_g2 });
+
+impl_validated_serde!(Aw11GlobalKey);
+
+impl_wire!(Aw11PublicKey: Aw11, PublicKey { _attr });
+
+impl_json!(Aw11PublicKey { _attr: tuples(JSON_PK_ATTR) });
+
+impl Fingerprint for Aw11PublicKey {}
+
+impl_validate!(Aw11PublicKey {
+    _attr: unique(0),
+} |_self| {
+    for (_, _e_gg_alpha, _g2_y) in _self._attr.iter() {
+        check_gt("_attr", _e_gg_alpha)?;
+        check_g2("_attr", _g2_y)?;
+    }
+});
+
+impl_validated_serde!(Aw11PublicKey);
+
+impl_wire!(Aw11MasterKey: Aw11, MasterKey { _attr });
+
+impl_json!(Aw11MasterKey { _attr: tuples(JSON_MK_ATTR) });
+
+impl Fingerprint for Aw11MasterKey {}
+
+impl_validate!(Aw11MasterKey {
+    _attr: unique(0),
+} |_self| {
+    for (_, _alpha, _y) in _self._attr.iter() {
+        check_fr("_attr", _alpha)?;
+        check_fr("_attr", _y)?;
+    }
+});
+
+impl_validated_serde!(Aw11MasterKey);
+
+impl_wire!(Aw11Ciphertext: Aw11, Ciphertext { _policy, _c_0, _c, _ct, _kdf, _suite; _issuer });
+
+impl_json!(Aw11Ciphertext {
+    _policy: tuple(POLICY_FIELDS), _c_0, _c: tuples(JSON_C), _ct, _kdf, _suite, _issuer
+});
+
+impl_validate!(Aw11Ciphertext {
+    _policy: check_policy,
+    _c_0: check_gt,
+    _issuer: check_issuer_length,
+} |_self| {
+    for (_, _c1, _c2, _c3) in _self._c.iter() {
+        check_gt("_c", _c1)?;
+        check_g2("_c", _c2)?;
+        check_g2("_c", _c3)?;
+    }
+});
+
+impl_validated_serde!(Aw11Ciphertext);
+
+impl_wire!(Aw11Encapsulation: Aw11, Encapsulation { _policy, _c_0, _c, _kdf; _issuer });
+
+impl_json!(Aw11Encapsulation { _policy: tuple(POLICY_FIELDS), _c_0, _c: tuples(JSON_C), _kdf, _issuer });
+
+impl_validate!(Aw11Encapsulation {
+    _policy: check_policy,
+    _c_0: check_gt,
+    _issuer: check_issuer_length,
+} |_self| {
+    for (_, _c1, _c2, _c3) in _self._c.iter() {
+        check_gt("_c", _c1)?;
+        check_g2("_c", _c2)?;
+        check_g2("_c", _c3)?;
+    }
+});
+
+impl_validated_serde!(Aw11Encapsulation);
+
+impl_wire!(Aw11SecretKey: Aw11, SecretKey { _gid, _attr, _hash; _issuer });
+
+impl_json!(Aw11SecretKey { _gid, _attr: tuples(JSON_SK_ATTR), _hash, _issuer });
+
+impl_validate!(Aw11SecretKey {
+    _attr: unique(0),
+    _issuer: check_issuer_length,
+} |_self| {
+    for (_, _k) in _self._attr.iter() {
+        check_g1("_attr", _k)?;
+    }
+});
+
+impl_validated_serde!(Aw11SecretKey);
+
+impl WireFormat for (Aw11PublicKey, Aw11MasterKey) {
+    const SCHEME: SchemeId = SchemeId::Aw11;
+    const KIND: WireKind = WireKind::AuthorityKey;
+}
+
+impl Json for (Aw11PublicKey, Aw11MasterKey) {
+    fn to_json(&self) -> Value {
+        let mut _writer = JsonWriter::new();
+        _writer
+            .field("pk", &self.0)
+            .field("mk", &self.1);
+        _writer.into_value()
+    }
+
+    fn from_json(_value: &Value) -> Result<(Aw11PublicKey, Aw11MasterKey), RabeError> {
+        let mut _reader = JsonReader::new(_value)?;
+        let _object = (_reader.field("pk")?, _reader.field("mk")?);
+        _reader.finish()?;
+        Ok(_object)
+    }
+
+    fn schema() -> Value {
+        let mut _schema = SchemaWriter::new();
+        _schema
+            .field::<Aw11PublicKey>("pk")
+            .field::<Aw11MasterKey>("mk");
+        _schema.into_value()
+    }
+}
+
+impl Fingerprint for (Aw11PublicKey, Aw11MasterKey) {}
+
+impl Validate for (Aw11PublicKey, Aw11MasterKey) {
+    fn validate(&self) -> Result<(), RabeError> {
+        self.0.validate()?;
+        self.1.validate()?;
+        // the public and the master key of an authority handle the same attributes
+        if !self.0._attr.iter().map(|_attr| &_attr.0).eq(self.1._attr.iter().map(|_attr| &_attr.0)) {
+            return Err(ValidationError::Inconsistent { _field: "_attr".to_string(), _reason: "the public and the master key have different attributes".to_string() }.into());
+        }
+        Ok(())
+    }
+}
+
 /// Sets up a new AW11 Scheme by creating a Global Parameters Key (GK)
 pub fn setup() -> Aw11GlobalKey {
     // random number generator
@@ -102,16 +309,16 @@ pub fn setup() -> Aw11GlobalKey {
 ///
 /// # Remarks
 ///
-/// In this scheme, all attributes are converted to upper case bevor calculation, i.e. they are case insensitive
-/// This means that all attributes "tEsT", "TEST" and "test" are the same in this scheme.
+/// Attributes are case sensitive and stored in their canonical encoding (see `utils::attribute`),
+/// i.e. "dept = finance" and "dept=finance" are the same attribute, but "TEST" and "test" are not.
 pub fn authgen(
     _gk: &Aw11GlobalKey,
     _attributes: &Vec<String>,
-) -> Option<(Aw11PublicKey, Aw11MasterKey)> {
+) -> Result<(Aw11PublicKey, Aw11MasterKey), RabeError> {
     // if no attibutes or an empty policy
     // maybe add empty msk also here
     if _attributes.is_empty() {
-        return None;
+        return Err(RabeError::new("Error in aw11/authgen: no attributes given."));
     }
     // random number generator
     let mut _rng = rand::thread_rng();
@@ -119,19 +326,19 @@ pub fn authgen(
     let mut _sk: Vec<(String, Fr, Fr)> = Vec::new(); //dictionary of {s: {alpha_i, y_i}}
     let mut _pk: Vec<(String, Gt, G2)> = Vec::new(); // dictionary of {s: {e(g,g)^alpha_i, g1^y_i}}
     // now calculate attribute values
-    for _attr in _attributes {
+    for _attr in canonicalize(_attributes)? {
         // calculate randomness
         let _alpha_i:Fr = _rng.gen();
         let _y_i:Fr = _rng.gen();
-        _sk.push((_attr.clone().to_uppercase(), _alpha_i, _y_i));
+        _sk.push((_attr.clone(), _alpha_i, _y_i));
         _pk.push((
-            _attr.clone().to_uppercase(),
+            _attr,
             pairing(_gk._g1, _gk._g2).pow(_alpha_i),
             _gk._g2 * _y_i,
         ));
     }
     // return PK and MSK
-    return Some((Aw11PublicKey { _attr: _pk }, Aw11MasterKey { _attr: _sk }));
+    Ok((Aw11PublicKey { _attr: _pk }, Aw11MasterKey { _attr: _sk }))
 }
 
 /// Sets up and generates a new User by creating a secret user key (SK). The key is created for a user with a given "name" on the given set of attributes.
@@ -145,8 +352,7 @@ pub fn authgen(
 ///
 /// # Remarks
 ///
-/// In this scheme, all attributes are converted to upper case bevor calculation, i.e. they are case insensitive
-/// This means that all attributes "tEsT", "TEST" and "test" are treated the same in this scheme.
+/// Attributes are case sensitive and stored in their canonical encoding (see `utils::attribute`).
 pub fn keygen(
     _gk: &Aw11GlobalKey,
     _msk: &Aw11MasterKey,
@@ -160,6 +366,8 @@ pub fn keygen(
     let mut _sk: Aw11SecretKey = Aw11SecretKey {
         _gid: _name.clone(),
         _attr: Vec::new(),
+        _hash: HashVersion::CURRENT,
+        _issuer: _gk.fingerprint(),
     };
     for _attribute in _attributes {
         add_attribute(_gk, _msk, _attribute, &mut _sk);
@@ -185,15 +393,20 @@ pub fn add_attribute(
     if _attribute.is_empty() || _sk._gid.is_empty() {
         return;
     }
-    let _h_g1 = blake2b_hash_g1(_gk._g1, &_sk._gid);
-    let _auth_attribute = _msk
+    let _attribute = match canonical_attribute(_attribute) {
+        Ok(_attribute) => _attribute,
+        Err(_) => return,
+    };
+    let _h_g1 = _sk._hash.hash_g1(_gk._g1, HASH_GID, &[_sk._gid.as_bytes()]);
+    let _auth_attribute = match _msk
         ._attr
         .iter()
-        .filter(|_attr| _attr.0 == _attribute.to_string())
-        .nth(0)
-        .unwrap();
+        .find(|_attr| _attr.0 == _attribute) {
+        Some(_auth_attribute) => _auth_attribute,
+        None => return,
+    };
     _sk._attr.push((
-        _auth_attribute.0.clone().to_uppercase(),
+        _auth_attribute.0.clone(),
         (_gk._g1 * _auth_attribute.1) + (_h_g1 * _auth_attribute.2),
     ));
 }
@@ -213,12 +426,56 @@ pub fn encrypt(
     _language: PolicyLanguage,
     _plaintext: &[u8],
 ) -> Result<Aw11Ciphertext, RabeError> {
+    encrypt_with_suite(_gk, _pks, _policy, _language, _plaintext, AeadSuite::default())
+}
+
+/// Same as encrypt(), but encrypts the plaintext with the given AEAD suite.
+///
+/// # Arguments
+///
+///	* `_gk` - A Global Parameters Key (GK), generated by setup()
+///	* `_pk` - A Public Parameters Key (MK), associated with an authority and generated by authgen()
+///	* `_policy` - A JSON String policy describing the access rights
+///	* `_plaintext` - The plaintext data given as a Vector of u8.
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn encrypt_with_suite(
+    _gk: &Aw11GlobalKey,
+    _pks: &Vec<Aw11PublicKey>,
+    _policy: &String,
+    _language: PolicyLanguage,
+    _plaintext: &[u8],
+    _suite: AeadSuite,
+) -> Result<Aw11Ciphertext, RabeError> {
+    let (_key, _enc) = encapsulate(_gk, _pks, _policy, _language)?;
+    //Encrypt plaintext using derived key from secret
+    let _ct = encrypt_symmetric_key(&_key, SchemeId::Aw11, _suite, &policy_header(&_enc._policy.0, _enc._policy.1), &header_elements(&_enc), _plaintext)?;
+    let Aw11Encapsulation { _policy, _c_0, _c, _kdf, _issuer } = _enc;
+    Ok(Aw11Ciphertext { _policy, _c_0, _c, _ct, _kdf, _suite, _issuer })
+}
+
+/// The encapsulate algorithm of AW11. Generates a new random SymmetricKey and its Aw11Encapsulation under an access policy.
+///
+/// # Arguments
+///
+///	* `_gk` - A Global Parameters Key (GK), generated by setup()
+///	* `_pks` - The public keys of all attributes used in the policy, generated by authgen()
+///	* `_policy` - A policy describing the access rights
+///	* `_language` - The language the policy is written in
+///
+pub fn encapsulate(
+    _gk: &Aw11GlobalKey,
+    _pks: &Vec<Aw11PublicKey>,
+    _policy: &String,
+    _language: PolicyLanguage,
+) -> Result<(SymmetricKey, Aw11Encapsulation), RabeError> {
     // random number generator
     let mut _rng = rand::thread_rng();
     match parse(_policy, _language) {
         Ok(pol) => {
+            SchemeId::Aw11.check_policy(&pol)?;
             // an msp policy from the given String
-            let msp: AbePolicy = AbePolicy::from_policy(&pol).unwrap();
+            let msp: AbePolicy = AbePolicy::from_policy(&pol)?;
             let _num_cols = msp._m[0].len();
             let _num_rows = msp._m.len();
             // pick randomness
@@ -233,12 +490,12 @@ pub fn encrypt(
             let mut _c: Vec<(String, Gt, G2, G2)> = Vec::new();
             for (_i, (_attr_name, _attr_share)) in _s_shares.into_iter().enumerate() {
                 let _r_x:Fr = _rng.gen();
-                let _pk_attr = find_pk_attr(_pks, &_attr_name.to_uppercase());
+                let _pk_attr = find_pk_attr(_pks, &_attr_name);
                 match _pk_attr {
                     None => {},
                     Some(_attr) => {
                         _c.push((
-                            _attr_name.clone().to_uppercase(),
+                            _attr_name.clone(),
                             pairing(_gk._g1, _gk._g2).pow(_attr_share) * _attr.1.pow(_r_x),
                             _gk._g2 * _r_x,
                             (_attr.2 * _r_x) + (_gk._g2 * _w_shares[_i].1),
@@ -246,10 +503,9 @@ pub fn encrypt(
                     }
                 }
             }
-            //Encrypt plaintext using derived key from secret
             let _policy = _policy.to_string();
-            let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
-            Ok(Aw11Ciphertext { _policy: (_policy, _language), _c_0, _c, _ct })
+            let _key = SymmetricKey::derive(KdfVersion::CURRENT, &_msg, SchemeId::Aw11, &policy_header(&_policy, _language));
+            Ok((_key, Aw11Encapsulation { _policy: (_policy, _language), _c_0, _c, _kdf: KdfVersion::CURRENT, _issuer: _gk.fingerprint() }))
         },
         Err(e) => Err(e)
     }
@@ -266,6 +522,24 @@ pub fn decrypt(
     gk: &Aw11GlobalKey,
     sk: &Aw11SecretKey,
     ct: &Aw11Ciphertext) -> Result<Vec<u8>, RabeError> {
+    let _enc = ct.encapsulation();
+    let _key = decapsulate(gk, sk, &_enc)?;
+    // Decrypt plaintext using derived secret from cp-abe scheme
+    decrypt_symmetric_key(_enc._kdf, &_key, SchemeId::Aw11, ct._suite, &policy_header(&_enc._policy.0, _enc._policy.1), &header_elements(&_enc), &ct._ct)
+}
+
+/// The decapsulate algorithm of AW11. Recovers the SymmetricKey of an Aw11Encapsulation if the attributes in SK match its policy.
+///
+/// # Arguments
+///
+///	* `_gk` - A Global Parameters Key (GK), generated by setup()
+///	* `_sk` - A secret user key (SK), associated with a set of attributes.
+///	* `_enc` - An Aw11Encapsulation, generated by encapsulate()
+pub fn decapsulate(
+    gk: &Aw11GlobalKey,
+    sk: &Aw11SecretKey,
+    enc: &Aw11Encapsulation) -> Result<SymmetricKey, RabeError> {
+    check_issuer(&sk._issuer, &enc._issuer)?;
     let _str_attr = sk
         ._attr
         .iter()
@@ -274,9 +548,9 @@ pub fn decrypt(
             _str
         })
         .collect::<Vec<_>>();
-    return match parse(ct._policy.0.as_ref(), ct._policy.1) {
+    match parse(enc._policy.0.as_ref(), enc._policy.1) {
         Ok(pol) => {
-            return if traverse_policy(&_str_attr, &pol, PolicyType::Leaf) == false {
+            if !traverse_policy(&_str_attr, &pol, PolicyType::Leaf) {
                 Err(RabeError::new("Error: attributes in sk do not match policy in ct."))
             } else {
                 let _pruned = calc_pruned(&_str_attr, &pol, None);
@@ -284,9 +558,9 @@ pub fn decrypt(
                     Err(e) => Err(e),
                     Ok(_p) => {
                         let (_match, _list) = _p;
-                        let _coeffs = calc_coefficients(&pol, Some(Fr::one()), None).unwrap();
+                        let _coeffs = calc_coefficients(&pol, Some(Fr::one()), None, Some(&_str_attr)).unwrap();
                         if _match {
-                            let _h_g1 = blake2b_hash_g1(gk._g1, &sk._gid);
+                            let _h_g1 = sk._hash.hash_g1(gk._g1, HASH_GID, &[sk._gid.as_bytes()]);
                             let mut _egg_s = Gt::one();
                             for _current in _list.iter() {
                                 let _sk_attr = sk
@@ -295,11 +569,10 @@ pub fn decrypt(
                                     .filter(|_attr| _attr.0 == _current.to_string())
                                     .nth(0)
                                     .unwrap();
-                                let _ct_attr = ct
+                                let _ct_attr = enc
                                     ._c
                                     .iter()
-                                    .filter(|_attr| _attr.0 == _current.to_string())
-                                    .nth(0)
+                                    .find(|_attr| _attr.0 == *_current)
                                     .unwrap();
                                 let num = _ct_attr.1 * pairing(_h_g1, _ct_attr.3);
                                 let dem = pairing(_sk_attr.1, _ct_attr.2);
@@ -311,10 +584,9 @@ pub fn decrypt(
                                     .unwrap();
                                 _egg_s = _egg_s * ((num * dem.inverse()).pow(_coeff));
                             }
-                            let _msg = ct._c_0 * _egg_s.inverse();
+                            let _msg = enc._c_0 * _egg_s.inverse();
                             //println!("dec: {:?}", serde_json::to_string(&_msg).unwrap());
-                            // Decrypt plaintext using derived secret from cp-abe scheme
-                            decrypt_symmetric(&_msg, &ct._ct)
+                            Ok(SymmetricKey::derive(enc._kdf, &_msg, SchemeId::Aw11, &policy_header(&enc._policy.0, enc._policy.1)))
                         } else {
                             Err(RabeError::new("Error in aw11/decrypt: attributes in sk do not match policy in ct."))
                         }
@@ -333,7 +605,7 @@ pub fn decrypt(
 ///	* `_attr` - An attribute
 ///
 fn find_pk_attr(_pks: &Vec<Aw11PublicKey>, _attr: &String) -> Option<(String, Gt, G2)> {
-    for _pk in _pks.into_iter() {
+    for _pk in _pks.iter() {
         let _pk_attr = _pk
             ._attr
             .clone()
@@ -347,6 +619,91 @@ fn find_pk_attr(_pks: &Vec<Aw11PublicKey>, _attr: &String) -> Option<(String, Gt
     return None;
 }
 
+/// The public group elements of a ciphertext, authenticated as associated data
+fn header_elements(_enc: &Aw11Encapsulation) -> Vec<u8> {
+    let mut _elements = HeaderEncoder::new();
+    _elements.gt(&_enc._c_0).count(_enc._c.len());
+    for (_attr, _c1, _c2, _c3) in _enc._c.iter() {
+        _elements.str(_attr).gt(_c1).g2(_c2).g2(_c3);
+    }
+    _elements.finish()
+}
+
+/// The AW11 multi-authority scheme, for use through the `MultiAuthorityAbe` trait.
+/// AW11 has no central master key, an authority key is the key pair generated by authgen().
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Aw11;
+
+impl MultiAuthorityAbe for Aw11 {
+    const ID: SchemeId = SchemeId::Aw11;
+
+    type GlobalKey = Aw11GlobalKey;
+    type MasterKey = ();
+    type AuthorityKey = (Aw11PublicKey, Aw11MasterKey);
+    type PublicAttributeKey = Aw11PublicKey;
+    type SecretKey = Aw11SecretKey;
+    type Ciphertext = Aw11Ciphertext;
+    type Encapsulation = Aw11Encapsulation;
+
+    fn setup(&self) -> (Aw11GlobalKey, ()) {
+        (setup(), ())
+    }
+
+    fn authgen(&self, _gk: &Aw11GlobalKey, _mk: &(), _name: &str, _attributes: &[String]) -> Result<(Aw11PublicKey, Aw11MasterKey), RabeError> {
+        authgen(_gk, &_attributes.to_vec())
+    }
+
+    fn keygen(&self, _gk: &Aw11GlobalKey, _mk: &(), _authority: &(Aw11PublicKey, Aw11MasterKey), _name: &str) -> Result<Aw11SecretKey, RabeError> {
+        if _name.is_empty() {
+            return Err(RabeError::new("Error in aw11/keygen: no user name given."));
+        }
+        Ok(Aw11SecretKey { _gid: _name.to_string(), _attr: Vec::new(), _hash: HashVersion::CURRENT, _issuer: _gk.fingerprint() })
+    }
+
+    fn request_attribute_pk(&self, _gk: &Aw11GlobalKey, _authority: &(Aw11PublicKey, Aw11MasterKey), _attribute: &str) -> Result<Aw11PublicKey, RabeError> {
+        let _attribute = canonical_attribute(_attribute)?;
+        let _attr: Vec<(String, Gt, G2)> = _authority.0._attr
+            .iter()
+            .filter(|_attr| _attr.0 == _attribute)
+            .cloned()
+            .collect();
+        if _attr.is_empty() {
+            return Err(RabeError::new(&format!("Error in aw11/request_attribute_pk: attribute {} is not handled by this authority.", _attribute)));
+        }
+        Ok(Aw11PublicKey { _attr })
+    }
+
+    fn request_attribute_sk(&self, _gk: &Aw11GlobalKey, _authority: &(Aw11PublicKey, Aw11MasterKey), _sk: &mut Aw11SecretKey, _attribute: &str) -> Result<(), RabeError> {
+        let _attribute = canonical_attribute(_attribute)?;
+        if !_authority.1._attr.iter().any(|_attr| _attr.0 == _attribute) {
+            return Err(RabeError::new(&format!("Error in aw11/request_attribute_sk: attribute {} is not handled by this authority.", _attribute)));
+        }
+        add_attribute(_gk, &_authority.1, &_attribute, _sk);
+        Ok(())
+    }
+
+    fn encrypt_with_suite(&self, _gk: &Aw11GlobalKey, _pks: &[Aw11PublicKey], _policy: &str, _language: PolicyLanguage, _plaintext: &[u8], _suite: AeadSuite) -> Result<Aw11Ciphertext, RabeError> {
+        encrypt_with_suite(_gk, &_pks.to_vec(), &_policy.to_string(), _language, _plaintext, _suite)
+    }
+
+    fn decrypt(&self, _gk: &Aw11GlobalKey, _sk: &Aw11SecretKey, _ct: &Aw11Ciphertext) -> Result<Vec<u8>, RabeError> {
+        decrypt(_gk, _sk, _ct)
+    }
+
+    fn encapsulate(&self, _gk: &Aw11GlobalKey, _pks: &[Aw11PublicKey], _policy: &str, _language: PolicyLanguage) -> Result<(SymmetricKey, Aw11Encapsulation), RabeError> {
+        encapsulate(_gk, &_pks.to_vec(), &_policy.to_string(), _language)
+    }
+
+    fn decapsulate(&self, _gk: &Aw11GlobalKey, _sk: &Aw11SecretKey, _enc: &Aw11Encapsulation) -> Result<SymmetricKey, RabeError> {
+        decapsulate(_gk, _sk, _enc)
+    }
+
+    fn explain(&self, _sk: &Aw11SecretKey, _ct: &Aw11Ciphertext) -> Result<Explanation, RabeError> {
+        let _attributes: Vec<String> = _sk._attr.iter().map(|_attr| _attr.0.clone()).collect();
+        explain(&Policy::parse(&_ct._policy.0, _ct._policy.1)?, &_attributes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -404,6 +761,75 @@ mod tests {
         assert_eq!(_matching, _plaintext);
     }
 
+    #[test]
+    fn single() {
+        // global setup
+        let _gp = setup();
+        // setup attribute authority 1 with
+        // a set of two attributes "ADMIN" "B"
+        let att_authority1: Vec<String> = vec![String::from("ADMIN"), String::from("B")];
+        let (_auth1_pk, _auth1_msk) = authgen(&_gp, &att_authority1).unwrap();
+        // setup a user "bob" with the attribute and "alice" without
+        let _bob = keygen(&_gp, &_auth1_msk, &String::from("bob"), &vec![String::from("ADMIN")]).unwrap();
+        let _alice = keygen(&_gp, &_auth1_msk, &String::from("alice"), &vec![String::from("B")]).unwrap();
+        // our plaintext
+        let _plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        // our policy consists of a single attribute
+        let _policy = String::from(r#""ADMIN""#);
+        // cp-abe ciphertext
+        let ct_cp: Aw11Ciphertext = encrypt(&_gp, &vec![_auth1_pk], &_policy, PolicyLanguage::HumanPolicy, &_plaintext).unwrap();
+        // and now decrypt again with mathcing sk
+        assert_eq!(decrypt(&_gp, &_bob, &ct_cp).unwrap(), _plaintext);
+        assert!(decrypt(&_gp, &_alice, &ct_cp).is_err());
+    }
+
+    #[test]
+    fn key_value() {
+        // global setup
+        let _gp = setup();
+        // attributes are case sensitive and canonicalized
+        let (_auth1_pk, _auth1_msk) = authgen(&_gp, &vec![String::from("dept = finance"), String::from("Dept=finance")]).unwrap();
+        let _bob = keygen(&_gp, &_auth1_msk, &String::from("bob"), &vec![String::from("dept=finance")]).unwrap();
+        let _alice = keygen(&_gp, &_auth1_msk, &String::from("alice"), &vec![String::from("Dept=finance")]).unwrap();
+        // our plaintext
+        let _plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let _policy = String::from(r#"dept == "finance""#);
+        let ct_cp: Aw11Ciphertext = encrypt(&_gp, &vec![_auth1_pk], &_policy, PolicyLanguage::HumanPolicy, &_plaintext).unwrap();
+        assert_eq!(decrypt(&_gp, &_bob, &ct_cp).unwrap(), _plaintext);
+        assert!(decrypt(&_gp, &_alice, &ct_cp).is_err());
+    }
+
+    #[test]
+    fn threshold() {
+        // global setup
+        let _gp = setup();
+        // setup attribute authority 1 with
+        // a set of three attributes "A" "B" "C"
+        let att_authority1: Vec<String> = vec![String::from("A"), String::from("B"), String::from("C")];
+        let (_auth1_pk, _auth1_msk) = authgen(&_gp, &att_authority1).unwrap();
+        // setup a user "bob" and give him some attribute-keys
+        let att_bob: Vec<String> = vec![String::from("A"), String::from("C")];
+        let _bob = keygen(&_gp, &_auth1_msk, &String::from("bob"), &att_bob).unwrap();
+        // setup a user "alice" with just a single attribute
+        let att_alice: Vec<String> = vec![String::from("B")];
+        let _alice = keygen(&_gp, &_auth1_msk, &String::from("alice"), &att_alice).unwrap();
+        // our plaintext
+        let _plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        // our policy
+        let _policy = String::from(r#"2 of ("A", "B", "C")"#);
+        // a vector of public attribute keys
+        let _pks: Vec<Aw11PublicKey> = vec![_auth1_pk];
+        // cp-abe ciphertext
+        let ct_cp: Aw11Ciphertext = encrypt(&_gp, &_pks, &_policy, PolicyLanguage::HumanPolicy, &_plaintext).unwrap();
+        // and now decrypt again with mathcing sk
+        let _matching = decrypt(&_gp, &_bob, &ct_cp).unwrap();
+        assert_eq!(_matching, _plaintext);
+        assert!(decrypt(&_gp, &_alice, &ct_cp).is_err());
+    }
+
     #[test]
     fn or() {
         // global setup
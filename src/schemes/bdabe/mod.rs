@@ -30,17 +30,23 @@ use std::string::String;
 use rand::Rng;
 use rabe_bn::{Group, Fr, G1, G2, Gt, pairing};
 use utils::{
+    attribute::canonical_attribute,
     policy::*,
     tools::*,
     aes::*,
-    hash::blake2b_hash_fr
+    hash::HashVersion
 };
 use utils::policy::pest::{PolicyLanguage, parse, PolicyType};
 use RabeError;
-use utils::policy::dnf::policy_in_dnf;
+use utils::validate::{check_g1, check_g2, check_gt, check_fr, check_unique, check_policy, check_issuer_length};
+use utils::fingerprint::{Fingerprint, check_issuer};
+use utils::json::POLICY_FIELDS;
+use schemes::{MultiAuthorityAbe, SchemeId};
+use utils::policy::{ast::Policy, explain::{explain, Explanation}};
 
 /// A BDABE Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabePublicKey {
     pub _g1: G1,
     pub _g2: G2,
@@ -51,20 +57,26 @@ pub struct BdabePublicKey {
 
 /// A BDABE Master Key (MK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabeMasterKey {
     pub _y: Fr,
 }
 
 /// A BDABE User Key (PKu, SKu and SKa's)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabeUserKey {
     pub _sk: BdabeSecretUserKey,
     pub _pk: BdabePublicUserKey,
     pub _ska: Vec<BdabeSecretAttributeKey>,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
 }
 
 /// A BDABE Public User Key (PKu)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabePublicUserKey {
     pub _u: String,
     pub _u1: G1,
@@ -73,6 +85,7 @@ pub struct BdabePublicUserKey {
 
 /// A BDABE Secret User Key (SKu)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabeSecretUserKey {
     pub _u1: G1,
     pub _u2: G2,
@@ -80,23 +93,30 @@ pub struct BdabeSecretUserKey {
 
 /// A BDABE Secret Attribute Key (SKa)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabeSecretAttributeKey {
     pub _str: String,
     pub _au1: G1,
     pub _au2: G2,
+    #[serde(default)]
+    pub _hash: HashVersion,
 }
 
 /// A BDABE Public Attribute Key (PKa)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabePublicAttributeKey {
     pub _str: String,
     pub _a1: G1,
     pub _a2: G2,
     pub _a3: Gt,
+    #[serde(default)]
+    pub _hash: HashVersion,
 }
 
 /// A BDABE Secret Authority Key (SKauth)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabeSecretAuthorityKey {
     pub _a1: G1,
     pub _a2: G2,
@@ -117,12 +137,167 @@ pub struct BdabeCiphertextTuple {
 
 /// A BDABE Ciphertext (CT)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct BdabeCiphertext {
     pub _policy: (String, PolicyLanguage),
     pub _j: Vec<BdabeCiphertextTuple>,
     pub _ct: Vec<u8>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    #[serde(default)]
+    pub _suite: AeadSuite,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
 }
 
+/// A BDABE key encapsulation: a BdabeCiphertext without the symmetrically encrypted data
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
+pub struct BdabeEncapsulation {
+    pub _policy: (String, PolicyLanguage),
+    pub _j: Vec<BdabeCiphertextTuple>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
+}
+
+impl BdabeCiphertext {
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> BdabeEncapsulation {
+        BdabeEncapsulation {
+            _policy: self._policy.clone(),
+            _j: self._j.clone(),
+            _hash: self._hash,
+            _kdf: self._kdf,
+            _issuer: self._issuer.clone(),
+        }
+    }
+}
+
+impl_wire!(BdabePublicKey: Bdabe, GlobalKey { _g1, _g2, _p1, _p2, _e_gg_y });
+
+impl_json!(BdabePublicKey { _g1, _g2, _p1, _p2, _e_gg_y });
+
+impl Fingerprint for BdabePublicKey {}
+
+impl_validate!(BdabePublicKey {
+    _g1: check_g1,
+    _g2: check_g2,
+    _p1: check_g1,
+    _p2: check_g2,
+    _e_gg_y: check_gt,
+});
+
+impl_validated_serde!(BdabePublicKey);
+
+impl_wire!(BdabeMasterKey: Bdabe, MasterKey { _y });
+
+impl_json!(BdabeMasterKey { _y });
+
+impl Fingerprint for BdabeMasterKey {}
+
+impl_validate!(BdabeMasterKey { _y: check_fr });
+
+impl_validated_serde!(BdabeMasterKey);
+
+impl_wire!(BdabeUserKey: Bdabe, SecretKey { _sk, _pk, _ska; _issuer });
+
+impl_json!(BdabeUserKey { _sk, _pk, _ska, _issuer });
+
+impl_validate!(BdabeUserKey {
+    _sk: validate,
+    _pk: validate,
+    _ska: unique(_str),
+    _ska: each,
+    _issuer: check_issuer_length,
+});
+
+impl_validated_serde!(BdabeUserKey);
+
+impl_wire!(BdabePublicUserKey: Bdabe, PublicUserKey { _u, _u1, _u2 });
+
+impl_json!(BdabePublicUserKey { _u, _u1, _u2 });
+
+impl Fingerprint for BdabePublicUserKey {}
+
+impl_validate!(BdabePublicUserKey { _u1: check_g1, _u2: check_g2 });
+
+impl_validated_serde!(BdabePublicUserKey);
+
+impl_wire!(BdabeSecretUserKey: Bdabe, SecretUserKey { _u1, _u2 });
+
+impl_json!(BdabeSecretUserKey { _u1, _u2 });
+
+impl_validate!(BdabeSecretUserKey { _u1: check_g1, _u2: check_g2 });
+
+impl_validated_serde!(BdabeSecretUserKey);
+
+impl_wire!(BdabeSecretAttributeKey: Bdabe, SecretAttributeKey { _str, _au1, _au2, _hash });
+
+impl_json!(BdabeSecretAttributeKey { _str, _au1, _au2, _hash });
+
+impl Fingerprint for BdabeSecretAttributeKey {}
+
+impl_validate!(BdabeSecretAttributeKey { _au1: check_g1, _au2: check_g2 });
+
+impl_validated_serde!(BdabeSecretAttributeKey);
+
+impl_wire!(BdabePublicAttributeKey: Bdabe, PublicAttributeKey { _str, _a1, _a2, _a3, _hash });
+
+impl_json!(BdabePublicAttributeKey { _str, _a1, _a2, _a3, _hash });
+
+impl Fingerprint for BdabePublicAttributeKey {}
+
+impl_validate!(BdabePublicAttributeKey { _a1: check_g1, _a2: check_g2, _a3: check_gt });
+
+impl_validated_serde!(BdabePublicAttributeKey);
+
+impl_wire!(BdabeSecretAuthorityKey: Bdabe, AuthorityKey { _a1, _a2, _a3, _a });
+
+impl_json!(BdabeSecretAuthorityKey { _a1, _a2, _a3, _a });
+
+impl Fingerprint for BdabeSecretAuthorityKey {}
+
+impl_validate!(BdabeSecretAuthorityKey { _a1: check_g1, _a2: check_g2, _a3: check_fr });
+
+impl_validated_serde!(BdabeSecretAuthorityKey);
+
+impl_wire!(BdabeCiphertextTuple { _str, _e1, _e2, _e3, _e4, _e5 });
+
+impl_json!(BdabeCiphertextTuple { _str, _e1, _e2, _e3, _e4, _e5 });
+
+impl_validate!(BdabeCiphertextTuple {
+    _str: check_unique,
+    _e1: check_gt,
+    _e2: check_g1,
+    _e3: check_g2,
+    _e4: check_g1,
+    _e5: check_g2,
+});
+
+impl_wire!(BdabeCiphertext: Bdabe, Ciphertext { _policy, _j, _ct, _hash, _kdf, _suite; _issuer });
+
+impl_json!(BdabeCiphertext { _policy: tuple(POLICY_FIELDS), _j, _ct, _hash, _kdf, _suite, _issuer });
+
+impl_validate!(BdabeCiphertext { _policy: check_policy, _j: each, _issuer: check_issuer_length });
+
+impl_validated_serde!(BdabeCiphertext);
+
+impl_wire!(BdabeEncapsulation: Bdabe, Encapsulation { _policy, _j, _hash, _kdf; _issuer });
+
+impl_json!(BdabeEncapsulation { _policy: tuple(POLICY_FIELDS), _j, _hash, _kdf, _issuer });
+
+impl_validate!(BdabeEncapsulation { _policy: check_policy, _j: each, _issuer: check_issuer_length });
+
+impl_validated_serde!(BdabeEncapsulation);
+
 /// The setup algorithm of BDABE. Generates a BdabePublicKey and a BdabeMasterKey.
 pub fn setup() -> (BdabePublicKey, BdabeMasterKey) {
     // random number generator
@@ -195,6 +370,7 @@ pub fn keygen(
             _u2: _pk._g2 * _r_u,
         },
         _ska: Vec::new(),
+        _issuer: _pk.fingerprint(),
     }
 }
 
@@ -211,15 +387,17 @@ pub fn request_attribute_pk(
     _ska: &BdabeSecretAuthorityKey,
     _attribute: &String,
 ) -> Option<BdabePublicAttributeKey> {
+    let _attribute = &canonical_attribute(_attribute).ok()?;
     // if attribute a is from authority sk_a
     return if from_authority(_attribute, &_ska._a) {
-        let exponent = blake2b_hash_fr(_attribute) * blake2b_hash_fr(&_ska._a) * _ska._a3;
+        let exponent = attribute_exponent(HashVersion::CURRENT, _attribute, &_ska._a) * _ska._a3;
         // return PK and mke
         Some(BdabePublicAttributeKey {
             _str: _attribute.clone(),
             _a1: _pk._g1 * exponent,
             _a2: _pk._g2 * exponent,
             _a3: _pk._e_gg_y.pow(exponent),
+            _hash: HashVersion::CURRENT,
         })
     } else {
         None
@@ -239,14 +417,16 @@ pub fn request_attribute_sk(
     _ska: &BdabeSecretAuthorityKey,
     _attribute: &String,
 ) -> Option<BdabeSecretAttributeKey> {
+    let _attribute = &canonical_attribute(_attribute).ok()?;
     // if attribute a is from authority sk_a
     return if from_authority(_attribute, &_ska._a) && is_eligible(_attribute, &_pku._u) {
-        let exponent = blake2b_hash_fr(_attribute) * blake2b_hash_fr(&_ska._a) * _ska._a3;
+        let exponent = attribute_exponent(HashVersion::CURRENT, _attribute, &_ska._a) * _ska._a3;
         // return PK and mke
         Some(BdabeSecretAttributeKey {
             _str: _attribute.to_string(),
             _au1: _pku._u1 * exponent,
             _au2: _pku._u2 * exponent,
+            _hash: HashVersion::CURRENT,
         })
     } else {
         None
@@ -270,37 +450,75 @@ pub fn encrypt(
     _plaintext: &[u8],
     _language: PolicyLanguage,
 ) -> Result<BdabeCiphertext, RabeError> {
+    encrypt_with_suite(_pk, _attr_pks, _policy, _plaintext, _language, AeadSuite::default())
+}
+
+/// Same as encrypt(), but encrypts the plaintext with the given AEAD suite.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_attr_pks` - A Vector of all BdabePublicAttributeKeys that are involded in the policy
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn encrypt_with_suite(
+    _pk: &BdabePublicKey,
+    _attr_pks: &Vec<BdabePublicAttributeKey>,
+    _policy: &String,
+    _plaintext: &[u8],
+    _language: PolicyLanguage,
+    _suite: AeadSuite,
+) -> Result<BdabeCiphertext, RabeError> {
+    let (_key, _enc) = encapsulate(_pk, _attr_pks, _policy, _language)?;
+    //Encrypt plaintext using derived key from secret
+    let _ct = encrypt_symmetric_key(&_key, SchemeId::Bdabe, _suite, &policy_header(&_enc._policy.0, _enc._policy.1), &header_elements(&_enc), _plaintext)?;
+    let BdabeEncapsulation { _policy, _j, _hash, _kdf, _issuer } = _enc;
+    Ok(BdabeCiphertext { _policy, _j, _ct, _hash, _kdf, _suite, _issuer })
+}
+
+/// The encapsulate algorithm of BDABE. Generates a new random SymmetricKey and its BdabeEncapsulation under an access policy.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_attr_pks` - A Vector of all BdabePublicAttributeKeys that are involded in the policy
+///	* `_policy` - An access policy given as String
+///	* `_language` - The language the policy is written in
+///
+pub fn encapsulate(
+    _pk: &BdabePublicKey,
+    _attr_pks: &Vec<BdabePublicAttributeKey>,
+    _policy: &String,
+    _language: PolicyLanguage,
+) -> Result<(SymmetricKey, BdabeEncapsulation), RabeError> {
     match parse(_policy, _language) {
         Ok(pol) => {
-            // if policy is in DNF
-            if policy_in_dnf(&pol, false, None) {
-                // random number generator
-                let mut _rng = rand::thread_rng();
-                let _policy = _policy.to_string();
-                // an DNF policy from the given String
-                let dnf: dnf::DnfPolicy = dnf::DnfPolicy::from_string(&_policy, _attr_pks, _language).unwrap();
-                // random Gt msg
-                let _msg = pairing(_rng.gen(), _rng.gen());
-                // CT result vector
-                let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
-                let mut _j: Vec<BdabeCiphertextTuple> = Vec::new();
-                // now add randomness using _r_j
-                for _term in dnf._terms {
-                    let _r_j: Fr = _rng.gen();
-                    _j.push(BdabeCiphertextTuple {
-                        _str: _term.0,
-                        _e1: _term.1.pow(_r_j) * _msg,
-                        _e2: _pk._p1 * _r_j,
-                        _e3: _pk._p2 * _r_j,
-                        _e4: _term.3 * _r_j,
-                        _e5: _term.4 * _r_j,
-                    });
-                }
-                //Encrypt plaintext using derived key from secret
-                Ok(BdabeCiphertext {_policy: (_policy, _language), _j, _ct })
-            } else {
-                Err(RabeError::new("Error in bdabe/encrypt: Policy not in DNF."))
+            SchemeId::Bdabe.check_policy(&pol)?;
+            // random number generator
+            let mut _rng = rand::thread_rng();
+            let _policy = _policy.to_string();
+            // the policy converted into DNF
+            let dnf: dnf::DnfPolicy = dnf::DnfPolicy::from_policy(&pol, _attr_pks)?;
+            let _hash = HashVersion::common(_attr_pks.iter().map(|_attr_pk| _attr_pk._hash))?;
+            // random Gt msg
+            let _msg = pairing(_rng.gen(), _rng.gen());
+            let mut _j: Vec<BdabeCiphertextTuple> = Vec::new();
+            // now add randomness using _r_j
+            for _term in dnf._terms {
+                let _r_j: Fr = _rng.gen();
+                _j.push(BdabeCiphertextTuple {
+                    _str: _term.0,
+                    _e1: _term.1.pow(_r_j) * _msg,
+                    _e2: _pk._p1 * _r_j,
+                    _e3: _pk._p2 * _r_j,
+                    _e4: _term.3 * _r_j,
+                    _e5: _term.4 * _r_j,
+                });
             }
+            let _key = SymmetricKey::derive(KdfVersion::CURRENT, &_msg, SchemeId::Bdabe, &policy_header(&_policy, _language));
+            Ok((_key, BdabeEncapsulation {_policy: (_policy, _language), _j, _hash, _kdf: KdfVersion::CURRENT, _issuer: _pk.fingerprint() }))
         },
         Err(e) => Err(e)
     }
@@ -318,20 +536,40 @@ pub fn decrypt(
     _pk: &BdabePublicKey,
     _sk: &BdabeUserKey,
     _ct: &BdabeCiphertext) -> Result<Vec<u8>, RabeError> {
-    let _str_attr = _sk
-        ._ska
+    let _enc = _ct.encapsulation();
+    let _key = decapsulate(_pk, _sk, &_enc)?;
+    // Decrypt plaintext using derived secret from Bdabe scheme
+    decrypt_symmetric_key(_enc._kdf, &_key, SchemeId::Bdabe, _ct._suite, &policy_header(&_enc._policy.0, _enc._policy.1), &header_elements(&_enc), &_ct._ct)
+}
+
+/// The decapsulate algorithm of BDABE. Recovers the SymmetricKey of a BdabeEncapsulation with a matching BdabeUserKey.
+///
+/// # Arguments
+///
+///	* `_pk` - A BdabePublicKey (PK), generated by the function setup()
+///	* `_sk` - A BdabeUserKey (SK), generated by the function keygen()
+///	* `_enc` - A BdabeEncapsulation, generated by the function encapsulate()
+///
+pub fn decapsulate(
+    _pk: &BdabePublicKey,
+    _sk: &BdabeUserKey,
+    _enc: &BdabeEncapsulation) -> Result<SymmetricKey, RabeError> {
+    check_issuer(&_sk._issuer, &_enc._issuer)?;
+    // only attribute keys issued with the hash of the ciphertext can take part
+    let _ska: Vec<BdabeSecretAttributeKey> = _sk._ska.iter().filter(|_key| _key._hash == _enc._hash).cloned().collect();
+    let _str_attr = _ska
         .iter()
         .map(|_values| _values._str.to_string())
         .collect::<Vec<_>>();
-    match parse(_ct._policy.0.as_ref(), _ct._policy.1) {
+    match parse(_enc._policy.0.as_ref(), _enc._policy.1) {
         Ok(pol) => {
             if traverse_policy(&_str_attr, &pol, PolicyType::Leaf) == false {
                 Err(RabeError::new("Error in bdabe/decrypt: attributes in sk do not match policy in ct."))
             } else {
                 let mut _msg = Gt::one();
-                for (_i, _ct_j) in _ct._j.iter().enumerate() {
-                    if is_satisfiable(&_ct_j._str, &_sk._ska) {
-                        let _sk_sum = calc_satisfiable(&_ct_j._str, &_sk._ska);
+                for _ct_j in _enc._j.iter() {
+                    if is_satisfiable(&_ct_j._str, &_ska) {
+                        let _sk_sum = calc_satisfiable(&_ct_j._str, &_ska);
                         _msg = _ct_j._e1
                             * pairing(_ct_j._e2, _sk_sum.1)
                             * pairing(_sk_sum.0, _ct_j._e3)
@@ -339,8 +577,7 @@ pub fn decrypt(
                         break;
                     }
                 }
-                // Decrypt plaintext using derived secret from Bdabe scheme
-                decrypt_symmetric(&_msg, &_ct._ct)
+                Ok(SymmetricKey::derive(_enc._kdf, &_msg, SchemeId::Bdabe, &policy_header(&_enc._policy.0, _enc._policy.1)))
             }
         },
         Err(e) => Err(e)
@@ -361,7 +598,7 @@ pub fn decrypt(
 fn is_satisfiable(_conjunction: &Vec<String>, _sk: &Vec<BdabeSecretAttributeKey>) -> bool {
     let mut _ret: bool = true;
     for _attr in _conjunction {
-        match _sk.into_iter().find(|&x| x._str == *_attr) {
+        match _sk.iter().find(|&x| x._str == *_attr) {
             None => {
                 _ret = false;
                 break;
@@ -387,7 +624,7 @@ fn calc_satisfiable(
     let mut ret: (G1, G2) = (G1::one(), G2::one());
     for _i in 0usize.._conjunction.len() {
         match _sk
-            .into_iter()
+            .iter()
             .find(|&x| x._str == _conjunction[_i].to_string())
         {
             None => {}
@@ -403,6 +640,19 @@ fn calc_satisfiable(
     ret
 }
 
+/// BDABE Scheme helper function: attribute_exponent
+/// Derives the exponent of an attribute key from the name of the attribute and the name of its authority.
+///
+/// # Arguments
+///
+///	* `_hash` - The hash version of the attribute key
+///	* `_attr` - Name of the attribute given as String
+///	* `_authority` - Name of the auhtority given as String
+///
+fn attribute_exponent(_hash: HashVersion, _attr: &str, _authority: &str) -> Fr {
+    _hash.hash_fr("rabe/bdabe/attribute", &[_attr.as_bytes()]) * _hash.hash_fr("rabe/bdabe/authority", &[_authority.as_bytes()])
+}
+
 /// BDABE Scheme helper function: from_authority
 /// Returns true if a specific attribute is handled by a given authority.
 /// Please adopt or implement you own logic. Right now the algorithm checks if the first part
@@ -436,6 +686,90 @@ fn is_eligible(_attr: &String, _user: &String) -> bool {
     return true;
 }
 
+/// The public group elements of a ciphertext, authenticated as associated data
+fn header_elements(_enc: &BdabeEncapsulation) -> Vec<u8> {
+    let mut _elements = HeaderEncoder::new();
+    _elements.count(_enc._j.len());
+    for _j in _enc._j.iter() {
+        _elements.count(_j._str.len());
+        for _str in _j._str.iter() {
+            _elements.str(_str);
+        }
+        _elements.gt(&_j._e1).g1(&_j._e2).g2(&_j._e3).g1(&_j._e4).g2(&_j._e5);
+    }
+    _elements.finish()
+}
+
+/// The BDABE multi-authority scheme, for use through the `MultiAuthorityAbe` trait.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bdabe;
+
+impl MultiAuthorityAbe for Bdabe {
+    const ID: SchemeId = SchemeId::Bdabe;
+
+    type GlobalKey = BdabePublicKey;
+    type MasterKey = BdabeMasterKey;
+    type AuthorityKey = BdabeSecretAuthorityKey;
+    type PublicAttributeKey = BdabePublicAttributeKey;
+    type SecretKey = BdabeUserKey;
+    type Ciphertext = BdabeCiphertext;
+    type Encapsulation = BdabeEncapsulation;
+
+    fn setup(&self) -> (BdabePublicKey, BdabeMasterKey) {
+        setup()
+    }
+
+    fn authgen(&self, _pk: &BdabePublicKey, _mk: &BdabeMasterKey, _name: &str, _attributes: &[String]) -> Result<BdabeSecretAuthorityKey, RabeError> {
+        // the authority handles every attribute under its name prefix, the given ones must carry it
+        for _attribute in _attributes {
+            if !from_authority(&canonical_attribute(_attribute)?, &_name.to_string()) {
+                return Err(RabeError::new(&format!("Error in bdabe/authgen: attribute {} is not handled by authority {}.", _attribute, _name)));
+            }
+        }
+        Ok(authgen(_pk, _mk, &_name.to_string()))
+    }
+
+    fn keygen(&self, _pk: &BdabePublicKey, _mk: &BdabeMasterKey, _ska: &BdabeSecretAuthorityKey, _name: &str) -> Result<BdabeUserKey, RabeError> {
+        Ok(keygen(_pk, _ska, &_name.to_string()))
+    }
+
+    fn request_attribute_pk(&self, _pk: &BdabePublicKey, _ska: &BdabeSecretAuthorityKey, _attribute: &str) -> Result<BdabePublicAttributeKey, RabeError> {
+        request_attribute_pk(_pk, _ska, &_attribute.to_string())
+            .ok_or_else(|| RabeError::new(&format!("Error in bdabe/request_attribute_pk: attribute {} is not handled by authority {}.", _attribute, _ska._a)))
+    }
+
+    fn request_attribute_sk(&self, _pk: &BdabePublicKey, _ska: &BdabeSecretAuthorityKey, _sk: &mut BdabeUserKey, _attribute: &str) -> Result<(), RabeError> {
+        match request_attribute_sk(&_sk._pk, _ska, &_attribute.to_string()) {
+            Some(_ska_attr) => {
+                _sk._ska.push(_ska_attr);
+                Ok(())
+            },
+            None => Err(RabeError::new(&format!("Error in bdabe/request_attribute_sk: attribute {} is not handled by authority {}.", _attribute, _ska._a)))
+        }
+    }
+
+    fn encrypt_with_suite(&self, _pk: &BdabePublicKey, _attr_pks: &[BdabePublicAttributeKey], _policy: &str, _language: PolicyLanguage, _plaintext: &[u8], _suite: AeadSuite) -> Result<BdabeCiphertext, RabeError> {
+        encrypt_with_suite(_pk, &_attr_pks.to_vec(), &_policy.to_string(), _plaintext, _language, _suite)
+    }
+
+    fn decrypt(&self, _pk: &BdabePublicKey, _sk: &BdabeUserKey, _ct: &BdabeCiphertext) -> Result<Vec<u8>, RabeError> {
+        decrypt(_pk, _sk, _ct)
+    }
+
+    fn encapsulate(&self, _pk: &BdabePublicKey, _attr_pks: &[BdabePublicAttributeKey], _policy: &str, _language: PolicyLanguage) -> Result<(SymmetricKey, BdabeEncapsulation), RabeError> {
+        encapsulate(_pk, &_attr_pks.to_vec(), &_policy.to_string(), _language)
+    }
+
+    fn decapsulate(&self, _pk: &BdabePublicKey, _sk: &BdabeUserKey, _enc: &BdabeEncapsulation) -> Result<SymmetricKey, RabeError> {
+        decapsulate(_pk, _sk, _enc)
+    }
+
+    fn explain(&self, _sk: &BdabeUserKey, _ct: &BdabeCiphertext) -> Result<Explanation, RabeError> {
+        let _attributes: Vec<String> = _sk._ska.iter().map(|_attr| _attr._str.clone()).collect();
+        explain(&Policy::parse(&_ct._policy.0, _ct._policy.1)?, &_attributes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -479,6 +813,40 @@ mod tests {
         assert_eq!(_match.unwrap(), _plaintext);
     }
 
+    #[test]
+    fn single() {
+        // setup scheme
+        let (_pk, _msk) = setup();
+        // authority1
+        let _a1_key = authgen(&_pk, &_msk, &String::from("aa1"));
+        // generate mutable user key(in order to add attribute sk's later on)
+        let mut _u_key = keygen(&_pk, &_a1_key, &String::from("u1"));
+        // our attributes
+        let _att1 = String::from("aa1::admin");
+        let _att2 = String::from("aa1::B");
+        // authority1 owns both
+        let _att1_pk = request_attribute_pk(&_pk, &_a1_key, &_att1).unwrap();
+        let _att2_pk = request_attribute_pk(&_pk, &_a1_key, &_att2).unwrap();
+        // our plaintext
+        let _plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        // our policy consists of a single attribute
+        let _policy = String::from(r#""aa1::admin""#);
+        // cp-abe ciphertext
+        let _ct: BdabeCiphertext =
+            encrypt(&_pk, &vec![_att1_pk, _att2_pk], &_policy, &_plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        // the user only holds B
+        _u_key
+            ._ska
+            .push(request_attribute_sk(&_u_key._pk, &_a1_key, &_att2).unwrap());
+        assert!(decrypt(&_pk, &_u_key, &_ct).is_err());
+        // and now decrypt again with mathcing sk
+        _u_key
+            ._ska
+            .push(request_attribute_sk(&_u_key._pk, &_a1_key, &_att1).unwrap());
+        assert_eq!(decrypt(&_pk, &_u_key, &_ct).unwrap(), _plaintext);
+    }
+
     #[test]
     fn or() {
         // setup scheme
@@ -23,16 +23,26 @@
 use rabe_bn::{Fr, G1, G2, Gt, pairing};
 use rand::Rng;
 use utils::{
-    secretsharing::{gen_shares_policy, calc_pruned, calc_coefficients},
+    attribute::canonicalize,
+    secretsharing::{gen_shares_policy_with, calc_pruned, calc_coefficients},
     tools::*,
     aes::*,
-    hash::blake2b_hash_g2
+    hash::HashVersion
 };
 use utils::policy::pest::{PolicyLanguage, parse, PolicyType};
 use RabeError;
+use utils::validate::{check_g1, check_g2, check_gt, check_fr_invertible, check_policy, check_issuer_length};
+use utils::fingerprint::{Fingerprint, check_issuer};
+use utils::json::POLICY_FIELDS;
+use schemes::{CpAbe, CpAbeCca, SchemeId};
+use utils::policy::{ast::Policy, explain::{explain, Explanation}};
+
+/// The domain of the attribute hashes
+const HASH_ATTRIBUTE: &str = "rabe/bsw/attribute";
 
 /// A BSW Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct CpAbePublicKey {
     pub _g1: G1,
     pub _g2: G2,
@@ -43,6 +53,7 @@ pub struct CpAbePublicKey {
 
 /// A BSW Master Key (MSK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct CpAbeMasterKey {
     pub _beta: Fr,
     pub _g2_alpha: G2,
@@ -50,19 +61,67 @@ pub struct CpAbeMasterKey {
 
 /// A BSW Ciphertext (CT)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct CpAbeCiphertext {
     pub _policy: (String, PolicyLanguage),
     pub _c: G1,
     pub _c_p: Gt,
     pub _c_y: Vec<CpAbeAttribute>,
     pub _ct: Vec<u8>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    #[serde(default)]
+    pub _suite: AeadSuite,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
+}
+
+/// A BSW key encapsulation: a CpAbeCiphertext without the symmetrically encrypted data
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
+pub struct CpAbeEncapsulation {
+    pub _policy: (String, PolicyLanguage),
+    pub _c: G1,
+    pub _c_p: Gt,
+    pub _c_y: Vec<CpAbeAttribute>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
+}
+
+impl CpAbeCiphertext {
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> CpAbeEncapsulation {
+        CpAbeEncapsulation {
+            _policy: self._policy.clone(),
+            _c: self._c,
+            _c_p: self._c_p,
+            _c_y: self._c_y.clone(),
+            _hash: self._hash,
+            _kdf: self._kdf,
+            _issuer: self._issuer.clone(),
+        }
+    }
 }
 
 /// A BSW Secret User Key (SK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct CpAbeSecretKey {
     pub _d: G2,
     pub _d_j: Vec<CpAbeAttribute>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
 }
 
 /// A BSW Attribute
@@ -73,6 +132,85 @@ pub struct CpAbeAttribute {
     pub _g2: G2,
 }
 
+impl_wire!(CpAbePublicKey: Bsw, PublicKey { _g1, _g2, _h, _f, _e_gg_alpha });
+
+impl_json!(CpAbePublicKey { _g1, _g2, _h, _f, _e_gg_alpha });
+
+impl Fingerprint for CpAbePublicKey {}
+
+impl_validate!(CpAbePublicKey {
+    _g1: check_g1,
+    _g2: check_g2,
+    _h: check_g1,
+    _f: check_g2,
+    _e_gg_alpha: check_gt,
+});
+
+impl_validated_serde!(CpAbePublicKey);
+
+impl_wire!(CpAbeMasterKey: Bsw, MasterKey { _beta, _g2_alpha });
+
+impl_json!(CpAbeMasterKey { _beta, _g2_alpha });
+
+impl Fingerprint for CpAbeMasterKey {}
+
+impl_validate!(CpAbeMasterKey {
+    // keygen inverts _beta
+    _beta: check_fr_invertible,
+    _g2_alpha: check_g2,
+});
+
+impl_validated_serde!(CpAbeMasterKey);
+
+impl_wire!(CpAbeCiphertext: Bsw, Ciphertext { _policy, _c, _c_p, _c_y, _ct, _hash, _kdf, _suite; _issuer });
+
+impl_json!(CpAbeCiphertext {
+    _policy: tuple(POLICY_FIELDS), _c, _c_p, _c_y, _ct, _hash, _kdf, _suite, _issuer
+});
+
+impl_validate!(CpAbeCiphertext {
+    _policy: check_policy,
+    _c: check_g1,
+    _c_p: check_gt,
+    _c_y: each,
+    _issuer: check_issuer_length,
+});
+
+impl_validated_serde!(CpAbeCiphertext);
+
+impl_wire!(CpAbeEncapsulation: Bsw, Encapsulation { _policy, _c, _c_p, _c_y, _hash, _kdf; _issuer });
+
+impl_json!(CpAbeEncapsulation { _policy: tuple(POLICY_FIELDS), _c, _c_p, _c_y, _hash, _kdf, _issuer });
+
+impl_validate!(CpAbeEncapsulation {
+    _policy: check_policy,
+    _c: check_g1,
+    _c_p: check_gt,
+    _c_y: each,
+    _issuer: check_issuer_length,
+});
+
+impl_validated_serde!(CpAbeEncapsulation);
+
+impl_wire!(CpAbeSecretKey: Bsw, SecretKey { _d, _d_j, _hash; _issuer });
+
+impl_json!(CpAbeSecretKey { _d, _d_j, _hash, _issuer });
+
+impl_validate!(CpAbeSecretKey {
+    _d: check_g2,
+    _d_j: unique(_str),
+    _d_j: each,
+    _issuer: check_issuer_length,
+});
+
+impl_validated_serde!(CpAbeSecretKey);
+
+impl_wire!(CpAbeAttribute { _str, _g1, _g2 });
+
+impl_json!(CpAbeAttribute { _str, _g1, _g2 });
+
+impl_validate!(CpAbeAttribute { _g1: check_g1, _g2: check_g2 });
+
 /// The setup algorithm of BSW CP-ABE. Generates a new CpAbePublicKey and a new CpAbeMasterKey.
 pub fn setup() -> (CpAbePublicKey, CpAbeMasterKey) {
     // random number generator
@@ -111,12 +249,13 @@ pub fn keygen(
     _pk: &CpAbePublicKey,
     _msk: &CpAbeMasterKey,
     _attributes: &Vec<String>,
-) -> Option<CpAbeSecretKey> {
+) -> Result<CpAbeSecretKey, RabeError> {
     // if no attibutes or an empty policy
     // maybe add empty msk also here
     if _attributes.is_empty() || _attributes.len() == 0 {
-        return None;
+        return Err(RabeError::new("Error in bsw/keygen: no attributes given."));
     }
+    let _attributes = &canonicalize(_attributes)?;
     // random number generator
     let mut _rng = rand::thread_rng();
     // generate random r1 and r2 and sum of both
@@ -130,10 +269,10 @@ pub fn keygen(
         _d_j.push(CpAbeAttribute {
             _str: _j.clone(), // attribute name
             _g1: _pk._g1 * _r_j, // D_j Prime
-            _g2: _g_r + (blake2b_hash_g2(_pk._g2, &_j) * _r_j), // D_j
+            _g2: _g_r + (HashVersion::CURRENT.hash_g2(_pk._g2, HASH_ATTRIBUTE, &[_j.as_bytes()]) * _r_j), // D_j
         });
     }
-    return Some(CpAbeSecretKey {_d, _d_j});
+    Ok(CpAbeSecretKey {_d, _d_j, _hash: HashVersion::CURRENT, _issuer: _pk.fingerprint()})
 }
 
 /// The delegate generation algorithm of BSW CP-ABE. Generates a new CpAbeSecretKey using a CpAbePublicKey, a CpAbeSecretKey and a subset of attributes (of the key _sk) given as Vec<String>.
@@ -148,21 +287,19 @@ pub fn delegate(
     _pk: &CpAbePublicKey,
     _sk: &CpAbeSecretKey,
     _subset: &Vec<String>,
-) -> Option<CpAbeSecretKey> {
+) -> Result<CpAbeSecretKey, RabeError> {
     let _str_attr = _sk._d_j
         .iter()
         .map(|_values| _values._str.to_string())
         .collect::<Vec<_>>();
-
+    let _subset = &canonicalize(_subset)?;
     return if !is_subset(&_subset, &_str_attr) {
-        println!("Error: the given attribute set is not a subset of the given sk.");
-        None
+        Err(RabeError::new("Error in bsw/delegate: the given attribute set is not a subset of the given sk."))
     } else {
         // if no attibutes or an empty policy
         // maybe add empty msk also here
         if _subset.is_empty() || _subset.len() == 0 {
-            println!("Error: the given attribute subset is empty.");
-            return None;
+            return Err(RabeError::new("Error in bsw/delegate: the given attribute subset is empty."));
         }
         // random number generator
         let mut _rng = rand::thread_rng();
@@ -181,12 +318,14 @@ pub fn delegate(
             _d_k.push(CpAbeAttribute {
                 _str: _attr.clone(),
                 _g1: _d_j_val.0 + (_pk._g1 * _r_j),
-                _g2: _d_j_val.1 + (blake2b_hash_g2(_pk._g2, &_attr) * _r_j) + (_pk._g2 * _r),
+                _g2: _d_j_val.1 + (_sk._hash.hash_g2(_pk._g2, HASH_ATTRIBUTE, &[_attr.as_bytes()]) * _r_j) + (_pk._g2 * _r),
             });
         }
-        Some(CpAbeSecretKey {
+        Ok(CpAbeSecretKey {
             _d: _sk._d + (_pk._f * _r),
             _d_j: _d_k,
+            _hash: _sk._hash,
+            _issuer: _sk._issuer.clone(),
         })
     }
 }
@@ -204,35 +343,106 @@ pub fn encrypt(
     _policy: &String,
     _plaintext: &Vec<u8>,
     _language: PolicyLanguage,
+) -> Result<CpAbeCiphertext, RabeError> {
+    encrypt_with_suite(_pk, _policy, _plaintext, _language, AeadSuite::default())
+}
+
+/// Same as encrypt(), but encrypts the plaintext with the given AEAD suite.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn encrypt_with_suite(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+    _language: PolicyLanguage,
+    _suite: AeadSuite,
 ) -> Result<CpAbeCiphertext, RabeError> {
     if _plaintext.is_empty() || _policy.is_empty() {
-        RabeError::new("Error in bsw/encrypt: Plaintext or policy is empty.");
+        return Err(RabeError::new("Error in bsw/encrypt: Plaintext or policy is empty."));
     }
+    let (_key, _enc) = encapsulate(_pk, _policy, _language)?;
+    seal(&_key, _enc, _plaintext, _suite)
+}
+
+/// Encrypts plaintext data under the SymmetricKey of a CpAbeEncapsulation, see encapsulate().
+///
+/// # Arguments
+///
+///	* `_key` - The SymmetricKey of `_enc`
+///	* `_enc` - A BSW CP-ABE Encapsulation
+///	* `_plaintext` - plaintext data given as a slice of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn seal(_key: &SymmetricKey, _enc: CpAbeEncapsulation, _plaintext: &[u8], _suite: AeadSuite) -> Result<CpAbeCiphertext, RabeError> {
+    //Encrypt plaintext using derived key from secret
+    let _ct = encrypt_symmetric_key(_key, SchemeId::Bsw, _suite, &policy_header(&_enc._policy.0, _enc._policy.1), &header_elements(&_enc), _plaintext)?;
+    let CpAbeEncapsulation { _policy, _c, _c_p, _c_y, _hash, _kdf, _issuer } = _enc;
+    Ok(CpAbeCiphertext {_policy, _c, _c_p, _c_y, _ct, _hash, _kdf, _suite, _issuer})
+}
+
+/// The encapsulate algorithm of BSW CP-ABE. Generates a new random SymmetricKey and its CpAbeEncapsulation under an access policy.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as String
+///	* `_language` - The language the policy is written in
+///
+pub fn encapsulate(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _language: PolicyLanguage,
+) -> Result<(SymmetricKey, CpAbeEncapsulation), RabeError> {
     let mut _rng = rand::thread_rng();
+    let _msg: Gt = _rng.gen();
+    let _enc = encapsulate_with(_pk, _policy, _language, &_msg, &mut _rng)?;
+    let _key = SymmetricKey::derive(KdfVersion::CURRENT, &_msg, SchemeId::Bsw, &policy_header(_policy, _language));
+    Ok((_key, _enc))
+}
+
+/// Encapsulates a given Gt message under an access policy, drawing all randomness from `_rng`.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as String
+///	* `_language` - The language the policy is written in
+///	* `_msg` - The Gt message the SymmetricKey is derived from
+///	* `_rng` - The random number generator
+///
+pub fn encapsulate_with<R: Rng>(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _language: PolicyLanguage,
+    _msg: &Gt,
+    _rng: &mut R,
+) -> Result<CpAbeEncapsulation, RabeError> {
     // the shared root secret
     let _s:Fr = _rng.gen();
-    let _msg: Gt = _rng.gen();
     match parse(_policy, _language) {
         Ok(pol) => {
-            let _shares: Vec<(String, Fr)> = gen_shares_policy(_s, &pol, None).unwrap();
+            SchemeId::Bsw.check_policy(&pol)?;
+            let _shares: Vec<(String, Fr)> = gen_shares_policy_with(_s, &pol, None, _rng).unwrap();
             let _c = _pk._h * _s;
-            let _c_p = _pk._e_gg_alpha.pow(_s) * _msg;
+            let _c_p = _pk._e_gg_alpha.pow(_s) * *_msg;
             let mut _c_y: Vec<CpAbeAttribute> = Vec::new();
             for (_j, _j_val) in _shares {
                 _c_y.push(CpAbeAttribute {
                     _str: _j.clone(),
                     _g1: _pk._g1 * _j_val,
-                    _g2: blake2b_hash_g2(_pk._g2, &_j) * _j_val,
+                    _g2: HashVersion::CURRENT.hash_g2(_pk._g2, HASH_ATTRIBUTE, &[_j.as_bytes()]) * _j_val,
                 });
             }
-            let _policy = _policy.to_string();
-            let _ct = encrypt_symmetric(&_msg, &_plaintext).unwrap();
-            //Encrypt plaintext using derived key from secret
-            return Ok(CpAbeCiphertext {_policy: (_policy, _language), _c, _c_p, _c_y, _ct});
+            Ok(CpAbeEncapsulation {_policy: (_policy.to_string(), _language), _c, _c_p, _c_y, _hash: HashVersion::CURRENT, _kdf: KdfVersion::CURRENT, _issuer: _pk.fingerprint()})
         },
         Err(e) => Err(e)
     }
-
 }
 
 /// The decrypt algorithm of BSW CP-ABE. Reconstructs the original plaintext data as Vec<u8>, given a CpAbeCiphertext with a matching CpAbeSecretKey.
@@ -243,11 +453,49 @@ pub fn encrypt(
 ///	* `_ct` - An BSW CP-ABE Ciphertext
 ///
 pub fn decrypt(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+    let _key = decapsulate(_sk, &_ct.encapsulation())?;
+    open(&_key, _ct)
+}
+
+/// Decrypts a CpAbeCiphertext under the SymmetricKey of its encapsulation, see decapsulate().
+///
+/// # Arguments
+///
+///	* `_key` - The SymmetricKey of the encapsulation of `_ct`
+///	* `_ct` - A BSW CP-ABE Ciphertext
+///
+pub fn open(_key: &SymmetricKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+    // Decrypt plaintext using derived secret from cp-abe scheme
+    decrypt_symmetric_key(_ct._kdf, _key, SchemeId::Bsw, _ct._suite, &policy_header(&_ct._policy.0, _ct._policy.1), &header_elements(&_ct.encapsulation()), &_ct._ct)
+}
+
+/// The decapsulate algorithm of BSW CP-ABE. Recovers the SymmetricKey of a CpAbeEncapsulation with a matching CpAbeSecretKey.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_enc` - A BSW CP-ABE Encapsulation, generated by the function encapsulate()
+///
+pub fn decapsulate(_sk: &CpAbeSecretKey, _enc: &CpAbeEncapsulation) -> Result<SymmetricKey, RabeError> {
+    let _msg = decapsulate_message(_sk, _enc)?;
+    Ok(SymmetricKey::derive(_enc._kdf, &_msg, SchemeId::Bsw, &policy_header(&_enc._policy.0, _enc._policy.1)))
+}
+
+/// Recovers the Gt message of a CpAbeEncapsulation with a matching CpAbeSecretKey.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_enc` - A BSW CP-ABE Encapsulation
+///
+pub fn decapsulate_message(_sk: &CpAbeSecretKey, _enc: &CpAbeEncapsulation) -> Result<Gt, RabeError> {
+    _sk._hash.check(_enc._hash)?;
+    check_issuer(&_sk._issuer, &_enc._issuer)?;
     let _str_attr = _sk._d_j
         .iter()
         .map(|_values| _values._str.to_string())
         .collect::<Vec<_>>();
-    match parse(_ct._policy.0.as_ref(), _ct._policy.1) {
+    match parse(_enc._policy.0.as_ref(), _enc._policy.1) {
         Ok(pol) => {
             return if traverse_policy(&_str_attr, &pol, PolicyType::Leaf) == false {
                 Err(RabeError::new("Error in bsw/encrypt: attributes do not match policy."))
@@ -258,10 +506,10 @@ pub fn decrypt(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, R
                         if !_pruned.0 {
                             Err(RabeError::new("Error in bsw/encrypt: attributes do not match policy."))
                         } else {
-                            let _z = calc_coefficients(&pol, Some(Fr::one()), None).unwrap();
+                            let _z = calc_coefficients(&pol, Some(Fr::one()), None, Some(&_str_attr)).unwrap();
                             let mut _a = Gt::one();
                             for _j in _pruned.1 {
-                                match _ct._c_y.iter().find(|x| x._str == _j.to_string()) {
+                                match _enc._c_y.iter().find(|x| x._str == _j) {
                                     Some(_c_j) => {
                                         match _sk._d_j.iter().find(|x| x._str == _j.to_string()) {
                                             Some(_d_j) => {
@@ -284,9 +532,7 @@ pub fn decrypt(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, R
                                     }
                                 }
                             }
-                            let _msg = _ct._c_p * ((pairing(_ct._c, _sk._d)) * _a.inverse()).inverse();
-                            // Decrypt plaintext using derived secret from cp-abe scheme
-                            decrypt_symmetric(&_msg, &_ct._ct)
+                            Ok(_enc._c_p * ((pairing(_enc._c, _sk._d)) * _a.inverse()).inverse())
                         }
                     }
                 }
@@ -296,6 +542,85 @@ pub fn decrypt(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, R
     }
 }
 
+/// The public group elements of a ciphertext, authenticated as associated data
+fn header_elements(_enc: &CpAbeEncapsulation) -> Vec<u8> {
+    let mut _elements = HeaderEncoder::new();
+    _elements.g1(&_enc._c).gt(&_enc._c_p).count(_enc._c_y.len());
+    for _c_y in _enc._c_y.iter() {
+        _elements.str(&_c_y._str).g1(&_c_y._g1).g2(&_c_y._g2);
+    }
+    _elements.finish()
+}
+
+/// The BSW CP-ABE scheme, for use through the `CpAbe` trait
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bsw;
+
+impl CpAbe for Bsw {
+    const ID: SchemeId = SchemeId::Bsw;
+
+    type PublicKey = CpAbePublicKey;
+    type MasterKey = CpAbeMasterKey;
+    type SecretKey = CpAbeSecretKey;
+    type Ciphertext = CpAbeCiphertext;
+    type Encapsulation = CpAbeEncapsulation;
+
+    fn setup(&self) -> (CpAbePublicKey, CpAbeMasterKey) {
+        setup()
+    }
+
+    fn keygen(&self, _pk: &CpAbePublicKey, _msk: &CpAbeMasterKey, _attributes: &[String]) -> Result<CpAbeSecretKey, RabeError> {
+        keygen(_pk, _msk, &_attributes.to_vec())
+    }
+
+    fn encrypt_with_suite(&self, _pk: &CpAbePublicKey, _policy: &str, _language: PolicyLanguage, _plaintext: &[u8], _suite: AeadSuite) -> Result<CpAbeCiphertext, RabeError> {
+        encrypt_with_suite(_pk, &_policy.to_string(), &_plaintext.to_vec(), _language, _suite)
+    }
+
+    fn decrypt(&self, _sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+        decrypt(_sk, _ct)
+    }
+
+    fn encapsulate(&self, _pk: &CpAbePublicKey, _policy: &str, _language: PolicyLanguage) -> Result<(SymmetricKey, CpAbeEncapsulation), RabeError> {
+        encapsulate(_pk, &_policy.to_string(), _language)
+    }
+
+    fn decapsulate(&self, _sk: &CpAbeSecretKey, _enc: &CpAbeEncapsulation) -> Result<SymmetricKey, RabeError> {
+        decapsulate(_sk, _enc)
+    }
+
+    fn explain(&self, _sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Explanation, RabeError> {
+        let _attributes: Vec<String> = _sk._d_j.iter().map(|_attr| _attr._str.clone()).collect();
+        explain(&Policy::parse(&_ct._policy.0, _ct._policy.1)?, &_attributes)
+    }
+}
+
+impl CpAbeCca for Bsw {
+    fn encapsulate_with<R: Rng>(&self, _pk: &CpAbePublicKey, _policy: &str, _language: PolicyLanguage, _msg: &Gt, _rng: &mut R) -> Result<CpAbeEncapsulation, RabeError> {
+        encapsulate_with(_pk, &_policy.to_string(), _language, _msg, _rng)
+    }
+
+    fn decapsulate_message(&self, _sk: &CpAbeSecretKey, _enc: &CpAbeEncapsulation) -> Result<Gt, RabeError> {
+        decapsulate_message(_sk, _enc)
+    }
+
+    fn encapsulation_policy(&self, _enc: &CpAbeEncapsulation) -> (String, PolicyLanguage) {
+        _enc._policy.clone()
+    }
+
+    fn encapsulation(&self, _ct: &CpAbeCiphertext) -> CpAbeEncapsulation {
+        _ct.encapsulation()
+    }
+
+    fn seal(&self, _key: &SymmetricKey, _enc: CpAbeEncapsulation, _plaintext: &[u8], _suite: AeadSuite) -> Result<CpAbeCiphertext, RabeError> {
+        seal(_key, _enc, _plaintext, _suite)
+    }
+
+    fn open(&self, _key: &SymmetricKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+        open(_key, _ct)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -334,6 +659,15 @@ mod tests {
         assert_eq!(_no_match.is_ok(), false);
     }
 
+    #[test]
+    fn empty_input() {
+        let (pk, _msk) = setup();
+        let policy = String::from(r#""A" or "B""#);
+        let plaintext = String::from("plaintext").into_bytes();
+        assert!(encrypt(&pk, &policy, &Vec::new(), PolicyLanguage::HumanPolicy).is_err());
+        assert!(encrypt(&pk, &String::new(), &plaintext, PolicyLanguage::HumanPolicy).is_err());
+    }
+
     #[test]
     fn and10() {
         // setup scheme
@@ -508,6 +842,24 @@ mod tests {
         assert_eq!(_no_match.is_ok(), false);
     }
 
+    #[test]
+    fn single() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy consists of a single attribute
+        let policy = String::from(r#""admin""#);
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&keygen(&pk, &msk, &vec![String::from("admin"), String::from("B")]).unwrap(), &ct_cp);
+        assert_eq!(_match.unwrap(), plaintext);
+        let _no_match = decrypt(&keygen(&pk, &msk, &vec![String::from("B")]).unwrap(), &ct_cp);
+        assert!(_no_match.is_err());
+    }
+
     #[test]
     fn or_and() {
         // setup scheme
@@ -566,4 +918,129 @@ mod tests {
         assert_eq!(_match.is_ok(), true);
         assert_eq!(_match.unwrap(), plaintext);
     }
+
+    #[test]
+    fn invalid_attributes() {
+        let (pk, msk) = setup();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        // the validation error of the attribute is returned, not swallowed
+        let _error = keygen(&pk, &msk, &vec![String::from("dept=fin!ance")]).err().unwrap();
+        assert!(_error.to_string().contains("invalid character '!'"));
+        let _error = delegate(&pk, &sk, &vec![String::from("dept=fin!ance")]).err().unwrap();
+        assert!(_error.to_string().contains("invalid character '!'"));
+        assert!(delegate(&pk, &sk, &vec![String::from("B")]).is_err());
+    }
+
+    #[test]
+    fn legacy_hash() {
+        let (pk, msk) = setup();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &String::from(r#""A""#), &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(ct_cp._hash, HashVersion::CURRENT);
+        // ciphertexts serialized before the hash version was recorded used the legacy hash
+        let mut _json = serde_json::to_value(&ct_cp).unwrap();
+        _json.as_object_mut().unwrap().remove("_hash");
+        let _legacy: CpAbeCiphertext = serde_json::from_value(_json).unwrap();
+        assert_eq!(_legacy._hash, HashVersion::Legacy);
+        assert!(decrypt(&sk, &_legacy).is_err());
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn key_mismatch() {
+        let (pk, msk) = setup();
+        let (other_pk, other_msk) = setup();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &String::from(r#""A""#), &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        let other_sk: CpAbeSecretKey = keygen(&other_pk, &other_msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(sk._issuer, pk.fingerprint());
+        assert_eq!(ct_cp._issuer, pk.fingerprint());
+        assert_ne!(pk.fingerprint(), msk.fingerprint());
+        assert_eq!(delegate(&pk, &sk, &vec![String::from("A")]).unwrap()._issuer, sk._issuer);
+        let _error = decrypt(&other_sk, &ct_cp).unwrap_err();
+        assert_eq!(_error.key_mismatch().unwrap()._key, other_pk.fingerprint());
+        // keys serialized before the issuer was recorded are not checked
+        let mut _json = serde_json::to_value(&other_sk).unwrap();
+        _json.as_object_mut().unwrap().remove("_issuer");
+        let _legacy: CpAbeSecretKey = serde_json::from_value(_json).unwrap();
+        assert!(_legacy._issuer.is_empty());
+        assert!(decrypt(&_legacy, &ct_cp).unwrap_err().key_mismatch().is_none());
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn kdf_binds_header() {
+        let (pk, msk) = setup();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &String::from(r#""A" or "B""#), &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(ct_cp._kdf, KdfVersion::CURRENT);
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        // an equivalent policy still satisfies the key, but derives another symmetric key
+        let mut _swapped = ct_cp.clone();
+        _swapped._policy.0 = String::from(r#""B" or "A""#);
+        assert!(decrypt(&sk, &_swapped).is_err());
+        // legacy ciphertexts are still decrypted with the Display based KDF
+        let _msg = pk._e_gg_alpha;
+        let _legacy = encrypt_symmetric(&_msg, &plaintext).unwrap();
+        assert_eq!(decrypt_symmetric_gt(KdfVersion::Legacy, &_msg, SchemeId::Bsw, AeadSuite::Aes256Ccm, &[], &[], &_legacy).unwrap(), plaintext);
+        assert!(decrypt_symmetric_gt(KdfVersion::Hkdf, &_msg, SchemeId::Bsw, AeadSuite::Aes256Ccm, &[], &[], &_legacy).is_err());
+    }
+
+    #[test]
+    fn aad_binds_elements() {
+        let (pk, msk) = setup();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &String::from(r#""A" or "B""#), &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        // the element of "B" is not needed to decrypt with "A", but it is authenticated
+        let mut _tampered = ct_cp.clone();
+        let _b = _tampered._c_y.iter_mut().find(|_c_y| _c_y._str == "B").unwrap();
+        _b._g1 = _b._g1 + pk._g1;
+        assert!(decrypt(&sk, &_tampered).is_err());
+        // the associated data cannot be stripped by downgrading the recorded KDF
+        let mut _downgraded = ct_cp.clone();
+        _downgraded._kdf = KdfVersion::Hkdf;
+        assert!(decrypt(&sk, &_downgraded).is_err());
+        _tampered._kdf = KdfVersion::Hkdf;
+        assert!(decrypt(&sk, &_tampered).is_err());
+    }
+
+    #[test]
+    fn cca_rejects_mauled_encapsulation() {
+        let (pk, msk) = setup();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        let (_key, _enc) = Bsw.encapsulate_cca(&pk, r#""A" or "B""#, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(Bsw.decapsulate_cca(&pk, &sk, &_enc).unwrap().as_bytes(), _key.as_bytes());
+        // multiplying _c_p by a known factor multiplies the recovered message by it
+        let _factor: Gt = rand::thread_rng().gen();
+        let mut _mauled = _enc.clone();
+        _mauled._c_p = _mauled._c_p * _factor;
+        assert!(decapsulate_message(&sk, &_mauled).unwrap() == decapsulate_message(&sk, &_enc).unwrap() * _factor);
+        assert!(Bsw.decapsulate_cca(&pk, &sk, &_mauled).is_err());
+    }
+
+    #[test]
+    fn aead_suite() {
+        let (pk, msk) = setup();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        let policy = String::from(r#""A""#);
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        // ciphertexts serialized before the suite was recorded used AES-256-CCM
+        let ct_ccm: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+        let mut _json = serde_json::to_value(&ct_ccm).unwrap();
+        _json.as_object_mut().unwrap().remove("_suite");
+        let _legacy: CpAbeCiphertext = serde_json::from_value(_json).unwrap();
+        assert_eq!(_legacy._suite, AeadSuite::Aes256Ccm);
+        assert_eq!(decrypt(&sk, &_legacy).unwrap(), plaintext);
+        let mut ct_gcm: CpAbeCiphertext = encrypt_with_suite(&pk, &policy, &plaintext, PolicyLanguage::HumanPolicy, AeadSuite::Aes256Gcm).unwrap();
+        assert_eq!(ct_gcm._ct.len(), 12 + plaintext.len() + 16);
+        assert_eq!(decrypt(&sk, &ct_gcm).unwrap(), plaintext);
+        ct_gcm._suite = AeadSuite::ChaCha20Poly1305;
+        assert!(decrypt(&sk, &ct_gcm).is_err());
+        assert_eq!("chacha20-poly1305".parse::<AeadSuite>().unwrap(), AeadSuite::ChaCha20Poly1305);
+    }
 }
@@ -3,7 +3,7 @@
 //! * Developped by Allison Lewko, Amit Sahai and Brent Waters, "Revocation Systems with Very Small Private Keys"
 //! * Published in Security and Privacy, 2010. SP'10. IEEE Symposium on. IEEE
 //! * Available from http://eprint.iacr.org/2008/309.pdf
-//! * Type: encryption (key-policy attribute-based)
+//! * Type: encryption (key-policy attribute-based), supports negated attributes (`not "A"`) in policies
 //! * Setting: bilinear groups (asymmetric)
 //! * Authors: Georg Bramm
 //! * Date:	04/2018
@@ -23,17 +23,34 @@
 use rabe_bn::{Group, Fr, G1, G2, Gt, pairing};
 use std::ops::Neg;
 use utils::{
+    attribute::canonicalize,
     tools::*,
     secretsharing::{gen_shares_policy, calc_coefficients, calc_pruned},
     aes::*,
-    hash::{blake2b_hash_fr, blake2b_hash_g1}
+    hash::HashVersion
 };
 use rand::Rng;
 use utils::policy::pest::{PolicyLanguage, parse};
 use RabeError;
+use utils::validate::{ValidationError, check_g1, check_g2, check_gt, check_fr, check_policy, check_issuer_length};
+use utils::fingerprint::{Fingerprint, check_issuer};
+use utils::json::POLICY_FIELDS;
+use schemes::{KpAbe, KpAbeCca, SchemeId};
+use utils::policy::{ast::Policy, explain::{explain, Explanation}};
+
+/// The JSON field names of the tuples in _dj
+const JSON_DJ: &[&str] = &["attribute", "d_1", "d_2", "d_3", "d_4", "d_5"];
+/// The JSON field names of the tuples in _ej
+const JSON_EJ: &[&str] = &["attribute", "e_3", "e_4", "e_5"];
+
+/// The domain of the attribute hashes to G1
+const HASH_ATTRIBUTE: &str = "rabe/lsw/attribute";
+/// The domain of the attribute hashes to Fr, which negated attributes are built on
+const HASH_EXPONENT: &str = "rabe/lsw/exponent";
 
 /// A LSW Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct KpAbePublicKey {
     _g_g1: G1,
     _g_g2: G2,
@@ -45,6 +62,7 @@ pub struct KpAbePublicKey {
 
 /// A LSW Master Key (MSK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct KpAbeMasterKey {
     _alpha1: Fr,
     _alpha2: Fr,
@@ -55,20 +73,174 @@ pub struct KpAbeMasterKey {
 
 /// A LSW Secret User Key (SK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct KpAbeSecretKey {
     _policy: (String, PolicyLanguage),
-    _dj: Vec<(String, G1, G2, G1, G1, G1)>,
+    _dj: Vec<(String, G1, G2, G1, G2, G2)>,
+    #[serde(default)]
+    _hash: HashVersion,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    _issuer: Vec<u8>,
 }
 
 /// A LSW Ciphertext (CT)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct KpAbeCiphertext {
     _e1: Gt,
     _e2: G2,
     _ej: Vec<(String, G1, G1, G1)>,
     _ct: Vec<u8>,
+    #[serde(default)]
+    _hash: HashVersion,
+    #[serde(default)]
+    _kdf: KdfVersion,
+    #[serde(default)]
+    _suite: AeadSuite,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    _issuer: Vec<u8>,
+}
+
+/// A LSW key encapsulation: a KpAbeCiphertext without the symmetrically encrypted data
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
+pub struct KpAbeEncapsulation {
+    _e1: Gt,
+    _e2: G2,
+    _ej: Vec<(String, G1, G1, G1)>,
+    #[serde(default)]
+    _hash: HashVersion,
+    #[serde(default)]
+    _kdf: KdfVersion,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    _issuer: Vec<u8>,
+}
+
+impl KpAbeCiphertext {
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> KpAbeEncapsulation {
+        KpAbeEncapsulation {
+            _e1: self._e1,
+            _e2: self._e2,
+            _ej: self._ej.clone(),
+            _hash: self._hash,
+            _kdf: self._kdf,
+            _issuer: self._issuer.clone(),
+        }
+    }
+}
+
+impl KpAbeEncapsulation {
+    /// The attributes the encapsulation is labelled with
+    fn attributes(&self) -> Vec<String> {
+        self._ej.iter().map(|_attr| _attr.0.clone()).collect()
+    }
 }
 
+impl_wire!(KpAbePublicKey: Lsw, PublicKey { _g_g1, _g_g2, _g_g1_b, _g_g1_b2, _h_g1_b, _e_gg_alpha });
+
+impl_json!(KpAbePublicKey { _g_g1, _g_g2, _g_g1_b, _g_g1_b2, _h_g1_b, _e_gg_alpha });
+
+impl Fingerprint for KpAbePublicKey {}
+
+impl_validate!(KpAbePublicKey {
+    _g_g1: check_g1,
+    _g_g2: check_g2,
+    _g_g1_b: check_g1,
+    _g_g1_b2: check_g1,
+    _h_g1_b: check_g1,
+    _e_gg_alpha: check_gt,
+});
+
+impl_validated_serde!(KpAbePublicKey);
+
+impl_wire!(KpAbeMasterKey: Lsw, MasterKey { _alpha1, _alpha2, _beta, _h_g1, _h_g2 });
+
+impl_json!(KpAbeMasterKey { _alpha1, _alpha2, _beta, _h_g1, _h_g2 });
+
+impl Fingerprint for KpAbeMasterKey {}
+
+impl_validate!(KpAbeMasterKey {
+    _alpha1: check_fr,
+    _alpha2: check_fr,
+    _beta: check_fr,
+    _h_g1: check_g1,
+    _h_g2: check_g2,
+});
+
+impl_validated_serde!(KpAbeMasterKey);
+
+impl_wire!(KpAbeSecretKey: Lsw, SecretKey { _policy, _dj, _hash; _issuer });
+
+impl_json!(KpAbeSecretKey { _policy: tuple(POLICY_FIELDS), _dj: tuples(JSON_DJ), _hash, _issuer });
+
+impl_validate!(KpAbeSecretKey {
+    _policy: check_policy,
+    _issuer: check_issuer_length,
+} |_self| {
+    // the first two components belong to positive, the last three to negated attributes,
+    // the other ones are the identity
+    for (_attr, _d1, _d2, _d3, _d4, _d5) in _self._dj.iter() {
+        if _attr.starts_with('!') {
+            if !(_d1.is_zero() && _d2.is_zero()) {
+                return Err(ValidationError::Inconsistent { _field: "_dj".to_string(), _reason: format!("negated attribute {} has positive components", _attr) }.into());
+            }
+            check_g1("_dj", _d3)?;
+            check_g2("_dj", _d4)?;
+            check_g2("_dj", _d5)?;
+        } else {
+            if !(_d3.is_zero() && _d4.is_zero() && _d5.is_zero()) {
+                return Err(ValidationError::Inconsistent { _field: "_dj".to_string(), _reason: format!("attribute {} has negated components", _attr) }.into());
+            }
+            check_g1("_dj", _d1)?;
+            check_g2("_dj", _d2)?;
+        }
+    }
+});
+
+impl_validated_serde!(KpAbeSecretKey);
+
+impl_wire!(KpAbeCiphertext: Lsw, Ciphertext { _e1, _e2, _ej, _ct, _hash, _kdf, _suite; _issuer });
+
+impl_json!(KpAbeCiphertext { _e1, _e2, _ej: tuples(JSON_EJ), _ct, _hash, _kdf, _suite, _issuer });
+
+impl_validate!(KpAbeCiphertext {
+    _e1: check_gt,
+    _e2: check_g2,
+    _ej: unique(0),
+    _issuer: check_issuer_length,
+} |_self| {
+    for (_, _e1, _e2, _e3) in _self._ej.iter() {
+        check_g1("_ej", _e1)?;
+        check_g1("_ej", _e2)?;
+        check_g1("_ej", _e3)?;
+    }
+});
+
+impl_validated_serde!(KpAbeCiphertext);
+
+impl_wire!(KpAbeEncapsulation: Lsw, Encapsulation { _e1, _e2, _ej, _hash, _kdf; _issuer });
+
+impl_json!(KpAbeEncapsulation { _e1, _e2, _ej: tuples(JSON_EJ), _hash, _kdf, _issuer });
+
+impl_validate!(KpAbeEncapsulation {
+    _e1: check_gt,
+    _e2: check_g2,
+    _ej: unique(0),
+    _issuer: check_issuer_length,
+} |_self| {
+    for (_, _e1, _e2, _e3) in _self._ej.iter() {
+        check_g1("_ej", _e1)?;
+        check_g1("_ej", _e2)?;
+        check_g1("_ej", _e3)?;
+    }
+});
+
+impl_validated_serde!(KpAbeEncapsulation);
+
 /// The setup algorithm of LSW KP-ABE. Generates a new KpAbePublicKey and a new KpAbeMasterKey.
 pub fn setup() -> (KpAbePublicKey, KpAbeMasterKey) {
     // random number generator
@@ -80,8 +252,10 @@ pub fn setup() -> (KpAbePublicKey, KpAbeMasterKey) {
     let _alpha = _alpha1 * _alpha2;
     let _g_g1:G1 = _rng.gen();
     let _g_g2:G2 = _rng.gen();
-    let _h_g1:G1 = _rng.gen();
-    let _h_g2:G2 = _rng.gen();
+    // h has the same discrete logarithm in G1 and G2, which is needed to decrypt using negated attributes
+    let _eta:Fr = _rng.gen();
+    let _h_g1 = _g_g1 * _eta;
+    let _h_g2 = _g_g2 * _eta;
     let _g_g1_b = _g_g1 * _beta;
     let _g_g1_b2 = _g_g1_b * _beta;
     let _h_g1_b = _h_g1 * _beta;
@@ -101,7 +275,7 @@ pub fn setup() -> (KpAbePublicKey, KpAbeMasterKey) {
 ///
 ///	* `_pk` - A Public Key (PK), generated by the function setup()
 ///	* `_msk` - A Master Key (MSK), generated by the function setup()
-///	* `_policy` - An access policy given as JSON String
+///	* `_policy` - An access policy given as JSON String. It may contain negated attributes (`not "A"`)
 ///
 pub fn keygen(
     _pk: &KpAbePublicKey,
@@ -114,33 +288,36 @@ pub fn keygen(
     match parse(_policy, _language) {
         Ok(pol) => {
             let _shares = gen_shares_policy(_msk._alpha1, &pol, None).unwrap();
-            let mut _d: Vec<(String, G1, G2, G1, G1, G1)> = Vec::new();
+            let mut _d: Vec<(String, G1, G2, G1, G2, G2)> = Vec::new();
             for (_share_str, _share_value) in _shares.into_iter() {
                 let _r:Fr = _rng.gen();
                 if is_negative(&_share_str) {
+                    let _attr = _share_str[1..].to_string();
                     _d.push((
                         _share_str.to_string(),
                         G1::zero(),
                         G2::zero(),
-                        (_pk._g_g1 * _share_value) + (_pk._g_g1_b2 * _r),
-                        _pk._g_g1_b * (blake2b_hash_fr(&_share_str) * _r) + (_msk._h_g1 * _r),
-                        _pk._g_g1 * _r.neg(),
+                        (_pk._g_g1 * (_msk._alpha2 * _share_value)) + (_pk._g_g1_b2 * _r),
+                        (_pk._g_g2 * (_msk._beta * _r * HashVersion::CURRENT.hash_fr(HASH_EXPONENT, &[_attr.as_bytes()]))) + (_msk._h_g2 * _r),
+                        _pk._g_g2 * _r.neg(),
                     ));
                 } else {
                     _d.push((
                         _share_str.to_string(),
                         (_pk._g_g1 * (_msk._alpha2 * _share_value))
-                            + (blake2b_hash_g1(_pk._g_g1, &_share_str) * _r),
+                            + (HashVersion::CURRENT.hash_g1(_pk._g_g1, HASH_ATTRIBUTE, &[_share_str.as_bytes()]) * _r),
                         _pk._g_g2 * _r,
                         G1::zero(),
-                        G1::zero(),
-                        G1::zero(),
+                        G2::zero(),
+                        G2::zero(),
                     ));
                 }
             }
             return Ok(KpAbeSecretKey {
                 _policy: (_policy.clone(), _language),
                 _dj: _d,
+                _hash: HashVersion::CURRENT,
+                _issuer: _pk.fingerprint(),
             });
         },
         Err(e) => Err(e)
@@ -159,38 +336,111 @@ pub fn encrypt(
     _pk: &KpAbePublicKey,
     _attributes: &Vec<String>,
     _plaintext: &[u8],
-) -> Option<KpAbeCiphertext> {
-    if _attributes.is_empty() || _plaintext.is_empty() {
-        return None;
+) -> Result<KpAbeCiphertext, RabeError> {
+    encrypt_with_suite(_pk, _attributes, _plaintext, AeadSuite::default())
+}
+
+/// Same as encrypt(), but encrypts the plaintext with the given AEAD suite.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_attributes` - A set of attributes given as String Vector
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn encrypt_with_suite(
+    _pk: &KpAbePublicKey,
+    _attributes: &Vec<String>,
+    _plaintext: &[u8],
+    _suite: AeadSuite,
+) -> Result<KpAbeCiphertext, RabeError> {
+    if _plaintext.is_empty() {
+        return Err(RabeError::new("Error in lsw/encrypt: no plaintext given."));
+    }
+    let (_key, _enc) = encapsulate(_pk, _attributes)?;
+    seal(&_key, _enc, _plaintext, _suite)
+}
+
+/// Encrypts plaintext data under the SymmetricKey of a KpAbeEncapsulation, see encapsulate().
+///
+/// # Arguments
+///
+///	* `_key` - The SymmetricKey of `_enc`
+///	* `_enc` - A LSW KP-ABE Encapsulation
+///	* `_plaintext` - plaintext data given as a slice of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn seal(_key: &SymmetricKey, _enc: KpAbeEncapsulation, _plaintext: &[u8], _suite: AeadSuite) -> Result<KpAbeCiphertext, RabeError> {
+    //Encrypt plaintext using derived key from secret
+    let _ct = encrypt_symmetric_key(_key, SchemeId::Lsw, _suite, &attribute_header(&_enc.attributes()), &header_elements(&_enc), _plaintext)?;
+    let KpAbeEncapsulation { _e1, _e2, _ej, _hash, _kdf, _issuer } = _enc;
+    Ok(KpAbeCiphertext {_e1, _e2, _ej, _ct, _hash, _kdf, _suite, _issuer})
+}
+
+/// The encapsulate algorithm of LSW KP-ABE. Generates a new random SymmetricKey and its KpAbeEncapsulation under a set of attributes.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_attributes` - A set of attributes given as String Vector
+///
+pub fn encapsulate(
+    _pk: &KpAbePublicKey,
+    _attributes: &Vec<String>,
+) -> Result<(SymmetricKey, KpAbeEncapsulation), RabeError> {
+    // random number generator
+    let mut _rng = rand::thread_rng();
+    // random message
+    let _msg: Gt = _rng.gen();
+    let _enc = encapsulate_with(_pk, _attributes, &_msg, &mut _rng)?;
+    let _key = SymmetricKey::derive(KdfVersion::CURRENT, &_msg, SchemeId::Lsw, &attribute_header(&_enc.attributes()));
+    Ok((_key, _enc))
+}
+
+/// Encapsulates a given Gt message under a set of attributes, drawing all randomness from `_rng`.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_attributes` - A set of attributes given as String Vector
+///	* `_msg` - The Gt message the SymmetricKey is derived from
+///	* `_rng` - The random number generator
+///
+pub fn encapsulate_with<R: Rng>(
+    _pk: &KpAbePublicKey,
+    _attributes: &Vec<String>,
+    _msg: &Gt,
+    _rng: &mut R,
+) -> Result<KpAbeEncapsulation, RabeError> {
+    if _attributes.is_empty() {
+        return Err(RabeError::new("Error in lsw/encapsulate: no attributes given."));
     } else {
-        // random number generator
-        let mut _rng = rand::thread_rng();
+        let _attributes = &canonicalize(_attributes)?;
         // attribute vector
         let mut _ej: Vec<(String, G1, G1, G1)> = Vec::new();
         // random secret
         let _s:Fr = _rng.gen();
-        // sx vector
+        // sx vector: one share of s per attribute
         let mut _sx: Vec<Fr> = Vec::new();
-        _sx.push(_s);
-        for (_i, _attr) in _attributes.iter().enumerate() {
-            _sx.push(_rng.gen());
-            _sx[0] = _sx[0] - _sx[_i];
+        let mut _sum = Fr::zero();
+        for _ in 1.._attributes.len() {
+            let _share: Fr = _rng.gen();
+            _sum = _sum + _share;
+            _sx.push(_share);
         }
-        for (_i, _attr) in _attributes.into_iter().enumerate() {
+        _sx.push(_s - _sum);
+        for (_i, _attr) in _attributes.iter().enumerate() {
             _ej.push((
                 _attr.to_string(),
-                blake2b_hash_g1(_pk._g_g1, &_attr) * _s,
+                HashVersion::CURRENT.hash_g1(_pk._g_g1, HASH_ATTRIBUTE, &[_attr.as_bytes()]) * _s,
                 _pk._g_g1_b * _sx[_i],
-                (_pk._g_g1_b2 * (_sx[_i] * blake2b_hash_fr(&_attr))) + (_pk._h_g1_b * _sx[_i]),
+                (_pk._g_g1_b2 * (_sx[_i] * HashVersion::CURRENT.hash_fr(HASH_EXPONENT, &[_attr.as_bytes()]))) + (_pk._h_g1_b * _sx[_i]),
             ));
         }
-        // random message
-        let _msg: Gt = _rng.gen();
-        let _e1 = _pk._e_gg_alpha.pow(_s) * _msg;
+        let _e1 = _pk._e_gg_alpha.pow(_s) * *_msg;
         let _e2 = _pk._g_g2 * _s;
-        let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
-        //Encrypt plaintext using derived key from secret
-        Some(KpAbeCiphertext {_e1, _e2, _ej, _ct})
+        Ok(KpAbeEncapsulation {_e1, _e2, _ej, _hash: HashVersion::CURRENT, _kdf: KdfVersion::CURRENT, _issuer: _pk.fingerprint()})
     }
 }
 
@@ -202,11 +452,46 @@ pub fn encrypt(
 ///	* `_ct` - A LSW KP-ABE Ciphertext
 ///
 pub fn decrypt(_sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
-    let _attrs_str = _ct
-        ._ej
-        .iter()
-        .map(|values| values.clone().0.to_string())
-        .collect::<Vec<_>>();
+    let _key = decapsulate(_sk, &_ct.encapsulation())?;
+    open(&_key, _ct)
+}
+
+/// Decrypts a KpAbeCiphertext under the SymmetricKey of its encapsulation, see decapsulate().
+///
+/// # Arguments
+///
+///	* `_key` - The SymmetricKey of the encapsulation of `_ct`
+///	* `_ct` - A LSW KP-ABE Ciphertext
+///
+pub fn open(_key: &SymmetricKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+    let _enc = _ct.encapsulation();
+    // Decrypt plaintext using derived secret from kp-abe scheme
+    decrypt_symmetric_key(_ct._kdf, _key, SchemeId::Lsw, _ct._suite, &attribute_header(&_enc.attributes()), &header_elements(&_enc), &_ct._ct)
+}
+
+/// The decapsulate algorithm of LSW KP-ABE. Recovers the SymmetricKey of a KpAbeEncapsulation with a matching KpAbeSecretKey.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_enc` - A LSW KP-ABE Encapsulation, generated by the function encapsulate()
+///
+pub fn decapsulate(_sk: &KpAbeSecretKey, _enc: &KpAbeEncapsulation) -> Result<SymmetricKey, RabeError> {
+    let _msg = decapsulate_message(_sk, _enc)?;
+    Ok(SymmetricKey::derive(_enc._kdf, &_msg, SchemeId::Lsw, &attribute_header(&_enc.attributes())))
+}
+
+/// Recovers the Gt message of a KpAbeEncapsulation with a matching KpAbeSecretKey.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_enc` - A LSW KP-ABE Encapsulation
+///
+pub fn decapsulate_message(_sk: &KpAbeSecretKey, _enc: &KpAbeEncapsulation) -> Result<Gt, RabeError> {
+    _sk._hash.check(_enc._hash)?;
+    check_issuer(&_sk._issuer, &_enc._issuer)?;
+    let _attrs_str = _enc.attributes();
     match parse(_sk._policy.0.as_ref(), _sk._policy.1) {
         Ok(pol) => {
             let _pruned = calc_pruned(&_attrs_str, &pol, None);
@@ -217,7 +502,7 @@ pub fn decrypt(_sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, R
                     if _match {
                         let mut _prod_t = Gt::one();
                         let mut _z_y = Gt::one();
-                        let _coeffs: Vec<(String, Fr)> = calc_coefficients(&pol, Some(Fr::one()), None).unwrap();
+                        let _coeffs: Vec<(String, Fr)> = calc_coefficients(&pol, Some(Fr::one()), None, Some(&_attrs_str)).unwrap();
                         for _attr_str in _list.iter() {
                             let _sk_attr = _sk
                                 ._dj
@@ -225,35 +510,34 @@ pub fn decrypt(_sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, R
                                 .filter(|_attr| _attr.0 == _attr_str.to_string())
                                 .nth(0)
                                 .unwrap();
-                            let _ct_attr = _ct
-                                ._ej
-                                .iter()
-                                .filter(|_attr| _attr.0 == _attr_str.to_string())
-                                .nth(0)
-                                .unwrap();
                             let _coeff_attr = _coeffs
                                 .iter()
                                 .filter(|_attr| _attr.0 == _attr_str.to_string())
                                 .nth(0)
                                 .unwrap();
                             if is_negative(&_attr_str) {
-                                // TODO !!
-                                /*let _sum_e4 = G2::zero();
-                                let _sum_e5 = G2::zero();
-                                _prod_t = _prod_t *
-                                    (pairing(sk._d_i[_i].3, ct._e2) *
-                                         (pairing(sk._d_i[_i].4, _sum_e4) * pairing(sk._d_i[_i].5, _sum_e5))
-                                             .inverse());
-                                */
+                                // the negated attribute x' is not in the ciphertext, so x' - x is invertible for all its attributes x
+                                let _x_neg = _sk._hash.hash_fr(HASH_EXPONENT, &[&_attr_str.as_bytes()[1..]]);
+                                let mut _e_rs = Gt::one();
+                                for _ct_attr in _enc._ej.iter() {
+                                    let _inv = (_x_neg - _sk._hash.hash_fr(HASH_EXPONENT, &[_ct_attr.0.as_bytes()]))
+                                        .inverse()
+                                        .ok_or_else(|| RabeError::new("Error in lsw/decrypt: attributes do not match policy."))?;
+                                    _e_rs = _e_rs * (pairing(_ct_attr.2, _sk_attr.4) * pairing(_ct_attr.3, _sk_attr.5)).pow(_inv);
+                                }
+                                _z_y = pairing(_sk_attr.3, _enc._e2) * _e_rs.inverse();
                             } else {
-                                _z_y = pairing(_sk_attr.1, _ct._e2)
+                                let _ct_attr = _enc
+                                    ._ej
+                                    .iter()
+                                    .find(|_attr| _attr.0 == _attr_str.as_str())
+                                    .unwrap();
+                                _z_y = pairing(_sk_attr.1, _enc._e2)
                                     * pairing(_ct_attr.1, _sk_attr.2).inverse();
                             }
                             _prod_t = _prod_t * _z_y.pow(_coeff_attr.1);
                         }
-                        let _msg = _ct._e1 * _prod_t.inverse();
-                        // Decrypt plaintext using derived secret from cp-abe scheme
-                        decrypt_symmetric(&_msg, &_ct._ct)
+                        Ok(_enc._e1 * _prod_t.inverse())
                     } else {
                         Err(RabeError::new("Error in lsw/decrypt: attributes do not match policy."))
                     }
@@ -264,6 +548,85 @@ pub fn decrypt(_sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, R
     }
 }
 
+/// The public group elements of a ciphertext, authenticated as associated data
+fn header_elements(_enc: &KpAbeEncapsulation) -> Vec<u8> {
+    let mut _elements = HeaderEncoder::new();
+    _elements.gt(&_enc._e1).g2(&_enc._e2).count(_enc._ej.len());
+    for (_attr, _e1, _e2, _e3) in _enc._ej.iter() {
+        _elements.str(_attr).g1(_e1).g1(_e2).g1(_e3);
+    }
+    _elements.finish()
+}
+
+/// The LSW KP-ABE scheme, for use through the `KpAbe` trait
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Lsw;
+
+impl KpAbe for Lsw {
+    const ID: SchemeId = SchemeId::Lsw;
+
+    type PublicKey = KpAbePublicKey;
+    type MasterKey = KpAbeMasterKey;
+    type SecretKey = KpAbeSecretKey;
+    type Ciphertext = KpAbeCiphertext;
+    type Encapsulation = KpAbeEncapsulation;
+
+    fn setup(&self) -> (KpAbePublicKey, KpAbeMasterKey) {
+        setup()
+    }
+
+    fn keygen(&self, _pk: &KpAbePublicKey, _msk: &KpAbeMasterKey, _policy: &str, _language: PolicyLanguage) -> Result<KpAbeSecretKey, RabeError> {
+        keygen(_pk, _msk, &_policy.to_string(), _language)
+    }
+
+    fn encrypt_with_suite(&self, _pk: &KpAbePublicKey, _attributes: &[String], _plaintext: &[u8], _suite: AeadSuite) -> Result<KpAbeCiphertext, RabeError> {
+        encrypt_with_suite(_pk, &_attributes.to_vec(), _plaintext, _suite)
+    }
+
+    fn decrypt(&self, _sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+        decrypt(_sk, _ct)
+    }
+
+    fn encapsulate(&self, _pk: &KpAbePublicKey, _attributes: &[String]) -> Result<(SymmetricKey, KpAbeEncapsulation), RabeError> {
+        encapsulate(_pk, &_attributes.to_vec())
+    }
+
+    fn decapsulate(&self, _sk: &KpAbeSecretKey, _enc: &KpAbeEncapsulation) -> Result<SymmetricKey, RabeError> {
+        decapsulate(_sk, _enc)
+    }
+
+    fn explain(&self, _sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Explanation, RabeError> {
+        let _attributes: Vec<String> = _ct._ej.iter().map(|_attr| _attr.0.clone()).collect();
+        explain(&Policy::parse(&_sk._policy.0, _sk._policy.1)?, &_attributes)
+    }
+}
+
+impl KpAbeCca for Lsw {
+    fn encapsulate_with<R: Rng>(&self, _pk: &KpAbePublicKey, _attributes: &[String], _msg: &Gt, _rng: &mut R) -> Result<KpAbeEncapsulation, RabeError> {
+        encapsulate_with(_pk, &_attributes.to_vec(), _msg, _rng)
+    }
+
+    fn decapsulate_message(&self, _sk: &KpAbeSecretKey, _enc: &KpAbeEncapsulation) -> Result<Gt, RabeError> {
+        decapsulate_message(_sk, _enc)
+    }
+
+    fn encapsulation_attributes(&self, _enc: &KpAbeEncapsulation) -> Vec<String> {
+        _enc.attributes()
+    }
+
+    fn encapsulation(&self, _ct: &KpAbeCiphertext) -> KpAbeEncapsulation {
+        _ct.encapsulation()
+    }
+
+    fn seal(&self, _key: &SymmetricKey, _enc: KpAbeEncapsulation, _plaintext: &[u8], _suite: AeadSuite) -> Result<KpAbeCiphertext, RabeError> {
+        seal(_key, _enc, _plaintext, _suite)
+    }
+
+    fn open(&self, _key: &SymmetricKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+        open(_key, _ct)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -313,6 +676,25 @@ mod tests {
         assert_eq!(decrypt(&sk, &ct_kp_matching).unwrap(), plaintext);
     }
 
+    #[test]
+    fn single() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        // our policy consists of a single attribute
+        let policy = String::from(r#"{"name": "admin"}"#);
+        // kp-abe ciphertexts
+        let ct_kp_matching: KpAbeCiphertext = encrypt(&pk, &vec![String::from("admin"), String::from("B")], &plaintext).unwrap();
+        let ct_kp_not_matching: KpAbeCiphertext = encrypt(&pk, &vec![String::from("B")], &plaintext).unwrap();
+        // a kp-abe SK key
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, &policy, PolicyLanguage::JsonPolicy).unwrap();
+        // and now decrypt again with matching sk
+        assert_eq!(decrypt(&sk, &ct_kp_matching).unwrap(), plaintext);
+        assert!(decrypt(&sk, &ct_kp_not_matching).is_err());
+    }
+
     #[test]
     fn or_and() {
         // setup scheme
@@ -357,4 +739,30 @@ mod tests {
         let res = decrypt(&sk, &ct_kp_matching);
         assert_eq!(res.is_ok(), false);
     }
+
+    #[test]
+    fn negation() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        // our policy: A, but not if revoked, or B and C without D
+        let policy = String::from(r#"("A" and not "revoked") or not ("D" or not "B" or not "C")"#);
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, &policy, PolicyLanguage::HumanPolicy).unwrap();
+        let attributes = |_names: &[&str]| -> Vec<String> { _names.iter().map(|_n| _n.to_string()).collect() };
+        // kp-abe ciphertexts
+        let ct_a = encrypt(&pk, &attributes(&["A", "X"]), &plaintext).unwrap();
+        let ct_a_revoked = encrypt(&pk, &attributes(&["A", "revoked"]), &plaintext).unwrap();
+        let ct_bc = encrypt(&pk, &attributes(&["B", "C", "revoked"]), &plaintext).unwrap();
+        let ct_bcd = encrypt(&pk, &attributes(&["B", "C", "D"]), &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_a).unwrap(), plaintext);
+        assert!(decrypt(&sk, &ct_a_revoked).is_err());
+        assert_eq!(decrypt(&sk, &ct_bc).unwrap(), plaintext);
+        assert!(decrypt(&sk, &ct_bcd).is_err());
+        // a negated threshold gate: at most one of A, B and C
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, &String::from(r#"not 2 of ("A", "B", "C")"#), PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(decrypt(&sk, &ct_a).unwrap(), plaintext);
+        assert!(decrypt(&sk, &ct_bc).is_err());
+    }
 }
@@ -32,17 +32,23 @@ use rabe_bn::{Group, Fr, G1, G2, Gt, pairing};
 use rand::Rng;
 use std::string::String;
 use utils::{
+    attribute::canonical_attribute,
     aes::*,
-    hash::blake2b_hash_fr,
+    hash::HashVersion,
     policy::dnf::DnfPolicy,
     tools::*
 };
 use utils::policy::pest::{PolicyLanguage, parse, PolicyType};
-use utils::policy::dnf::policy_in_dnf;
 use RabeError;
+use utils::validate::{check_g1, check_g2, check_gt, check_fr, check_unique, check_policy, check_issuer_length};
+use utils::fingerprint::{Fingerprint, check_issuer};
+use utils::json::POLICY_FIELDS;
+use schemes::{MultiAuthorityAbe, SchemeId};
+use utils::policy::{ast::Policy, explain::{explain, Explanation}};
 
 /// A MKE08 Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08PublicKey {
     pub _g1: G1,
     pub _g2: G2,
@@ -54,6 +60,7 @@ pub struct Mke08PublicKey {
 
 /// A MKE08 Master Key (MK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08MasterKey {
     pub _g1_y: G1,
     pub _g2_y: G2,
@@ -61,14 +68,19 @@ pub struct Mke08MasterKey {
 
 /// A MKE08 User Key (SK), consisting of a Secret User Key (SKu), a Public User Key (PKu) and a Vector of Secret Attribute Keys (SKau)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08UserKey {
     pub _sk_u: Mke08SecretUserKey,
     pub _pk_u: Mke08PublicUserKey,
     pub _sk_a: Vec<Mke08SecretAttributeKey>,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
 }
 
 /// A MKE08 Public User Key (PKu)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08PublicUserKey {
     pub _u: String,
     pub _pk_g1: G1,
@@ -77,6 +89,7 @@ pub struct Mke08PublicUserKey {
 
 /// A MKE08 Secret User Key (SKu)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08SecretUserKey {
     pub _sk_g1: G1,
     pub _sk_g2: G2,
@@ -84,6 +97,7 @@ pub struct Mke08SecretUserKey {
 
 /// A MKE08 Secret Authrotiy Key (SKauth)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08SecretAuthorityKey {
     pub _a: String,
     pub _r: Fr,
@@ -91,28 +105,44 @@ pub struct Mke08SecretAuthorityKey {
 
 /// A MKE08 Public Attribute Key (PKa)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08PublicAttributeKey {
     pub _str: String,
     pub _g1: G1,
     pub _g2: G2,
     pub _gt1: Gt,
     pub _gt2: Gt,
+    #[serde(default)]
+    pub _hash: HashVersion,
 }
 
 /// A MKE08 Secret Attribute Key (SKa)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08SecretAttributeKey {
     pub _str: String,
     pub _g1: G1,
     pub _g2: G2,
+    #[serde(default)]
+    pub _hash: HashVersion,
 }
 
 /// A MKE08 Ciphertext (CT) consisting of the AES encrypted data as well as a Vector of all Conjunctions of the access policy
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Mke08Ciphertext {
     pub _policy: (String, PolicyLanguage),
     pub _e: Vec<Mke08CTConjunction>,
     pub _ct: Vec<u8>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    #[serde(default)]
+    pub _suite: AeadSuite,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
 }
 
 /// A MKE08 Ciphertext Conjunction (CTcon)
@@ -127,6 +157,158 @@ pub struct Mke08CTConjunction {
     pub _j6: G2,
 }
 
+/// A MKE08 key encapsulation: a Mke08Ciphertext without the symmetrically encrypted data
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
+pub struct Mke08Encapsulation {
+    pub _policy: (String, PolicyLanguage),
+    pub _e: Vec<Mke08CTConjunction>,
+    #[serde(default)]
+    pub _hash: HashVersion,
+    #[serde(default)]
+    pub _kdf: KdfVersion,
+    /// The fingerprint of the issuing global parameters, see utils::fingerprint
+    #[serde(default)]
+    pub _issuer: Vec<u8>,
+}
+
+impl Mke08Ciphertext {
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> Mke08Encapsulation {
+        Mke08Encapsulation {
+            _policy: self._policy.clone(),
+            _e: self._e.clone(),
+            _hash: self._hash,
+            _kdf: self._kdf,
+            _issuer: self._issuer.clone(),
+        }
+    }
+}
+
+impl_wire!(Mke08PublicKey: Mke08, GlobalKey { _g1, _g2, _p1, _p2, _e_gg_y1, _e_gg_y2 });
+
+impl_json!(Mke08PublicKey { _g1, _g2, _p1, _p2, _e_gg_y1, _e_gg_y2 });
+
+impl Fingerprint for Mke08PublicKey {}
+
+impl_validate!(Mke08PublicKey {
+    _g1: check_g1,
+    _g2: check_g2,
+    _p1: check_g1,
+    _p2: check_g2,
+    _e_gg_y1: check_gt,
+    _e_gg_y2: check_gt,
+});
+
+impl_validated_serde!(Mke08PublicKey);
+
+impl_wire!(Mke08MasterKey: Mke08, MasterKey { _g1_y, _g2_y });
+
+impl_json!(Mke08MasterKey { _g1_y, _g2_y });
+
+impl Fingerprint for Mke08MasterKey {}
+
+impl_validate!(Mke08MasterKey { _g1_y: check_g1, _g2_y: check_g2 });
+
+impl_validated_serde!(Mke08MasterKey);
+
+impl_wire!(Mke08UserKey: Mke08, SecretKey { _sk_u, _pk_u, _sk_a; _issuer });
+
+impl_json!(Mke08UserKey { _sk_u, _pk_u, _sk_a, _issuer });
+
+impl_validate!(Mke08UserKey {
+    _sk_u: validate,
+    _pk_u: validate,
+    _sk_a: unique(_str),
+    _sk_a: each,
+    _issuer: check_issuer_length,
+});
+
+impl_validated_serde!(Mke08UserKey);
+
+impl_wire!(Mke08PublicUserKey: Mke08, PublicUserKey { _u, _pk_g1, _pk_g2 });
+
+impl_json!(Mke08PublicUserKey { _u, _pk_g1, _pk_g2 });
+
+impl Fingerprint for Mke08PublicUserKey {}
+
+impl_validate!(Mke08PublicUserKey { _pk_g1: check_g1, _pk_g2: check_g2 });
+
+impl_validated_serde!(Mke08PublicUserKey);
+
+impl_wire!(Mke08SecretUserKey: Mke08, SecretUserKey { _sk_g1, _sk_g2 });
+
+impl_json!(Mke08SecretUserKey { _sk_g1, _sk_g2 });
+
+impl_validate!(Mke08SecretUserKey { _sk_g1: check_g1, _sk_g2: check_g2 });
+
+impl_validated_serde!(Mke08SecretUserKey);
+
+impl_wire!(Mke08SecretAuthorityKey: Mke08, AuthorityKey { _a, _r });
+
+impl_json!(Mke08SecretAuthorityKey { _a, _r });
+
+impl Fingerprint for Mke08SecretAuthorityKey {}
+
+impl_validate!(Mke08SecretAuthorityKey { _r: check_fr });
+
+impl_validated_serde!(Mke08SecretAuthorityKey);
+
+impl_wire!(Mke08PublicAttributeKey: Mke08, PublicAttributeKey { _str, _g1, _g2, _gt1, _gt2, _hash });
+
+impl_json!(Mke08PublicAttributeKey { _str, _g1, _g2, _gt1, _gt2, _hash });
+
+impl Fingerprint for Mke08PublicAttributeKey {}
+
+impl_validate!(Mke08PublicAttributeKey {
+    _g1: check_g1,
+    _g2: check_g2,
+    _gt1: check_gt,
+    _gt2: check_gt,
+});
+
+impl_validated_serde!(Mke08PublicAttributeKey);
+
+impl_wire!(Mke08SecretAttributeKey: Mke08, SecretAttributeKey { _str, _g1, _g2, _hash });
+
+impl_json!(Mke08SecretAttributeKey { _str, _g1, _g2, _hash });
+
+impl Fingerprint for Mke08SecretAttributeKey {}
+
+impl_validate!(Mke08SecretAttributeKey { _g1: check_g1, _g2: check_g2 });
+
+impl_validated_serde!(Mke08SecretAttributeKey);
+
+impl_wire!(Mke08Ciphertext: Mke08, Ciphertext { _policy, _e, _ct, _hash, _kdf, _suite; _issuer });
+
+impl_json!(Mke08Ciphertext { _policy: tuple(POLICY_FIELDS), _e, _ct, _hash, _kdf, _suite, _issuer });
+
+impl_validate!(Mke08Ciphertext { _policy: check_policy, _e: each, _issuer: check_issuer_length });
+
+impl_validated_serde!(Mke08Ciphertext);
+
+impl_wire!(Mke08CTConjunction { _str, _j1, _j2, _j3, _j4, _j5, _j6 });
+
+impl_json!(Mke08CTConjunction { _str, _j1, _j2, _j3, _j4, _j5, _j6 });
+
+impl_validate!(Mke08CTConjunction {
+    _str: check_unique,
+    _j1: check_gt,
+    _j2: check_gt,
+    _j3: check_g1,
+    _j4: check_g2,
+    _j5: check_g1,
+    _j6: check_g2,
+});
+
+impl_wire!(Mke08Encapsulation: Mke08, Encapsulation { _policy, _e, _hash, _kdf; _issuer });
+
+impl_json!(Mke08Encapsulation { _policy: tuple(POLICY_FIELDS), _e, _hash, _kdf, _issuer });
+
+impl_validate!(Mke08Encapsulation { _policy: check_policy, _e: each, _issuer: check_issuer_length });
+
+impl_validated_serde!(Mke08Encapsulation);
+
 /// The setup algorithm of MKE08. Generates a Mke08PublicKey and a Mke08PublicKey.
 pub fn setup() -> (Mke08PublicKey, Mke08MasterKey) {
     // random number generator
@@ -172,6 +354,7 @@ pub fn keygen(_pk: &Mke08PublicKey, _mk: &Mke08MasterKey, _u: &String) -> Mke08U
             _pk_g2: _pk._g2 * _mk_u,
         },
         _sk_a: Vec::new(),
+        _issuer: _pk.fingerprint(),
     };
 }
 
@@ -205,9 +388,10 @@ pub fn request_authority_pk(
     _a: &String,
     _sk_a: &Mke08SecretAuthorityKey,
 ) -> Option<Mke08PublicAttributeKey> {
+    let _a = &canonical_attribute(_a).ok()?;
     // if attribute a is from authority sk_a
     return if from_authority(_a, &_sk_a._a) {
-        let exponent = blake2b_hash_fr(_a) * blake2b_hash_fr(&_sk_a._a) * _sk_a._r;
+        let exponent = attribute_exponent(HashVersion::CURRENT, _a, &_sk_a._a) * _sk_a._r;
         // return PK and mke
         Some(Mke08PublicAttributeKey {
             _str: _a.clone(),
@@ -215,6 +399,7 @@ pub fn request_authority_pk(
             _g2: _pk._g2 * exponent,
             _gt1: _pk._e_gg_y1.pow(exponent),
             _gt2: _pk._e_gg_y2.pow(exponent),
+            _hash: HashVersion::CURRENT,
         })
     } else {
         None
@@ -234,14 +419,16 @@ pub fn request_authority_sk(
     _sk_a: &Mke08SecretAuthorityKey,
     _pk_u: &Mke08PublicUserKey,
 ) -> Option<Mke08SecretAttributeKey> {
+    let _a = &canonical_attribute(_a).ok()?;
     // if attribute a is from authority sk_a
     return if from_authority(_a, &_sk_a._a) && is_eligible(_a, &_pk_u._u) {
-        let exponent = blake2b_hash_fr(_a) * blake2b_hash_fr(&_sk_a._a) * _sk_a._r;
+        let exponent = attribute_exponent(HashVersion::CURRENT, _a, &_sk_a._a) * _sk_a._r;
         // return PK and mke
         Some(Mke08SecretAttributeKey {
             _str: _a.clone(),
             _g1: _pk_u._pk_g1 * exponent,
             _g2: _pk_u._pk_g2 * exponent,
+            _hash: HashVersion::CURRENT,
         })
     } else {
         None
@@ -265,40 +452,79 @@ pub fn encrypt(
     _language: PolicyLanguage,
     _plaintext: &[u8],
 ) -> Result<Mke08Ciphertext, RabeError> {
+    encrypt_with_suite(_pk, _attr_pks, _policy, _language, _plaintext, AeadSuite::default())
+}
+
+/// Same as encrypt(), but encrypts the plaintext with the given AEAD suite.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_attr_pks` - A Vector of all Mke08PublicAttributeKey that are involded in the policy
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn encrypt_with_suite(
+    _pk: &Mke08PublicKey,
+    _attr_pks: &Vec<Mke08PublicAttributeKey>,
+    _policy: &String,
+    _language: PolicyLanguage,
+    _plaintext: &[u8],
+    _suite: AeadSuite,
+) -> Result<Mke08Ciphertext, RabeError> {
+    let (_key, _enc) = encapsulate(_pk, _attr_pks, _policy, _language)?;
+    //Encrypt plaintext using derived key from secret
+    let _ct = encrypt_symmetric_key(&_key, SchemeId::Mke08, _suite, &policy_header(&_enc._policy.0, _enc._policy.1), &header_elements(&_enc), _plaintext)?;
+    let Mke08Encapsulation { _policy, _e, _hash, _kdf, _issuer } = _enc;
+    Ok(Mke08Ciphertext { _policy, _e, _ct, _hash, _kdf, _suite, _issuer })
+}
+
+/// The encapsulate algorithm of MKE08. Generates a new random SymmetricKey and its Mke08Encapsulation under an access policy.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_attr_pks` - A Vector of all Mke08PublicAttributeKey that are involded in the policy
+///	* `_policy` - An access policy given as String
+///	* `_language` - The language the policy is written in
+///
+pub fn encapsulate(
+    _pk: &Mke08PublicKey,
+    _attr_pks: &Vec<Mke08PublicAttributeKey>,
+    _policy: &String,
+    _language: PolicyLanguage,
+) -> Result<(SymmetricKey, Mke08Encapsulation), RabeError> {
     match parse(_policy, _language) {
         Ok(pol) => {
-            // if policy is in DNF
-            return if policy_in_dnf(&pol, false, None) {
-                // random number generator
-                let mut _rng = rand::thread_rng();
-                // an DNF policy from the given String
-                let policy = DnfPolicy::from_string(&_policy, _attr_pks, _language).unwrap();
-                // random Gt msgs
-                let _msg1 = pairing(_rng.gen(), _rng.gen());
-                let _msg2 = _msg1.pow(_rng.gen());
-                let _msg = _msg1 * _msg2;
-                // CT result vectors
-                let mut _e: Vec<Mke08CTConjunction> = Vec::new();
-                // now add randomness using _r_j
-                for _term in policy._terms.into_iter() {
-                    let _r_j: Fr = _rng.gen();
-                    _e.push(Mke08CTConjunction {
-                        _str: _term.0,
-                        _j1: _term.1.pow(_r_j) * _msg1,
-                        _j2: _term.2.pow(_r_j) * _msg2,
-                        _j3: _pk._p1 * _r_j,
-                        _j4: _pk._p2 * _r_j,
-                        _j5: _term.3 * _r_j,
-                        _j6: _term.4 * _r_j,
-                    });
-                }
-                //Encrypt plaintext using derived key from secret
-                let _policy = _policy.to_string();
-                let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
-                Ok(Mke08Ciphertext { _policy: (_policy, _language), _e, _ct})
-            } else {
-                Err(RabeError::new("Error in mke08/encrypt: policy is not in dnf"))
+            SchemeId::Mke08.check_policy(&pol)?;
+            // random number generator
+            let mut _rng = rand::thread_rng();
+            // the policy converted into DNF
+            let policy = DnfPolicy::from_policy(&pol, _attr_pks)?;
+            let _hash = HashVersion::common(_attr_pks.iter().map(|_attr_pk| _attr_pk._hash))?;
+            // random Gt msgs
+            let _msg1 = pairing(_rng.gen(), _rng.gen());
+            let _msg2 = _msg1.pow(_rng.gen());
+            let _msg = _msg1 * _msg2;
+            // CT result vectors
+            let mut _e: Vec<Mke08CTConjunction> = Vec::new();
+            // now add randomness using _r_j
+            for _term in policy._terms.into_iter() {
+                let _r_j: Fr = _rng.gen();
+                _e.push(Mke08CTConjunction {
+                    _str: _term.0,
+                    _j1: _term.1.pow(_r_j) * _msg1,
+                    _j2: _term.2.pow(_r_j) * _msg2,
+                    _j3: _pk._p1 * _r_j,
+                    _j4: _pk._p2 * _r_j,
+                    _j5: _term.3 * _r_j,
+                    _j6: _term.4 * _r_j,
+                });
             }
+            let _policy = _policy.to_string();
+            let _key = SymmetricKey::derive(KdfVersion::CURRENT, &_msg, SchemeId::Mke08, &policy_header(&_policy, _language));
+            Ok((_key, Mke08Encapsulation { _policy: (_policy, _language), _e, _hash, _kdf: KdfVersion::CURRENT, _issuer: _pk.fingerprint() }))
         },
         Err(e) => Err(e)
     }
@@ -317,22 +543,43 @@ pub fn decrypt(
     _pk: &Mke08PublicKey,
     _sk: &Mke08UserKey,
     _ct: &Mke08Ciphertext) -> Result<Vec<u8>, RabeError> {
-    let _attr = _sk._sk_a
+    let _enc = _ct.encapsulation();
+    let _key = decapsulate(_pk, _sk, &_enc)?;
+    // Decrypt plaintext using derived secret from mke08 scheme
+    decrypt_symmetric_key(_enc._kdf, &_key, SchemeId::Mke08, _ct._suite, &policy_header(&_enc._policy.0, _enc._policy.1), &header_elements(&_enc), &_ct._ct)
+}
+
+/// The decapsulate algorithm of MKE08. Recovers the SymmetricKey of a Mke08Encapsulation with a matching Mke08UserKey.
+///
+/// # Arguments
+///
+///	* `_pk` - A Mke08PublicKey (PK), generated by the function setup()
+///	* `_sk` - A Mke08UserKey (SK), generated by the function keygen()
+///	* `_enc` - A Mke08Encapsulation, generated by the function encapsulate()
+///
+pub fn decapsulate(
+    _pk: &Mke08PublicKey,
+    _sk: &Mke08UserKey,
+    _enc: &Mke08Encapsulation) -> Result<SymmetricKey, RabeError> {
+    check_issuer(&_sk._issuer, &_enc._issuer)?;
+    // only attribute keys issued with the hash of the ciphertext can take part
+    let _sk_a: Vec<Mke08SecretAttributeKey> = _sk._sk_a.iter().filter(|_key| _key._hash == _enc._hash).cloned().collect();
+    let _attr = _sk_a
         .iter()
         .map(|triple| {
             let _a = triple.clone();
             _a._str.to_string()
         })
         .collect::<Vec<_>>();
-    match parse(_ct._policy.0.as_ref(), _ct._policy.1) {
+    match parse(_enc._policy.0.as_ref(), _enc._policy.1) {
         Ok(pol) => {
             return if traverse_policy(&_attr, &pol, PolicyType::Leaf) == false {
                 Err(RabeError::new("Error in mke08/decrypt: attributes in sk do not match policy in ct."))
             } else {
                 let mut _msg = Gt::one();
-                for (_i, _e_j) in _ct._e.iter().enumerate() {
-                    if is_satisfiable(&_e_j._str, &_sk._sk_a) {
-                        let _sk_sum = calc_satisfiable(&_e_j._str, &_sk._sk_a);
+                for _e_j in _enc._e.iter() {
+                    if is_satisfiable(&_e_j._str, &_sk_a) {
+                        let _sk_sum = calc_satisfiable(&_e_j._str, &_sk_a);
                         _msg = _e_j._j1 * _e_j._j2 * pairing(_e_j._j3, _sk_sum.1) *
                             pairing(_sk_sum.0, _e_j._j4) *
                             (pairing(_e_j._j5, _sk._sk_u._sk_g2) * pairing(_sk._sk_u._sk_g1, _e_j._j6))
@@ -340,8 +587,7 @@ pub fn decrypt(
                         break;
                     }
                 }
-                // Decrypt plaintext using derived secret from mke08 scheme
-                decrypt_symmetric(&_msg, &_ct._ct)
+                Ok(SymmetricKey::derive(_enc._kdf, &_msg, SchemeId::Mke08, &policy_header(&_enc._policy.0, _enc._policy.1)))
             }
         },
         Err(e) => Err(e)
@@ -360,7 +606,7 @@ pub fn decrypt(
 fn is_satisfiable(_conjunction: &Vec<String>, _sk: &Vec<Mke08SecretAttributeKey>) -> bool {
     let mut _ret: bool = true;
     for _attr in _conjunction {
-        match _sk.into_iter().find(|&x| x._str == *_attr) {
+        match _sk.iter().find(|&x| x._str == *_attr) {
             None => {
                 _ret = false;
                 break;
@@ -385,7 +631,7 @@ fn calc_satisfiable(
 ) -> (G1, G2) {
     let mut ret: (G1, G2) = (G1::one(), G2::one());
     for _i in 0usize.._conjunction.len() {
-        match _sk.into_iter().find(
+        match _sk.iter().find(
             |&x| x._str == _conjunction[_i].to_string(),
         ) {
             None => {}
@@ -401,6 +647,19 @@ fn calc_satisfiable(
     ret
 }
 
+/// MKE08 Scheme helper function: attribute_exponent
+/// Derives the exponent of an attribute key from the name of the attribute and the name of its authority.
+///
+/// # Arguments
+///
+///	* `_hash` - The hash version of the attribute key
+///	* `_attr` - Name of the attribute given as String
+///	* `_authority` - Name of the auhtority given as String
+///
+fn attribute_exponent(_hash: HashVersion, _attr: &str, _authority: &str) -> Fr {
+    _hash.hash_fr("rabe/mke08/attribute", &[_attr.as_bytes()]) * _hash.hash_fr("rabe/mke08/authority", &[_authority.as_bytes()])
+}
+
 /// MKE08 Scheme helper function: from_authority
 /// Returns true if a specific attribute is handled by a given authority.
 /// Please adopt or implement you own logic. Right now the algorithm checks if the first part
@@ -436,6 +695,90 @@ fn is_eligible(_attr: &String, _user: &String) -> bool {
     return true;
 }
 
+/// The public group elements of a ciphertext, authenticated as associated data
+fn header_elements(_enc: &Mke08Encapsulation) -> Vec<u8> {
+    let mut _elements = HeaderEncoder::new();
+    _elements.count(_enc._e.len());
+    for _e in _enc._e.iter() {
+        _elements.count(_e._str.len());
+        for _str in _e._str.iter() {
+            _elements.str(_str);
+        }
+        _elements.gt(&_e._j1).gt(&_e._j2).g1(&_e._j3).g2(&_e._j4).g1(&_e._j5).g2(&_e._j6);
+    }
+    _elements.finish()
+}
+
+/// The MKE08 multi-authority scheme, for use through the `MultiAuthorityAbe` trait.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mke08;
+
+impl MultiAuthorityAbe for Mke08 {
+    const ID: SchemeId = SchemeId::Mke08;
+
+    type GlobalKey = Mke08PublicKey;
+    type MasterKey = Mke08MasterKey;
+    type AuthorityKey = Mke08SecretAuthorityKey;
+    type PublicAttributeKey = Mke08PublicAttributeKey;
+    type SecretKey = Mke08UserKey;
+    type Ciphertext = Mke08Ciphertext;
+    type Encapsulation = Mke08Encapsulation;
+
+    fn setup(&self) -> (Mke08PublicKey, Mke08MasterKey) {
+        setup()
+    }
+
+    fn authgen(&self, _pk: &Mke08PublicKey, _mk: &Mke08MasterKey, _name: &str, _attributes: &[String]) -> Result<Mke08SecretAuthorityKey, RabeError> {
+        // the authority handles every attribute under its name prefix, the given ones must carry it
+        for _attribute in _attributes {
+            if !from_authority(&canonical_attribute(_attribute)?, &_name.to_string()) {
+                return Err(RabeError::new(&format!("Error in mke08/authgen: attribute {} is not handled by authority {}.", _attribute, _name)));
+            }
+        }
+        Ok(authgen(&_name.to_string()))
+    }
+
+    fn keygen(&self, _pk: &Mke08PublicKey, _mk: &Mke08MasterKey, _authority: &Mke08SecretAuthorityKey, _name: &str) -> Result<Mke08UserKey, RabeError> {
+        Ok(keygen(_pk, _mk, &_name.to_string()))
+    }
+
+    fn request_attribute_pk(&self, _pk: &Mke08PublicKey, _sk_a: &Mke08SecretAuthorityKey, _a: &str) -> Result<Mke08PublicAttributeKey, RabeError> {
+        request_authority_pk(_pk, &_a.to_string(), _sk_a)
+            .ok_or_else(|| RabeError::new(&format!("Error in mke08/request_authority_pk: attribute {} is not handled by authority {}.", _a, _sk_a._a)))
+    }
+
+    fn request_attribute_sk(&self, _pk: &Mke08PublicKey, _sk_a: &Mke08SecretAuthorityKey, _sk: &mut Mke08UserKey, _a: &str) -> Result<(), RabeError> {
+        match request_authority_sk(&_a.to_string(), _sk_a, &_sk._pk_u) {
+            Some(_sk_attr) => {
+                _sk._sk_a.push(_sk_attr);
+                Ok(())
+            },
+            None => Err(RabeError::new(&format!("Error in mke08/request_authority_sk: attribute {} is not handled by authority {}.", _a, _sk_a._a)))
+        }
+    }
+
+    fn encrypt_with_suite(&self, _pk: &Mke08PublicKey, _attr_pks: &[Mke08PublicAttributeKey], _policy: &str, _language: PolicyLanguage, _plaintext: &[u8], _suite: AeadSuite) -> Result<Mke08Ciphertext, RabeError> {
+        encrypt_with_suite(_pk, &_attr_pks.to_vec(), &_policy.to_string(), _language, _plaintext, _suite)
+    }
+
+    fn decrypt(&self, _pk: &Mke08PublicKey, _sk: &Mke08UserKey, _ct: &Mke08Ciphertext) -> Result<Vec<u8>, RabeError> {
+        decrypt(_pk, _sk, _ct)
+    }
+
+    fn encapsulate(&self, _pk: &Mke08PublicKey, _attr_pks: &[Mke08PublicAttributeKey], _policy: &str, _language: PolicyLanguage) -> Result<(SymmetricKey, Mke08Encapsulation), RabeError> {
+        encapsulate(_pk, &_attr_pks.to_vec(), &_policy.to_string(), _language)
+    }
+
+    fn decapsulate(&self, _pk: &Mke08PublicKey, _sk: &Mke08UserKey, _enc: &Mke08Encapsulation) -> Result<SymmetricKey, RabeError> {
+        decapsulate(_pk, _sk, _enc)
+    }
+
+    fn explain(&self, _sk: &Mke08UserKey, _ct: &Mke08Ciphertext) -> Result<Explanation, RabeError> {
+        let _attributes: Vec<String> = _sk._sk_a.iter().map(|_attr| _attr._str.clone()).collect();
+        explain(&Policy::parse(&_ct._policy.0, _ct._policy.1)?, &_attributes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -479,6 +822,76 @@ mod tests {
         assert_eq!(_match.unwrap(), _plaintext);
     }
 
+    #[test]
+    fn not_in_dnf() {
+        // setup scheme
+        let (_pk, _msk) = setup();
+        // generate mutable user key(in order to add attribute sk's later on)
+        let mut _u_key = keygen(&_pk, &_msk, &String::from("user1"));
+        // authority1
+        let _a1_key = authgen(&String::from("aa1"));
+        // authority2
+        let _a2_key = authgen(&String::from("aa2"));
+        // our attributes
+        let _att1 = String::from("aa1::A");
+        let _att2 = String::from("aa2::B");
+        let _att3 = String::from("aa2::C");
+        let _pks = vec![
+            request_authority_pk(&_pk, &_att1, &_a1_key).unwrap(),
+            request_authority_pk(&_pk, &_att2, &_a2_key).unwrap(),
+            request_authority_pk(&_pk, &_att3, &_a2_key).unwrap(),
+        ];
+        // our plaintext
+        let _plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy is converted into DNF during encryption
+        let _policy = String::from(r#""aa1::A" and ("aa2::B" or "aa2::C")"#);
+        let _ct: Mke08Ciphertext = encrypt(&_pk, &_pks, &_policy, PolicyLanguage::HumanPolicy, &_plaintext).unwrap();
+        assert_eq!(_ct._e.len(), 2);
+        _u_key._sk_a.push(
+            request_authority_sk(&_att1, &_a1_key, &_u_key._pk_u).unwrap(),
+        );
+        assert!(decrypt(&_pk, &_u_key, &_ct).is_err());
+        _u_key._sk_a.push(
+            request_authority_sk(&_att3, &_a2_key, &_u_key._pk_u).unwrap(),
+        );
+        assert_eq!(decrypt(&_pk, &_u_key, &_ct).unwrap(), _plaintext);
+    }
+
+    #[test]
+    fn single() {
+        // setup scheme
+        let (_pk, _msk) = setup();
+        // generate mutable user key(in order to add attribute sk's later on)
+        let mut _u_key = keygen(&_pk, &_msk, &String::from("user1"));
+        // authority1
+        let _a1_key = authgen(&String::from("aa1"));
+        // our attributes
+        let _att1 = String::from("aa1::admin");
+        let _att2 = String::from("aa1::B");
+        // authority1 owns both
+        let _att1_pk = request_authority_pk(&_pk, &_att1, &_a1_key).unwrap();
+        let _att2_pk = request_authority_pk(&_pk, &_att2, &_a1_key).unwrap();
+        // our plaintext
+        let _plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy consists of a single attribute
+        let _policy = String::from(r#""aa1::admin""#);
+        // cp-abe ciphertext
+        let _ct: Mke08Ciphertext = encrypt(&_pk, &vec![_att1_pk, _att2_pk], &_policy, PolicyLanguage::HumanPolicy, &_plaintext)
+            .unwrap();
+        // the user only holds B
+        _u_key._sk_a.push(
+            request_authority_sk(&_att2, &_a1_key, &_u_key._pk_u).unwrap(),
+        );
+        assert!(decrypt(&_pk, &_u_key, &_ct).is_err());
+        // and now decrypt again with mathcing sk
+        _u_key._sk_a.push(
+            request_authority_sk(&_att1, &_a1_key, &_u_key._pk_u).unwrap(),
+        );
+        assert_eq!(decrypt(&_pk, &_u_key, &_ct).unwrap(), _plaintext);
+    }
+
     #[test]
     fn or() {
         // setup scheme
@@ -577,18 +990,21 @@ mod tests {
             _str: String::from("A"),
             _g1: G1::one(),
             _g2: G2::one(),
+            _hash: HashVersion::CURRENT,
         });
         assert!(!is_satisfiable(&_conjunction, &_sk_as));
         _sk_as.push(Mke08SecretAttributeKey {
             _str: String::from("B"),
             _g1: G1::one(),
             _g2: G2::one(),
+            _hash: HashVersion::CURRENT,
         });
         assert!(!is_satisfiable(&_conjunction, &_sk_as));
         _sk_as.push(Mke08SecretAttributeKey {
             _str: String::from("C"),
             _g1: G1::one(),
             _g2: G2::one(),
+            _hash: HashVersion::CURRENT,
         });
         assert!(is_satisfiable(&_conjunction, &_sk_as));
     }
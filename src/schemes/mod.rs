@@ -8,11 +8,1270 @@
 //! * BSW CP-ABE
 //! * LSW KP-ABE
 //! * MKE08 CP-ABE
+//! * YCT14 KP-ABE
 //!
+//! Besides their own free functions, all schemes implement one of the traits
+//! `CpAbe`, `KpAbe` or `MultiAuthorityAbe`, so that code can be written generic over the scheme.
+//! Policies are given either as String in one of the `PolicyLanguage`s or as a `Policy`.
+//!
+//! Every scheme is a key encapsulation mechanism (KEM) at its core: encapsulate() returns a
+//! random `SymmetricKey` together with its encapsulation, e.g. to wrap the data encryption key
+//! of an existing envelope encryption, and decapsulate() recovers the key. encrypt() and
+//! decrypt() encrypt the plaintext with an AEAD under the encapsulated key.
+//!
+//! BSW, AC17 and LSW additionally implement `CpAbeCca` or `KpAbeCca`, an opt-in CCA2-secure
+//! mode by the Fujisaki-Okamoto transform (encrypt_cca() and decrypt_cca()).
+//!
+//! # Examples
+//!
+//! ```
+//! use rabe::schemes::{CpAbe, bsw::Bsw, ac17::Ac17Cp};
+//! use rabe::utils::policy::pest::PolicyLanguage;
+//! fn roundtrip<S: CpAbe>(scheme: &S) -> Vec<u8> {
+//!     let (pk, msk) = scheme.setup();
+//!     let sk = scheme.keygen(&pk, &msk, &["A".to_string(), "B".to_string()]).unwrap();
+//!     let ct = scheme.encrypt(&pk, r#""A" and "B""#, PolicyLanguage::HumanPolicy, b"our plaintext!").unwrap();
+//!     scheme.decrypt(&sk, &ct).unwrap()
+//! }
+//! assert_eq!(roundtrip(&Bsw), b"our plaintext!".to_vec());
+//! assert_eq!(roundtrip(&Ac17Cp), b"our plaintext!".to_vec());
+//! ```
+use std::{fmt, str::FromStr};
+use rabe_bn::Gt;
+use rand::Rng;
+use serde::{Serialize, de::DeserializeOwned};
+use std::io::{Read, Write};
+use utils::aes::{AeadSuite, KdfVersion, SymmetricKey, attribute_header, fo_rng, policy_header, stream::{self, DecryptReader, EncryptWriter}};
+use utils::attribute::canonicalize;
+use utils::fingerprint::Fingerprint;
+use utils::policy::{ast::Policy, explain::Explanation, pest::{is_monotone, PolicyLanguage, PolicyValue}};
+use RabeError;
+
 pub mod ac17;
 pub mod aw11;
 pub mod bdabe;
 pub mod bsw;
 pub mod lsw;
 pub mod mke08;
+pub mod registry;
 pub mod yct14;
+
+/// Identifies one of the implemented schemes, e.g. in the header of an encoded key, see `registry::Registry`
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum SchemeId {
+    Ac17Cp,
+    Ac17Kp,
+    Aw11,
+    Bdabe,
+    Bsw,
+    Lsw,
+    Mke08,
+    Yct14,
+}
+
+impl SchemeId {
+    /// All implemented schemes
+    pub fn all() -> Vec<SchemeId> {
+        vec![
+            SchemeId::Ac17Cp,
+            SchemeId::Ac17Kp,
+            SchemeId::Aw11,
+            SchemeId::Bdabe,
+            SchemeId::Bsw,
+            SchemeId::Lsw,
+            SchemeId::Mke08,
+            SchemeId::Yct14,
+        ]
+    }
+
+    /// Returns true if the scheme supports negated attributes (`not "A"`) in its policies
+    pub fn supports_negation(&self) -> bool {
+        *self == SchemeId::Lsw
+    }
+
+    /// Checks that the scheme supports all operators used in a policy
+    ///
+    /// # Arguments
+    ///
+    ///	* `_policy` - A parsed access policy
+    pub fn check_policy(&self, _policy: &PolicyValue) -> Result<(), RabeError> {
+        if !self.supports_negation() && !is_monotone(_policy) {
+            return Err(RabeError::new(&format!("{} does not support negated attributes in policies", self.name())));
+        }
+        Ok(())
+    }
+
+    /// The name of the scheme, as used by the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            SchemeId::Ac17Cp => "AC17CP",
+            SchemeId::Ac17Kp => "AC17KP",
+            SchemeId::Aw11 => "AW11",
+            SchemeId::Bdabe => "BDABE",
+            SchemeId::Bsw => "BSW",
+            SchemeId::Lsw => "LSW",
+            SchemeId::Mke08 => "MKE08",
+            SchemeId::Yct14 => "YCT14",
+        }
+    }
+}
+
+impl fmt::Display for SchemeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SchemeId {
+    type Err = RabeError;
+
+    fn from_str(s: &str) -> Result<SchemeId, RabeError> {
+        SchemeId::all()
+            .into_iter()
+            .find(|id| id.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| RabeError::new(&format!("unknown scheme {}", s)))
+    }
+}
+
+/// A Ciphertext-Policy ABE scheme: ciphertexts carry a policy, secret keys carry a set of attributes.
+pub trait CpAbe {
+    /// The identifier of this scheme
+    const ID: SchemeId;
+
+    type PublicKey: Serialize + DeserializeOwned + Clone + Fingerprint;
+    type MasterKey: Serialize + DeserializeOwned + Clone;
+    type SecretKey: Serialize + DeserializeOwned + Clone;
+    type Ciphertext: Serialize + DeserializeOwned + Clone;
+    type Encapsulation: Serialize + DeserializeOwned + Clone + PartialEq;
+
+    /// Generates a new Public Key (PK) and Master Key (MSK).
+    fn setup(&self) -> (Self::PublicKey, Self::MasterKey);
+
+    /// Generates a Secret Key (SK) for the given set of attributes.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_msk` - A Master Key (MSK), generated by setup()
+    ///	* `_attributes` - The attributes assigned to this user key
+    ///
+    fn keygen(
+        &self,
+        _pk: &Self::PublicKey,
+        _msk: &Self::MasterKey,
+        _attributes: &[String],
+    ) -> Result<Self::SecretKey, RabeError>;
+
+    /// Encrypts some plaintext data under an access policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///
+    fn encrypt(
+        &self,
+        _pk: &Self::PublicKey,
+        _policy: &str,
+        _language: PolicyLanguage,
+        _plaintext: &[u8],
+    ) -> Result<Self::Ciphertext, RabeError> {
+        self.encrypt_with_suite(_pk, _policy, _language, _plaintext, AeadSuite::default())
+    }
+
+    /// Encrypts some plaintext data under an access policy, using the given AEAD suite.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///	* `_suite` - The AEAD that encrypts the plaintext
+    ///
+    fn encrypt_with_suite(
+        &self,
+        _pk: &Self::PublicKey,
+        _policy: &str,
+        _language: PolicyLanguage,
+        _plaintext: &[u8],
+        _suite: AeadSuite,
+    ) -> Result<Self::Ciphertext, RabeError>;
+
+    /// Encrypts some plaintext data under an access policy given as `Policy`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_policy` - An access policy
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///
+    fn encrypt_policy(
+        &self,
+        _pk: &Self::PublicKey,
+        _policy: &Policy,
+        _plaintext: &[u8],
+    ) -> Result<Self::Ciphertext, RabeError> {
+        self.encrypt(_pk, &_policy.display(PolicyLanguage::JsonPolicy).to_string(), PolicyLanguage::JsonPolicy, _plaintext)
+    }
+
+    /// Decrypts a ciphertext if the attributes of the Secret Key (SK) satisfy its policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_ct` - A Ciphertext (CT), generated by encrypt()
+    ///
+    fn decrypt(&self, _sk: &Self::SecretKey, _ct: &Self::Ciphertext) -> Result<Vec<u8>, RabeError>;
+
+    /// Generates a random SymmetricKey and encapsulates it under an access policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///
+    fn encapsulate(
+        &self,
+        _pk: &Self::PublicKey,
+        _policy: &str,
+        _language: PolicyLanguage,
+    ) -> Result<(SymmetricKey, Self::Encapsulation), RabeError>;
+
+    /// Recovers the SymmetricKey of an encapsulation if the attributes of the Secret Key (SK) satisfy its policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_enc` - An encapsulation, generated by encapsulate()
+    ///
+    fn decapsulate(&self, _sk: &Self::SecretKey, _enc: &Self::Encapsulation) -> Result<SymmetricKey, RabeError>;
+
+    /// Starts encrypting a stream of plaintext data under an access policy in constant memory.
+    /// A random stream key is encrypted with the scheme and written to `_output`, followed by the
+    /// chunks written to the returned `EncryptWriter`, see `utils::aes::stream`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///	* `_suite` - The AEAD that encrypts the stream
+    ///	* `_output` - The writer the encrypted stream is written to
+    ///
+    fn encrypt_stream<W: Write>(
+        &self,
+        _pk: &Self::PublicKey,
+        _policy: &str,
+        _language: PolicyLanguage,
+        _suite: AeadSuite,
+        mut _output: W,
+    ) -> Result<EncryptWriter<W>, RabeError> {
+        let _key = stream::generate_key();
+        let _ct = self.encrypt_with_suite(_pk, _policy, _language, &_key, _suite)?;
+        stream::write_header(&mut _output, &_ct, _suite)?;
+        Ok(EncryptWriter::new(_output, &_key, _suite)?)
+    }
+
+    /// Starts decrypting a stream written by encrypt_stream(), if the attributes of the Secret Key (SK) satisfy its policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_input` - The reader the encrypted stream is read from
+    ///
+    fn decrypt_stream<R: Read>(&self, _sk: &Self::SecretKey, mut _input: R) -> Result<DecryptReader<R>, RabeError> {
+        let (_ct, _suite) = stream::read_header::<R, Self::Ciphertext>(&mut _input)?;
+        let _key = self.decrypt(_sk, &_ct)?;
+        Ok(DecryptReader::new(_input, &_key, _suite)?)
+    }
+
+    /// Explains whether the attributes of the Secret Key (SK) satisfy the policy of a ciphertext,
+    /// see `utils::policy::explain`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_ct` - A Ciphertext (CT), generated by encrypt()
+    ///
+    fn explain(&self, _sk: &Self::SecretKey, _ct: &Self::Ciphertext) -> Result<Explanation, RabeError>;
+}
+
+/// A Key-Policy ABE scheme: secret keys carry a policy, ciphertexts carry a set of attributes.
+pub trait KpAbe {
+    /// The identifier of this scheme
+    const ID: SchemeId;
+
+    type PublicKey: Serialize + DeserializeOwned + Clone + Fingerprint;
+    type MasterKey: Serialize + DeserializeOwned + Clone;
+    type SecretKey: Serialize + DeserializeOwned + Clone;
+    type Ciphertext: Serialize + DeserializeOwned + Clone;
+    type Encapsulation: Serialize + DeserializeOwned + Clone + PartialEq;
+
+    /// Generates a new Public Key (PK) and Master Key (MSK).
+    fn setup(&self) -> (Self::PublicKey, Self::MasterKey);
+
+    /// Generates a Secret Key (SK) for the given access policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_msk` - A Master Key (MSK), generated by setup()
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///
+    fn keygen(
+        &self,
+        _pk: &Self::PublicKey,
+        _msk: &Self::MasterKey,
+        _policy: &str,
+        _language: PolicyLanguage,
+    ) -> Result<Self::SecretKey, RabeError>;
+
+    /// Generates a Secret Key (SK) for the given access policy given as `Policy`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_msk` - A Master Key (MSK), generated by setup()
+    ///	* `_policy` - An access policy
+    ///
+    fn keygen_policy(
+        &self,
+        _pk: &Self::PublicKey,
+        _msk: &Self::MasterKey,
+        _policy: &Policy,
+    ) -> Result<Self::SecretKey, RabeError> {
+        self.keygen(_pk, _msk, &_policy.display(PolicyLanguage::JsonPolicy).to_string(), PolicyLanguage::JsonPolicy)
+    }
+
+    /// Encrypts some plaintext data under a set of attributes.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_attributes` - The attributes the ciphertext is labelled with
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///
+    fn encrypt(
+        &self,
+        _pk: &Self::PublicKey,
+        _attributes: &[String],
+        _plaintext: &[u8],
+    ) -> Result<Self::Ciphertext, RabeError> {
+        self.encrypt_with_suite(_pk, _attributes, _plaintext, AeadSuite::default())
+    }
+
+    /// Encrypts some plaintext data under a set of attributes, using the given AEAD suite.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_attributes` - The attributes the ciphertext is labelled with
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///	* `_suite` - The AEAD that encrypts the plaintext
+    ///
+    fn encrypt_with_suite(
+        &self,
+        _pk: &Self::PublicKey,
+        _attributes: &[String],
+        _plaintext: &[u8],
+        _suite: AeadSuite,
+    ) -> Result<Self::Ciphertext, RabeError>;
+
+    /// Decrypts a ciphertext if its attributes satisfy the policy of the Secret Key (SK).
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_ct` - A Ciphertext (CT), generated by encrypt()
+    ///
+    fn decrypt(&self, _sk: &Self::SecretKey, _ct: &Self::Ciphertext) -> Result<Vec<u8>, RabeError>;
+
+    /// Generates a random SymmetricKey and encapsulates it under a set of attributes.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_attributes` - The attributes the encapsulation is labelled with
+    ///
+    fn encapsulate(
+        &self,
+        _pk: &Self::PublicKey,
+        _attributes: &[String],
+    ) -> Result<(SymmetricKey, Self::Encapsulation), RabeError>;
+
+    /// Recovers the SymmetricKey of an encapsulation if its attributes satisfy the policy of the Secret Key (SK).
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_enc` - An encapsulation, generated by encapsulate()
+    ///
+    fn decapsulate(&self, _sk: &Self::SecretKey, _enc: &Self::Encapsulation) -> Result<SymmetricKey, RabeError>;
+
+    /// Starts encrypting a stream of plaintext data under a set of attributes in constant memory.
+    /// A random stream key is encrypted with the scheme and written to `_output`, followed by the
+    /// chunks written to the returned `EncryptWriter`, see `utils::aes::stream`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_attributes` - The attributes the ciphertext is labelled with
+    ///	* `_suite` - The AEAD that encrypts the stream
+    ///	* `_output` - The writer the encrypted stream is written to
+    ///
+    fn encrypt_stream<W: Write>(
+        &self,
+        _pk: &Self::PublicKey,
+        _attributes: &[String],
+        _suite: AeadSuite,
+        mut _output: W,
+    ) -> Result<EncryptWriter<W>, RabeError> {
+        let _key = stream::generate_key();
+        let _ct = self.encrypt_with_suite(_pk, _attributes, &_key, _suite)?;
+        stream::write_header(&mut _output, &_ct, _suite)?;
+        Ok(EncryptWriter::new(_output, &_key, _suite)?)
+    }
+
+    /// Starts decrypting a stream written by encrypt_stream(), if its attributes satisfy the policy of the Secret Key (SK).
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_input` - The reader the encrypted stream is read from
+    ///
+    fn decrypt_stream<R: Read>(&self, _sk: &Self::SecretKey, mut _input: R) -> Result<DecryptReader<R>, RabeError> {
+        let (_ct, _suite) = stream::read_header::<R, Self::Ciphertext>(&mut _input)?;
+        let _key = self.decrypt(_sk, &_ct)?;
+        Ok(DecryptReader::new(_input, &_key, _suite)?)
+    }
+
+    /// Explains whether the attributes of a ciphertext satisfy the policy of the Secret Key (SK),
+    /// see `utils::policy::explain`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_ct` - A Ciphertext (CT), generated by encrypt()
+    ///
+    fn explain(&self, _sk: &Self::SecretKey, _ct: &Self::Ciphertext) -> Result<Explanation, RabeError>;
+}
+
+/// A `CpAbe` scheme that can draw all randomness of an encapsulation from a given random number
+/// generator, which gives an opt-in CCA2-secure mode by the Fujisaki-Okamoto transform.
+///
+/// encapsulate_cca() derives the randomness from a hash of the random Gt message and the policy,
+/// see `utils::aes::fo_rng`. decapsulate_cca() repeats the encapsulation of the recovered message
+/// and rejects the encapsulation unless both are equal, so modified ciphertexts do not decrypt.
+/// Ciphertexts of encrypt() are rejected by decrypt_cca(), and need to be decrypted by decrypt().
+pub trait CpAbeCca: CpAbe {
+    /// Encapsulates the given Gt message under an access policy, drawing all randomness from `_rng`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///	* `_msg` - The Gt message the SymmetricKey is derived from
+    ///	* `_rng` - The random number generator
+    ///
+    fn encapsulate_with<R: Rng>(
+        &self,
+        _pk: &Self::PublicKey,
+        _policy: &str,
+        _language: PolicyLanguage,
+        _msg: &Gt,
+        _rng: &mut R,
+    ) -> Result<Self::Encapsulation, RabeError>;
+
+    /// Recovers the Gt message of an encapsulation if the attributes of the Secret Key (SK) satisfy its policy.
+    fn decapsulate_message(&self, _sk: &Self::SecretKey, _enc: &Self::Encapsulation) -> Result<Gt, RabeError>;
+
+    /// The access policy of an encapsulation
+    fn encapsulation_policy(&self, _enc: &Self::Encapsulation) -> (String, PolicyLanguage);
+
+    /// The encapsulation of a ciphertext
+    fn encapsulation(&self, _ct: &Self::Ciphertext) -> Self::Encapsulation;
+
+    /// Encrypts some plaintext data under the key of an encapsulation, which is the second half of encrypt().
+    ///
+    /// # Arguments
+    ///
+    ///	* `_key` - The SymmetricKey of `_enc`
+    ///	* `_enc` - An encapsulation, generated by encapsulate()
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///	* `_suite` - The AEAD that encrypts the plaintext
+    ///
+    fn seal(&self, _key: &SymmetricKey, _enc: Self::Encapsulation, _plaintext: &[u8], _suite: AeadSuite) -> Result<Self::Ciphertext, RabeError>;
+
+    /// Decrypts a ciphertext under the key of its encapsulation, which is the second half of decrypt().
+    fn open(&self, _key: &SymmetricKey, _ct: &Self::Ciphertext) -> Result<Vec<u8>, RabeError>;
+
+    /// Generates a random SymmetricKey and encapsulates it under an access policy in CCA2-secure mode.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///
+    fn encapsulate_cca(
+        &self,
+        _pk: &Self::PublicKey,
+        _policy: &str,
+        _language: PolicyLanguage,
+    ) -> Result<(SymmetricKey, Self::Encapsulation), RabeError> {
+        let _msg: Gt = rand::thread_rng().gen();
+        let _header = policy_header(_policy, _language);
+        let _enc = self.encapsulate_with(_pk, _policy, _language, &_msg, &mut fo_rng(&_msg, Self::ID, &_header))?;
+        Ok((SymmetricKey::derive(KdfVersion::CURRENT, &_msg, Self::ID, &_header), _enc))
+    }
+
+    /// Recovers the SymmetricKey of an encapsulation generated by encapsulate_cca(), if the attributes of the
+    /// Secret Key (SK) satisfy its policy and the encapsulation is unmodified.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - The Public Key (PK) the encapsulation was generated with
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_enc` - An encapsulation, generated by encapsulate_cca()
+    ///
+    fn decapsulate_cca(&self, _pk: &Self::PublicKey, _sk: &Self::SecretKey, _enc: &Self::Encapsulation) -> Result<SymmetricKey, RabeError> {
+        let _msg = self.decapsulate_message(_sk, _enc)?;
+        let (_policy, _language) = self.encapsulation_policy(_enc);
+        let _header = policy_header(&_policy, _language);
+        let _check = self.encapsulate_with(_pk, &_policy, _language, &_msg, &mut fo_rng(&_msg, Self::ID, &_header))?;
+        if _check != *_enc {
+            return Err(RabeError::new("the encapsulation does not match its re-encapsulation"));
+        }
+        Ok(SymmetricKey::derive(KdfVersion::CURRENT, &_msg, Self::ID, &_header))
+    }
+
+    /// Encrypts some plaintext data under an access policy in CCA2-secure mode.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///	* `_suite` - The AEAD that encrypts the plaintext
+    ///
+    fn encrypt_cca(
+        &self,
+        _pk: &Self::PublicKey,
+        _policy: &str,
+        _language: PolicyLanguage,
+        _plaintext: &[u8],
+        _suite: AeadSuite,
+    ) -> Result<Self::Ciphertext, RabeError> {
+        let (_key, _enc) = self.encapsulate_cca(_pk, _policy, _language)?;
+        self.seal(&_key, _enc, _plaintext, _suite)
+    }
+
+    /// Decrypts a ciphertext generated by encrypt_cca(), if the attributes of the Secret Key (SK) satisfy its policy
+    /// and the ciphertext is unmodified.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - The Public Key (PK) the ciphertext was generated with
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_ct` - A Ciphertext (CT), generated by encrypt_cca()
+    ///
+    fn decrypt_cca(&self, _pk: &Self::PublicKey, _sk: &Self::SecretKey, _ct: &Self::Ciphertext) -> Result<Vec<u8>, RabeError> {
+        let _key = self.decapsulate_cca(_pk, _sk, &self.encapsulation(_ct))?;
+        self.open(&_key, _ct)
+    }
+}
+
+/// A `KpAbe` scheme that can draw all randomness of an encapsulation from a given random number
+/// generator, which gives an opt-in CCA2-secure mode by the Fujisaki-Okamoto transform, see `CpAbeCca`.
+pub trait KpAbeCca: KpAbe {
+    /// Encapsulates the given Gt message under a set of attributes, drawing all randomness from `_rng`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_attributes` - The attributes the encapsulation is labelled with
+    ///	* `_msg` - The Gt message the SymmetricKey is derived from
+    ///	* `_rng` - The random number generator
+    ///
+    fn encapsulate_with<R: Rng>(
+        &self,
+        _pk: &Self::PublicKey,
+        _attributes: &[String],
+        _msg: &Gt,
+        _rng: &mut R,
+    ) -> Result<Self::Encapsulation, RabeError>;
+
+    /// Recovers the Gt message of an encapsulation if its attributes satisfy the policy of the Secret Key (SK).
+    fn decapsulate_message(&self, _sk: &Self::SecretKey, _enc: &Self::Encapsulation) -> Result<Gt, RabeError>;
+
+    /// The attributes of an encapsulation
+    fn encapsulation_attributes(&self, _enc: &Self::Encapsulation) -> Vec<String>;
+
+    /// The encapsulation of a ciphertext
+    fn encapsulation(&self, _ct: &Self::Ciphertext) -> Self::Encapsulation;
+
+    /// Encrypts some plaintext data under the key of an encapsulation, which is the second half of encrypt().
+    ///
+    /// # Arguments
+    ///
+    ///	* `_key` - The SymmetricKey of `_enc`
+    ///	* `_enc` - An encapsulation, generated by encapsulate()
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///	* `_suite` - The AEAD that encrypts the plaintext
+    ///
+    fn seal(&self, _key: &SymmetricKey, _enc: Self::Encapsulation, _plaintext: &[u8], _suite: AeadSuite) -> Result<Self::Ciphertext, RabeError>;
+
+    /// Decrypts a ciphertext under the key of its encapsulation, which is the second half of decrypt().
+    fn open(&self, _key: &SymmetricKey, _ct: &Self::Ciphertext) -> Result<Vec<u8>, RabeError>;
+
+    /// Generates a random SymmetricKey and encapsulates it under a set of attributes in CCA2-secure mode.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_attributes` - The attributes the encapsulation is labelled with
+    ///
+    fn encapsulate_cca(&self, _pk: &Self::PublicKey, _attributes: &[String]) -> Result<(SymmetricKey, Self::Encapsulation), RabeError> {
+        let _attributes = canonicalize(_attributes)?;
+        let _msg: Gt = rand::thread_rng().gen();
+        let _header = attribute_header(&_attributes);
+        let _enc = self.encapsulate_with(_pk, &_attributes, &_msg, &mut fo_rng(&_msg, Self::ID, &_header))?;
+        Ok((SymmetricKey::derive(KdfVersion::CURRENT, &_msg, Self::ID, &_header), _enc))
+    }
+
+    /// Recovers the SymmetricKey of an encapsulation generated by encapsulate_cca(), if its attributes
+    /// satisfy the policy of the Secret Key (SK) and the encapsulation is unmodified.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - The Public Key (PK) the encapsulation was generated with
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_enc` - An encapsulation, generated by encapsulate_cca()
+    ///
+    fn decapsulate_cca(&self, _pk: &Self::PublicKey, _sk: &Self::SecretKey, _enc: &Self::Encapsulation) -> Result<SymmetricKey, RabeError> {
+        let _msg = self.decapsulate_message(_sk, _enc)?;
+        let _attributes = self.encapsulation_attributes(_enc);
+        let _header = attribute_header(&_attributes);
+        let _check = self.encapsulate_with(_pk, &_attributes, &_msg, &mut fo_rng(&_msg, Self::ID, &_header))?;
+        if _check != *_enc {
+            return Err(RabeError::new("the encapsulation does not match its re-encapsulation"));
+        }
+        Ok(SymmetricKey::derive(KdfVersion::CURRENT, &_msg, Self::ID, &_header))
+    }
+
+    /// Encrypts some plaintext data under a set of attributes in CCA2-secure mode.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - A Public Key (PK), generated by setup()
+    ///	* `_attributes` - The attributes the ciphertext is labelled with
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///	* `_suite` - The AEAD that encrypts the plaintext
+    ///
+    fn encrypt_cca(
+        &self,
+        _pk: &Self::PublicKey,
+        _attributes: &[String],
+        _plaintext: &[u8],
+        _suite: AeadSuite,
+    ) -> Result<Self::Ciphertext, RabeError> {
+        let (_key, _enc) = self.encapsulate_cca(_pk, _attributes)?;
+        self.seal(&_key, _enc, _plaintext, _suite)
+    }
+
+    /// Decrypts a ciphertext generated by encrypt_cca(), if its attributes satisfy the policy of the
+    /// Secret Key (SK) and the ciphertext is unmodified.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - The Public Key (PK) the ciphertext was generated with
+    ///	* `_sk` - A Secret Key (SK), generated by keygen()
+    ///	* `_ct` - A Ciphertext (CT), generated by encrypt_cca()
+    ///
+    fn decrypt_cca(&self, _pk: &Self::PublicKey, _sk: &Self::SecretKey, _ct: &Self::Ciphertext) -> Result<Vec<u8>, RabeError> {
+        let _key = self.decapsulate_cca(_pk, _sk, &self.encapsulation(_ct))?;
+        self.open(&_key, _ct)
+    }
+}
+
+/// A multi-authority CP-ABE scheme: attributes are issued by independent authorities.
+///
+/// Schemes without a central master key use `()` as `MasterKey`.
+pub trait MultiAuthorityAbe {
+    /// The identifier of this scheme
+    const ID: SchemeId;
+
+    type GlobalKey: Serialize + DeserializeOwned + Clone + Fingerprint;
+    type MasterKey: Serialize + DeserializeOwned + Clone;
+    type AuthorityKey: Serialize + DeserializeOwned + Clone;
+    type PublicAttributeKey: Serialize + DeserializeOwned + Clone;
+    type SecretKey: Serialize + DeserializeOwned + Clone;
+    type Ciphertext: Serialize + DeserializeOwned + Clone;
+    type Encapsulation: Serialize + DeserializeOwned + Clone + PartialEq;
+
+    /// Generates the global parameters (GK) and, if the scheme has one, the central Master Key (MK).
+    fn setup(&self) -> (Self::GlobalKey, Self::MasterKey);
+
+    /// Sets up a new authority.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_mk` - The central Master Key (MK), generated by setup()
+    ///	* `_name` - The name of the authority. Must be unique.
+    ///	* `_attributes` - The attributes handled by this authority
+    ///
+    fn authgen(
+        &self,
+        _gk: &Self::GlobalKey,
+        _mk: &Self::MasterKey,
+        _name: &str,
+        _attributes: &[String],
+    ) -> Result<Self::AuthorityKey, RabeError>;
+
+    /// Generates a Secret Key (SK) for a user without any attributes. Attributes are added by request_attribute_sk().
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_mk` - The central Master Key (MK), generated by setup()
+    ///	* `_authority` - An authority key, generated by authgen()
+    ///	* `_name` - The name of the user. Must be unique.
+    ///
+    fn keygen(
+        &self,
+        _gk: &Self::GlobalKey,
+        _mk: &Self::MasterKey,
+        _authority: &Self::AuthorityKey,
+        _name: &str,
+    ) -> Result<Self::SecretKey, RabeError>;
+
+    /// Requests the public key of an attribute from the authority handling it.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_authority` - An authority key, generated by authgen()
+    ///	* `_attribute` - The attribute
+    ///
+    fn request_attribute_pk(
+        &self,
+        _gk: &Self::GlobalKey,
+        _authority: &Self::AuthorityKey,
+        _attribute: &str,
+    ) -> Result<Self::PublicAttributeKey, RabeError>;
+
+    /// Requests the secret key of an attribute from the authority handling it and adds it to the user key.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_authority` - An authority key, generated by authgen()
+    ///	* `_sk` - The Secret Key (SK) of the user, generated by keygen()
+    ///	* `_attribute` - The attribute
+    ///
+    fn request_attribute_sk(
+        &self,
+        _gk: &Self::GlobalKey,
+        _authority: &Self::AuthorityKey,
+        _sk: &mut Self::SecretKey,
+        _attribute: &str,
+    ) -> Result<(), RabeError>;
+
+    /// Encrypts some plaintext data under an access policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_pks` - The public attribute keys of all attributes used in the policy
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///
+    fn encrypt(
+        &self,
+        _gk: &Self::GlobalKey,
+        _pks: &[Self::PublicAttributeKey],
+        _policy: &str,
+        _language: PolicyLanguage,
+        _plaintext: &[u8],
+    ) -> Result<Self::Ciphertext, RabeError> {
+        self.encrypt_with_suite(_gk, _pks, _policy, _language, _plaintext, AeadSuite::default())
+    }
+
+    /// Encrypts some plaintext data under an access policy, using the given AEAD suite.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_pks` - The public attribute keys of all attributes used in the policy
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///	* `_suite` - The AEAD that encrypts the plaintext
+    ///
+    fn encrypt_with_suite(
+        &self,
+        _gk: &Self::GlobalKey,
+        _pks: &[Self::PublicAttributeKey],
+        _policy: &str,
+        _language: PolicyLanguage,
+        _plaintext: &[u8],
+        _suite: AeadSuite,
+    ) -> Result<Self::Ciphertext, RabeError>;
+
+    /// Encrypts some plaintext data under an access policy given as `Policy`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_pks` - The public attribute keys of all attributes used in the policy
+    ///	* `_policy` - An access policy
+    ///	* `_plaintext` - plaintext data given as a slice of u8
+    ///
+    fn encrypt_policy(
+        &self,
+        _gk: &Self::GlobalKey,
+        _pks: &[Self::PublicAttributeKey],
+        _policy: &Policy,
+        _plaintext: &[u8],
+    ) -> Result<Self::Ciphertext, RabeError> {
+        self.encrypt(_gk, _pks, &_policy.display(PolicyLanguage::JsonPolicy).to_string(), PolicyLanguage::JsonPolicy, _plaintext)
+    }
+
+    /// Decrypts a ciphertext if the attributes of the Secret Key (SK) satisfy its policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_sk` - The Secret Key (SK) of the user
+    ///	* `_ct` - A Ciphertext (CT), generated by encrypt()
+    ///
+    fn decrypt(
+        &self,
+        _gk: &Self::GlobalKey,
+        _sk: &Self::SecretKey,
+        _ct: &Self::Ciphertext,
+    ) -> Result<Vec<u8>, RabeError>;
+
+    /// Generates a random SymmetricKey and encapsulates it under an access policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_pks` - The public attribute keys of all attributes used in the policy
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///
+    fn encapsulate(
+        &self,
+        _gk: &Self::GlobalKey,
+        _pks: &[Self::PublicAttributeKey],
+        _policy: &str,
+        _language: PolicyLanguage,
+    ) -> Result<(SymmetricKey, Self::Encapsulation), RabeError>;
+
+    /// Recovers the SymmetricKey of an encapsulation if the attributes of the Secret Key (SK) satisfy its policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_sk` - The Secret Key (SK) of the user
+    ///	* `_enc` - An encapsulation, generated by encapsulate()
+    ///
+    fn decapsulate(
+        &self,
+        _gk: &Self::GlobalKey,
+        _sk: &Self::SecretKey,
+        _enc: &Self::Encapsulation,
+    ) -> Result<SymmetricKey, RabeError>;
+
+    /// Starts encrypting a stream of plaintext data under an access policy in constant memory.
+    /// A random stream key is encrypted with the scheme and written to `_output`, followed by the
+    /// chunks written to the returned `EncryptWriter`, see `utils::aes::stream`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_pks` - The public attribute keys of all attributes used in the policy
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    ///	* `_suite` - The AEAD that encrypts the stream
+    ///	* `_output` - The writer the encrypted stream is written to
+    ///
+    fn encrypt_stream<W: Write>(
+        &self,
+        _gk: &Self::GlobalKey,
+        _pks: &[Self::PublicAttributeKey],
+        _policy: &str,
+        _language: PolicyLanguage,
+        _suite: AeadSuite,
+        mut _output: W,
+    ) -> Result<EncryptWriter<W>, RabeError> {
+        let _key = stream::generate_key();
+        let _ct = self.encrypt_with_suite(_gk, _pks, _policy, _language, &_key, _suite)?;
+        stream::write_header(&mut _output, &_ct, _suite)?;
+        Ok(EncryptWriter::new(_output, &_key, _suite)?)
+    }
+
+    /// Starts decrypting a stream written by encrypt_stream(), if the attributes of the Secret Key (SK) satisfy its policy.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global parameters (GK), generated by setup()
+    ///	* `_sk` - The Secret Key (SK) of the user
+    ///	* `_input` - The reader the encrypted stream is read from
+    ///
+    fn decrypt_stream<R: Read>(&self, _gk: &Self::GlobalKey, _sk: &Self::SecretKey, mut _input: R) -> Result<DecryptReader<R>, RabeError> {
+        let (_ct, _suite) = stream::read_header::<R, Self::Ciphertext>(&mut _input)?;
+        let _key = self.decrypt(_gk, _sk, &_ct)?;
+        Ok(DecryptReader::new(_input, &_key, _suite)?)
+    }
+
+    /// Explains whether the attributes of the Secret Key (SK) satisfy the policy of a ciphertext,
+    /// see `utils::policy::explain`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - The Secret Key (SK) of the user
+    ///	* `_ct` - A Ciphertext (CT), generated by encrypt()
+    ///
+    fn explain(&self, _sk: &Self::SecretKey, _ct: &Self::Ciphertext) -> Result<Explanation, RabeError>;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::policy::comparison::numeric_attribute;
+    use super::{ac17::{Ac17Cp, Ac17Kp}, bsw::Bsw, lsw::Lsw, yct14::Yct14, aw11::Aw11, mke08::Mke08, bdabe::Bdabe};
+
+    fn cp_roundtrip<S: CpAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let ct = scheme.encrypt(&pk, r#""A" and ("B" or "C")"#, PolicyLanguage::HumanPolicy, &plaintext).unwrap();
+        let sk_match = scheme.keygen(&pk, &msk, &["A".to_string(), "C".to_string()]).unwrap();
+        let sk_no_match = scheme.keygen(&pk, &msk, &["B".to_string(), "C".to_string()]).unwrap();
+        assert_eq!(scheme.decrypt(&sk_match, &ct).unwrap(), plaintext);
+        assert!(scheme.decrypt(&sk_no_match, &ct).is_err());
+        assert_eq!(scheme.explain(&sk_match, &ct).unwrap()._satisfying, attributes(&["A", "C"]));
+        assert_eq!(scheme.explain(&sk_no_match, &ct).unwrap()._branches[0].1, attributes(&["A"]));
+    }
+
+    fn kp_roundtrip<S: KpAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let ct = scheme.encrypt(&pk, &["A".to_string(), "C".to_string()], &plaintext).unwrap();
+        let sk_match = scheme.keygen(&pk, &msk, r#""A" and ("B" or "C")"#, PolicyLanguage::HumanPolicy).unwrap();
+        let sk_no_match = scheme.keygen(&pk, &msk, r#""A" and "B""#, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(scheme.decrypt(&sk_match, &ct).unwrap(), plaintext);
+        assert!(scheme.decrypt(&sk_no_match, &ct).is_err());
+        assert!(scheme.explain(&sk_match, &ct).unwrap()._satisfied);
+        assert_eq!(scheme.explain(&sk_no_match, &ct).unwrap()._branches[0].1, attributes(&["B"]));
+    }
+
+    fn ma_roundtrip<S: MultiAuthorityAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (gk, mk) = scheme.setup();
+        let aa1 = scheme.authgen(&gk, &mk, "AA1", &["AA1::A".to_string()]).unwrap();
+        let aa2 = scheme.authgen(&gk, &mk, "AA2", &["AA2::B".to_string(), "AA2::C".to_string()]).unwrap();
+        let pks = vec![
+            scheme.request_attribute_pk(&gk, &aa1, "AA1::A").unwrap(),
+            scheme.request_attribute_pk(&gk, &aa2, "AA2::B").unwrap(),
+            scheme.request_attribute_pk(&gk, &aa2, "AA2::C").unwrap(),
+        ];
+        let policy = r#"("AA1::A" and "AA2::B") or "AA2::C""#;
+        let ct = scheme.encrypt(&gk, &pks, policy, PolicyLanguage::HumanPolicy, &plaintext).unwrap();
+        let mut sk = scheme.keygen(&gk, &mk, &aa1, "bob").unwrap();
+        scheme.request_attribute_sk(&gk, &aa1, &mut sk, "AA1::A").unwrap();
+        assert!(scheme.decrypt(&gk, &sk, &ct).is_err());
+        assert!(!scheme.explain(&sk, &ct).unwrap()._satisfied);
+        scheme.request_attribute_sk(&gk, &aa2, &mut sk, "AA2::B").unwrap();
+        assert_eq!(scheme.decrypt(&gk, &sk, &ct).unwrap(), plaintext);
+        assert_eq!(scheme.explain(&sk, &ct).unwrap()._satisfying, attributes(&["AA1::A", "AA2::B"]));
+    }
+
+    fn attributes(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn cp_threshold<S: CpAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let ct = scheme.encrypt(&pk, r#""D" and 2 of ("A", "B", "C")"#, PolicyLanguage::HumanPolicy, &plaintext).unwrap();
+        let sk_match = scheme.keygen(&pk, &msk, &attributes(&["B", "C", "D"])).unwrap();
+        let sk_no_match = scheme.keygen(&pk, &msk, &attributes(&["A", "D"])).unwrap();
+        assert_eq!(scheme.decrypt(&sk_match, &ct).unwrap(), plaintext);
+        assert!(scheme.decrypt(&sk_no_match, &ct).is_err());
+    }
+
+    fn kp_threshold<S: KpAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let sk = scheme.keygen(&pk, &msk, r#""D" and 2 of ("A", "B", "C")"#, PolicyLanguage::HumanPolicy).unwrap();
+        let ct_match = scheme.encrypt(&pk, &attributes(&["A", "C", "D"]), &plaintext).unwrap();
+        let ct_no_match = scheme.encrypt(&pk, &attributes(&["B", "D"]), &plaintext).unwrap();
+        assert_eq!(scheme.decrypt(&sk, &ct_match).unwrap(), plaintext);
+        assert!(scheme.decrypt(&sk, &ct_no_match).is_err());
+    }
+
+    fn cp_comparison<S: CpAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let ct = scheme.encrypt(&pk, r#""age" >= 18 and "A""#, PolicyLanguage::HumanPolicy, &plaintext).unwrap();
+        let mut adult = numeric_attribute("age", 42).unwrap();
+        adult.push("A".to_string());
+        let mut minor = numeric_attribute("age", 17).unwrap();
+        minor.push("A".to_string());
+        assert_eq!(scheme.decrypt(&scheme.keygen(&pk, &msk, &adult).unwrap(), &ct).unwrap(), plaintext);
+        assert!(scheme.decrypt(&scheme.keygen(&pk, &msk, &minor).unwrap(), &ct).is_err());
+    }
+
+    fn kp_comparison<S: KpAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let sk = scheme.keygen(&pk, &msk, r#""clearance" < 3"#, PolicyLanguage::HumanPolicy).unwrap();
+        let ct_match = scheme.encrypt(&pk, &numeric_attribute("clearance", 2).unwrap(), &plaintext).unwrap();
+        let ct_no_match = scheme.encrypt(&pk, &numeric_attribute("clearance", 3).unwrap(), &plaintext).unwrap();
+        assert_eq!(scheme.decrypt(&sk, &ct_match).unwrap(), plaintext);
+        assert!(scheme.decrypt(&sk, &ct_no_match).is_err());
+    }
+
+    fn cp_suites<S: CpAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let sk = scheme.keygen(&pk, &msk, &attributes(&["A"])).unwrap();
+        for suite in AeadSuite::all() {
+            let ct = scheme.encrypt_with_suite(&pk, r#""A" or "B""#, PolicyLanguage::HumanPolicy, &plaintext, suite).unwrap();
+            assert_eq!(scheme.decrypt(&sk, &ct).unwrap(), plaintext);
+        }
+    }
+
+    fn kp_suites<S: KpAbe>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let sk = scheme.keygen(&pk, &msk, r#""A" or "B""#, PolicyLanguage::HumanPolicy).unwrap();
+        for suite in AeadSuite::all() {
+            let ct = scheme.encrypt_with_suite(&pk, &attributes(&["A"]), &plaintext, suite).unwrap();
+            assert_eq!(scheme.decrypt(&sk, &ct).unwrap(), plaintext);
+        }
+    }
+
+    fn cp_kem<S: CpAbe>(scheme: S) {
+        let (pk, msk) = scheme.setup();
+        let (key, enc) = scheme.encapsulate(&pk, r#""A" and "B""#, PolicyLanguage::HumanPolicy).unwrap();
+        let sk_match = scheme.keygen(&pk, &msk, &attributes(&["A", "B"])).unwrap();
+        let sk_no_match = scheme.keygen(&pk, &msk, &attributes(&["A"])).unwrap();
+        assert_eq!(scheme.decapsulate(&sk_match, &enc).unwrap().as_bytes(), key.as_bytes());
+        assert!(scheme.decapsulate(&sk_no_match, &enc).is_err());
+    }
+
+    fn kp_kem<S: KpAbe>(scheme: S) {
+        let (pk, msk) = scheme.setup();
+        let (key, enc) = scheme.encapsulate(&pk, &attributes(&["A", "B"])).unwrap();
+        let sk_match = scheme.keygen(&pk, &msk, r#""A" and "B""#, PolicyLanguage::HumanPolicy).unwrap();
+        let sk_no_match = scheme.keygen(&pk, &msk, r#""C""#, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(scheme.decapsulate(&sk_match, &enc).unwrap().as_bytes(), key.as_bytes());
+        assert!(scheme.decapsulate(&sk_no_match, &enc).is_err());
+    }
+
+    fn ma_kem<S: MultiAuthorityAbe>(scheme: S) {
+        let (gk, mk) = scheme.setup();
+        let aa1 = scheme.authgen(&gk, &mk, "AA1", &attributes(&["AA1::A", "AA1::B"])).unwrap();
+        let pks = vec![scheme.request_attribute_pk(&gk, &aa1, "AA1::A").unwrap(), scheme.request_attribute_pk(&gk, &aa1, "AA1::B").unwrap()];
+        let (key, enc) = scheme.encapsulate(&gk, &pks, r#""AA1::A" and "AA1::B""#, PolicyLanguage::HumanPolicy).unwrap();
+        let mut sk = scheme.keygen(&gk, &mk, &aa1, "bob").unwrap();
+        scheme.request_attribute_sk(&gk, &aa1, &mut sk, "AA1::A").unwrap();
+        assert!(scheme.decapsulate(&gk, &sk, &enc).is_err());
+        scheme.request_attribute_sk(&gk, &aa1, &mut sk, "AA1::B").unwrap();
+        assert_eq!(scheme.decapsulate(&gk, &sk, &enc).unwrap().as_bytes(), key.as_bytes());
+    }
+
+    fn cp_cca<S: CpAbeCca>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let policy = r#""A" and ("B" or "C")"#;
+        let ct = scheme.encrypt_cca(&pk, policy, PolicyLanguage::HumanPolicy, &plaintext, AeadSuite::default()).unwrap();
+        let sk_match = scheme.keygen(&pk, &msk, &attributes(&["A", "C"])).unwrap();
+        let sk_no_match = scheme.keygen(&pk, &msk, &attributes(&["B", "C"])).unwrap();
+        assert_eq!(scheme.decrypt_cca(&pk, &sk_match, &ct).unwrap(), plaintext);
+        assert!(scheme.decrypt_cca(&pk, &sk_no_match, &ct).is_err());
+        // the randomness of encrypt() is not derived from the message
+        let ct_cpa = scheme.encrypt(&pk, policy, PolicyLanguage::HumanPolicy, &plaintext).unwrap();
+        assert!(scheme.decrypt_cca(&pk, &sk_match, &ct_cpa).is_err());
+        // re-encapsulating needs the public key the ciphertext was generated with
+        let (pk_other, _) = scheme.setup();
+        assert!(scheme.decrypt_cca(&pk_other, &sk_match, &ct).is_err());
+    }
+
+    fn kp_cca<S: KpAbeCca>(scheme: S) {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let (pk, msk) = scheme.setup();
+        let ct = scheme.encrypt_cca(&pk, &attributes(&["A", "C"]), &plaintext, AeadSuite::default()).unwrap();
+        let sk_match = scheme.keygen(&pk, &msk, r#""A" and ("B" or "C")"#, PolicyLanguage::HumanPolicy).unwrap();
+        let sk_no_match = scheme.keygen(&pk, &msk, r#""A" and "B""#, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(scheme.decrypt_cca(&pk, &sk_match, &ct).unwrap(), plaintext);
+        assert!(scheme.decrypt_cca(&pk, &sk_no_match, &ct).is_err());
+        let ct_cpa = scheme.encrypt(&pk, &attributes(&["A", "C"]), &plaintext).unwrap();
+        assert!(scheme.decrypt_cca(&pk, &sk_match, &ct_cpa).is_err());
+    }
+
+    #[test]
+    fn policy_schemes() {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let policy = Policy::and(vec![Policy::attr("A").unwrap(), Policy::or(vec![Policy::attr("B").unwrap(), Policy::attr("C").unwrap()])]);
+        let (pk, msk) = Bsw.setup();
+        let ct = Bsw.encrypt_policy(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(Bsw.decrypt(&Bsw.keygen(&pk, &msk, &attributes(&["A", "C"])).unwrap(), &ct).unwrap(), plaintext);
+        assert!(Bsw.decrypt(&Bsw.keygen(&pk, &msk, &attributes(&["B", "C"])).unwrap(), &ct).is_err());
+        let (pk, msk) = Lsw.setup();
+        let sk = Lsw.keygen_policy(&pk, &msk, &policy).unwrap();
+        assert_eq!(Lsw.decrypt(&sk, &Lsw.encrypt(&pk, &attributes(&["A", "B"]), &plaintext).unwrap()).unwrap(), plaintext);
+        assert!(Lsw.decrypt(&sk, &Lsw.encrypt(&pk, &attributes(&["B", "C"]), &plaintext).unwrap()).is_err());
+        let (gk, mk) = Aw11.setup();
+        let aa1 = Aw11.authgen(&gk, &mk, "AA1", &attributes(&["A", "B"])).unwrap();
+        let pks = vec![Aw11.request_attribute_pk(&gk, &aa1, "A").unwrap(), Aw11.request_attribute_pk(&gk, &aa1, "B").unwrap()];
+        let ct = Aw11.encrypt_policy(&gk, &pks, &Policy::or(vec![Policy::attr("A").unwrap(), Policy::attr("B").unwrap()]), &plaintext).unwrap();
+        let mut sk = Aw11.keygen(&gk, &mk, &aa1, "bob").unwrap();
+        Aw11.request_attribute_sk(&gk, &aa1, &mut sk, "B").unwrap();
+        assert_eq!(Aw11.decrypt(&gk, &sk, &ct).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn negation_schemes() {
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let policy = r#""A" and not "B""#;
+        let (pk, _) = Bsw.setup();
+        assert!(Bsw.encrypt(&pk, policy, PolicyLanguage::HumanPolicy, &plaintext).is_err());
+        // a double negation is monotone
+        assert!(Bsw.encrypt(&pk, r#"not not "A""#, PolicyLanguage::HumanPolicy, &plaintext).is_ok());
+        let (pk, msk_ac17) = Ac17Kp.setup();
+        let error = Ac17Kp.keygen(&pk, &msk_ac17, policy, PolicyLanguage::HumanPolicy).err().unwrap();
+        assert_eq!(error.to_string(), "RabeError: AC17KP does not support negated attributes in policies");
+        let yct14 = Yct14::try_new(attributes(&["A", "B"])).unwrap();
+        let (pk_yct14, msk_yct14) = yct14.setup();
+        assert!(yct14.keygen(&pk_yct14, &msk_yct14, policy, PolicyLanguage::HumanPolicy).is_err());
+        let (gk, mk) = Mke08.setup();
+        let aa1 = Mke08.authgen(&gk, &mk, "AA1", &attributes(&["AA1::A", "AA1::B"])).unwrap();
+        let pks = vec![Mke08.request_attribute_pk(&gk, &aa1, "AA1::A").unwrap(), Mke08.request_attribute_pk(&gk, &aa1, "AA1::B").unwrap()];
+        assert!(Mke08.encrypt(&gk, &pks, r#""AA1::A" and not "AA1::B""#, PolicyLanguage::HumanPolicy, &plaintext).is_err());
+        // LSW supports non-monotonic access structures
+        let (pk, msk) = Lsw.setup();
+        let sk = Lsw.keygen_policy(&pk, &msk, &Policy::and(vec![Policy::attr("A").unwrap(), !Policy::attr("B").unwrap()])).unwrap();
+        let ct_match = Lsw.encrypt(&pk, &attributes(&["A", "C"]), &plaintext).unwrap();
+        let ct_no_match = Lsw.encrypt(&pk, &attributes(&["A", "B"]), &plaintext).unwrap();
+        assert_eq!(Lsw.decrypt(&sk, &ct_match).unwrap(), plaintext);
+        assert!(Lsw.decrypt(&sk, &ct_no_match).is_err());
+        assert_eq!(Lsw.explain(&sk, &ct_match).unwrap()._satisfying, attributes(&["A", "!B"]));
+        assert_eq!(Lsw.explain(&sk, &ct_no_match).unwrap().to_string(), "policy is not satisfied, missing attributes per branch:\n  A and not B: missing not B");
+    }
+
+    #[test]
+    fn cp_schemes() {
+        cp_roundtrip(Bsw);
+        cp_roundtrip(Ac17Cp);
+    }
+
+    #[test]
+    fn threshold_schemes() {
+        cp_threshold(Bsw);
+        cp_threshold(Ac17Cp);
+        kp_threshold(Ac17Kp);
+        kp_threshold(Lsw);
+        kp_threshold(Yct14::try_new(attributes(&["A", "B", "C", "D"])).unwrap());
+    }
+
+    // comparisons expand to one leaf per bit, so only one scheme of each kind is tested here
+    #[test]
+    fn cp_comparison_schemes() {
+        cp_comparison(Bsw);
+    }
+
+    #[test]
+    fn kp_comparison_schemes() {
+        kp_comparison(Lsw);
+    }
+
+    #[test]
+    fn kp_schemes() {
+        kp_roundtrip(Ac17Kp);
+        kp_roundtrip(Lsw);
+        kp_roundtrip(Yct14::try_new(vec!["A".to_string(), "B".to_string(), "C".to_string()]).unwrap());
+    }
+
+    #[test]
+    fn streams() {
+        let plaintext: Vec<u8> = (0..3 * stream::CHUNK_SIZE / 2).map(|i| i as u8).collect();
+        let (pk, msk) = Bsw.setup();
+        let mut writer = Bsw.encrypt_stream(&pk, r#""A" or "B""#, PolicyLanguage::HumanPolicy, AeadSuite::Aes256Gcm, Vec::new()).unwrap();
+        writer.write_all(&plaintext).unwrap();
+        let ct = writer.finish().unwrap();
+        let mut decrypted = Vec::new();
+        Bsw.decrypt_stream(&Bsw.keygen(&pk, &msk, &attributes(&["B"])).unwrap(), &ct[..]).unwrap().read_to_end(&mut decrypted).unwrap();
+        assert_eq!(decrypted, plaintext);
+        assert!(Bsw.decrypt_stream(&Bsw.keygen(&pk, &msk, &attributes(&["C"])).unwrap(), &ct[..]).is_err());
+        let (pk, msk) = Lsw.setup();
+        let mut writer = Lsw.encrypt_stream(&pk, &attributes(&["A"]), AeadSuite::default(), Vec::new()).unwrap();
+        writer.write_all(&plaintext).unwrap();
+        let ct = writer.finish().unwrap();
+        let sk = Lsw.keygen(&pk, &msk, r#""A""#, PolicyLanguage::HumanPolicy).unwrap();
+        let mut decrypted = Vec::new();
+        Lsw.decrypt_stream(&sk, &ct[..ct.len() - stream::TAG_SIZE]).unwrap().read_to_end(&mut decrypted).err().unwrap();
+        let mut decrypted = Vec::new();
+        Lsw.decrypt_stream(&sk, &ct[..]).unwrap().read_to_end(&mut decrypted).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn aead_suites() {
+        cp_suites(Bsw);
+        cp_suites(Ac17Cp);
+        kp_suites(Ac17Kp);
+        kp_suites(Lsw);
+        kp_suites(Yct14::try_new(attributes(&["A", "B"])).unwrap());
+    }
+
+    #[test]
+    fn kem_schemes() {
+        cp_kem(Bsw);
+        cp_kem(Ac17Cp);
+        kp_kem(Ac17Kp);
+        kp_kem(Lsw);
+        kp_kem(Yct14::try_new(attributes(&["A", "B", "C"])).unwrap());
+        ma_kem(Aw11);
+        ma_kem(Mke08);
+        ma_kem(Bdabe);
+    }
+
+    #[test]
+    fn cca_schemes() {
+        cp_cca(Bsw);
+        cp_cca(Ac17Cp);
+        kp_cca(Ac17Kp);
+        kp_cca(Lsw);
+    }
+
+    #[test]
+    fn multi_authority_schemes() {
+        ma_roundtrip(Aw11);
+        ma_roundtrip(Mke08);
+        ma_roundtrip(Bdabe);
+    }
+
+    #[test]
+    fn authority_prefix() {
+        let (gk, mk) = Mke08.setup();
+        assert!(Mke08.authgen(&gk, &mk, "AA1", &attributes(&["AA1::A", "AA2::B"])).is_err());
+        assert!(Mke08.authgen(&gk, &mk, "AA1", &attributes(&["A"])).is_err());
+        let (gk, mk) = Bdabe.setup();
+        assert!(Bdabe.authgen(&gk, &mk, "AA1", &attributes(&["AA2::A"])).is_err());
+        assert!(Bdabe.authgen(&gk, &mk, "AA1", &attributes(&["AA1::A"])).is_ok());
+    }
+}
@@ -0,0 +1,284 @@
+//! A runtime registry of schemes, to decrypt keys and ciphertexts without knowing their scheme.
+//!
+//! Every key and ciphertext records the scheme that produced it, in the header of its wire
+//! encoding (see utils::wire) or in the `scheme` field of its JSON representation (see
+//! utils::json). The `Registry` reads it from an `Encoded` object and dispatches `decrypt` and
+//! `explain` to that scheme. Whether a secret key and a ciphertext belong to the same public key
+//! is checked by the scheme, see utils::fingerprint.
+//!
+//! # Examples
+//!
+//! ```
+//! use rabe::schemes::{CpAbe, SchemeId, bsw::Bsw, registry::*};
+//! use rabe::utils::{armor::armor, policy::pest::PolicyLanguage};
+//! let (pk, msk) = Bsw.setup();
+//! let sk = armor(&Bsw.keygen(&pk, &msk, &["A".to_string()]).unwrap());
+//! let ct = armor(&Bsw.encrypt(&pk, r#""A""#, PolicyLanguage::HumanPolicy, b"our plaintext!").unwrap());
+//! // later, without knowing the scheme
+//! let (sk, ct) = (Encoded::from_text(&sk).unwrap(), Encoded::from_text(&ct).unwrap());
+//! let registry = Registry::default();
+//! assert_eq!(registry.scheme(&sk, &ct).unwrap(), SchemeId::Bsw);
+//! assert_eq!(registry.decrypt(None, &sk, &ct).unwrap(), b"our plaintext!".to_vec());
+//! ```
+use std::collections::HashMap;
+use std::str::FromStr;
+use serde_json::Value;
+use schemes::{
+    CpAbe,
+    KpAbe,
+    MultiAuthorityAbe,
+    SchemeId,
+    ac17::{Ac17Cp, Ac17Kp},
+    aw11::Aw11,
+    bdabe::Bdabe,
+    bsw::Bsw,
+    lsw::Lsw,
+    mke08::Mke08,
+    yct14::Yct14
+};
+use utils::{
+    armor::Armor,
+    json::JsonFormat,
+    policy::explain::Explanation,
+    wire::read_header
+};
+use RabeError;
+
+/// A key or ciphertext of a scheme that is not known yet
+#[derive(PartialEq, Clone, Debug)]
+pub enum Encoded {
+    /// The wire encoding, see utils::wire
+    Wire(Vec<u8>),
+    /// The JSON representation, see utils::json
+    Json(Value),
+}
+
+/// The signature of a scheme's decrypt function inside the Registry. The first argument is the
+/// global key of a multi-authority scheme.
+pub type DecryptFn = fn(Option<&Encoded>, &Encoded, &Encoded) -> Result<Vec<u8>, RabeError>;
+
+/// The signature of a scheme's explain function inside the Registry
+pub type ExplainFn = fn(&Encoded, &Encoded) -> Result<Explanation, RabeError>;
+
+/// Dispatches decryption of keys and ciphertexts to the scheme that produced them
+#[derive(Clone)]
+pub struct Registry {
+    _schemes: HashMap<SchemeId, (DecryptFn, ExplainFn)>,
+}
+
+impl Encoded {
+    /// Parses the text of an armored object (see utils::armor) or of a JSON representation
+    ///
+    /// # Arguments
+    ///
+    ///	* `_text` - The armored object or JSON text
+    ///
+    pub fn from_text(_text: &str) -> Result<Encoded, RabeError> {
+        if _text.trim_start().starts_with('{') {
+            Ok(Encoded::Json(serde_json::from_str(_text)?))
+        } else {
+            Ok(Encoded::Wire(Armor::decode(_text)?._payload))
+        }
+    }
+
+    /// The scheme recorded in the header of the object
+    pub fn scheme(&self) -> Result<SchemeId, RabeError> {
+        match self {
+            Encoded::Wire(_bytes) => Ok(read_header(_bytes)?._scheme),
+            Encoded::Json(_value) => match _value.get("scheme").and_then(Value::as_str) {
+                Some(_scheme) => SchemeId::from_str(_scheme),
+                None => Err(RabeError::new("json: missing scheme")),
+            },
+        }
+    }
+
+    /// Decodes the object, which must be of type T
+    pub fn decode<T: JsonFormat>(&self) -> Result<T, RabeError> {
+        match self {
+            Encoded::Wire(_bytes) => T::from_bytes(_bytes),
+            Encoded::Json(_value) => T::from_json_value(_value),
+        }
+    }
+}
+
+/// Decrypts a ciphertext of a CP-ABE scheme, for use as DecryptFn
+pub fn cp_decrypt<S: CpAbe + Default>(_gk: Option<&Encoded>, _sk: &Encoded, _ct: &Encoded) -> Result<Vec<u8>, RabeError>
+where S::SecretKey: JsonFormat, S::Ciphertext: JsonFormat {
+    S::default().decrypt(&_sk.decode()?, &_ct.decode()?)
+}
+
+/// Explains a ciphertext of a CP-ABE scheme, for use as ExplainFn
+pub fn cp_explain<S: CpAbe + Default>(_sk: &Encoded, _ct: &Encoded) -> Result<Explanation, RabeError>
+where S::SecretKey: JsonFormat, S::Ciphertext: JsonFormat {
+    S::default().explain(&_sk.decode()?, &_ct.decode()?)
+}
+
+/// Decrypts a ciphertext of a KP-ABE scheme, for use as DecryptFn
+pub fn kp_decrypt<S: KpAbe + Default>(_gk: Option<&Encoded>, _sk: &Encoded, _ct: &Encoded) -> Result<Vec<u8>, RabeError>
+where S::SecretKey: JsonFormat, S::Ciphertext: JsonFormat {
+    S::default().decrypt(&_sk.decode()?, &_ct.decode()?)
+}
+
+/// Explains a ciphertext of a KP-ABE scheme, for use as ExplainFn
+pub fn kp_explain<S: KpAbe + Default>(_sk: &Encoded, _ct: &Encoded) -> Result<Explanation, RabeError>
+where S::SecretKey: JsonFormat, S::Ciphertext: JsonFormat {
+    S::default().explain(&_sk.decode()?, &_ct.decode()?)
+}
+
+/// Decrypts a ciphertext of a multi-authority scheme with its global key, for use as DecryptFn
+pub fn ma_decrypt<S: MultiAuthorityAbe + Default>(_gk: Option<&Encoded>, _sk: &Encoded, _ct: &Encoded) -> Result<Vec<u8>, RabeError>
+where S::GlobalKey: JsonFormat, S::SecretKey: JsonFormat, S::Ciphertext: JsonFormat {
+    let _gk = _gk.ok_or_else(|| RabeError::new(&format!("Error in registry/decrypt: {} needs the global key.", S::ID)))?;
+    S::default().decrypt(&_gk.decode()?, &_sk.decode()?, &_ct.decode()?)
+}
+
+/// Explains a ciphertext of a multi-authority scheme, for use as ExplainFn
+pub fn ma_explain<S: MultiAuthorityAbe + Default>(_sk: &Encoded, _ct: &Encoded) -> Result<Explanation, RabeError>
+where S::SecretKey: JsonFormat, S::Ciphertext: JsonFormat {
+    S::default().explain(&_sk.decode()?, &_ct.decode()?)
+}
+
+impl Registry {
+    /// Creates a Registry without any schemes
+    pub fn empty() -> Registry {
+        Registry { _schemes: HashMap::new() }
+    }
+
+    /// Registers (or replaces) the decrypt and explain functions of a scheme
+    pub fn register(&mut self, _scheme: SchemeId, _decrypt: DecryptFn, _explain: ExplainFn) {
+        self._schemes.insert(_scheme, (_decrypt, _explain));
+    }
+
+    /// Returns true if the given scheme is registered
+    pub fn contains(&self, _scheme: SchemeId) -> bool {
+        self._schemes.contains_key(&_scheme)
+    }
+
+    /// Returns the scheme of a secret key and a ciphertext, failing if they were produced by
+    /// different schemes or if the scheme is not registered.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A secret key
+    ///	* `_ct` - A ciphertext
+    ///
+    pub fn scheme(&self, _sk: &Encoded, _ct: &Encoded) -> Result<SchemeId, RabeError> {
+        let (_sk_scheme, _ct_scheme) = (_sk.scheme()?, _ct.scheme()?);
+        if _sk_scheme != _ct_scheme {
+            return Err(RabeError::new(&format!(
+                "Error in registry: the secret key belongs to scheme {} but the ciphertext was produced by scheme {}.",
+                _sk_scheme,
+                _ct_scheme
+            )));
+        }
+        if !self.contains(_ct_scheme) {
+            return Err(RabeError::new(&format!("Error in registry: scheme {} is not registered.", _ct_scheme)));
+        }
+        Ok(_ct_scheme)
+    }
+
+    /// Decrypts a ciphertext with a secret key, using the scheme recorded in their headers.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_gk` - The global key of a multi-authority scheme (the global parameters of AW11, the public key of BDABE and MKE08)
+    ///	* `_sk` - A secret key
+    ///	* `_ct` - A ciphertext
+    ///
+    pub fn decrypt(&self, _gk: Option<&Encoded>, _sk: &Encoded, _ct: &Encoded) -> Result<Vec<u8>, RabeError> {
+        let _scheme = self.scheme(_sk, _ct)?;
+        (self._schemes[&_scheme].0)(_gk, _sk, _ct)
+    }
+
+    /// Explains why a secret key can or cannot decrypt a ciphertext, using the scheme recorded in their headers.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_sk` - A secret key
+    ///	* `_ct` - A ciphertext
+    ///
+    pub fn explain(&self, _sk: &Encoded, _ct: &Encoded) -> Result<Explanation, RabeError> {
+        let _scheme = self.scheme(_sk, _ct)?;
+        (self._schemes[&_scheme].1)(_sk, _ct)
+    }
+}
+
+impl Default for Registry {
+    /// Creates a Registry containing all schemes of this library
+    fn default() -> Registry {
+        let mut _registry = Registry::empty();
+        _registry.register(SchemeId::Ac17Cp, cp_decrypt::<Ac17Cp>, cp_explain::<Ac17Cp>);
+        _registry.register(SchemeId::Ac17Kp, kp_decrypt::<Ac17Kp>, kp_explain::<Ac17Kp>);
+        _registry.register(SchemeId::Aw11, ma_decrypt::<Aw11>, ma_explain::<Aw11>);
+        _registry.register(SchemeId::Bdabe, ma_decrypt::<Bdabe>, ma_explain::<Bdabe>);
+        _registry.register(SchemeId::Bsw, cp_decrypt::<Bsw>, cp_explain::<Bsw>);
+        _registry.register(SchemeId::Lsw, kp_decrypt::<Lsw>, kp_explain::<Lsw>);
+        _registry.register(SchemeId::Mke08, ma_decrypt::<Mke08>, ma_explain::<Mke08>);
+        _registry.register(SchemeId::Yct14, kp_decrypt::<Yct14>, kp_explain::<Yct14>);
+        _registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::armor::armor;
+    use utils::policy::pest::PolicyLanguage;
+    use utils::wire::WireFormat;
+
+    #[test]
+    fn dispatch() {
+        let registry = Registry::default();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let attributes = vec!["A".to_string(), "B".to_string()];
+        let policy = r#""A" and "B""#;
+        // a cp scheme, armored
+        let (pk, msk) = Ac17Cp.setup();
+        let sk = Encoded::from_text(&armor(&Ac17Cp.keygen(&pk, &msk, &attributes).unwrap())).unwrap();
+        let ct = Encoded::from_text(&armor(&Ac17Cp.encrypt(&pk, policy, PolicyLanguage::HumanPolicy, &plaintext).unwrap())).unwrap();
+        assert_eq!(registry.scheme(&sk, &ct).unwrap(), SchemeId::Ac17Cp);
+        assert_eq!(registry.decrypt(None, &sk, &ct).unwrap(), plaintext);
+        assert!(registry.explain(&sk, &ct).unwrap()._satisfied);
+        // a kp scheme, in JSON
+        let (pk, msk) = Lsw.setup();
+        let sk = Encoded::from_text(&Lsw.keygen(&pk, &msk, policy, PolicyLanguage::HumanPolicy).unwrap().to_json_string()).unwrap();
+        let ct = Encoded::from_text(&Lsw.encrypt(&pk, &attributes, &plaintext).unwrap().to_json_string()).unwrap();
+        assert_eq!(registry.decrypt(None, &sk, &ct).unwrap(), plaintext);
+        // a multi-authority scheme needs its global key
+        let (gk, mk) = Aw11.setup();
+        let _authority = Aw11.authgen(&gk, &mk, "AA1", &["AA1::A".to_string()]).unwrap();
+        let mut _sk = Aw11.keygen(&gk, &mk, &_authority, "bob").unwrap();
+        Aw11.request_attribute_sk(&gk, &_authority, &mut _sk, "AA1::A").unwrap();
+        let _pk = Aw11.request_attribute_pk(&gk, &_authority, "AA1::A").unwrap();
+        let sk = Encoded::Wire(_sk.to_bytes());
+        let ct = Encoded::Wire(Aw11.encrypt(&gk, &[_pk], r#""AA1::A""#, PolicyLanguage::HumanPolicy, &plaintext).unwrap().to_bytes());
+        assert!(registry.decrypt(None, &sk, &ct).is_err());
+        assert_eq!(registry.decrypt(Some(&Encoded::Wire(gk.to_bytes())), &sk, &ct).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn mismatch() {
+        let registry = Registry::default();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let attributes = vec!["A".to_string(), "B".to_string()];
+        let policy = r#""A" and "B""#;
+        let (pk, msk) = Bsw.setup();
+        let (other_pk, other_msk) = Bsw.setup();
+        let sk = Encoded::Wire(Bsw.keygen(&pk, &msk, &attributes).unwrap().to_bytes());
+        let other_sk = Encoded::Wire(Bsw.keygen(&other_pk, &other_msk, &attributes).unwrap().to_bytes());
+        let ct = Encoded::Wire(Bsw.encrypt(&pk, policy, PolicyLanguage::HumanPolicy, &plaintext).unwrap().to_bytes());
+        // a key of another public key
+        assert!(registry.decrypt(None, &other_sk, &ct).is_err());
+        // a key of another scheme
+        let (ac17_pk, ac17_msk) = Ac17Cp.setup();
+        let wrong_scheme = Encoded::Wire(Ac17Cp.keygen(&ac17_pk, &ac17_msk, &attributes).unwrap().to_bytes());
+        let err = registry.decrypt(None, &wrong_scheme, &ct).unwrap_err();
+        assert!(err.to_string().contains("belongs to scheme AC17CP"));
+        // a JSON object without a scheme
+        assert!(Encoded::Json(serde_json::json!({"kind": "SecretKey"})).scheme().is_err());
+        // an empty registry
+        assert!(Registry::empty().decrypt(None, &sk, &ct).is_err());
+        assert_eq!(registry.decrypt(None, &sk, &ct).unwrap(), plaintext);
+    }
+}
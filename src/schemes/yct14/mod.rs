@@ -12,7 +12,7 @@
 //! ```
 //! use rabe::schemes::yct14::*;
 //! use rabe::utils::policy::pest::PolicyLanguage;
-//! let (pk, msk) = setup(vec!["A".to_string(), "B".to_string(), "C".to_string()]);
+//! let (pk, msk) = try_setup(vec!["A".to_string(), "B".to_string(), "C".to_string()]).unwrap();
 //!let plaintext = String::from("our plaintext!").into_bytes();
 //!let policy = String::from(r#""A" or "B""#);
 //!let ct_kp: Yct14AbeCiphertext = encrypt(&pk, &vec!["A".to_string(), "B".to_string()], &plaintext).unwrap();
@@ -21,12 +21,20 @@
 //! ```
 use rabe_bn::{Fr, Gt};
 use utils::{
+    attribute::{canonical_attribute, canonicalize},
     secretsharing::{gen_shares_policy, calc_coefficients, calc_pruned},
     aes::*
 };
 use rand::Rng;
 use utils::policy::pest::{PolicyLanguage, parse};
 use RabeError;
+use utils::wire::{Wire, Reader, Writer, read_variant};
+use utils::validate::{ValidationError, check_gt, check_fr, check_fr_invertible, check_policy, check_issuer_length};
+use utils::fingerprint::{Fingerprint, check_issuer};
+use utils::json::{Json, JsonReader, JsonWriter, SchemaWriter, POLICY_FIELDS};
+use serde_json::{Value, json};
+use schemes::{KpAbe, SchemeId};
+use utils::policy::{ast::Policy, explain::{explain, Explanation}};
 use std::ops::Mul;
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
@@ -37,6 +45,7 @@ pub struct Yct14Attribute {
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Yct14Type {
     Public(Gt),
     Private(Fr),
@@ -45,7 +54,7 @@ pub enum Yct14Type {
 impl Yct14Type {
     pub fn public(&self) -> Result<Gt, RabeError> {
         match self {
-            Yct14Type::Public(g) => Ok(g.clone()),
+            Yct14Type::Public(g) => Ok(*g),
             _ => Err(RabeError::new("no public value (Gt) found"))
         }
     }
@@ -116,6 +125,7 @@ impl Yct14Attribute {
 
 /// A Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Yct14AbePublicKey {
     g: Gt,
     attributes: Vec<Yct14Attribute>
@@ -123,6 +133,7 @@ pub struct Yct14AbePublicKey {
 
 /// A Master Key (MSK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Yct14AbeMasterKey {
     s: Fr,
     attributes: Vec<Yct14Attribute>
@@ -145,9 +156,13 @@ impl Yct14AbeMasterKey {
 
 /// A Secret User Key (SK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Yct14AbeSecretKey {
     policy: (String, PolicyLanguage),
     du: Vec<Yct14Attribute>,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    issuer: Vec<u8>,
 }
 
 impl Yct14AbeSecretKey {
@@ -167,12 +182,43 @@ impl Yct14AbeSecretKey {
 
 /// A Ciphertext (CT)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
 pub struct Yct14AbeCiphertext {
     attributes: Vec<Yct14Attribute>,
     ct: Vec<u8>,
+    #[serde(default)]
+    kdf: KdfVersion,
+    #[serde(default)]
+    suite: AeadSuite,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    issuer: Vec<u8>,
 }
 
 impl Yct14AbeCiphertext {
+    pub fn get_public(&self, attribute: &String) -> Result<Gt, RabeError> {
+        self.encapsulation().get_public(attribute)
+    }
+
+    /// The key encapsulation of this ciphertext
+    pub fn encapsulation(&self) -> Yct14AbeEncapsulation {
+        Yct14AbeEncapsulation { attributes: self.attributes.clone(), kdf: self.kdf, issuer: self.issuer.clone() }
+    }
+}
+
+/// A key encapsulation: a Yct14AbeCiphertext without the symmetrically encrypted data
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self")]
+pub struct Yct14AbeEncapsulation {
+    attributes: Vec<Yct14Attribute>,
+    #[serde(default)]
+    kdf: KdfVersion,
+    /// The fingerprint of the issuing public key, see utils::fingerprint
+    #[serde(default)]
+    issuer: Vec<u8>,
+}
+
+impl Yct14AbeEncapsulation {
     pub fn get_public(&self, attribute: &String) -> Result<Gt, RabeError> {
         let res: Option<Gt> = self.attributes
             .clone()
@@ -185,10 +231,176 @@ impl Yct14AbeCiphertext {
             .nth(0);
         res.ok_or(RabeError::new(&format!("no private key found for {}", attribute)))
     }
+
+    /// The names of the attributes the encapsulation is labelled with
+    fn names(&self) -> Vec<String> {
+        self.attributes.iter().map(|value| value.name.clone()).collect()
+    }
+}
+
+/// The error for an attribute without a node of the expected type
+fn missing_node(_field: &str, _name: &str, _expected: &str) -> ValidationError {
+    ValidationError::Inconsistent { _field: _field.to_string(), _reason: format!("attribute {} has no {} value", _name, _expected) }
+}
+
+/// Checks that all attributes have a valid public value
+fn check_public(_field: &str, _attributes: &[Yct14Attribute]) -> Result<(), ValidationError> {
+    for _attribute in _attributes.iter() {
+        match &_attribute.node {
+            Some(Yct14Type::Public(_g)) => check_gt(_field, _g)?,
+            _ => return Err(missing_node(_field, &_attribute.name, "public")),
+        }
+    }
+    Ok(())
+}
+
+impl_wire!(Yct14Attribute { name, node });
+
+impl_json!(Yct14Attribute { name, node });
+
+impl_wire!(Yct14AbePublicKey: Yct14, PublicKey { g, attributes });
+
+impl_json!(Yct14AbePublicKey { g, attributes });
+
+impl Fingerprint for Yct14AbePublicKey {}
+
+impl_validate!(Yct14AbePublicKey { g: check_gt, attributes: unique(name), attributes: check_public });
+
+impl_validated_serde!(Yct14AbePublicKey);
+
+impl_wire!(Yct14AbeMasterKey: Yct14, MasterKey { s, attributes });
+
+impl_json!(Yct14AbeMasterKey { s, attributes });
+
+impl Fingerprint for Yct14AbeMasterKey {}
+
+impl_validate!(Yct14AbeMasterKey {
+    s: check_fr,
+    attributes: unique(name),
+} |_self| {
+    // keygen inverts the private values of the attributes
+    for _attribute in _self.attributes.iter() {
+        match &_attribute.node {
+            Some(Yct14Type::Private(_si)) => check_fr_invertible("attributes", _si)?,
+            _ => return Err(missing_node("attributes", &_attribute.name, "private").into()),
+        }
+    }
+});
+
+impl_validated_serde!(Yct14AbeMasterKey);
+
+impl_wire!(Yct14AbeSecretKey: Yct14, SecretKey { policy, du; issuer });
+
+impl_json!(Yct14AbeSecretKey { policy: tuple(POLICY_FIELDS), du, issuer });
+
+impl_validate!(Yct14AbeSecretKey {
+    policy: check_policy,
+    issuer: check_issuer_length,
+} |_self| {
+    for _attribute in _self.du.iter() {
+        match &_attribute.node {
+            Some(Yct14Type::Private(_d)) => check_fr("du", _d)?,
+            _ => return Err(missing_node("du", &_attribute.name, "private").into()),
+        }
+    }
+    // decapsulate needs a share of every leaf of the policy
+    for _leaf in Policy::parse(&_self.policy.0, _self.policy.1)?.attributes() {
+        if !_self.du.iter().any(|_attribute| _attribute.name == _leaf) {
+            return Err(ValidationError::Inconsistent { _field: "du".to_string(), _reason: format!("no share of the policy attribute {}", _leaf) }.into());
+        }
+    }
+});
+
+impl_validated_serde!(Yct14AbeSecretKey);
+
+impl_wire!(Yct14AbeCiphertext: Yct14, Ciphertext { attributes, ct, kdf, suite; issuer });
+
+impl_json!(Yct14AbeCiphertext { attributes, ct, kdf, suite, issuer });
+
+impl_validate!(Yct14AbeCiphertext {
+    attributes: unique(name),
+    attributes: check_public,
+    issuer: check_issuer_length,
+});
+
+impl_validated_serde!(Yct14AbeCiphertext);
+
+impl_wire!(Yct14AbeEncapsulation: Yct14, Encapsulation { attributes, kdf; issuer });
+
+impl_json!(Yct14AbeEncapsulation { attributes, kdf, issuer });
+
+impl_validate!(Yct14AbeEncapsulation {
+    attributes: unique(name),
+    attributes: check_public,
+    issuer: check_issuer_length,
+});
+
+impl_validated_serde!(Yct14AbeEncapsulation);
+
+impl Wire for Yct14Type {
+    fn encode(&self, _writer: &mut Writer) {
+        match self {
+            Yct14Type::Public(g) => _writer.write(&0u8).write(g),
+            Yct14Type::Private(fr) => _writer.write(&1u8).write(fr),
+        };
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<Yct14Type, RabeError> {
+        match read_variant(_reader, 2)? {
+            0 => Ok(Yct14Type::Public(_reader.read()?)),
+            _ => Ok(Yct14Type::Private(_reader.read()?)),
+        }
+    }
+}
+
+impl Json for Yct14Type {
+    fn to_json(&self) -> Value {
+        let mut _writer = JsonWriter::new();
+        match self {
+            Yct14Type::Public(g) => _writer.field("Public", g),
+            Yct14Type::Private(fr) => _writer.field("Private", fr),
+        };
+        _writer.into_value()
+    }
+
+    fn from_json(_value: &Value) -> Result<Yct14Type, RabeError> {
+        let mut _reader = JsonReader::new(_value)?;
+        let _type = match _reader.take("Public") {
+            Ok(g) => Yct14Type::Public(Json::from_json(&g)?),
+            Err(_) => Yct14Type::Private(_reader.field("Private")?),
+        };
+        _reader.finish()?;
+        Ok(_type)
+    }
+
+    fn schema() -> Value {
+        let mut _public = SchemaWriter::new();
+        _public.field::<Gt>("Public");
+        let mut _private = SchemaWriter::new();
+        _private.field::<Fr>("Private");
+        json!({ "oneOf": [_public.into_value(), _private.into_value()] })
+    }
 }
 
 /// The setup algorithm of KP-ABE. Generates a new Yct14AbePublicKey and a new Yct14AbeMasterKey.
+/// Attributes that are not valid are kept as they are.
+#[deprecated(note = "use try_setup, which rejects invalid attributes")]
 pub fn setup(attribute_keys: Vec<String>) -> (Yct14AbePublicKey, Yct14AbeMasterKey) {
+    setup_universe(attribute_keys.into_iter().map(|_attribute| canonical_attribute(&_attribute).unwrap_or(_attribute)).collect())
+}
+
+/// The setup algorithm of KP-ABE. Generates a new Yct14AbePublicKey and a new Yct14AbeMasterKey.
+/// Fails if one of the attributes is not a valid attribute.
+pub fn try_setup(attribute_keys: Vec<String>) -> Result<(Yct14AbePublicKey, Yct14AbeMasterKey), RabeError> {
+    let mut _universe: Vec<String> = Vec::new();
+    for attribute in attribute_keys {
+        _universe.push(canonical_attribute(&attribute)?);
+    }
+    Ok(setup_universe(_universe))
+}
+
+/// Generates the keys for an attribute universe that is already canonical.
+fn setup_universe(attribute_keys: Vec<String>) -> (Yct14AbePublicKey, Yct14AbeMasterKey) {
     // random number generator
     let mut _rng = rand::thread_rng();
     // attribute vec
@@ -229,6 +441,7 @@ pub fn keygen(
 ) -> Result<Yct14AbeSecretKey, RabeError> {
     match parse(_policy, _language) {
         Ok(pol) => {
+            SchemeId::Yct14.check_policy(&pol)?;
             let mut du: Vec<Yct14Attribute> = Vec::new();
             match gen_shares_policy(_msk.s, &pol, None) {
                 Some(shares) => {
@@ -243,7 +456,8 @@ pub fn keygen(
                     }
                     Ok(Yct14AbeSecretKey {
                         policy: (_policy.clone(), _language),
-                        du
+                        du,
+                        issuer: _pk.fingerprint(),
                     })
                 },
                 None => Err(RabeError::new("could not generate shares during keygen()"))
@@ -263,30 +477,64 @@ pub fn encrypt(
     pk: &Yct14AbePublicKey,
     _attributes: &Vec<String>,
     _plaintext: &[u8],
+) -> Result<Yct14AbeCiphertext, RabeError> {
+    encrypt_with_suite(pk, _attributes, _plaintext, AeadSuite::default())
+}
+
+/// Same as encrypt(), but encrypts the plaintext with the given AEAD suite.
+///
+/// # Arguments
+///
+///	* `pk` - A Public Key (PK), generated by the function setup()
+///	* `_attributes` - A set of attributes given as String Vector
+///	* `_plaintext` - plaintext data given as a vec<u8>
+///	* `_suite` - The AEAD that encrypts the plaintext
+///
+pub fn encrypt_with_suite(
+    pk: &Yct14AbePublicKey,
+    _attributes: &Vec<String>,
+    _plaintext: &[u8],
+    _suite: AeadSuite,
 ) -> Result<Yct14AbeCiphertext, RabeError> {
     if _attributes.is_empty() {
         return Err(RabeError::new("attributes empty"));
-    } 
+    }
     else if _plaintext.is_empty() {
         return Err(RabeError::new("plaintext empty"));
     }
-    else {
-        // attribute vector
-        let mut attributes: Vec<Yct14Attribute> = Vec::new();
-        // random secret
-        let k: Fr = rand::thread_rng().gen();
-        // aes secret = public g ** random k
-        let _cs: Gt = pk.g.pow(k);
+    let (_key, _enc) = encapsulate(pk, _attributes)?;
+    //Encrypt plaintext using aes secret
+    let ct = encrypt_symmetric_key(&_key, SchemeId::Yct14, _suite, &attribute_header(&_enc.names()), &header_elements(&_enc), _plaintext)?;
+    Ok(Yct14AbeCiphertext { attributes: _enc.attributes, ct, kdf: _enc.kdf, suite: _suite, issuer: _enc.issuer })
+}
 
-        for attr in _attributes.into_iter() {
-            attributes.push(Yct14Attribute::public_from(attr, pk, k));
-        }
-        //Encrypt plaintext using aes secret
-        match encrypt_symmetric(&_cs, &_plaintext.to_vec()) {
-            Ok(ct) => Ok(Yct14AbeCiphertext { attributes, ct }),
-            Err(e) => Err(e)
-        }
+/// The encapsulate algorithm of KP-ABE. Generates a new random SymmetricKey and its Yct14AbeEncapsulation under a set of attributes.
+///
+/// # Arguments
+///
+///	* `pk` - A Public Key (PK), generated by the function setup()
+///	* `_attributes` - A set of attributes given as String Vector
+///
+pub fn encapsulate(
+    pk: &Yct14AbePublicKey,
+    _attributes: &Vec<String>,
+) -> Result<(SymmetricKey, Yct14AbeEncapsulation), RabeError> {
+    if _attributes.is_empty() {
+        return Err(RabeError::new("attributes empty"));
+    }
+    // attribute vector
+    let mut attributes: Vec<Yct14Attribute> = Vec::new();
+    // random secret
+    let k: Fr = rand::thread_rng().gen();
+    // aes secret = public g ** random k
+    let _cs: Gt = pk.g.pow(k);
+
+    let _attributes = canonicalize(_attributes)?;
+    for attr in _attributes.iter() {
+        attributes.push(Yct14Attribute::public_from(attr, pk, k));
     }
+    let _key = SymmetricKey::derive(KdfVersion::CURRENT, &_cs, SchemeId::Yct14, &attribute_header(&_attributes));
+    Ok((_key, Yct14AbeEncapsulation { attributes, kdf: KdfVersion::CURRENT, issuer: pk.fingerprint() }))
 }
 
 /// # Arguments
@@ -295,11 +543,21 @@ pub fn encrypt(
 ///	* `_ct` - A Ciphertext (CT), generated by encrypt()
 ///
 pub fn decrypt(_sk: &Yct14AbeSecretKey, _ct: &Yct14AbeCiphertext) -> Result<Vec<u8>, RabeError> {
-    let _attrs_str = _ct
-        .attributes
-        .iter()
-        .map(|value| value.name.clone())
-        .collect::<Vec<String>>();
+    let _enc = _ct.encapsulation();
+    let _key = decapsulate(_sk, &_enc)?;
+    decrypt_symmetric_key(_enc.kdf, &_key, SchemeId::Yct14, _ct.suite, &attribute_header(&_enc.names()), &header_elements(&_enc), &_ct.ct)
+}
+
+/// The decapsulate algorithm of KP-ABE. Recovers the SymmetricKey of a Yct14AbeEncapsulation with a matching Yct14AbeSecretKey.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by keygen()
+///	* `_enc` - An Encapsulation, generated by encapsulate()
+///
+pub fn decapsulate(_sk: &Yct14AbeSecretKey, _enc: &Yct14AbeEncapsulation) -> Result<SymmetricKey, RabeError> {
+    check_issuer(&_sk.issuer, &_enc.issuer)?;
+    let _attrs_str = _enc.names();
     match parse(_sk.policy.0.as_ref(), _sk.policy.1) {
         Ok(pol) => {
             return match calc_pruned(&_attrs_str, &pol, None) {
@@ -308,19 +566,18 @@ pub fn decrypt(_sk: &Yct14AbeSecretKey, _ct: &Yct14AbeCiphertext) -> Result<Vec<
                     let (_match, _list) = _p;
                     if _match {
                         let mut _prod_t = Gt::one();
-                        let _coeffs: Vec<(String, Fr)> = calc_coefficients(&pol, Some(Fr::one()), None).unwrap();
+                        let _coeffs: Vec<(String, Fr)> = calc_coefficients(&pol, Some(Fr::one()), None, Some(&_attrs_str))
+                            .ok_or_else(|| RabeError::new("Error in yct14/decapsulate: could not calculate the coefficients."))?;
                         for _attr in _list.into_iter() {
-                            let z = _ct.get_public(&_attr).unwrap().pow(_sk.get_private(&_attr).unwrap());
+                            let z = _enc.get_public(&_attr)?.pow(_sk.get_private(&_attr)?);
                             let coeff = _coeffs
-                                .clone()
-                                .into_iter()
-                                .filter(|a| a.0 == _attr)
-                                .map(|a| a.1 )
-                                .nth(0)
-                                .unwrap();
+                                .iter()
+                                .find(|a| a.0 == _attr)
+                                .map(|a| a.1)
+                                .ok_or_else(|| RabeError::new(&format!("Error in yct14/decapsulate: no coefficient found for {}.", _attr)))?;
                             _prod_t = _prod_t * z.pow(coeff);
                         }
-                        decrypt_symmetric(&_prod_t, &_ct.ct)
+                        Ok(SymmetricKey::derive(_enc.kdf, &_prod_t, SchemeId::Yct14, &attribute_header(&_attrs_str)))
                     } else {
                         Err(RabeError::new("Error in decrypt: attributes do not match policy."))
                     }
@@ -331,11 +588,115 @@ pub fn decrypt(_sk: &Yct14AbeSecretKey, _ct: &Yct14AbeCiphertext) -> Result<Vec<
     }
 }
 
+/// The public group elements of a ciphertext, authenticated as associated data
+fn header_elements(_enc: &Yct14AbeEncapsulation) -> Vec<u8> {
+    let mut _elements = HeaderEncoder::new();
+    _elements.count(_enc.attributes.len());
+    for _attr in _enc.attributes.iter() {
+        _elements.str(&_attr.name);
+        match &_attr.node {
+            Some(Yct14Type::Public(_g)) => _elements.gt(_g),
+            // ciphertexts only carry public values
+            _ => _elements.count(0),
+        };
+    }
+    _elements.finish()
+}
+
+/// The YCT14 KP-ABE scheme, for use through the `KpAbe` trait.
+/// Unlike the other schemes, setup() needs the attribute universe up front.
+#[derive(Clone, Debug, Default)]
+pub struct Yct14 {
+    universe: Vec<String>,
+}
+
+impl Yct14 {
+    /// Attributes that are not valid are kept as they are.
+    #[deprecated(note = "use try_new, which rejects invalid attributes")]
+    pub fn new(universe: Vec<String>) -> Yct14 {
+        Yct14 { universe: universe.into_iter().map(|_attribute| canonical_attribute(&_attribute).unwrap_or(_attribute)).collect() }
+    }
+
+    /// Fails if one of the attributes is not a valid attribute.
+    pub fn try_new(universe: Vec<String>) -> Result<Yct14, RabeError> {
+        let mut _universe: Vec<String> = Vec::new();
+        for attribute in universe {
+            _universe.push(canonical_attribute(&attribute)?);
+        }
+        Ok(Yct14 { universe: _universe })
+    }
+
+    /// The attribute universe, in canonical form.
+    pub fn universe(&self) -> &[String] {
+        &self.universe
+    }
+}
+
+impl KpAbe for Yct14 {
+    const ID: SchemeId = SchemeId::Yct14;
+
+    type PublicKey = Yct14AbePublicKey;
+    type MasterKey = Yct14AbeMasterKey;
+    type SecretKey = Yct14AbeSecretKey;
+    type Ciphertext = Yct14AbeCiphertext;
+    type Encapsulation = Yct14AbeEncapsulation;
+
+    fn setup(&self) -> (Yct14AbePublicKey, Yct14AbeMasterKey) {
+        setup_universe(self.universe.clone())
+    }
+
+    fn keygen(&self, _pk: &Yct14AbePublicKey, _msk: &Yct14AbeMasterKey, _policy: &str, _language: PolicyLanguage) -> Result<Yct14AbeSecretKey, RabeError> {
+        keygen(_pk, _msk, &_policy.to_string(), _language)
+    }
+
+    fn encrypt_with_suite(&self, pk: &Yct14AbePublicKey, _attributes: &[String], _plaintext: &[u8], _suite: AeadSuite) -> Result<Yct14AbeCiphertext, RabeError> {
+        encrypt_with_suite(pk, &_attributes.to_vec(), _plaintext, _suite)
+    }
+
+    fn decrypt(&self, _sk: &Yct14AbeSecretKey, _ct: &Yct14AbeCiphertext) -> Result<Vec<u8>, RabeError> {
+        decrypt(_sk, _ct)
+    }
+
+    fn encapsulate(&self, pk: &Yct14AbePublicKey, _attributes: &[String]) -> Result<(SymmetricKey, Yct14AbeEncapsulation), RabeError> {
+        encapsulate(pk, &_attributes.to_vec())
+    }
+
+    fn decapsulate(&self, _sk: &Yct14AbeSecretKey, _enc: &Yct14AbeEncapsulation) -> Result<SymmetricKey, RabeError> {
+        decapsulate(_sk, _enc)
+    }
+
+    fn explain(&self, _sk: &Yct14AbeSecretKey, _ct: &Yct14AbeCiphertext) -> Result<Explanation, RabeError> {
+        let _attributes: Vec<String> = _ct.attributes.iter().map(|_attr| _attr.name.clone()).collect();
+        explain(&Policy::parse(&_sk.policy.0, _sk.policy.1)?, &_attributes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
 
+    #[test]
+    fn single() {
+        // a set of attributes
+        let attributes: Vec<String> = vec![String::from("admin"), String::from("B")];
+        // setup scheme
+        let (pk, msk) = try_setup(attributes.clone()).unwrap();
+        // our plaintext
+        let plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        // our policy consists of a single attribute
+        let policy = String::from(r#""admin""#);
+        // kp-abe ciphertexts
+        let ct: Yct14AbeCiphertext = encrypt(&pk, &attributes, &plaintext).unwrap();
+        let ct_no_match: Yct14AbeCiphertext = encrypt(&pk, &vec![String::from("B")], &plaintext).unwrap();
+        // a kp-abe SK key
+        let sk: Yct14AbeSecretKey = keygen(&pk, &msk, &policy, PolicyLanguage::HumanPolicy).unwrap();
+        // and now decrypt again with matching sk
+        assert_eq!(decrypt(&sk, &ct).unwrap(), plaintext);
+        assert!(decrypt(&sk, &ct_no_match).is_err());
+    }
+
     #[test]
     fn or() {
         // a set of attributes
@@ -344,7 +705,7 @@ mod tests {
         attributes.push(String::from("B"));
         attributes.push(String::from("C"));
         // setup scheme
-        let (pk, msk) = setup(attributes.clone());
+        let (pk, msk) = try_setup(attributes.clone()).unwrap();
         //println!("pk attrs: {:?}", serde_json::to_string(&pk.attributes).unwrap());
         //println!("msk attrs: {:?}", serde_json::to_string(&msk.attributes).unwrap());
         // our plaintext
@@ -362,4 +723,37 @@ mod tests {
         assert_eq!(decrypt(&sk, &ct).unwrap(), plaintext);
     }
 
+    #[test]
+    fn missing_shares() {
+        use utils::validate::Validate;
+        let attributes: Vec<String> = vec![String::from("A"), String::from("B")];
+        let (pk, msk) = try_setup(attributes.clone()).unwrap();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        let ct: Yct14AbeCiphertext = encrypt(&pk, &attributes, &plaintext).unwrap();
+        let sk: Yct14AbeSecretKey = keygen(&pk, &msk, &String::from(r#""A" and "B""#), PolicyLanguage::HumanPolicy).unwrap();
+        assert!(sk.validate().is_ok());
+        // a key without the share of B is rejected instead of panicking in decrypt
+        let mut _truncated = sk.clone();
+        _truncated.du.retain(|_attribute| _attribute.name != "B");
+        let _missing = ValidationError::Inconsistent { _field: "du".to_string(), _reason: String::from("no share of the policy attribute B") };
+        assert_eq!(_truncated.validate().err().unwrap().validation_error(), Some(&_missing));
+        assert!(serde_json::from_str::<Yct14AbeSecretKey>(&serde_json::to_string(&_truncated).unwrap()).is_err());
+        assert!(decrypt(&_truncated, &ct).err().unwrap().to_string().contains("no private key found for B"));
+        assert_eq!(decrypt(&sk, &ct).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn invalid_universe() {
+        let attributes: Vec<String> = vec![String::from("A"), String::from("dept=fin=ance")];
+        assert!(try_setup(attributes.clone()).is_err());
+        assert!(Yct14::try_new(attributes.clone()).is_err());
+        assert_eq!(Yct14::try_new(vec![String::from(" hr :: A ")]).unwrap().universe(), &[String::from("hr::A")]);
+        // the deprecated constructors keep the invalid attributes as they are
+        #[allow(deprecated)]
+        {
+            assert_eq!(Yct14::new(attributes.clone()).universe(), &attributes[..]);
+            assert_eq!(setup(vec![String::from(" hr :: A ")]).0.attributes[0].name, "hr::A");
+        }
+    }
+
 }
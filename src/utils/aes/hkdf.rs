@@ -0,0 +1,103 @@
+//! HKDF (RFC 5869) instantiated with HMAC-SHA3-256, using the `hmac` and `hkdf` crates.
+use sha3::Sha3_256;
+use hmac::{Hmac, Mac, NewMac};
+use hkdf::Hkdf;
+
+/// The output size of SHA3-256 in bytes
+pub const HASH_SIZE: usize = 32;
+
+/// HMAC-SHA3-256
+///
+/// # Arguments
+///
+///	* `_key` - The HMAC key
+///	* `_data` - The authenticated data
+///
+pub fn hmac_sha3_256(_key: &[u8], _data: &[u8]) -> [u8; HASH_SIZE] {
+    // HMAC accepts keys of any length
+    let mut _hmac = Hmac::<Sha3_256>::new_varkey(_key).unwrap();
+    _hmac.update(_data);
+    let mut _mac = [0u8; HASH_SIZE];
+    _mac.copy_from_slice(&_hmac.finalize().into_bytes());
+    _mac
+}
+
+/// HKDF-Extract: derives a pseudorandom key from the input keying material
+///
+/// # Arguments
+///
+///	* `_salt` - A non-secret salt
+///	* `_ikm` - The input keying material
+///
+pub fn extract(_salt: &[u8], _ikm: &[u8]) -> [u8; HASH_SIZE] {
+    let (_prk, _) = Hkdf::<Sha3_256>::extract(Some(_salt), _ikm);
+    let mut _bytes = [0u8; HASH_SIZE];
+    _bytes.copy_from_slice(&_prk);
+    _bytes
+}
+
+/// HKDF-Expand: expands a pseudorandom key to `_length` bytes bound to `_info`
+///
+/// # Arguments
+///
+///	* `_prk` - A pseudorandom key, generated by extract()
+///	* `_info` - The context the output is bound to
+///	* `_length` - The output length in bytes, at most 255 * 32
+///
+pub fn expand(_prk: &[u8; HASH_SIZE], _info: &[u8], _length: usize) -> Vec<u8> {
+    let mut _okm = vec![0u8; _length];
+    Hkdf::<Sha3_256>::from_prk(_prk)
+        .unwrap()
+        .expand(_info, &mut _okm)
+        .expect("hkdf: output too long");
+    _okm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(_bytes: &[u8]) -> String {
+        _bytes.iter().map(|_byte| format!("{:02x}", _byte)).collect()
+    }
+
+    #[test]
+    fn nist_hmac_sha3_256() {
+        // the HMAC-SHA3-256 examples of NIST (csrc.nist.gov, "Cryptographic Standards and
+        // Guidelines: Examples with Intermediate Values"), for keys shorter than, as long as and
+        // longer than the block size of 136 bytes
+        let _key: Vec<u8> = (0x00u8..=0xa7).collect();
+        assert_eq!(
+            hex(&hmac_sha3_256(&_key[..0x20], b"Sample message for keylen<blocklen")),
+            "4fe8e202c4f058e8dddc23d8c34e467343e23555e24fc2f025d598f558f67205"
+        );
+        assert_eq!(
+            hex(&hmac_sha3_256(&_key[..0x88], b"Sample message for keylen=blocklen")),
+            "68b94e2e538a9be4103bebb5aa016d47961d4d1aa906061313b557f8af2c3faa"
+        );
+        assert_eq!(
+            hex(&hmac_sha3_256(&_key, b"Sample message for keylen>blocklen")),
+            "9bcf2c238e235c3ce88404e813bd2f3a97185ac6f238c63d6229a00b07974258"
+        );
+    }
+
+    #[test]
+    fn rfc5869_inputs() {
+        // the inputs of RFC 5869 test case 1, the outputs computed with Python's hmac and
+        // hashlib.sha3_256
+        let _ikm = [0x0bu8; 22];
+        let _salt: Vec<u8> = (0x00u8..0x0d).collect();
+        let _info: Vec<u8> = (0xf0u8..0xfa).collect();
+        let _prk = extract(&_salt, &_ikm);
+        assert_eq!(hex(&_prk), "7d4194836f7a113a44677abc825640ade07af1c1d69a9a4b109b280a8fe54ef0");
+        assert_eq!(
+            hex(&expand(&_prk, &_info, 42)),
+            "0c5160501d65021deaf2c14f5abce04c5bd2635abceeba61c2edb6e8ed72674900557728f2c9f2c4c179"
+        );
+        // keys longer than a block are hashed first
+        assert_eq!(
+            hex(&hmac_sha3_256(&[b'k'; 200], b"data")),
+            "5e2cc697c8296e59f4328b1bc437bd4b7710b829d7a500fab14b88925bfcd595"
+        );
+    }
+}
@@ -5,51 +5,409 @@
 
 use sha3::{Sha3_256, Digest};
 use aes::Aes256;
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::ChaCha20Poly1305;
 use ccm::{self, aead::{NewAead, AeadInPlace}};
-use ccm::aead::generic_array::GenericArray;
+use ccm::aead::generic_array::typenum::Unsigned;
 
-use rand::{Rng, thread_rng};
-use std::convert::TryInto;
+use rabe_bn::{G1, G2, Gt};
+use rand::{RngCore, SeedableRng, thread_rng};
+use rand_chacha::ChaCha20Rng;
+use std::{fmt, str::FromStr};
+use schemes::SchemeId;
+use utils::hash::{encode_fields, g1_to_bytes, g2_to_bytes, gt_to_bytes};
+use utils::policy::pest::PolicyLanguage;
 use RabeError;
 
+pub mod hkdf;
+pub mod stream;
+
+/// The key derivation used for the symmetric encryption, recorded in ciphertexts.
+///
+/// Ciphertexts serialized before the KDF was recorded deserialize as `Legacy`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum KdfVersion {
+    /// SHA3-256 of the `Display` string of the Gt element, see kdf()
+    #[default]
+    Legacy,
+    /// HKDF-SHA3-256 of the canonical encoding of the Gt element, see hkdf_key()
+    Hkdf,
+    /// Hkdf, and the ciphertext header is authenticated as associated data, see associated_data()
+    HkdfAad,
+}
+
+impl KdfVersion {
+    /// The KDF used for new ciphertexts
+    pub const CURRENT: KdfVersion = KdfVersion::HkdfAad;
+}
+
+/// The AEAD that encrypts the plaintext under the derived key, recorded in ciphertexts.
+///
+/// All suites use a 256 bit key and a 16 byte tag and store a random nonce in front of the
+/// ciphertext. Ciphertexts serialized before the suite was recorded deserialize as `Aes256Ccm`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum AeadSuite {
+    /// AES-256-CCM with a 13 byte nonce
+    #[default]
+    Aes256Ccm,
+    /// AES-256-GCM with a 12 byte nonce
+    Aes256Gcm,
+    /// ChaCha20-Poly1305 (RFC 8439) with a 12 byte nonce
+    ChaCha20Poly1305,
+}
+
+impl AeadSuite {
+    /// All supported suites
+    pub fn all() -> Vec<AeadSuite> {
+        vec![AeadSuite::Aes256Ccm, AeadSuite::Aes256Gcm, AeadSuite::ChaCha20Poly1305]
+    }
+
+    /// The name of the suite, as used by the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            AeadSuite::Aes256Ccm => "AES-256-CCM",
+            AeadSuite::Aes256Gcm => "AES-256-GCM",
+            AeadSuite::ChaCha20Poly1305 => "CHACHA20-POLY1305",
+        }
+    }
+
+    /// The size of the nonce in bytes
+    pub fn nonce_size(&self) -> usize {
+        match self {
+            AeadSuite::Aes256Ccm => <Aes256Ccm as AeadInPlace>::NonceSize::to_usize(),
+            AeadSuite::Aes256Gcm => <Aes256Gcm as AeadInPlace>::NonceSize::to_usize(),
+            AeadSuite::ChaCha20Poly1305 => <ChaCha20Poly1305 as AeadInPlace>::NonceSize::to_usize(),
+        }
+    }
+
+    /// Encrypts `buffer` in place and appends the tag, `nonce` must have nonce_size() bytes
+    fn encrypt_in_place(&self, key: &[u8; 32], nonce: &[u8], aad: &[u8], buffer: &mut Vec<u8>) -> Result<(), RabeError> {
+        match self {
+            AeadSuite::Aes256Ccm => encrypt_in_place::<Aes256Ccm>(key, nonce, aad, buffer),
+            AeadSuite::Aes256Gcm => encrypt_in_place::<Aes256Gcm>(key, nonce, aad, buffer),
+            AeadSuite::ChaCha20Poly1305 => encrypt_in_place::<ChaCha20Poly1305>(key, nonce, aad, buffer),
+        }
+    }
+
+    /// Verifies the tag at the end of `buffer` and decrypts it in place
+    fn decrypt_in_place(&self, key: &[u8; 32], nonce: &[u8], aad: &[u8], buffer: &mut Vec<u8>) -> Result<(), RabeError> {
+        match self {
+            AeadSuite::Aes256Ccm => decrypt_in_place::<Aes256Ccm>(key, nonce, aad, buffer),
+            AeadSuite::Aes256Gcm => decrypt_in_place::<Aes256Gcm>(key, nonce, aad, buffer),
+            AeadSuite::ChaCha20Poly1305 => decrypt_in_place::<ChaCha20Poly1305>(key, nonce, aad, buffer),
+        }
+    }
+
+    /// Encrypts under a fresh random nonce, which is prepended to the ciphertext
+    fn seal(&self, key: &[u8; 32], aad: &[u8], _plaintext: &[u8]) -> Result<Vec<u8>, RabeError> {
+        let mut iv = vec![0u8; self.nonce_size()];
+        thread_rng().fill_bytes(&mut iv);
+        let mut res: Vec<u8> = _plaintext.to_vec();
+        self.encrypt_in_place(key, &iv, aad, &mut res)?;
+        res.splice(0..0, iv.iter().cloned()); // add IV at the beginning
+        Ok(res)
+    }
+
+    /// Decrypts a ciphertext produced by seal()
+    fn open(&self, key: &[u8; 32], aad: &[u8], _iv_ct: &[u8]) -> Result<Vec<u8>, RabeError> {
+        let _iv_len = self.nonce_size();
+        if _iv_ct.len() < _iv_len {
+            return Err(RabeError::new(&format!("Error extracting IV from ciphertext: Expected an IV of {} bytes", _iv_len)));
+        }
+        let mut data = _iv_ct[_iv_len..].to_vec();
+        self.decrypt_in_place(key, &_iv_ct[.._iv_len], aad, &mut data)?;
+        Ok(data)
+    }
+}
+
+impl fmt::Display for AeadSuite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for AeadSuite {
+    type Err = RabeError;
+
+    fn from_str(s: &str) -> Result<AeadSuite, RabeError> {
+        AeadSuite::all()
+            .into_iter()
+            .find(|suite| suite.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| RabeError::new(&format!("unknown AEAD suite {}", s)))
+    }
+}
+
+//  key length 256 bit,  tag size 16 byte,  nonce size 13 bytes
+type Aes256Ccm = ccm::Ccm<Aes256, ccm::consts::U16, ccm::consts::U13>;
+
+/// A 256 bit symmetric key, e.g. the key returned by the encapsulate() function of a scheme.
+///
+/// The key is derived from the secret Gt element of the scheme, see derive().
+#[derive(Clone)]
+pub struct SymmetricKey([u8; 32]);
+
+impl SymmetricKey {
+    /// Derives the key from a secret Gt element with the given KDF, see hkdf_key()
+    ///
+    /// # Arguments
+    ///
+    ///	* `_kdf` - The KDF, e.g. KdfVersion::CURRENT
+    ///	* `_msg` - The secret Gt element
+    ///	* `_scheme` - The scheme that produces the ciphertext
+    ///	* `_header` - The public ciphertext header, see policy_header() and attribute_header()
+    ///
+    pub fn derive(_kdf: KdfVersion, _msg: &Gt, _scheme: SchemeId, _header: &[u8]) -> SymmetricKey {
+        let _key = match _kdf {
+            KdfVersion::Legacy => kdf(_msg),
+            KdfVersion::Hkdf | KdfVersion::HkdfAad => hkdf_key(_kdf, _msg, _scheme, _header),
+        };
+        SymmetricKey(_key)
+    }
+
+    /// Wraps 32 bytes of key material
+    pub fn from_bytes(_bytes: &[u8; 32]) -> SymmetricKey {
+        SymmetricKey(*_bytes)
+    }
+
+    /// The key material
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    fn as_array(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SymmetricKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SymmetricKey(..)")
+    }
+}
+
+/// Key Encapsulation Mechanism (Encryption Function), using the current KDF
+///
+/// # Arguments
+///
+///	* `_msg` - The secret Gt element the key is derived from
+///	* `_scheme` - The scheme that produces the ciphertext, used as HKDF salt
+///	* `_suite` - The AEAD that encrypts the plaintext
+///	* `_header` - The public ciphertext header (e.g. its policy), used as HKDF info, see policy_header() and attribute_header()
+///	* `_elements` - The public group elements of the ciphertext, see HeaderEncoder
+///	* `_plaintext` - The plaintext data
+///
+pub fn encrypt_symmetric_gt(_msg: &Gt, _scheme: SchemeId, _suite: AeadSuite, _header: &[u8], _elements: &[u8], _plaintext: &[u8]) -> Result<Vec<u8>, RabeError> {
+    let _key = SymmetricKey::derive(KdfVersion::CURRENT, _msg, _scheme, _header);
+    encrypt_symmetric_key(&_key, _scheme, _suite, _header, _elements, _plaintext)
+}
+
+/// Key Encapsulation Mechanism (Decryption Function), using the KDF recorded in the ciphertext
+///
+/// # Arguments
+///
+///	* `_kdf` - The KDF the ciphertext was produced with
+///	* `_msg` - The secret Gt element the key is derived from
+///	* `_scheme` - The scheme that produced the ciphertext
+///	* `_suite` - The AEAD the ciphertext was produced with
+///	* `_header` - The public ciphertext header, as given to encrypt_symmetric_gt()
+///	* `_elements` - The public group elements of the ciphertext, as given to encrypt_symmetric_gt()
+///	* `_iv_ct` - The IV and the symmetric ciphertext
+///
+pub fn decrypt_symmetric_gt(_kdf: KdfVersion, _msg: &Gt, _scheme: SchemeId, _suite: AeadSuite, _header: &[u8], _elements: &[u8], _iv_ct: &Vec<u8>) -> Result<Vec<u8>, RabeError> {
+    let _key = SymmetricKey::derive(_kdf, _msg, _scheme, _header);
+    decrypt_symmetric_key(_kdf, &_key, _scheme, _suite, _header, _elements, _iv_ct)
+}
+
+/// Encrypts the plaintext under a key derived with the current KDF, e.g. by the encapsulate()
+/// function of a scheme, and authenticates the ciphertext header as associated data.
+///
+/// # Arguments
+///
+///	* `_key` - The symmetric key
+///	* `_scheme` - The scheme that produces the ciphertext
+///	* `_suite` - The AEAD that encrypts the plaintext
+///	* `_header` - The public ciphertext header, see policy_header() and attribute_header()
+///	* `_elements` - The public group elements of the ciphertext, see HeaderEncoder
+///	* `_plaintext` - The plaintext data
+///
+pub fn encrypt_symmetric_key(_key: &SymmetricKey, _scheme: SchemeId, _suite: AeadSuite, _header: &[u8], _elements: &[u8], _plaintext: &[u8]) -> Result<Vec<u8>, RabeError> {
+    let _aad = associated_data(_scheme, _suite, _header, _elements);
+    _suite.seal(_key.as_array(), &_aad, _plaintext)
+}
+
+/// Decrypts a ciphertext produced by encrypt_symmetric_key(), or by an earlier version with the given KDF
+///
+/// # Arguments
+///
+///	* `_kdf` - The KDF the key was derived with
+///	* `_key` - The symmetric key
+///	* `_scheme` - The scheme that produced the ciphertext
+///	* `_suite` - The AEAD the ciphertext was produced with
+///	* `_header` - The public ciphertext header, as given to encrypt_symmetric_key()
+///	* `_elements` - The public group elements of the ciphertext, as given to encrypt_symmetric_key()
+///	* `_iv_ct` - The IV and the symmetric ciphertext
+///
+pub fn decrypt_symmetric_key(_kdf: KdfVersion, _key: &SymmetricKey, _scheme: SchemeId, _suite: AeadSuite, _header: &[u8], _elements: &[u8], _iv_ct: &[u8]) -> Result<Vec<u8>, RabeError> {
+    match _kdf {
+        KdfVersion::Legacy | KdfVersion::Hkdf => _suite.open(_key.as_array(), &[], _iv_ct),
+        KdfVersion::HkdfAad => _suite.open(_key.as_array(), &associated_data(_scheme, _suite, _header, _elements), _iv_ct),
+    }
+}
+
+/// The associated data authenticated by the AEAD: the scheme, the suite, the header and the
+/// public group elements of the ciphertext. Any change to them makes the decryption fail.
+///
+/// # Arguments
+///
+///	* `_scheme` - The scheme that produces the ciphertext
+///	* `_suite` - The AEAD that encrypts the plaintext
+///	* `_header` - The public ciphertext header, see policy_header() and attribute_header()
+///	* `_elements` - The public group elements of the ciphertext, see HeaderEncoder
+///
+pub fn associated_data(_scheme: SchemeId, _suite: AeadSuite, _header: &[u8], _elements: &[u8]) -> Vec<u8> {
+    encode_fields("rabe/aad", &[_scheme.name().as_bytes(), _suite.name().as_bytes(), _header, _elements])
+}
+
+/// Canonically encodes the public group elements of a ciphertext, in the order they are added.
+///
+/// Every element is a length-prefixed field, see encode_fields(). Lists are preceded by their
+/// length, so that no two ciphertexts of a scheme share an encoding.
+#[derive(Default)]
+pub struct HeaderEncoder {
+    _fields: Vec<Vec<u8>>,
+}
+
+impl HeaderEncoder {
+    pub fn new() -> HeaderEncoder {
+        HeaderEncoder::default()
+    }
+
+    /// adds the length of the list that follows
+    pub fn count(&mut self, _len: usize) -> &mut HeaderEncoder {
+        self._fields.push((_len as u64).to_be_bytes().to_vec());
+        self
+    }
+
+    /// adds a string, e.g. an attribute
+    pub fn str(&mut self, _str: &str) -> &mut HeaderEncoder {
+        self._fields.push(_str.as_bytes().to_vec());
+        self
+    }
+
+    /// adds an element of G1, see g1_to_bytes()
+    pub fn g1(&mut self, _g: &G1) -> &mut HeaderEncoder {
+        self._fields.push(g1_to_bytes(_g));
+        self
+    }
+
+    /// adds an element of G2, see g2_to_bytes()
+    pub fn g2(&mut self, _g: &G2) -> &mut HeaderEncoder {
+        self._fields.push(g2_to_bytes(_g));
+        self
+    }
+
+    /// adds an element of Gt, see gt_to_bytes()
+    pub fn gt(&mut self, _g: &Gt) -> &mut HeaderEncoder {
+        self._fields.push(gt_to_bytes(_g));
+        self
+    }
+
+    /// the encoding of all elements added so far
+    pub fn finish(&self) -> Vec<u8> {
+        let _fields: Vec<&[u8]> = self._fields.iter().map(|_field| _field.as_slice()).collect();
+        encode_fields("rabe/header/elements", &_fields)
+    }
+}
+
+/// Derives an AES-256 key with HKDF-SHA3-256 from the canonical encoding of a Gt element.
+/// The salt binds the scheme and, from HkdfAad on, the KDF version, so that a ciphertext whose
+/// recorded KDF is downgraded derives another key. The info binds the ciphertext header.
+///
+/// # Arguments
+///
+///	* `_kdf` - The KDF version, Hkdf or HkdfAad
+///	* `_msg` - The secret Gt element
+///	* `_scheme` - The scheme that produces the ciphertext
+///	* `_header` - The public ciphertext header
+///
+pub fn hkdf_key(_kdf: KdfVersion, _msg: &Gt, _scheme: SchemeId, _header: &[u8]) -> [u8; 32] {
+    let _salt = match _kdf {
+        // the salt of ciphertexts produced before the KDF version was bound
+        KdfVersion::Legacy | KdfVersion::Hkdf => encode_fields("rabe/kdf/salt", &[_scheme.name().as_bytes()]),
+        KdfVersion::HkdfAad => encode_fields("rabe/kdf/salt", &[_scheme.name().as_bytes(), b"HkdfAad"]),
+    };
+    let _prk = hkdf::extract(&_salt, &gt_to_bytes(_msg));
+    let mut _key = [0u8; 32];
+    _key.copy_from_slice(&hkdf::expand(&_prk, _header, 32));
+    _key
+}
+
+/// The random number generator of the Fujisaki-Okamoto transform. All randomness of a CCA2-secure
+/// encapsulation is drawn from it, so that decapsulation can repeat the encapsulation of the
+/// recovered message, see `schemes::CpAbeCca`.
+///
+/// # Arguments
+///
+///	* `_msg` - The secret Gt element
+///	* `_scheme` - The scheme that produces the ciphertext
+///	* `_header` - The public ciphertext header
+///
+pub fn fo_rng(_msg: &Gt, _scheme: SchemeId, _header: &[u8]) -> ChaCha20Rng {
+    let _salt = encode_fields("rabe/fo/salt", &[_scheme.name().as_bytes()]);
+    let _prk = hkdf::extract(&_salt, &gt_to_bytes(_msg));
+    let mut _seed = [0u8; 32];
+    _seed.copy_from_slice(&hkdf::expand(&_prk, _header, 32));
+    ChaCha20Rng::from_seed(_seed)
+}
+
+/// The header of a ciphertext that is bound to an access policy
+pub fn policy_header(_policy: &str, _language: PolicyLanguage) -> Vec<u8> {
+    let _language: &[u8] = match _language {
+        PolicyLanguage::JsonPolicy => b"json",
+        PolicyLanguage::HumanPolicy => b"human",
+    };
+    encode_fields("rabe/header/policy", &[_policy.as_bytes(), _language])
+}
+
+/// The header of a ciphertext that is bound to a set of attributes
+pub fn attribute_header(_attributes: &[String]) -> Vec<u8> {
+    let _fields: Vec<&[u8]> = _attributes.iter().map(|_attribute| _attribute.as_bytes()).collect();
+    encode_fields("rabe/header/attributes", &_fields)
+}
+
 /// Key Encapsulation Mechanism (Encryption Function)
+///
+/// Deprecated: the key is derived from the `Display` string of `_msg`, use encrypt_symmetric_gt()
 pub fn encrypt_symmetric<T: std::fmt::Display>(_msg: &T, _plaintext: &Vec<u8>) -> Result<Vec<u8>, RabeError> {
-    let mut rng = thread_rng();
-    let key = kdf(_msg);
-    let iv: [u8; 13] = rng.gen();
+    AeadSuite::Aes256Ccm.seal(&kdf(_msg), &[], _plaintext)
+}
 
-    //  key length 256 bit,  tag size 16 byte,  nonce size 13 bytes
-    let ccm: ccm::Ccm<Aes256, ccm::consts::U16, ccm::consts::U13> = ccm::Ccm::new(&key);
-    let mut res: Vec<u8> = _plaintext.clone();
-    ccm.encrypt_in_place(&GenericArray::from(iv), &[], &mut res)?;
-    res.splice(0..0, iv.iter().cloned()); // add IV at the beginning
-    Ok(res)
+fn encrypt_in_place<A: NewAead<KeySize = ccm::consts::U32> + AeadInPlace>(key: &[u8; 32], nonce: &[u8], aad: &[u8], buffer: &mut Vec<u8>) -> Result<(), RabeError> {
+    Ok(A::new(key.into()).encrypt_in_place(nonce.into(), aad, buffer)?)
 }
 
 /// Key Encapsulation Mechanism (Decryption Function)
+///
+/// Only needed for legacy ciphertexts, see decrypt_symmetric_gt()
 pub fn decrypt_symmetric<T: std::fmt::Display>(_msg: &T, _iv_ct: &Vec<u8>) -> Result<Vec<u8>, RabeError> {
-    let mut data = _iv_ct.clone().split_off(13);
-    let iv: [u8; 13] = match _iv_ct[..13].try_into() {
-        Ok(iv) => iv,
-        Err(_) => return Err(RabeError{ details: String::from("Error extracting IV from ciphertext: Expected an IV of 13 bytes")}), // this REALLY shouldn't happen.
-    };
-    let key = kdf(_msg);
+    AeadSuite::Aes256Ccm.open(&kdf(_msg), &[], _iv_ct)
+}
 
-    let ccm: ccm::Ccm<Aes256, ccm::consts::U16, ccm::consts::U13> = ccm::Ccm::new(&key);
-    ccm.decrypt_in_place(&GenericArray::from(iv), &[], &mut data)?;
-    Ok(data)
+fn decrypt_in_place<A: NewAead<KeySize = ccm::consts::U32> + AeadInPlace>(key: &[u8; 32], nonce: &[u8], aad: &[u8], buffer: &mut Vec<u8>) -> Result<(), RabeError> {
+    Ok(A::new(key.into()).decrypt_in_place(nonce.into(), aad, buffer)?)
 }
 
 /// Key derivation function - turns anything implementing the `Display` trait into a key for AES-256
-fn kdf<G: std::fmt::Display>(inp: &G) -> GenericArray<u8, ccm::consts::U32> {
+fn kdf<G: std::fmt::Display>(inp: &G) -> [u8; 32] {
     let mut hasher = Sha3_256::new();
     hasher.update(inp.to_string().into_bytes());
-    hasher.finalize()
+    hasher.finalize().into()
 }
 
-#[cfg(tests)]
+#[cfg(test)]
 mod tests {
     use super::*;
+    use rand::Rng;
     #[test]
     fn correctness_test() {
         let key = "7h15 15 4 v3ry 53cr37 k3y";
@@ -58,4 +416,40 @@ mod tests {
         let ciphertext = encrypt_symmetric(&key, &plaintext).unwrap();
         assert_eq!(decrypt_symmetric(&key, &ciphertext).unwrap(), plaintext);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn legacy_ciphertext() {
+        // produced by encrypt_symmetric() of rabe 0.2.6, before the KDF was recorded
+        let key = "7h15 15 4 v3ry 53cr37 k3y";
+        let plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let _hex = "aef66341495b5481aded3ce5ad7fd0cb10d39dfae111d24a40628aedf7c877223afde2db96137c614cb464f61c0750ff508e73c3fa563022c10454a0076a36d5e1978c1f702320730691ac940697e2492be0f8ff";
+        let mut ciphertext: Vec<u8> = (0.._hex.len()).step_by(2).map(|_i| u8::from_str_radix(&_hex[_i.._i + 2], 16).unwrap()).collect();
+        assert_eq!(decrypt_symmetric(&key, &ciphertext).unwrap(), plaintext);
+        // the legacy KDF does not authenticate associated data
+        let _key = SymmetricKey::from_bytes(&kdf(&key));
+        assert_eq!(decrypt_symmetric_key(KdfVersion::Legacy, &_key, SchemeId::Bsw, AeadSuite::Aes256Ccm, b"any", b"header", &ciphertext).unwrap(), plaintext);
+        assert!(decrypt_symmetric(&"another key", &ciphertext).is_err());
+        let _last = ciphertext.len() - 1;
+        ciphertext[_last] ^= 1;
+        assert!(decrypt_symmetric(&key, &ciphertext).is_err());
+    }
+
+    #[test]
+    fn kdf_downgrade() {
+        let _msg: Gt = rand::thread_rng().gen();
+        let plaintext = String::from("our plaintext!").into_bytes();
+        let _header = attribute_header(&[String::from("A")]);
+        let _ct = encrypt_symmetric_gt(&_msg, SchemeId::Bsw, AeadSuite::Aes256Gcm, &_header, b"elements", &plaintext).unwrap();
+        assert_eq!(decrypt_symmetric_gt(KdfVersion::HkdfAad, &_msg, SchemeId::Bsw, AeadSuite::Aes256Gcm, &_header, b"elements", &_ct).unwrap(), plaintext);
+        // the recorded KDF is not authenticated, but a downgraded KDF derives another key
+        let _aad = hkdf_key(KdfVersion::HkdfAad, &_msg, SchemeId::Bsw, &_header);
+        let _no_aad = hkdf_key(KdfVersion::Hkdf, &_msg, SchemeId::Bsw, &_header);
+        assert_ne!(_aad, _no_aad);
+        assert!(decrypt_symmetric_gt(KdfVersion::Hkdf, &_msg, SchemeId::Bsw, AeadSuite::Aes256Gcm, &_header, b"elements", &_ct).is_err());
+        // a ciphertext without associated data cannot be passed off as an HkdfAad ciphertext
+        let _ct = AeadSuite::Aes256Gcm.seal(&_no_aad, &[], &plaintext).unwrap();
+        assert_eq!(decrypt_symmetric_gt(KdfVersion::Hkdf, &_msg, SchemeId::Bsw, AeadSuite::Aes256Gcm, &_header, b"other", &_ct).unwrap(), plaintext);
+        assert!(decrypt_symmetric_gt(KdfVersion::HkdfAad, &_msg, SchemeId::Bsw, AeadSuite::Aes256Gcm, &_header, b"elements", &_ct).is_err());
+    }
+}
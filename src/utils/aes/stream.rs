@@ -0,0 +1,333 @@
+//! Chunked encryption of large plaintexts in constant memory.
+//!
+//! The plaintext is split into chunks of `CHUNK_SIZE` bytes, which are encrypted one by one
+//! with an `AeadSuite` (the STREAM construction of Hoang, Reyhanitabar, Rogaway and Vizár).
+//! The nonce of every chunk consists of a random prefix, the index of the chunk and a flag
+//! that marks the last chunk, so reordered, duplicated and truncated streams are detected.
+//!
+//! A stream starts with the nonce prefix, followed by the encrypted chunks. Every chunk but
+//! the last one has `CHUNK_SIZE + TAG_SIZE` bytes; the last one may be empty, but is always present.
+//!
+//! The ABE traits wrap this in `encrypt_stream()` and `decrypt_stream()`: a random key is
+//! encapsulated once with the scheme and written in front of the stream, see write_header().
+use std::io::{self, Read, Write};
+use rand::{RngCore, thread_rng};
+use serde::{Serialize, de::DeserializeOwned};
+use super::AeadSuite;
+use RabeError;
+
+/// The size of a plaintext chunk in bytes. AES-256-CCM with a 13 byte nonce encrypts at most 2^16 - 1 bytes.
+pub const CHUNK_SIZE: usize = 32 * 1024;
+/// The size of the tag appended to every chunk in bytes
+pub const TAG_SIZE: usize = 16;
+/// The size of the stream key in bytes
+pub const KEY_SIZE: usize = 32;
+/// The bytes a stream produced by the ABE traits starts with
+pub const MAGIC: &[u8; 8] = b"RABESTRM";
+/// The upper bound of the encapsulation header, to not allocate arbitrary amounts of memory
+const MAX_HEADER_SIZE: usize = 16 * 1024 * 1024;
+
+/// The key encapsulation in front of a stream
+#[derive(Serialize, Deserialize)]
+struct StreamHeader<C> {
+    _suite: AeadSuite,
+    _ct: C,
+}
+
+/// Writes the header of an ABE stream: `MAGIC`, the length of the header as u32 (big endian)
+/// and the CBOR encoded suite and ciphertext of the encapsulated stream key.
+///
+/// # Arguments
+///
+///	* `_output` - The writer the stream is written to
+///	* `_ct` - An ABE ciphertext of the stream key
+///	* `_suite` - The AEAD that encrypts the chunks
+///
+pub fn write_header<W: Write, C: Serialize>(_output: &mut W, _ct: &C, _suite: AeadSuite) -> Result<(), RabeError> {
+    let _header = serde_cbor::to_vec(&StreamHeader { _suite, _ct })?;
+    if _header.len() > MAX_HEADER_SIZE {
+        return Err(RabeError::new("stream header too large"));
+    }
+    _output.write_all(MAGIC)?;
+    _output.write_all(&(_header.len() as u32).to_be_bytes())?;
+    _output.write_all(&_header)?;
+    Ok(())
+}
+
+/// Reads the header written by write_header() and returns the ciphertext of the stream key and the suite.
+///
+/// # Arguments
+///
+///	* `_input` - The reader the stream is read from
+///
+pub fn read_header<R: Read, C: DeserializeOwned>(_input: &mut R) -> Result<(C, AeadSuite), RabeError> {
+    let mut _magic = [0u8; 8];
+    _input.read_exact(&mut _magic)?;
+    if &_magic != MAGIC {
+        return Err(RabeError::new("not a rabe stream"));
+    }
+    let mut _len = [0u8; 4];
+    _input.read_exact(&mut _len)?;
+    let _len = u32::from_be_bytes(_len) as usize;
+    if _len > MAX_HEADER_SIZE {
+        return Err(RabeError::new("stream header too large"));
+    }
+    let mut _header = vec![0u8; _len];
+    _input.read_exact(&mut _header)?;
+    let _header: StreamHeader<C> = serde_cbor::from_slice(&_header)?;
+    Ok((_header._ct, _header._suite))
+}
+
+/// Generates a random stream key
+pub fn generate_key() -> [u8; KEY_SIZE] {
+    let mut _key = [0u8; KEY_SIZE];
+    thread_rng().fill_bytes(&mut _key);
+    _key
+}
+
+/// The nonces of a stream: prefix || index (u32, big endian) || last chunk flag
+struct Nonces {
+    _prefix: Vec<u8>,
+    _index: u32,
+}
+
+impl Nonces {
+    fn next(&mut self, _last: bool) -> io::Result<Vec<u8>> {
+        let mut _nonce = self._prefix.clone();
+        _nonce.extend_from_slice(&self._index.to_be_bytes());
+        _nonce.push(_last as u8);
+        self._index = self._index.checked_add(1).ok_or_else(|| invalid_data("stream has too many chunks"))?;
+        Ok(_nonce)
+    }
+}
+
+fn key_array(_key: &[u8]) -> io::Result<[u8; KEY_SIZE]> {
+    if _key.len() != KEY_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "stream key must have 32 bytes"));
+    }
+    let mut _array = [0u8; KEY_SIZE];
+    _array.copy_from_slice(_key);
+    Ok(_array)
+}
+
+fn invalid_data(_msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, _msg)
+}
+
+/// Encrypts everything written to it chunk by chunk to the inner writer.
+///
+/// finish() must be called after the last write, otherwise the stream is truncated
+/// and its decryption fails.
+pub struct EncryptWriter<W: Write> {
+    _inner: W,
+    _suite: AeadSuite,
+    _key: [u8; KEY_SIZE],
+    _nonces: Nonces,
+    _buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    /// Starts a new stream and writes its nonce prefix.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_inner` - The writer the encrypted stream is written to
+    ///	* `_key` - The stream key of KEY_SIZE bytes, never to be used for another stream
+    ///	* `_suite` - The AEAD that encrypts the chunks
+    ///
+    pub fn new(mut _inner: W, _key: &[u8], _suite: AeadSuite) -> io::Result<EncryptWriter<W>> {
+        let _key = key_array(_key)?;
+        let mut _prefix = vec![0u8; _suite.nonce_size() - 5];
+        thread_rng().fill_bytes(&mut _prefix);
+        _inner.write_all(&_prefix)?;
+        Ok(EncryptWriter {
+            _inner,
+            _suite,
+            _key,
+            _nonces: Nonces { _prefix, _index: 0 },
+            _buffer: Vec::with_capacity(CHUNK_SIZE + TAG_SIZE + 1),
+        })
+    }
+
+    /// Encrypts and writes the first `_len` bytes of the buffer as one chunk
+    fn write_chunk(&mut self, _len: usize, _last: bool) -> io::Result<()> {
+        let mut _chunk: Vec<u8> = self._buffer.drain(.._len).collect();
+        let _nonce = self._nonces.next(_last)?;
+        self._suite
+            .encrypt_in_place(&self._key, &_nonce, &[], &mut _chunk)
+            .map_err(|_e| io::Error::other(_e.to_string()))?;
+        self._inner.write_all(&_chunk)
+    }
+
+    /// Encrypts the remaining data as the last chunk and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self._buffer.len() > CHUNK_SIZE {
+            self.write_chunk(CHUNK_SIZE, false)?;
+        }
+        let _len = self._buffer.len();
+        self.write_chunk(_len, true)?;
+        self._inner.flush()?;
+        Ok(self._inner)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // a full chunk is only written once more data follows, the last chunk is written by finish()
+        if self._buffer.len() > CHUNK_SIZE {
+            self.write_chunk(CHUNK_SIZE, false)?;
+        }
+        let _len = buf.len().min(CHUNK_SIZE + 1 - self._buffer.len());
+        self._buffer.extend_from_slice(&buf[.._len]);
+        Ok(_len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self._inner.flush()
+    }
+}
+
+/// Decrypts a stream written by an `EncryptWriter` chunk by chunk from the inner reader.
+///
+/// Every chunk is authenticated before it is returned. A stream that ends before its last
+/// chunk results in an error of kind `InvalidData`, so all data read before that error
+/// must be discarded.
+pub struct DecryptReader<R: Read> {
+    _inner: R,
+    _suite: AeadSuite,
+    _key: [u8; KEY_SIZE],
+    _nonces: Nonces,
+    _chunk: Vec<u8>,
+    _position: usize,
+    _lookahead: Option<u8>,
+    _finished: bool,
+}
+
+impl<R: Read> DecryptReader<R> {
+    /// Reads the nonce prefix of a stream.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_inner` - The reader the encrypted stream is read from
+    ///	* `_key` - The stream key the stream was encrypted with
+    ///	* `_suite` - The AEAD the stream was encrypted with
+    ///
+    pub fn new(mut _inner: R, _key: &[u8], _suite: AeadSuite) -> io::Result<DecryptReader<R>> {
+        let _key = key_array(_key)?;
+        let mut _prefix = vec![0u8; _suite.nonce_size() - 5];
+        _inner.read_exact(&mut _prefix)?;
+        Ok(DecryptReader {
+            _inner,
+            _suite,
+            _key,
+            _nonces: Nonces { _prefix, _index: 0 },
+            _chunk: Vec::new(),
+            _position: 0,
+            _lookahead: None,
+            _finished: false,
+        })
+    }
+
+    /// Reads and decrypts the next chunk. One byte is read ahead to find out whether it is the last one.
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let mut _sealed: Vec<u8> = Vec::with_capacity(CHUNK_SIZE + TAG_SIZE + 1);
+        _sealed.extend(self._lookahead.take());
+        let _missing = (CHUNK_SIZE + TAG_SIZE + 1 - _sealed.len()) as u64;
+        (&mut self._inner).take(_missing).read_to_end(&mut _sealed)?;
+        let _last = _sealed.len() <= CHUNK_SIZE + TAG_SIZE;
+        if !_last {
+            self._lookahead = _sealed.pop();
+        }
+        if _sealed.len() < TAG_SIZE {
+            return Err(invalid_data("stream is truncated"));
+        }
+        let _nonce = self._nonces.next(_last)?;
+        self._suite
+            .decrypt_in_place(&self._key, &_nonce, &[], &mut _sealed)
+            .map_err(|_e| invalid_data("stream chunk could not be authenticated, it is corrupted, reordered or truncated"))?;
+        self._chunk = _sealed;
+        self._position = 0;
+        self._finished = _last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self._position == self._chunk.len() {
+            if self._finished {
+                return Ok(0);
+            }
+            self.read_chunk()?;
+        }
+        let _len = buf.len().min(self._chunk.len() - self._position);
+        buf[.._len].copy_from_slice(&self._chunk[self._position..self._position + _len]);
+        self._position += _len;
+        Ok(_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypt(_plaintext: &[u8], _key: &[u8], _suite: AeadSuite) -> Vec<u8> {
+        let mut _writer = EncryptWriter::new(Vec::new(), _key, _suite).unwrap();
+        // odd sized writes cross the chunk boundaries
+        for _part in _plaintext.chunks(5000) {
+            _writer.write_all(_part).unwrap();
+        }
+        _writer.finish().unwrap()
+    }
+
+    fn decrypt(_ciphertext: &[u8], _key: &[u8], _suite: AeadSuite) -> io::Result<Vec<u8>> {
+        let mut _plaintext = Vec::new();
+        DecryptReader::new(_ciphertext, _key, _suite)?.read_to_end(&mut _plaintext)?;
+        Ok(_plaintext)
+    }
+
+    #[test]
+    fn roundtrip() {
+        let _key = generate_key();
+        for _suite in AeadSuite::all() {
+            for _len in [0, 1, CHUNK_SIZE - 1, CHUNK_SIZE, CHUNK_SIZE + 1, 3 * CHUNK_SIZE] {
+                let _plaintext: Vec<u8> = (0.._len).map(|_i| _i as u8).collect();
+                let _ciphertext = encrypt(&_plaintext, &_key, _suite);
+                let _chunks = _len.div_ceil(CHUNK_SIZE).max(1);
+                assert_eq!(_ciphertext.len(), _suite.nonce_size() - 5 + _len + _chunks * TAG_SIZE);
+                assert_eq!(decrypt(&_ciphertext, &_key, _suite).unwrap(), _plaintext);
+            }
+        }
+    }
+
+    #[test]
+    fn tampering() {
+        let _key = generate_key();
+        let _suite = AeadSuite::ChaCha20Poly1305;
+        let _plaintext = vec![42u8; 2 * CHUNK_SIZE + 10];
+        let _ciphertext = encrypt(&_plaintext, &_key, _suite);
+        let _prefix = _suite.nonce_size() - 5;
+        let _sealed = CHUNK_SIZE + TAG_SIZE;
+        // truncated at a chunk boundary
+        let _truncated = &_ciphertext[.._prefix + 2 * _sealed];
+        assert_eq!(decrypt(_truncated, &_key, _suite).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        // truncated inside a chunk
+        assert!(decrypt(&_ciphertext[.._ciphertext.len() - 1], &_key, _suite).is_err());
+        // reordered chunks
+        let mut _reordered = _ciphertext[.._prefix].to_vec();
+        _reordered.extend_from_slice(&_ciphertext[_prefix + _sealed.._prefix + 2 * _sealed]);
+        _reordered.extend_from_slice(&_ciphertext[_prefix.._prefix + _sealed]);
+        _reordered.extend_from_slice(&_ciphertext[_prefix + 2 * _sealed..]);
+        assert!(decrypt(&_reordered, &_key, _suite).is_err());
+        // appended data
+        let mut _extended = _ciphertext.clone();
+        _extended.extend_from_slice(&[0u8; TAG_SIZE]);
+        assert!(decrypt(&_extended, &_key, _suite).is_err());
+        // a flipped bit
+        let mut _flipped = _ciphertext.clone();
+        _flipped[_prefix + 10] ^= 1;
+        assert!(decrypt(&_flipped, &_key, _suite).is_err());
+        // the wrong key
+        assert!(decrypt(&_ciphertext, &generate_key(), _suite).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! A PEM-style text armor for keys and ciphertexts.
+//!
+//! The armor wraps the binary encoding of `utils::wire` in base64, labelled with the kind of
+//! object and preceded by headers:
+//!
+//! ```text
+//! -----BEGIN SECRET KEY-----
+//! Version: 1
+//! Scheme: BSW
+//! Key-Id: 5f2c9a0e6b1d3e47
+//!
+//! UkFCRQEFAwAAAIE...
+//! =h3Tr
+//! -----END SECRET KEY-----
+//! ```
+//!
+//! * the label is derived from the `WireKind` of the object, see label()
+//! * `Version` and `Scheme` are required, `Key-Id` is the hex encoded id of the key the object
+//!   belongs to and is optional. Public keys and global parameters carry their own id, see key_id()
+//! * the base64 body may be wrapped at any width up to 76 characters, armor() wraps at 64
+//! * the last line of the body is `=` and the base64 encoded CRC-24 of the payload (RFC 4880)
+//!
+//! Parsing is strict: unknown or repeated headers, a mismatched END label, a wrong checksum,
+//! non-canonical base64 and any text before BEGIN or after END are rejected. The scheme and
+//! kind of the armor must match the header of the payload.
+//!
+//! # Examples
+//!
+//! ```
+//! use rabe::schemes::bsw::*;
+//! use rabe::utils::armor::{armor, dearmor};
+//! let (pk, _msk) = setup();
+//! let _text = armor(&pk);
+//! assert!(_text.starts_with("-----BEGIN PUBLIC KEY-----\nVersion: 1\nScheme: BSW\n"));
+//! assert!(dearmor::<CpAbePublicKey>(&_text).unwrap() == pk);
+//! ```
+use std::str::FromStr;
+use base64::{decode, encode};
+use sha3::{Sha3_256, Digest};
+use schemes::SchemeId;
+use utils::wire::{WireFormat, WireKind, wire_header};
+use RabeError;
+
+/// The current version of the armor
+pub const ARMOR_VERSION: u8 = 1;
+/// The width armor() wraps the base64 body at
+pub const ARMOR_WIDTH: usize = 64;
+/// The maximal width of a line of the base64 body
+pub const ARMOR_MAX_WIDTH: usize = 76;
+/// The length of a key id in bytes
+pub const KEY_ID_LENGTH: usize = 8;
+
+const CRC24_INIT: u32 = 0xB704CE;
+const CRC24_POLY: u32 = 0x1864CFB;
+
+/// An armored object: the wire encoding of a key or ciphertext and its headers
+#[derive(PartialEq, Clone, Debug)]
+pub struct Armor {
+    pub _kind: WireKind,
+    pub _scheme: SchemeId,
+    pub _key_id: Option<Vec<u8>>,
+    pub _payload: Vec<u8>,
+}
+
+/// The label of the BEGIN and END lines of a kind of object
+pub fn label(_kind: WireKind) -> &'static str {
+    match _kind {
+        WireKind::PublicKey => "PUBLIC KEY",
+        WireKind::MasterKey => "MASTER SECRET KEY",
+        WireKind::SecretKey => "SECRET KEY",
+        WireKind::Ciphertext => "CIPHERTEXT",
+        WireKind::Encapsulation => "ENCAPSULATION",
+        WireKind::GlobalKey => "GLOBAL PARAMETERS",
+        WireKind::AuthorityKey => "SECRET AUTHORITY",
+        WireKind::PublicAttributeKey => "PUBLIC ATTRIBUTE",
+        WireKind::SecretAttributeKey => "SECRET ATTRIBUTE KEY",
+        WireKind::PublicUserKey => "PUBLIC USER KEY",
+        WireKind::SecretUserKey => "SECRET USER KEY",
+    }
+}
+
+/// The id of a key: the first 8 bytes of the SHA3-256 hash of its wire encoding, which is its
+/// fingerprint for the keys that implement `utils::fingerprint::Fingerprint`
+///
+/// # Arguments
+///
+///	* `_key` - The public key or global parameters
+///
+pub fn key_id<K: WireFormat>(_key: &K) -> Vec<u8> {
+    Sha3_256::digest(&_key.to_bytes())[..KEY_ID_LENGTH].to_vec()
+}
+
+/// Armors an object, see Armor::new()
+///
+/// # Arguments
+///
+///	* `_value` - The key or ciphertext
+///
+pub fn armor<T: WireFormat>(_value: &T) -> String {
+    Armor::new(_value).encode()
+}
+
+/// Parses an armored object of type T
+///
+/// # Arguments
+///
+///	* `_text` - The armored object
+///
+pub fn dearmor<T: WireFormat>(_text: &str) -> Result<T, RabeError> {
+    Armor::decode(_text)?.value()
+}
+
+impl Armor {
+    /// Armors the wire encoding of an object. Public keys and global parameters carry their own key_id().
+    ///
+    /// # Arguments
+    ///
+    ///	* `_value` - The key or ciphertext
+    ///
+    pub fn new<T: WireFormat>(_value: &T) -> Armor {
+        let _key_id = match T::KIND {
+            WireKind::PublicKey | WireKind::GlobalKey => Some(key_id(_value)),
+            _ => None,
+        };
+        Armor {
+            _kind: T::KIND,
+            _scheme: T::SCHEME,
+            _key_id,
+            _payload: _value.to_bytes(),
+        }
+    }
+
+    /// Sets the id of the key the object belongs to
+    pub fn with_key_id(mut self, _key_id: Vec<u8>) -> Armor {
+        self._key_id = Some(_key_id);
+        self
+    }
+
+    /// Decodes the armored object, which must be of type T
+    pub fn value<T: WireFormat>(&self) -> Result<T, RabeError> {
+        if self._kind != T::KIND || self._scheme != T::SCHEME {
+            return Err(RabeError::new(&format!(
+                "armor: expected a {} {}, found a {} {}", T::SCHEME, label(T::KIND), self._scheme, label(self._kind)
+            )));
+        }
+        T::from_bytes(&self._payload)
+    }
+
+    /// The armored text, terminated by a newline
+    pub fn encode(&self) -> String {
+        let _label = label(self._kind);
+        let mut _text = format!("-----BEGIN {}-----\nVersion: {}\nScheme: {}\n", _label, ARMOR_VERSION, self._scheme);
+        if let Some(_key_id) = &self._key_id {
+            _text.push_str(&format!("Key-Id: {}\n", to_hex(_key_id)));
+        }
+        _text.push('\n');
+        let _body = encode(&self._payload);
+        for _line in _body.as_bytes().chunks(ARMOR_WIDTH) {
+            // base64 is ASCII
+            _text.push_str(std::str::from_utf8(_line).unwrap());
+            _text.push('\n');
+        }
+        _text.push_str(&format!("={}\n-----END {}-----\n", encode(&crc24(&self._payload).to_be_bytes()[1..]), _label));
+        _text
+    }
+
+    /// Parses an armored object
+    ///
+    /// # Arguments
+    ///
+    ///	* `_text` - The armored object, with `\n` or `\r\n` line endings
+    ///
+    pub fn decode(_text: &str) -> Result<Armor, RabeError> {
+        let _text = _text.strip_suffix('\n').unwrap_or(_text);
+        let _lines: Vec<&str> = _text.split('\n').map(|_line| _line.strip_suffix('\r').unwrap_or(_line)).collect();
+        if _lines.len() < 6 {
+            return Err(RabeError::new("armor: truncated"));
+        }
+        let _kind = parse_delimiter(_lines[0], "BEGIN")?;
+        if parse_delimiter(_lines[_lines.len() - 1], "END")? != _kind {
+            return Err(RabeError::new("armor: END label does not match BEGIN label"));
+        }
+        let mut _version: Option<u8> = None;
+        let mut _scheme: Option<SchemeId> = None;
+        let mut _key_id: Option<Vec<u8>> = None;
+        let mut _index = 1;
+        while !_lines[_index].is_empty() {
+            let (_name, _value) = split_header(_lines[_index])?;
+            let _duplicate = match _name {
+                "Version" => _version.replace(_value.parse().map_err(|_| RabeError::new("armor: invalid Version header"))?).is_some(),
+                "Scheme" => _scheme.replace(SchemeId::from_str(_value)?).is_some(),
+                "Key-Id" => _key_id.replace(from_hex(_value)?).is_some(),
+                _ => return Err(RabeError::new(&format!("armor: unknown header {}", _name))),
+            };
+            if _duplicate {
+                return Err(RabeError::new(&format!("armor: repeated header {}", _name)));
+            }
+            _index += 1;
+            // the empty line must be followed by at least one line of body and the checksum
+            if _index >= _lines.len() - 2 {
+                return Err(RabeError::new("armor: missing empty line or body after the headers"));
+            }
+        }
+        match _version {
+            Some(ARMOR_VERSION) => {},
+            Some(_version) => return Err(RabeError::new(&format!("armor: unsupported version {}", _version))),
+            None => return Err(RabeError::new("armor: missing Version header")),
+        }
+        let _scheme = _scheme.ok_or_else(|| RabeError::new("armor: missing Scheme header"))?;
+        // the body lies between the empty line and the checksum line
+        let _body = &_lines[_index + 1.._lines.len() - 2];
+        if _body.is_empty() || _body.iter().any(|_line| _line.is_empty() || _line.len() > ARMOR_MAX_WIDTH) {
+            return Err(RabeError::new("armor: invalid line length in body"));
+        }
+        let _body: String = _body.concat();
+        let _payload = decode(&_body)?;
+        if encode(&_payload) != _body {
+            return Err(RabeError::new("armor: non-canonical base64"));
+        }
+        let _checksum = _lines[_lines.len() - 2]
+            .strip_prefix('=')
+            .ok_or_else(|| RabeError::new("armor: missing checksum"))?;
+        if _checksum != encode(&crc24(&_payload).to_be_bytes()[1..]) {
+            return Err(RabeError::new("armor: checksum mismatch"));
+        }
+        if !_payload.starts_with(&wire_header(_scheme, _kind)) {
+            return Err(RabeError::new("armor: headers do not match the payload"));
+        }
+        Ok(Armor { _kind, _scheme, _key_id, _payload })
+    }
+}
+
+/// parses `-----BEGIN <label>-----` or `-----END <label>-----`
+fn parse_delimiter(_line: &str, _delimiter: &str) -> Result<WireKind, RabeError> {
+    let _label = _line
+        .strip_prefix("-----")
+        .and_then(|_line| _line.strip_prefix(_delimiter))
+        .and_then(|_line| _line.strip_prefix(' '))
+        .and_then(|_line| _line.strip_suffix("-----"))
+        .ok_or_else(|| RabeError::new(&format!("armor: expected a {} line", _delimiter)))?;
+    WireKind::all()
+        .into_iter()
+        .find(|_kind| label(*_kind) == _label)
+        .ok_or_else(|| RabeError::new(&format!("armor: unknown label {}", _label)))
+}
+
+/// splits a `Name: value` header line
+fn split_header(_line: &str) -> Result<(&str, &str), RabeError> {
+    let _colon = _line.find(": ").ok_or_else(|| RabeError::new("armor: invalid header line"))?;
+    Ok((&_line[.._colon], &_line[_colon + 2..]))
+}
+
+fn to_hex(_bytes: &[u8]) -> String {
+    _bytes.iter().map(|_byte| format!("{:02x}", _byte)).collect()
+}
+
+fn from_hex(_hex: &str) -> Result<Vec<u8>, RabeError> {
+    let _invalid = || RabeError::new("armor: invalid Key-Id header");
+    if _hex.is_empty() || !_hex.bytes().all(|_c| _c.is_ascii_digit() || (b'a'..=b'f').contains(&_c)) {
+        return Err(_invalid());
+    }
+    _hex.as_bytes()
+        .chunks(2)
+        .map(|_pair| match _pair {
+            [_high, _low] => Ok(((*_high as char).to_digit(16).unwrap() * 16 + (*_low as char).to_digit(16).unwrap()) as u8),
+            _ => Err(_invalid()),
+        })
+        .collect()
+}
+
+/// the CRC-24 checksum of OpenPGP's ASCII armor
+fn crc24(_data: &[u8]) -> u32 {
+    let mut _crc = CRC24_INIT;
+    for _byte in _data {
+        _crc ^= (*_byte as u32) << 16;
+        for _ in 0..8 {
+            _crc <<= 1;
+            if _crc & 0x1000000 != 0 {
+                _crc ^= CRC24_POLY;
+            }
+        }
+    }
+    _crc & 0xFFFFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemes::bsw::{setup, keygen, CpAbeMasterKey, CpAbePublicKey, CpAbeSecretKey};
+    use schemes::lsw::KpAbePublicKey;
+
+    fn secret_key() -> (CpAbePublicKey, CpAbeSecretKey) {
+        let (_pk, _msk) = setup();
+        let _sk = keygen(&_pk, &_msk, &vec!["A".to_string(), "B".to_string()]).unwrap();
+        (_pk, _sk)
+    }
+
+    #[test]
+    fn crc24_test_vector() {
+        assert_eq!(crc24(b""), CRC24_INIT);
+        assert_eq!(crc24(b"123456789"), 0x21CF02);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let (_pk, _sk) = secret_key();
+        let _text = Armor::new(&_sk).with_key_id(key_id(&_pk)).encode();
+        assert!(_text.starts_with("-----BEGIN SECRET KEY-----\nVersion: 1\nScheme: BSW\nKey-Id: "));
+        assert!(_text.ends_with("-----END SECRET KEY-----\n"));
+        let _armor = Armor::decode(&_text).unwrap();
+        assert_eq!(_armor._key_id, Some(key_id(&_pk)));
+        assert!(_armor.value::<CpAbeSecretKey>().unwrap() == _sk);
+        // public keys carry their own id
+        assert_eq!(Armor::decode(&armor(&_pk)).unwrap()._key_id, Some(key_id(&_pk)));
+        // CRLF line endings and a missing final newline
+        assert!(dearmor::<CpAbeSecretKey>(&_text.replace('\n', "\r\n")).unwrap() == _sk);
+        assert!(dearmor::<CpAbeSecretKey>(_text.trim_end()).unwrap() == _sk);
+    }
+
+    #[test]
+    fn wrapped_base64() {
+        let (_pk, _) = secret_key();
+        let _text = armor(&_pk);
+        let _lines: Vec<&str> = _text.lines().collect();
+        let _start = _lines.iter().position(|_line| _line.is_empty()).unwrap() + 1;
+        let _body: String = _lines[_start.._lines.len() - 2].concat();
+        for _width in [1, 17, ARMOR_MAX_WIDTH].iter() {
+            let mut _rewrapped: Vec<String> = _lines[.._start].iter().map(|_line| _line.to_string()).collect();
+            for _chunk in _body.as_bytes().chunks(*_width) {
+                _rewrapped.push(String::from_utf8(_chunk.to_vec()).unwrap());
+            }
+            _rewrapped.extend(_lines[_lines.len() - 2..].iter().map(|_line| _line.to_string()));
+            assert!(dearmor::<CpAbePublicKey>(&_rewrapped.join("\n")).unwrap() == _pk);
+        }
+        // lines longer than the maximal width are rejected
+        let mut _long: Vec<String> = _lines[.._start].iter().map(|_line| _line.to_string()).collect();
+        _long.push(_body);
+        _long.extend(_lines[_lines.len() - 2..].iter().map(|_line| _line.to_string()));
+        assert!(Armor::decode(&_long.join("\n")).is_err());
+    }
+
+    #[test]
+    fn strict_parsing() {
+        let (_pk, _sk) = secret_key();
+        let _text = armor(&_sk);
+        // wrong type, label and scheme
+        assert!(dearmor::<CpAbeMasterKey>(&_text).is_err());
+        assert!(dearmor::<KpAbePublicKey>(&armor(&_pk)).is_err());
+        assert!(Armor::decode(&_text.replace("END SECRET KEY", "END PUBLIC KEY")).is_err());
+        assert!(Armor::decode(&_text.replace("Scheme: BSW", "Scheme: LSW")).is_err());
+        // headers
+        assert!(Armor::decode(&_text.replace("Version: 1", "Version: 2")).is_err());
+        assert!(Armor::decode(&_text.replace("Version: 1\n", "")).is_err());
+        assert!(Armor::decode(&_text.replace("Version: 1\n", "Version: 1\nVersion: 1\n")).is_err());
+        assert!(Armor::decode(&_text.replace("Version: 1\n", "Version: 1\nComment: hi\n")).is_err());
+        assert!(Armor::decode(&_text.replace("Scheme: BSW\n", "Scheme: BSW\nKey-Id: 0g\n")).is_err());
+        // surrounding text
+        assert!(Armor::decode(&format!("\n{}", _text)).is_err());
+        assert!(Armor::decode(&format!("{}\n", _text)).is_err());
+        assert!(Armor::decode(&format!("{}more", _text)).is_err());
+        // a flipped character in the body fails the checksum
+        let _start = _text.find("\n\n").unwrap() + 2;
+        let mut _body = _text.clone().into_bytes();
+        _body[_start + 10] = if _body[_start + 10] == b'A' { b'B' } else { b'A' };
+        assert!(Armor::decode(&String::from_utf8(_body).unwrap()).is_err());
+        // a missing checksum
+        let _checksum = _text.lines().rev().nth(1).unwrap();
+        assert!(Armor::decode(&_text.replace(&format!("{}\n", _checksum), "")).is_err());
+        // an empty line right before END leaves no room for the body
+        assert!(Armor::decode("-----BEGIN PUBLIC KEY-----\nVersion: 1\nScheme: BSW\nKey-Id: ab\n\n-----END PUBLIC KEY-----\n").is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! Typed attributes with an optional namespace and an optional value.
+//!
+//! The canonical encoding of a typed attribute is `namespace::name=value`, where either namespace
+//! or value may be omitted (`namespace::name`, `name=value`). Whitespace around the separators is
+//! removed, so `"dept = finance"` and `"dept=finance"` denote the same attribute. An attribute
+//! without a separator is a plain attribute as in earlier versions and kept as it is, e.g. `"A B"`
+//! or `1e+5`. Every scheme hashes attributes in their canonical encoding.
+use std::fmt;
+use std::str::FromStr;
+use RabeError;
+
+/// Maximal length of the canonical encoding of an attribute
+pub const MAX_ATTRIBUTE_LENGTH: usize = 256;
+/// Separates the namespace from the name
+pub const NAMESPACE_SEPARATOR: &str = "::";
+/// Separates the name from the value
+pub const VALUE_SEPARATOR: char = '=';
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Attribute {
+    pub _namespace: Option<String>,
+    pub _name: String,
+    pub _value: Option<String>,
+}
+
+impl Attribute {
+    /// Returns a new, validated attribute
+    ///
+    /// # Arguments
+    ///
+    ///	* `_namespace` - An optional namespace, e.g. the authority owning the attribute
+    ///	* `_name` - The name of the attribute
+    ///	* `_value` - An optional value of the attribute
+    pub fn new(_namespace: Option<&str>, _name: &str, _value: Option<&str>) -> Result<Attribute, RabeError> {
+        let _attribute = Attribute {
+            _namespace: _namespace.map(|_n| _n.trim().to_string()),
+            _name: _name.trim().to_string(),
+            _value: _value.map(|_v| _v.trim().to_string()),
+        };
+        _attribute.validate()?;
+        Ok(_attribute)
+    }
+
+    /// Checks the allowed characters of all parts and the maximal length of the canonical encoding
+    pub fn validate(&self) -> Result<(), RabeError> {
+        if let Some(_namespace) = &self._namespace {
+            check_part(_namespace, "namespace", |_c| _c.is_ascii_alphanumeric() || "_-.".contains(_c))?;
+        }
+        check_part(&self._name, "name", |_c| _c.is_ascii_alphanumeric() || "_-.:#@/".contains(_c))?;
+        if self._name.contains(NAMESPACE_SEPARATOR) {
+            return Err(RabeError::new(&format!("invalid attribute name {}: more than one namespace", self._name)));
+        }
+        if let Some(_value) = &self._value {
+            check_part(_value, "value", |_c| _c.is_ascii_alphanumeric() || "_-.:#@/+ ".contains(_c))?;
+        }
+        let _length = self.to_string().len();
+        if _length > MAX_ATTRIBUTE_LENGTH {
+            return Err(RabeError::new(&format!("attribute is too long ({} > {} characters)", _length, MAX_ATTRIBUTE_LENGTH)));
+        }
+        Ok(())
+    }
+}
+
+fn check_part<F: Fn(char) -> bool>(_part: &str, _kind: &str, _allowed: F) -> Result<(), RabeError> {
+    if _part.is_empty() {
+        return Err(RabeError::new(&format!("attribute {} must not be empty", _kind)));
+    }
+    match _part.chars().find(|_c| !_allowed(*_c)) {
+        Some(_c) => Err(RabeError::new(&format!("invalid character '{}' in attribute {} {}", _c, _kind, _part))),
+        None => Ok(()),
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(_namespace) = &self._namespace {
+            write!(f, "{}{}", _namespace, NAMESPACE_SEPARATOR)?;
+        }
+        write!(f, "{}", self._name)?;
+        if let Some(_value) = &self._value {
+            write!(f, "{}{}", VALUE_SEPARATOR, _value)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = RabeError;
+
+    fn from_str(_s: &str) -> Result<Attribute, RabeError> {
+        let (_namespace, _rest) = match _s.find(NAMESPACE_SEPARATOR) {
+            Some(_i) => (Some(&_s[.._i]), &_s[_i + NAMESPACE_SEPARATOR.len()..]),
+            None => (None, _s),
+        };
+        let (_name, _value) = match _rest.find(VALUE_SEPARATOR) {
+            Some(_i) => (&_rest[.._i], Some(&_rest[_i + 1..])),
+            None => (_rest, None),
+        };
+        Attribute::new(_namespace, _name, _value)
+    }
+}
+
+/// Returns true if an attribute has a namespace or a value, i.e. is validated as an Attribute
+///
+/// # Arguments
+///
+///	* `_attribute` - The attribute
+pub fn is_typed(_attribute: &str) -> bool {
+    _attribute.contains(NAMESPACE_SEPARATOR) || _attribute.contains(VALUE_SEPARATOR)
+}
+
+/// Returns the canonical encoding of an attribute given as String. A plain attribute (see
+/// is_typed()) may contain any character but a quote and is returned as it is.
+///
+/// # Arguments
+///
+///	* `_attribute` - The attribute
+pub fn canonical_attribute(_attribute: &str) -> Result<String, RabeError> {
+    if is_typed(_attribute) {
+        return Ok(Attribute::from_str(_attribute.trim())?.to_string());
+    }
+    if _attribute.is_empty() {
+        return Err(RabeError::new("attribute must not be empty"));
+    }
+    // negations are part of the policy, not of the attribute
+    if _attribute.starts_with('!') {
+        return Err(RabeError::new(&format!("invalid attribute {}: negated", _attribute)));
+    }
+    // a quote would end the leaf of a human policy
+    if _attribute.contains('"') {
+        return Err(RabeError::new(&format!("invalid character '\"' in attribute {}", _attribute)));
+    }
+    if _attribute.len() > MAX_ATTRIBUTE_LENGTH {
+        return Err(RabeError::new(&format!("attribute is too long ({} > {} characters)", _attribute.len(), MAX_ATTRIBUTE_LENGTH)));
+    }
+    Ok(_attribute.to_string())
+}
+
+/// Returns the canonical encoding of a list of attributes
+///
+/// # Arguments
+///
+///	* `_attributes` - The attributes
+pub fn canonicalize(_attributes: &[String]) -> Result<Vec<String>, RabeError> {
+    _attributes.iter().map(|_attribute| canonical_attribute(_attribute)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_canonical_encoding() {
+        let _attribute = Attribute::from_str(" hr :: dept = finance ").unwrap();
+        assert_eq!(_attribute, Attribute::new(Some("hr"), "dept", Some("finance")).unwrap());
+        assert_eq!(_attribute.to_string(), "hr::dept=finance");
+        assert_eq!(canonical_attribute("dept = finance").unwrap(), "dept=finance");
+        // negations are part of the policy, not of the attribute
+        assert!(canonical_attribute("!aa1::A").is_err());
+        assert!(canonical_attribute("!A").is_err());
+        // plain attributes are kept as they are
+        assert_eq!(canonical_attribute("A B").unwrap(), "A B");
+        assert_eq!(canonical_attribute("1e+5").unwrap(), "1e+5");
+        assert!(canonical_attribute("").is_err());
+        assert_eq!(canonical_attribute("age#3=1").unwrap(), "age#3=1");
+        assert_eq!(Attribute::from_str("admin").unwrap()._value, None);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(Attribute::from_str("").is_err());
+        assert!(Attribute::from_str("dept=").is_err());
+        assert!(Attribute::from_str("::dept").is_err());
+        assert!(Attribute::from_str("a::b::c").is_err());
+        assert!(Attribute::from_str("de\"pt").is_err());
+        assert!(Attribute::from_str("dept=fin=ance").is_err());
+        assert!(Attribute::from_str(&"x".repeat(MAX_ATTRIBUTE_LENGTH + 1)).is_err());
+        assert!(Attribute::from_str(&"x".repeat(MAX_ATTRIBUTE_LENGTH)).is_ok());
+    }
+}
@@ -60,10 +60,14 @@ pub fn write_from_vec(_path: &Path, _data: &Vec<u8>) {
     }
 }
 
+/// Returns the body of a BEGIN/END delimited file as one line, joining wrapped lines.
+/// See utils::armor for parsing armored keys and ciphertexts.
 pub fn read_raw(_raw: &String) -> String {
-    let lines = &mut _raw.lines();
-    let middle = lines.nth(1).unwrap().to_string();
-    return middle;
+    let lines: Vec<&str> = _raw.lines().collect();
+    if lines.len() < 3 {
+        return String::new();
+    }
+    lines[1..lines.len() - 1].concat()
 }
 
 pub fn write_file(_path: &Path, _content: String) -> bool {
@@ -0,0 +1,98 @@
+//! Stable fingerprints of keys and the issuer of secret keys and ciphertexts.
+//!
+//! The fingerprint of a key is the SHA3-256 hash of its wire encoding, see `utils::wire`, so it
+//! does not depend on how the key was stored. Public keys, master keys, global parameters,
+//! authority keys and attribute keys implement `Fingerprint`.
+//!
+//! Secret keys and ciphertexts record the fingerprint of the public key they were derived from
+//! (the global parameters in AW11, BDABE and MKE08) as their `_issuer`. Decrypting a ciphertext
+//! with a secret key of another public key fails early with a `KeyMismatch` instead of an
+//! opaque error of the symmetric encryption. Keys and ciphertexts created before the issuer
+//! was recorded have an empty issuer, which is not checked.
+//!
+//! # Examples
+//!
+//! ```
+//! use rabe::schemes::bsw::*;
+//! use rabe::utils::fingerprint::Fingerprint;
+//! use rabe::utils::policy::pest::PolicyLanguage;
+//! let (pk, msk) = setup();
+//! let (other_pk, other_msk) = setup();
+//! let sk = keygen(&other_pk, &other_msk, &vec!["A".to_string()]).unwrap();
+//! let plaintext = String::from("our plaintext!").into_bytes();
+//! let ct = encrypt(&pk, &String::from(r#""A""#), &plaintext, PolicyLanguage::HumanPolicy).unwrap();
+//! assert_eq!(ct._issuer, pk.fingerprint());
+//! assert!(decrypt(&sk, &ct).unwrap_err().key_mismatch().is_some());
+//! ```
+use std::fmt;
+use sha3::{Sha3_256, Digest};
+use utils::wire::WireFormat;
+use RabeError;
+
+/// The length of a fingerprint in bytes
+pub const FINGERPRINT_LENGTH: usize = 32;
+
+/// A key with a stable fingerprint
+pub trait Fingerprint: WireFormat {
+    /// The SHA3-256 hash of the wire encoding of the key
+    fn fingerprint(&self) -> Vec<u8> {
+        Sha3_256::digest(&self.to_bytes()).to_vec()
+    }
+}
+
+/// A secret key and a ciphertext that were derived from different public keys
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct KeyMismatch {
+    /// The issuer recorded in the secret key
+    pub _key: Vec<u8>,
+    /// The issuer recorded in the ciphertext
+    pub _ciphertext: Vec<u8>,
+}
+
+impl fmt::Display for KeyMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Key Mismatch: the secret key was issued by {}, but the ciphertext was encrypted for {}",
+            short_hex(&self._key),
+            short_hex(&self._ciphertext)
+        )
+    }
+}
+
+/// Checks that a secret key and a ciphertext were derived from the same public key. An empty
+/// issuer is unknown and matches every key.
+///
+/// # Arguments
+///
+///	* `_key` - The issuer recorded in the secret key
+///	* `_ciphertext` - The issuer recorded in the ciphertext
+///
+pub fn check_issuer(_key: &[u8], _ciphertext: &[u8]) -> Result<(), RabeError> {
+    if _key.is_empty() || _ciphertext.is_empty() || _key == _ciphertext {
+        Ok(())
+    } else {
+        Err(KeyMismatch { _key: _key.to_vec(), _ciphertext: _ciphertext.to_vec() }.into())
+    }
+}
+
+// the first 8 bytes of a fingerprint as hex, like the Key-Id of the armor
+fn short_hex(_fingerprint: &[u8]) -> String {
+    _fingerprint.iter().take(8).map(|_byte| format!("{:02x}", _byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issuers() {
+        let (_a, _b) = (vec![1u8; FINGERPRINT_LENGTH], vec![2u8; FINGERPRINT_LENGTH]);
+        assert!(check_issuer(&_a, &_a).is_ok());
+        assert!(check_issuer(&[], &_a).is_ok());
+        assert!(check_issuer(&_a, &[]).is_ok());
+        let _error = check_issuer(&_a, &_b).unwrap_err();
+        assert_eq!(_error.key_mismatch(), Some(&KeyMismatch { _key: _a, _ciphertext: _b }));
+        assert!(_error.to_string().contains("issued by 0101010101010101"));
+    }
+}
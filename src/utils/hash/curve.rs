@@ -0,0 +1,756 @@
+//! Hashing to the BN254 groups G1 and G2 by try-and-increment.
+//!
+//! A message is hashed together with a domain separation tag and a counter to a candidate
+//! x-coordinate until x^3 + b is a square. The sign of y is taken from the hash as well, so that
+//! no discrete logarithm of the resulting point is known to anyone. On G2 the twist point is
+//! additionally multiplied by the cofactor to land in the subgroup of order r.
+//!
+//! rabe-bn does not expose its field types, so the coordinates are computed here on the
+//! Montgomery representation of `rabe_bn::arith::U256`. The only way in and out of a rabe-bn
+//! element is its serde implementation, so the point is then read from the bincode encoding of
+//! Jacobian coordinates laid out like rabe-bn's, which is just their limbs in order. For the same
+//! reason g1_to_bytes(), g2_to_bytes() and gt_to_bytes() read the coordinates of an element from
+//! its bincode encoding.
+//!
+//! The canonical encodings of the binary wire format (see `utils::wire`) are implemented here as
+//! well: Fr and Gt as big-endian integers, G1 and G2 as compressed points.
+use std::ops::{Add, Sub, Mul, Neg};
+use blake2_rfc::blake2b::Blake2b;
+use rabe_bn::{Fr, G1, G2, Gt, Group, arith::{U256, U512}};
+use bincode::{serialize, deserialize};
+
+/// The domain separation tag for hashing to G1
+pub const DST_G1: &[u8] = b"RABE-V1-BN254G1_BLAKE2B_TAI_";
+/// The domain separation tag for hashing to G2
+pub const DST_G2: &[u8] = b"RABE-V1-BN254G2_BLAKE2B_TAI_";
+
+/// The number of candidates tried before giving up; each one succeeds with probability ~1/2
+const MAX_TRIES: u8 = 255;
+
+/// r, the order of G1, G2 and Gt and the modulus of Fr
+const ORDER: [u64; 4] = [0x43e1f593f0000001, 0x2833e84879b97091, 0xb85045b68181585d, 0x30644e72e131a029];
+/// -r^-1 mod 2^64, used to leave the Montgomery domain of Fr
+const FR_INV: u64 = 0xc2e1f593efffffff;
+/// p, the order of the base field
+const MODULUS: [u64; 4] = [0x3c208c16d87cfd47, 0x97816a916871ca8d, 0xb85045b68181585d, 0x30644e72e131a029];
+/// R^2 mod p, used to enter the Montgomery domain
+const R2: [u64; 4] = [0xf32cfc5b538afa89, 0xb5e71911d44501fb, 0x47ab1eff0a417ff6, 0x06d89f71cab8351f];
+/// R^3 mod p, used to correct the result of the binary inversion
+const R3: [u64; 4] = [0xb1cd6dafda1530df, 0x62f210e6a7283db6, 0xef7f0b0c0ada0afb, 0x20fd6e902d592544];
+/// R mod p, i.e. 1 in Montgomery form
+const ONE: [u64; 4] = [0xd35d438dc58f0d9d, 0x0a78eb28f5c70b3d, 0x666ea36f7879462c, 0x0e0a77c19a07df2f];
+/// -p^-1 mod 2^64
+const INV: u64 = 0x87d20782e4866389;
+/// (p + 1) / 4, the exponent of the square root since p = 3 mod 4
+const SQRT_EXP: [u64; 4] = [0x4f082305b61f3f52, 0x65e05aa45a1c72a3, 0x6e14116da0605617, 0x0c19139cb84c680a];
+/// b = 3 of G1: y^2 = x^3 + 3 (Montgomery form)
+const G1_B: [u64; 4] = [0x7a17caa950ad28d7, 0x1f6ac17ae15521b9, 0x334bea4e696bd284, 0x2a1f6744ce179d8e];
+/// b' = 3 / (9 + i) of the G2 twist (Montgomery form)
+const G2_B: ([u64; 4], [u64; 4]) = (
+    [0x3bf938e377b802a8, 0x020b1b273633535d, 0x26b7edf049755260, 0x2514c6324384a86d],
+    [0x38e7ecccd1dcff67, 0x65f0b37d93ce0d3e, 0xd749d0dd22ac00aa, 0x0141b9ce4a688d4d],
+);
+/// h = 2p - r, the cofactor of G2 on the twist
+const G2_COFACTOR: [u64; 4] = [0x345f2299c0f9fa8d, 0x06ceecda572a2489, 0xb85045b68181585e, 0x30644e72e131a029];
+
+/// An element of Fq in Montgomery form, laid out like `rabe_bn`'s Fq
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+struct Fq(U256);
+
+/// An element of Fq2 = Fq[i] / (i^2 + 1), laid out like `rabe_bn`'s Fq2
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+struct Fq2 {
+    c0: Fq,
+    c1: Fq,
+}
+
+/// A point in Jacobian coordinates, laid out like `rabe_bn`'s G
+#[derive(Serialize, Deserialize)]
+struct Jacobian<F> {
+    x: F,
+    y: F,
+    z: F,
+}
+
+impl Fq {
+    fn zero() -> Fq {
+        Fq(U256::zero())
+    }
+
+    fn one() -> Fq {
+        Fq(U256(ONE))
+    }
+
+    /// Reduces 64 bytes of hash output modulo p
+    fn from_hash(_hash: &[u8]) -> Fq {
+        let mut _buf = [0u8; 64];
+        _buf.copy_from_slice(_hash);
+        let mut _a = U512::interpret(&_buf).divrem(&U256(MODULUS)).1;
+        _a.mul(&U256(R2), &U256(MODULUS), INV);
+        Fq(_a)
+    }
+
+    /// Reads a 32 byte big-endian integer, if it is smaller than p
+    fn from_bytes(_bytes: &[u8]) -> Option<Fq> {
+        let mut _a = read_u256(_bytes)?;
+        if _a >= U256(MODULUS) {
+            return None;
+        }
+        _a.mul(&U256(R2), &U256(MODULUS), INV);
+        Some(Fq(_a))
+    }
+
+    /// The integer this element represents, outside of the Montgomery domain
+    fn to_u256(self) -> U256 {
+        let mut _a = self.0;
+        // multiplying by 1 leaves the Montgomery domain
+        _a.mul(&U256::one(), &U256(MODULUS), INV);
+        _a
+    }
+
+    /// The parity of the integer this element represents, which tells y and -y apart
+    fn is_odd(&self) -> bool {
+        self.to_u256().0[0] & 1 == 1
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Whether the Montgomery representation is smaller than p, which serde does not check
+    fn is_reduced(&self) -> bool {
+        self.0 < U256(MODULUS)
+    }
+
+    fn pow(&self, _exp: &U256) -> Fq {
+        let mut _res = Fq::one();
+        for _bit in _exp.bits() {
+            _res = _res * _res;
+            if _bit {
+                _res = _res * *self;
+            }
+        }
+        _res
+    }
+
+    fn inverse(&self) -> Option<Fq> {
+        if self.is_zero() {
+            return None;
+        }
+        let mut _a = self.0;
+        _a.invert(&U256(MODULUS));
+        _a.mul(&U256(R3), &U256(MODULUS), INV);
+        Some(Fq(_a))
+    }
+
+    fn sqrt(&self) -> Option<Fq> {
+        let _root = self.pow(&U256(SQRT_EXP));
+        if _root * _root == *self {
+            Some(_root)
+        } else {
+            None
+        }
+    }
+}
+
+impl Add for Fq {
+    type Output = Fq;
+
+    fn add(self, other: Fq) -> Fq {
+        let mut _a = self.0;
+        _a.add(&other.0, &U256(MODULUS));
+        Fq(_a)
+    }
+}
+
+impl Sub for Fq {
+    type Output = Fq;
+
+    fn sub(self, other: Fq) -> Fq {
+        let mut _a = self.0;
+        _a.sub(&other.0, &U256(MODULUS));
+        Fq(_a)
+    }
+}
+
+impl Mul for Fq {
+    type Output = Fq;
+
+    fn mul(self, other: Fq) -> Fq {
+        let mut _a = self.0;
+        _a.mul(&other.0, &U256(MODULUS), INV);
+        Fq(_a)
+    }
+}
+
+impl Neg for Fq {
+    type Output = Fq;
+
+    fn neg(self) -> Fq {
+        let mut _a = self.0;
+        _a.neg(&U256(MODULUS));
+        Fq(_a)
+    }
+}
+
+impl Fq2 {
+    fn new(c0: Fq, c1: Fq) -> Fq2 {
+        Fq2 { c0, c1 }
+    }
+
+    fn one() -> Fq2 {
+        Fq2::new(Fq::one(), Fq::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    fn is_reduced(&self) -> bool {
+        self.c0.is_reduced() && self.c1.is_reduced()
+    }
+
+    /// The parity of c1, or of c0 if c1 is zero, which tells y and -y apart
+    fn is_odd(&self) -> bool {
+        if self.c1.is_zero() {
+            self.c0.is_odd()
+        } else {
+            self.c1.is_odd()
+        }
+    }
+
+    /// Inverse via the conjugate: 1 / (a0 + a1 i) = (a0 - a1 i) / (a0^2 + a1^2)
+    fn inverse(&self) -> Option<Fq2> {
+        let _norm = (self.c0 * self.c0 + self.c1 * self.c1).inverse()?;
+        Some(Fq2::new(self.c0 * _norm, -self.c1 * _norm))
+    }
+
+    /// Square root via the norm: for a = a0 + a1 i, |a| = a0^2 + a1^2 has to be a square in Fq
+    fn sqrt(&self) -> Option<Fq2> {
+        if self.c1.is_zero() {
+            return match self.c0.sqrt() {
+                Some(_root) => Some(Fq2::new(_root, Fq::zero())),
+                // -1 is not a square in Fq, so -a0 is
+                None => self.c0.neg().sqrt().map(|_root| Fq2::new(Fq::zero(), _root)),
+            };
+        }
+        let _alpha = (self.c0 * self.c0 + self.c1 * self.c1).sqrt()?;
+        let _half = (Fq::one() + Fq::one()).inverse()?;
+        let _x0 = match ((self.c0 + _alpha) * _half).sqrt() {
+            Some(_x0) => _x0,
+            None => ((self.c0 - _alpha) * _half).sqrt()?,
+        };
+        let _x1 = self.c1 * (_x0 + _x0).inverse()?;
+        let _root = Fq2::new(_x0, _x1);
+        if _root * _root == *self {
+            Some(_root)
+        } else {
+            None
+        }
+    }
+}
+
+impl Add for Fq2 {
+    type Output = Fq2;
+
+    fn add(self, other: Fq2) -> Fq2 {
+        Fq2::new(self.c0 + other.c0, self.c1 + other.c1)
+    }
+}
+
+impl Mul for Fq2 {
+    type Output = Fq2;
+
+    fn mul(self, other: Fq2) -> Fq2 {
+        Fq2::new(
+            self.c0 * other.c0 - self.c1 * other.c1,
+            self.c0 * other.c1 + self.c1 * other.c0,
+        )
+    }
+}
+
+impl Neg for Fq2 {
+    type Output = Fq2;
+
+    fn neg(self) -> Fq2 {
+        Fq2::new(-self.c0, -self.c1)
+    }
+}
+
+/// Hashes the domain separation tag, the counter, a field index and the data with blake2b.
+/// The tag is length prefixed, so that no tag is a prefix of another.
+fn hash_to_field(_dst: &[u8], _counter: u8, _index: u8, _data: &[u8]) -> ([u8; 64], Fq) {
+    let mut _hasher = Blake2b::new(64);
+    _hasher.update(&[_dst.len() as u8]);
+    _hasher.update(_dst);
+    _hasher.update(&[_counter, _index]);
+    _hasher.update(_data);
+    let _hash = _hasher.finalize();
+    let mut _bytes = [0u8; 64];
+    _bytes.copy_from_slice(_hash.as_bytes());
+    (_bytes, Fq::from_hash(_hash.as_bytes()))
+}
+
+/// The sign of y, taken from the 512 bit hash that x was reduced from, so it is not determined by x
+fn sign(_hash: &[u8; 64]) -> bool {
+    _hash[0] & 1 == 1
+}
+
+/// Hashes data to a point of G1 with unknown discrete logarithm.
+///
+/// # Arguments
+///
+///	* `_data` - The data to hash, e.g. an attribute
+///
+pub fn hash_to_g1(_data: &[u8]) -> G1 {
+    for _counter in 0..MAX_TRIES {
+        let (_hash, _x) = hash_to_field(DST_G1, _counter, 0, _data);
+        if let Some(_y) = (_x * _x * _x + Fq(U256(G1_B))).sqrt() {
+            let _y = if sign(&_hash) { -_y } else { _y };
+            return reinterpret(&Jacobian { x: _x, y: _y, z: Fq::one() });
+        }
+    }
+    panic!("hash_to_g1: no point found after {} tries", MAX_TRIES)
+}
+
+/// Hashes data to a point of G2 with unknown discrete logarithm.
+///
+/// # Arguments
+///
+///	* `_data` - The data to hash, e.g. an attribute
+///
+pub fn hash_to_g2(_data: &[u8]) -> G2 {
+    let _b = Fq2::new(Fq(U256(G2_B.0)), Fq(U256(G2_B.1)));
+    for _counter in 0..MAX_TRIES {
+        let (_hash, _c0) = hash_to_field(DST_G2, _counter, 0, _data);
+        let (_, _c1) = hash_to_field(DST_G2, _counter, 1, _data);
+        let _x = Fq2::new(_c0, _c1);
+        if let Some(_y) = (_x * _x * _x + _b).sqrt() {
+            let _y = if sign(&_hash) { -_y } else { _y };
+            let _point: G2 = reinterpret(&Jacobian { x: _x, y: _y, z: Fq2::one() });
+            let _point = mul_u256(_point, &U256(G2_COFACTOR));
+            if !_point.is_zero() {
+                return _point;
+            }
+        }
+    }
+    panic!("hash_to_g2: no point found after {} tries", MAX_TRIES)
+}
+
+/// Encodes an element of Gt canonically as its 12 coefficients in Fq, each one a 32 byte
+/// big-endian integer. The coefficients follow the tower Fq12 = Fq6[w], Fq6 = Fq2[v],
+/// Fq2 = Fq[i], lowest coefficient first, e.g. Gt::one() is encoded as 1, 0, ..., 0.
+///
+/// # Arguments
+///
+///	* `_gt` - The element to encode
+///
+pub fn gt_to_bytes(_gt: &Gt) -> Vec<u8> {
+    let _coefficients: [Fq; 12] = reinterpret(_gt);
+    let mut _bytes: Vec<u8> = Vec::with_capacity(12 * 32);
+    for _coefficient in _coefficients.iter() {
+        push_fq(*_coefficient, &mut _bytes);
+    }
+    _bytes
+}
+
+/// Encodes a point of G1 canonically: 0x00 for the point at infinity, otherwise 0x04 followed
+/// by its affine coordinates x and y, each one a 32 byte big-endian integer.
+///
+/// # Arguments
+///
+///	* `_g` - The point to encode
+///
+pub fn g1_to_bytes(_g: &G1) -> Vec<u8> {
+    let _point: Jacobian<Fq> = reinterpret(_g);
+    let _zinv = match _point.z.inverse() {
+        Some(_zinv) => _zinv,
+        None => return vec![0x00],
+    };
+    let _zinv2 = _zinv * _zinv;
+    let mut _bytes: Vec<u8> = Vec::with_capacity(1 + 2 * 32);
+    _bytes.push(0x04);
+    push_fq(_point.x * _zinv2, &mut _bytes);
+    push_fq(_point.y * _zinv2 * _zinv, &mut _bytes);
+    _bytes
+}
+
+/// Encodes a point of G2 canonically: 0x00 for the point at infinity, otherwise 0x04 followed
+/// by its affine coordinates x and y in Fq2, each coefficient a 32 byte big-endian integer in
+/// the order x.c0, x.c1, y.c0, y.c1.
+///
+/// # Arguments
+///
+///	* `_g` - The point to encode
+///
+pub fn g2_to_bytes(_g: &G2) -> Vec<u8> {
+    let _point: Jacobian<Fq2> = reinterpret(_g);
+    if _point.z.is_zero() {
+        return vec![0x00];
+    }
+    let _zinv = _point.z.inverse().expect("g2_to_bytes: z is not zero");
+    let _zinv2 = _zinv * _zinv;
+    let (_x, _y) = (_point.x * _zinv2, _point.y * _zinv2 * _zinv);
+    let mut _bytes: Vec<u8> = Vec::with_capacity(1 + 4 * 32);
+    _bytes.push(0x04);
+    for _coefficient in [_x.c0, _x.c1, _y.c0, _y.c1].iter() {
+        push_fq(*_coefficient, &mut _bytes);
+    }
+    _bytes
+}
+
+/// Decodes an element of Gt from the encoding of gt_to_bytes(). Returns None if the encoding
+/// has the wrong length or a coefficient is not smaller than p.
+///
+/// # Arguments
+///
+///	* `_bytes` - The 384 byte encoding
+///
+pub fn gt_from_bytes(_bytes: &[u8]) -> Option<Gt> {
+    if _bytes.len() != 12 * 32 {
+        return None;
+    }
+    let mut _coefficients = [Fq::zero(); 12];
+    for (_coefficient, _chunk) in _coefficients.iter_mut().zip(_bytes.chunks(32)) {
+        *_coefficient = Fq::from_bytes(_chunk)?;
+    }
+    Some(reinterpret(&_coefficients))
+}
+
+/// Encodes a point of G1 compressed: its x-coordinate as a 32 byte big-endian integer, preceded
+/// by 0x02 if y is even and 0x03 if y is odd. The point at infinity is encoded as 33 zero bytes.
+///
+/// # Arguments
+///
+///	* `_g` - The point to encode
+///
+pub fn g1_to_compressed(_g: &G1) -> Vec<u8> {
+    let _point: Jacobian<Fq> = reinterpret(_g);
+    let _zinv = match _point.z.inverse() {
+        Some(_zinv) => _zinv,
+        None => return vec![0u8; 1 + 32],
+    };
+    let _zinv2 = _zinv * _zinv;
+    let _y = _point.y * _zinv2 * _zinv;
+    let mut _bytes: Vec<u8> = Vec::with_capacity(1 + 32);
+    _bytes.push(if _y.is_odd() { 0x03 } else { 0x02 });
+    push_fq(_point.x * _zinv2, &mut _bytes);
+    _bytes
+}
+
+/// Decodes a point of G1 from the encoding of g1_to_compressed(). Returns None if the encoding
+/// is malformed or x is not the x-coordinate of a point on the curve.
+///
+/// # Arguments
+///
+///	* `_bytes` - The 33 byte encoding
+///
+pub fn g1_from_compressed(_bytes: &[u8]) -> Option<G1> {
+    if _bytes.len() != 1 + 32 {
+        return None;
+    }
+    match _bytes[0] {
+        0x00 if _bytes.iter().all(|_byte| *_byte == 0) => Some(G1::zero()),
+        0x02 | 0x03 => {
+            let _x = Fq::from_bytes(&_bytes[1..])?;
+            let _y = (_x * _x * _x + Fq(U256(G1_B))).sqrt()?;
+            let _y = if _y.is_odd() == (_bytes[0] == 0x03) { _y } else { -_y };
+            Some(reinterpret(&Jacobian { x: _x, y: _y, z: Fq::one() }))
+        }
+        _ => None,
+    }
+}
+
+/// Encodes a point of G2 compressed: the coefficients x.c0 and x.c1 of its x-coordinate, each
+/// a 32 byte big-endian integer, preceded by 0x02 or 0x03 for the parity of y (of y.c1, or of
+/// y.c0 if y.c1 is zero). The point at infinity is encoded as 65 zero bytes.
+///
+/// # Arguments
+///
+///	* `_g` - The point to encode
+///
+pub fn g2_to_compressed(_g: &G2) -> Vec<u8> {
+    let _point: Jacobian<Fq2> = reinterpret(_g);
+    if _point.z.is_zero() {
+        return vec![0u8; 1 + 2 * 32];
+    }
+    let _zinv = _point.z.inverse().expect("g2_to_compressed: z is not zero");
+    let _zinv2 = _zinv * _zinv;
+    let (_x, _y) = (_point.x * _zinv2, _point.y * _zinv2 * _zinv);
+    let mut _bytes: Vec<u8> = Vec::with_capacity(1 + 2 * 32);
+    _bytes.push(if _y.is_odd() { 0x03 } else { 0x02 });
+    push_fq(_x.c0, &mut _bytes);
+    push_fq(_x.c1, &mut _bytes);
+    _bytes
+}
+
+/// Decodes a point of G2 from the encoding of g2_to_compressed(). Returns None if the encoding
+/// is malformed or x is not the x-coordinate of a point on the twist.
+///
+/// # Arguments
+///
+///	* `_bytes` - The 65 byte encoding
+///
+pub fn g2_from_compressed(_bytes: &[u8]) -> Option<G2> {
+    if _bytes.len() != 1 + 2 * 32 {
+        return None;
+    }
+    match _bytes[0] {
+        0x00 if _bytes.iter().all(|_byte| *_byte == 0) => Some(G2::zero()),
+        0x02 | 0x03 => {
+            let _x = Fq2::new(Fq::from_bytes(&_bytes[1..33])?, Fq::from_bytes(&_bytes[33..])?);
+            let _b = Fq2::new(Fq(U256(G2_B.0)), Fq(U256(G2_B.1)));
+            let _y = (_x * _x * _x + _b).sqrt()?;
+            let _y = if _y.is_odd() == (_bytes[0] == 0x03) { _y } else { -_y };
+            Some(reinterpret(&Jacobian { x: _x, y: _y, z: Fq2::one() }))
+        }
+        _ => None,
+    }
+}
+
+/// Encodes an element of Fr canonically as a 32 byte big-endian integer.
+///
+/// # Arguments
+///
+///	* `_fr` - The element to encode
+///
+pub fn fr_to_bytes(_fr: &Fr) -> Vec<u8> {
+    let mut _a: U256 = reinterpret(_fr);
+    // multiplying by 1 leaves the Montgomery domain
+    _a.mul(&U256::one(), &U256(ORDER), FR_INV);
+    let mut _bytes: Vec<u8> = Vec::with_capacity(32);
+    push_u256(&_a, &mut _bytes);
+    _bytes
+}
+
+/// Decodes an element of Fr from the encoding of fr_to_bytes(). Returns None if the encoding
+/// has the wrong length or the integer is not smaller than r.
+///
+/// # Arguments
+///
+///	* `_bytes` - The 32 byte encoding
+///
+pub fn fr_from_bytes(_bytes: &[u8]) -> Option<Fr> {
+    if read_u256(_bytes)? >= U256(ORDER) {
+        return None;
+    }
+    let mut _buf = [0u8; 64];
+    _buf[32..].copy_from_slice(_bytes);
+    Some(Fr::interpret(&_buf))
+}
+
+/// Returns true if a point of G1, e.g. one deserialized with serde, has reduced coordinates and
+/// is the point at infinity or lies on the curve. G1 has cofactor 1, so it is then in the group.
+///
+/// # Arguments
+///
+///	* `_g` - The point to check
+///
+pub fn g1_is_valid(_g: &G1) -> bool {
+    let _point: Jacobian<Fq> = reinterpret(_g);
+    if !(_point.x.is_reduced() && _point.y.is_reduced() && _point.z.is_reduced()) {
+        return false;
+    }
+    // y^2 = x^3 + b z^6 in Jacobian coordinates
+    let _z2 = _point.z * _point.z;
+    let _z6 = _z2 * _z2 * _z2;
+    _point.z.is_zero() || _point.y * _point.y == _point.x * _point.x * _point.x + Fq(U256(G1_B)) * _z6
+}
+
+/// Returns true if a point of G2, e.g. one deserialized with serde, has reduced coordinates and
+/// is the point at infinity or lies on the twist and in its subgroup of order r.
+///
+/// # Arguments
+///
+///	* `_g` - The point to check
+///
+pub fn g2_is_valid(_g: &G2) -> bool {
+    let _point: Jacobian<Fq2> = reinterpret(_g);
+    if !(_point.x.is_reduced() && _point.y.is_reduced() && _point.z.is_reduced()) {
+        return false;
+    }
+    let _b = Fq2::new(Fq(U256(G2_B.0)), Fq(U256(G2_B.1)));
+    let _z2 = _point.z * _point.z;
+    let _z6 = _z2 * _z2 * _z2;
+    _point.z.is_zero()
+        || (_point.y * _point.y == _point.x * _point.x * _point.x + _b * _z6 && mul_u256(*_g, &U256(ORDER)).is_zero())
+}
+
+/// Returns true if an element of Gt, e.g. one deserialized with serde, has reduced coefficients
+/// and lies in the subgroup of order r of the multiplicative group of Fq12.
+///
+/// # Arguments
+///
+///	* `_gt` - The element to check
+///
+pub fn gt_is_valid(_gt: &Gt) -> bool {
+    let _coefficients: [Fq; 12] = reinterpret(_gt);
+    // _gt^(r-1) * _gt = _gt^r, which is one exactly for the elements of order r
+    _coefficients.iter().all(Fq::is_reduced) && _gt.pow(-Fr::one()) * *_gt == Gt::one()
+}
+
+/// Returns true if the representation of an element of Fr, e.g. one deserialized with serde, is
+/// smaller than r.
+///
+/// # Arguments
+///
+///	* `_fr` - The element to check
+///
+pub fn fr_is_reduced(_fr: &Fr) -> bool {
+    reinterpret::<Fr, U256>(_fr) < U256(ORDER)
+}
+
+/// Appends an element of Fq as a 32 byte big-endian integer, outside of the Montgomery domain
+fn push_fq(_a: Fq, _bytes: &mut Vec<u8>) {
+    push_u256(&_a.to_u256(), _bytes);
+}
+
+/// Appends an integer as 32 byte big-endian
+fn push_u256(_a: &U256, _bytes: &mut Vec<u8>) {
+    for _limb in _a.0.iter().rev() {
+        _bytes.extend_from_slice(&_limb.to_be_bytes());
+    }
+}
+
+/// Reads a 32 byte big-endian integer
+fn read_u256(_bytes: &[u8]) -> Option<U256> {
+    if _bytes.len() != 32 {
+        return None;
+    }
+    let mut _limbs = [0u64; 4];
+    for (_i, _chunk) in _bytes.chunks(8).enumerate() {
+        let mut _limb = [0u8; 8];
+        _limb.copy_from_slice(_chunk);
+        _limbs[3 - _i] = u64::from_be_bytes(_limb);
+    }
+    Some(U256(_limbs))
+}
+
+/// Converts between a rabe-bn element and a type of the same layout, e.g. a point and its
+/// Jacobian coordinates, through their bincode encoding
+fn reinterpret<A: ::serde::Serialize, B: ::serde::de::DeserializeOwned>(_value: &A) -> B {
+    let _bytes = serialize(_value).expect("reinterpret: element serializes");
+    deserialize(&_bytes).expect("reinterpret: rabe-bn layout")
+}
+
+/// Multiplies by a scalar that does not fit into Fr, e.g. a cofactor
+fn mul_u256<G: Group + Add<Output = G> + Copy>(_point: G, _scalar: &U256) -> G {
+    let mut _res = G::zero();
+    for _bit in _scalar.bits() {
+        _res = _res + _res;
+        if _bit {
+            _res = _res + _point;
+        }
+    }
+    _res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rabe_bn::pairing;
+
+    #[test]
+    fn points_are_in_the_prime_order_subgroups() {
+        let _g1 = hash_to_g1(b"A");
+        let _g2 = hash_to_g2(b"A");
+        assert!(!_g1.is_zero() && !_g2.is_zero());
+        assert!(mul_u256(_g1, &U256(ORDER)).is_zero());
+        assert!(mul_u256(_g2, &U256(ORDER)).is_zero());
+        // G1 and G2 are not degenerate with respect to the pairing
+        let _a = Fr::from_str("1234567").unwrap();
+        assert!(pairing(_g1 * _a, _g2) == pairing(_g1, _g2 * _a));
+        assert!(pairing(_g1 * _a, _g2) == pairing(_g1, _g2).pow(_a));
+    }
+
+    #[test]
+    fn gt_encoding() {
+        let _one = gt_to_bytes(&Gt::one());
+        assert_eq!(_one.len(), 384);
+        assert_eq!(_one[31], 1);
+        assert_eq!(_one.iter().filter(|_byte| **_byte != 0).count(), 1);
+        let _gt = pairing(hash_to_g1(b"A"), hash_to_g2(b"A"));
+        assert_eq!(gt_to_bytes(&_gt), gt_to_bytes(&(_gt * Gt::one())));
+        assert_ne!(gt_to_bytes(&_gt), gt_to_bytes(&_gt.inverse()));
+    }
+
+    #[test]
+    fn point_encoding() {
+        assert_eq!(g1_to_bytes(&G1::zero()), vec![0x00]);
+        assert_eq!(g2_to_bytes(&G2::zero()), vec![0x00]);
+        // the generator of G1 is (1, 2)
+        let _one = g1_to_bytes(&G1::one());
+        assert_eq!(_one.len(), 65);
+        assert_eq!((_one[0], _one[32], _one[64]), (0x04, 1, 2));
+        let _a = Fr::from_str("1234567").unwrap();
+        let (_g1, _g2) = (hash_to_g1(b"A"), hash_to_g2(b"A"));
+        assert_eq!(g2_to_bytes(&_g2).len(), 129);
+        // different Jacobian representations of the same point encode the same
+        assert_eq!(g1_to_bytes(&(_g1 * _a + _g1)), g1_to_bytes(&(_g1 + _g1 * _a)));
+        assert_eq!(g2_to_bytes(&(_g2 * _a + _g2)), g2_to_bytes(&(_g2 + _g2 * _a)));
+        assert_ne!(g1_to_bytes(&_g1), g1_to_bytes(&-_g1));
+        assert_ne!(g2_to_bytes(&_g2), g2_to_bytes(&-_g2));
+    }
+
+    #[test]
+    fn compressed_encoding() {
+        let _a = Fr::from_str("1234567").unwrap();
+        assert_eq!(&fr_to_bytes(&_a)[29..], &[0x12, 0xd6, 0x87]);
+        assert!(fr_from_bytes(&fr_to_bytes(&_a)) == Some(_a));
+        assert!(fr_from_bytes(&fr_to_bytes(&-_a)) == Some(-_a));
+        // r itself is not a canonical encoding
+        let mut _order = Vec::new();
+        push_u256(&U256(ORDER), &mut _order);
+        assert!(fr_from_bytes(&_order).is_none());
+        let (_g1, _g2) = (hash_to_g1(b"A") * _a, hash_to_g2(b"A") * _a);
+        for _g in [_g1, -_g1, G1::zero(), G1::one()].iter() {
+            assert_eq!(g1_to_compressed(_g).len(), 33);
+            assert!(g1_from_compressed(&g1_to_compressed(_g)) == Some(*_g));
+        }
+        for _g in [_g2, -_g2, G2::zero(), G2::one()].iter() {
+            assert_eq!(g2_to_compressed(_g).len(), 65);
+            assert!(g2_from_compressed(&g2_to_compressed(_g)) == Some(*_g));
+        }
+        let _gt = pairing(_g1, _g2);
+        assert!(gt_from_bytes(&gt_to_bytes(&_gt)) == Some(_gt));
+        // x = 0 is not on the curve, since 3 is not a square
+        let mut _invalid = vec![0u8; 33];
+        _invalid[0] = 0x02;
+        assert!(g1_from_compressed(&_invalid).is_none());
+        _invalid[0] = 0x04;
+        assert!(g1_from_compressed(&_invalid).is_none());
+        assert!(gt_from_bytes(&[0xff; 384]).is_none());
+    }
+
+    #[test]
+    fn validity_checks() {
+        let (_g1, _g2) = (hash_to_g1(b"A"), hash_to_g2(b"A"));
+        assert!(g1_is_valid(&_g1) && g1_is_valid(&G1::zero()));
+        assert!(g2_is_valid(&_g2) && g2_is_valid(&G2::zero()));
+        assert!(gt_is_valid(&pairing(_g1, _g2)) && gt_is_valid(&Gt::one()));
+        assert!(fr_is_reduced(&-Fr::one()));
+        // a point of the twist that is not in the subgroup of order r
+        let _b = Fq2::new(Fq(U256(G2_B.0)), Fq(U256(G2_B.1)));
+        let _point = (0..MAX_TRIES)
+            .filter_map(|_counter| {
+                let _x = Fq2::new(hash_to_field(DST_G2, _counter, 0, b"A").1, hash_to_field(DST_G2, _counter, 1, b"A").1);
+                (_x * _x * _x + _b).sqrt().map(|_y| reinterpret::<Jacobian<Fq2>, G2>(&Jacobian { x: _x, y: _y, z: Fq2::one() }))
+            })
+            .next()
+            .unwrap();
+        assert!(!g2_is_valid(&_point));
+        assert!(g2_is_valid(&mul_u256(_point, &U256(G2_COFACTOR))));
+        // 2 is an element of Fq12, but not of order r
+        let mut _two = vec![0u8; 384];
+        _two[31] = 2;
+        assert!(!gt_is_valid(&gt_from_bytes(&_two).unwrap()));
+    }
+
+    #[test]
+    fn hashing_is_deterministic_and_separated() {
+        assert!(hash_to_g1(b"A") == hash_to_g1(b"A"));
+        assert!(hash_to_g2(b"A") == hash_to_g2(b"A"));
+        assert!(hash_to_g1(b"A") != hash_to_g1(b"B"));
+        assert!(hash_to_g2(b"A") != hash_to_g2(b"B"));
+    }
+}
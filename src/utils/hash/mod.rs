@@ -1,13 +1,150 @@
 use blake2_rfc::blake2b::blake2b;
 use rabe_bn::{G1, G2, Fr};
+use RabeError;
+
+mod curve;
+
+pub use self::curve::{hash_to_g1, hash_to_g2, g1_to_bytes, g2_to_bytes, gt_to_bytes, DST_G1, DST_G2};
+pub use self::curve::{fr_to_bytes, fr_from_bytes, g1_to_compressed, g1_from_compressed, g2_to_compressed, g2_from_compressed, gt_from_bytes};
+pub use self::curve::{g1_is_valid, g2_is_valid, gt_is_valid, fr_is_reduced};
+
+/// The way attributes are hashed to the groups, recorded in keys and ciphertexts.
+///
+/// Objects serialized before the version was recorded deserialize as `Legacy`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum HashVersion {
+    /// `g * H(m)`: the discrete log of every hashed value is publicly known
+    #[default]
+    Legacy,
+    /// try-and-increment hash-to-curve with domain separation, see hash_to_g1() and hash_to_g2()
+    V1,
+    /// V1, applied to a domain tag and length-prefixed fields instead of their concatenation,
+    /// see encode_fields()
+    V2,
+}
+
+impl HashVersion {
+    /// The version used for newly generated keys and ciphertexts
+    pub const CURRENT: HashVersion = HashVersion::V2;
+
+    /// hash a domain tag and a tuple of fields to an element of G1, `g` is only used by the legacy hash.
+    /// Before V2 only the concatenation of the fields was hashed.
+    pub fn hash_g1(&self, g: G1, _domain: &str, _fields: &[&[u8]]) -> G1 {
+        match self {
+            HashVersion::Legacy => blake2b_hash_g1(g, &concat_fields(_fields)),
+            HashVersion::V1 => hash_to_g1(concat_fields(_fields).as_bytes()),
+            HashVersion::V2 => hash_fields_g1(_domain, _fields),
+        }
+    }
+
+    /// hash a domain tag and a tuple of fields to an element of G2, `g` is only used by the legacy hash.
+    /// Before V2 only the concatenation of the fields was hashed.
+    pub fn hash_g2(&self, g: G2, _domain: &str, _fields: &[&[u8]]) -> G2 {
+        match self {
+            HashVersion::Legacy => blake2b_hash_g2(g, &concat_fields(_fields)),
+            HashVersion::V1 => hash_to_g2(concat_fields(_fields).as_bytes()),
+            HashVersion::V2 => hash_fields_g2(_domain, _fields),
+        }
+    }
+
+    /// hash a domain tag and a tuple of fields to Fr.
+    /// Before V2 only the concatenation of the fields was hashed.
+    pub fn hash_fr(&self, _domain: &str, _fields: &[&[u8]]) -> Fr {
+        match self {
+            HashVersion::Legacy | HashVersion::V1 => blake2b_hash_fr(&concat_fields(_fields)),
+            HashVersion::V2 => hash_fields_fr(_domain, _fields),
+        }
+    }
+
+    /// Returns the version shared by all given keys, or the current version if there are none.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_versions` - The hash versions of e.g. all public attribute keys used to encrypt
+    ///
+    pub fn common<I: IntoIterator<Item = HashVersion>>(_versions: I) -> Result<HashVersion, RabeError> {
+        let mut _versions = _versions.into_iter();
+        match _versions.next() {
+            None => Ok(HashVersion::CURRENT),
+            Some(_first) => match _versions.find(|_version| *_version != _first) {
+                None => Ok(_first),
+                Some(_other) => Err(RabeError::new(&format!(
+                    "the keys were produced with different hashes ({:?} and {:?}); re-issue the legacy keys",
+                    _first,
+                    _other
+                ))),
+            },
+        }
+    }
+
+    /// Checks that a key and a ciphertext were produced with the same hash.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_ct` - The hash version of the ciphertext
+    ///
+    pub fn check(&self, _ct: HashVersion) -> Result<(), RabeError> {
+        if *self == _ct {
+            Ok(())
+        } else {
+            Err(RabeError::new(&format!(
+                "the key uses the {:?} hash-to-curve, but the ciphertext was produced with {:?}; re-issue the key or re-encrypt",
+                self,
+                _ct
+            )))
+        }
+    }
+}
+
+/// Encodes a domain tag and a tuple of fields injectively: the tag and every field are prefixed
+/// by their length as u64 (big endian), so neither can run into the next one.
+///
+/// # Arguments
+///
+///	* `_domain` - The domain separation tag, e.g. "rabe/ac17/attribute"
+///	* `_fields` - The fields of the hashed tuple
+///
+pub fn encode_fields(_domain: &str, _fields: &[&[u8]]) -> Vec<u8> {
+    let mut _encoded: Vec<u8> = Vec::new();
+    for _part in Some(_domain.as_bytes()).into_iter().chain(_fields.iter().cloned()) {
+        _encoded.extend_from_slice(&(_part.len() as u64).to_be_bytes());
+        _encoded.extend_from_slice(_part);
+    }
+    _encoded
+}
+
+/// hash a domain tag and a tuple of fields to an element of G1, see encode_fields()
+pub fn hash_fields_g1(_domain: &str, _fields: &[&[u8]]) -> G1 {
+    hash_to_g1(&encode_fields(_domain, _fields))
+}
+
+/// hash a domain tag and a tuple of fields to an element of G2, see encode_fields()
+pub fn hash_fields_g2(_domain: &str, _fields: &[&[u8]]) -> G2 {
+    hash_to_g2(&encode_fields(_domain, _fields))
+}
+
+/// hash a domain tag and a tuple of fields to Fr using blake2b, see encode_fields()
+pub fn hash_fields_fr(_domain: &str, _fields: &[&[u8]]) -> Fr {
+    let hash = blake2b(64, &[], &encode_fields(_domain, _fields));
+    Fr::interpret(&pop(hash.as_ref()))
+}
+
+/// The input of the hashes before V2: the plain concatenation of all fields
+fn concat_fields(_fields: &[&[u8]]) -> String {
+    _fields.iter().map(|_field| String::from_utf8_lossy(_field)).collect()
+}
 
 /// hash a String to an element of G1 using blake2b and generator g
+///
+/// Deprecated: the discrete log of the result is known, use hash_to_g1()
 pub fn blake2b_hash_g1(g: G1, data: &String) -> G1 {
     let hash = blake2b(64,&[] , data.as_bytes());
     return g * Fr::interpret(&pop(hash.as_ref()));
 }
 
 /// hash a String to an element of G2 using blake2b and generator g
+///
+/// Deprecated: the discrete log of the result is known, use hash_to_g2()
 pub fn blake2b_hash_g2(g: G2, data: &String) -> G2 {
     let hash = blake2b(64, &[], data.as_bytes());
     return g * Fr::interpret(&pop(hash.as_ref()));
@@ -25,4 +162,26 @@ fn pop(barry: &[u8]) -> [u8; 64] {
         *p = x;
     }
     array
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rabe_bn::Group;
+
+    #[test]
+    fn structured_inputs_do_not_collide() {
+        let g = G1::one();
+        // "A1" || "0" and "A" || "10" concatenate to the same input
+        let _a1 = [&b"A1"[..], b"0"];
+        let _a10 = [&b"A"[..], b"10"];
+        assert!(HashVersion::Legacy.hash_g1(g, "d", &_a1) == HashVersion::Legacy.hash_g1(g, "d", &_a10));
+        assert!(HashVersion::V1.hash_g1(g, "d", &_a1) == HashVersion::V1.hash_g1(g, "d", &_a10));
+        assert_ne!(encode_fields("d", &_a1), encode_fields("d", &_a10));
+        assert!(HashVersion::V2.hash_g1(g, "d", &_a1) != HashVersion::V2.hash_g1(g, "d", &_a10));
+        assert!(HashVersion::V2.hash_fr("d", &_a1) != HashVersion::V2.hash_fr("d", &_a10));
+        // neither can the domain run into the fields
+        assert_ne!(encode_fields("d", &[b"A"]), encode_fields("dA", &[]));
+        assert!(hash_fields_g2("d1", &[b"A"]) != hash_fields_g2("d", &[b"1", b"A"]));
+    }
+}
@@ -0,0 +1,1051 @@
+//! A stable, human-readable JSON representation of keys and ciphertexts.
+//!
+//! Every object implementing `WireFormat` and `Json` is represented as a JSON object with a header
+//! and its fields:
+//!
+//! ```text
+//! {
+//!   "c": "AtbWP6lZ...",
+//!   "c_p": "BY3Tn2Kq...",
+//!   "c_y": [ { "g1": "...", "g2": "...", "str": "A" } ],
+//!   "ct": "dB2e...",
+//!   "hash": "V2",
+//!   "issuer": "k7WQ...",
+//!   "kdf": "HkdfAad",
+//!   "kind": "Ciphertext",
+//!   "policy": { "language": "HumanPolicy", "text": "\"A\"" },
+//!   "scheme": "BSW",
+//!   "suite": "Aes256Ccm",
+//!   "version": 1
+//! }
+//! ```
+//!
+//! * `scheme` is the name of the scheme, see `SchemeId::name()`, `version` the version of this
+//!   representation, currently `JSON_VERSION` (1), and `kind` the `WireKind` of the object
+//! * the fields are named like the fields of the Rust structs without the leading underscore.
+//!   Tuples are objects with named fields, see `JsonTuple`, e.g. a policy is
+//!   `{ "text": ..., "language": ... }`
+//! * `Fr`, `G1`, `G2` and `Gt` are the base64url (RFC 4648, without padding) encoded bytes of
+//!   their wire encoding, see `utils::wire`, e.g. a G1 element is a compressed point of 33 bytes
+//! * byte strings such as the symmetric ciphertext `ct` or the `issuer` fingerprint are base64url
+//!   encoded as well
+//! * `PolicyLanguage`, `HashVersion`, `KdfVersion` and `AeadSuite` are the names of their
+//!   variants, a `Yct14Type` is an object with the name of its variant as the only field
+//!
+//! Parsing is strict: a wrong scheme, version or kind, missing or unknown fields, padded or
+//! non-canonical base64 and invalid group elements are rejected, and parsed objects are checked
+//! with `Validate`. The JSON Schema of every object is generated by `JsonFormat::json_schema()`,
+//! the schemas of all keys and ciphertexts are kept in `src/utils/json/schema`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rabe::schemes::bsw::*;
+//! use rabe::utils::json::JsonFormat;
+//! let (pk, _msk) = setup();
+//! let _text = pk.to_json_string();
+//! assert!(_text.contains(r#""scheme": "BSW""#));
+//! assert!(CpAbePublicKey::from_json_str(&_text).unwrap() == pk);
+//! ```
+use base64::{decode_config, encode_config, URL_SAFE_NO_PAD};
+use rabe_bn::{Fr, G1, G2, Gt};
+use serde_json::{Map, Value, json};
+use utils::{
+    aes::{AeadSuite, KdfVersion},
+    hash::HashVersion,
+    policy::pest::PolicyLanguage,
+    wire::{Wire, WireFormat, Reader, Writer}
+};
+use RabeError;
+
+/// The current version of the JSON representation
+pub const JSON_VERSION: u64 = 1;
+/// The JSON Schema dialect of the generated schemas
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+/// The names of the elements of a policy `(String, PolicyLanguage)`
+pub const POLICY_FIELDS: &[&str] = &["text", "language"];
+
+/// A value with a JSON representation
+pub trait Json: Sized {
+    /// The JSON representation of self
+    fn to_json(&self) -> Value;
+    /// Parses the JSON representation of a value, failing on malformed input
+    fn from_json(_value: &Value) -> Result<Self, RabeError>;
+    /// The JSON Schema of the representation, referring to the definitions of definitions()
+    fn schema() -> Value;
+}
+
+/// A tuple, represented as an object whose fields are named by the containing struct
+pub trait JsonTuple: Sized {
+    /// The JSON representation of self, with a name for every element
+    fn to_json_named(&self, _names: &[&str]) -> Value;
+    /// Parses the JSON representation of a tuple with a name for every element
+    fn from_json_named(_value: &Value, _names: &[&str]) -> Result<Self, RabeError>;
+    /// The JSON Schema of the representation with a name for every element
+    fn schema_named(_names: &[&str]) -> Value;
+}
+
+/// A top-level object, represented with a header recording the scheme, version and kind
+pub trait JsonFormat: Json + WireFormat {
+    /// The JSON representation of self including the header
+    fn to_json_value(&self) -> Value {
+        let mut _value = self.to_json();
+        _value["scheme"] = json!(Self::SCHEME.name());
+        _value["version"] = json!(JSON_VERSION);
+        _value["kind"] = json!(format!("{:?}", Self::KIND));
+        _value
+    }
+
+    /// The pretty-printed JSON representation of self including the header
+    fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(&self.to_json_value()).unwrap()
+    }
+
+    /// Parses an object represented by to_json_value(), checking the header, rejecting unknown
+    /// fields and validating the object
+    ///
+    /// # Arguments
+    ///
+    ///	* `_value` - The JSON representation
+    ///
+    fn from_json_value(_value: &Value) -> Result<Self, RabeError> {
+        let mut _reader = JsonReader::new(_value)?;
+        let _scheme: String = _reader.field("scheme")?;
+        if _scheme != Self::SCHEME.name() {
+            return Err(RabeError::new(&format!("json: expected a {} object", Self::SCHEME)));
+        }
+        match _reader.take("version")?.as_u64() {
+            Some(JSON_VERSION) => {},
+            _ => return Err(RabeError::new("json: unsupported version")),
+        }
+        let _kind: String = _reader.field("kind")?;
+        if _kind != format!("{:?}", Self::KIND) {
+            return Err(RabeError::new(&format!("json: expected a {:?}", Self::KIND)));
+        }
+        let _object = Self::from_json(&_reader.into_value())?;
+        _object.validate()?;
+        Ok(_object)
+    }
+
+    /// Parses an object represented by to_json_string()
+    ///
+    /// # Arguments
+    ///
+    ///	* `_text` - The JSON text
+    ///
+    fn from_json_str(_text: &str) -> Result<Self, RabeError> {
+        Self::from_json_value(&serde_json::from_str(_text)?)
+    }
+
+    /// The JSON Schema of the representation including the header and all definitions
+    fn json_schema() -> Value {
+        let mut _schema = Self::schema();
+        _schema["properties"]["scheme"] = constant(Self::SCHEME.name());
+        _schema["properties"]["version"] = constant(JSON_VERSION);
+        _schema["properties"]["kind"] = constant(format!("{:?}", Self::KIND));
+        if let Some(_required) = _schema["required"].as_array_mut() {
+            _required.extend(vec![json!("scheme"), json!("version"), json!("kind")]);
+        }
+        _schema["$schema"] = json!(JSON_SCHEMA_DIALECT);
+        _schema["title"] = json!(format!("{} {:?}", Self::SCHEME, Self::KIND));
+        _schema["$defs"] = definitions();
+        _schema
+    }
+}
+
+impl<T: Json + WireFormat> JsonFormat for T {}
+
+/// Implements `Json` for a struct with the listed fields, each one named like the field without
+/// the leading underscore. A tuple is given with the names of its elements, `tuple(NAMES)`, and
+/// a vector of tuples with `tuples(NAMES)`.
+///
+/// ```ignore
+/// impl_json!(CpAbeCiphertext { _policy: tuple(POLICY_FIELDS), _c, _c_p, _c_y, _ct, _hash, _kdf, _suite, _issuer });
+/// ```
+macro_rules! impl_json {
+    ($type:ident { $($field:ident $(: $kind:ident($names:expr))?),+ $(,)? }) => {
+        impl $crate::utils::json::Json for $type {
+            fn to_json(&self) -> ::serde_json::Value {
+                let mut _writer = $crate::utils::json::JsonWriter::new();
+                $(impl_json!(@write _writer, self, $field $(, $kind($names))?);)+
+                _writer.into_value()
+            }
+
+            fn from_json(_value: &::serde_json::Value) -> Result<$type, $crate::RabeError> {
+                let mut _reader = $crate::utils::json::JsonReader::new(_value)?;
+                let _object = $type {
+                    $($field: impl_json!(@read _reader, $field $(, $kind($names))?),)+
+                };
+                _reader.finish()?;
+                Ok(_object)
+            }
+
+            fn schema() -> ::serde_json::Value {
+                let mut _schema = $crate::utils::json::SchemaWriter::new();
+                $(impl_json!(@schema _schema, $type, $field $(, $kind($names))?);)+
+                _schema.into_value()
+            }
+        }
+    };
+    (@name $field:ident) => {
+        stringify!($field).trim_start_matches('_')
+    };
+    (@write $writer:ident, $self:ident, $field:ident) => {
+        $writer.field(impl_json!(@name $field), &$self.$field);
+    };
+    (@write $writer:ident, $self:ident, $field:ident, tuple($names:expr)) => {
+        $writer.tuple(impl_json!(@name $field), &$self.$field, $names);
+    };
+    (@write $writer:ident, $self:ident, $field:ident, tuples($names:expr)) => {
+        $writer.tuples(impl_json!(@name $field), &$self.$field, $names);
+    };
+    (@read $reader:ident, $field:ident) => {
+        $reader.field(impl_json!(@name $field))?
+    };
+    (@read $reader:ident, $field:ident, tuple($names:expr)) => {
+        $reader.tuple(impl_json!(@name $field), $names)?
+    };
+    (@read $reader:ident, $field:ident, tuples($names:expr)) => {
+        $reader.tuples(impl_json!(@name $field), $names)?
+    };
+    // the type of a field is inferred from a function returning it
+    (@schema $schema:ident, $type:ident, $field:ident) => {{
+        fn field_schema<S, T: $crate::utils::json::Json>(_schema: &mut $crate::utils::json::SchemaWriter, _name: &str, _field: fn(&S) -> &T) {
+            _schema.field::<T>(_name);
+        }
+        field_schema(&mut $schema, impl_json!(@name $field), |_object: &$type| &_object.$field);
+    }};
+    (@schema $schema:ident, $type:ident, $field:ident, tuple($names:expr)) => {{
+        fn tuple_schema<S, T: $crate::utils::json::JsonTuple>(_schema: &mut $crate::utils::json::SchemaWriter, _name: &str, _names: &[&str], _field: fn(&S) -> &T) {
+            _schema.tuple::<T>(_name, _names);
+        }
+        tuple_schema(&mut $schema, impl_json!(@name $field), $names, |_object: &$type| &_object.$field);
+    }};
+    (@schema $schema:ident, $type:ident, $field:ident, tuples($names:expr)) => {{
+        fn tuples_schema<S, T: $crate::utils::json::JsonTuple>(_schema: &mut $crate::utils::json::SchemaWriter, _name: &str, _names: &[&str], _field: fn(&S) -> &Vec<T>) {
+            _schema.tuples::<T>(_name, _names);
+        }
+        tuples_schema(&mut $schema, impl_json!(@name $field), $names, |_object: &$type| &_object.$field);
+    }};
+}
+
+/// Collects the fields of an object
+#[derive(Default)]
+pub struct JsonWriter {
+    _fields: Map<String, Value>,
+}
+
+impl JsonWriter {
+    /// Creates an empty JsonWriter
+    pub fn new() -> JsonWriter {
+        JsonWriter { _fields: Map::new() }
+    }
+
+    /// Adds a field
+    pub fn field<T: Json>(&mut self, _name: &str, _value: &T) -> &mut JsonWriter {
+        self._fields.insert(_name.to_string(), _value.to_json());
+        self
+    }
+
+    /// Adds a tuple as an object with named fields
+    pub fn tuple<T: JsonTuple>(&mut self, _name: &str, _value: &T, _names: &[&str]) -> &mut JsonWriter {
+        self._fields.insert(_name.to_string(), _value.to_json_named(_names));
+        self
+    }
+
+    /// Adds a vector of tuples as an array of objects with named fields
+    pub fn tuples<T: JsonTuple>(&mut self, _name: &str, _values: &[T], _names: &[&str]) -> &mut JsonWriter {
+        let _values = _values.iter().map(|_value| _value.to_json_named(_names)).collect();
+        self._fields.insert(_name.to_string(), Value::Array(_values));
+        self
+    }
+
+    /// Returns the collected object
+    pub fn into_value(self) -> Value {
+        Value::Object(self._fields)
+    }
+}
+
+/// Reads the fields of an object
+pub struct JsonReader {
+    _fields: Map<String, Value>,
+}
+
+impl JsonReader {
+    /// Creates a JsonReader over an object
+    pub fn new(_value: &Value) -> Result<JsonReader, RabeError> {
+        match _value {
+            Value::Object(_fields) => Ok(JsonReader { _fields: _fields.clone() }),
+            _ => Err(RabeError::new("json: expected an object")),
+        }
+    }
+
+    /// Takes the raw value of a field
+    pub fn take(&mut self, _name: &str) -> Result<Value, RabeError> {
+        self._fields
+            .remove(_name)
+            .ok_or_else(|| RabeError::new(&format!("json: missing field {}", _name)))
+    }
+
+    /// Parses a field
+    pub fn field<T: Json>(&mut self, _name: &str) -> Result<T, RabeError> {
+        let _value = self.take(_name)?;
+        T::from_json(&_value).map_err(|_error| in_field(_error, _name))
+    }
+
+    /// Parses a tuple represented as an object with named fields
+    pub fn tuple<T: JsonTuple>(&mut self, _name: &str, _names: &[&str]) -> Result<T, RabeError> {
+        let _value = self.take(_name)?;
+        T::from_json_named(&_value, _names).map_err(|_error| in_field(_error, _name))
+    }
+
+    /// Parses a vector of tuples represented as an array of objects with named fields
+    pub fn tuples<T: JsonTuple>(&mut self, _name: &str, _names: &[&str]) -> Result<Vec<T>, RabeError> {
+        match self.take(_name)? {
+            Value::Array(_values) => _values
+                .iter()
+                .map(|_value| T::from_json_named(_value, _names))
+                .collect::<Result<Vec<T>, RabeError>>()
+                .map_err(|_error| in_field(_error, _name)),
+            _ => Err(in_field(RabeError::new("json: expected an array"), _name)),
+        }
+    }
+
+    /// Fails if any field is left
+    pub fn finish(&self) -> Result<(), RabeError> {
+        match self._fields.keys().next() {
+            None => Ok(()),
+            Some(_name) => Err(RabeError::new(&format!("json: unknown field {}", _name))),
+        }
+    }
+
+    /// Returns the fields that were not read
+    pub fn into_value(self) -> Value {
+        Value::Object(self._fields)
+    }
+}
+
+/// Collects the JSON Schema of an object
+#[derive(Default)]
+pub struct SchemaWriter {
+    _properties: Map<String, Value>,
+    _required: Vec<Value>,
+}
+
+impl SchemaWriter {
+    /// Creates the schema of an object without fields
+    pub fn new() -> SchemaWriter {
+        SchemaWriter { _properties: Map::new(), _required: Vec::new() }
+    }
+
+    /// Adds a required field
+    pub fn field<T: Json>(&mut self, _name: &str) -> &mut SchemaWriter {
+        self.property(_name, T::schema())
+    }
+
+    /// Adds a required tuple with named fields
+    pub fn tuple<T: JsonTuple>(&mut self, _name: &str, _names: &[&str]) -> &mut SchemaWriter {
+        self.property(_name, T::schema_named(_names))
+    }
+
+    /// Adds a required vector of tuples with named fields
+    pub fn tuples<T: JsonTuple>(&mut self, _name: &str, _names: &[&str]) -> &mut SchemaWriter {
+        self.property(_name, array(T::schema_named(_names)))
+    }
+
+    fn property(&mut self, _name: &str, _schema: Value) -> &mut SchemaWriter {
+        self._properties.insert(_name.to_string(), _schema);
+        self._required.push(Value::String(_name.to_string()));
+        self
+    }
+
+    /// Returns the schema of the object
+    pub fn into_value(self) -> Value {
+        json!({
+            "type": "object",
+            "properties": self._properties,
+            "required": self._required,
+            "additionalProperties": false
+        })
+    }
+}
+
+/// The definitions referred to by the schemas of the group elements, byte strings and enums
+pub fn definitions() -> Value {
+    json!({
+        "Fr": encoded("A scalar: 32 byte big-endian integer smaller than the group order", 32),
+        "G1": encoded("A G1 element: 33 byte compressed point", 33),
+        "G2": encoded("A G2 element: 65 byte compressed point", 65),
+        "Gt": encoded("A Gt element: the 384 bytes of its twelve coefficients", 384),
+        "Bytes": base64url("A byte string"),
+        "PolicyLanguage": variants::<PolicyLanguage>(),
+        "HashVersion": variants::<HashVersion>(),
+        "KdfVersion": variants::<KdfVersion>(),
+        "AeadSuite": variants::<AeadSuite>()
+    })
+}
+
+/// A reference to one of the definitions()
+pub fn reference(_name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", _name) })
+}
+
+/// The schema of an array of values of a schema
+pub fn array(_items: Value) -> Value {
+    json!({ "type": "array", "items": _items })
+}
+
+fn constant<T: Into<Value>>(_value: T) -> Value {
+    json!({ "const": _value.into() })
+}
+
+fn base64url(_description: &str) -> Value {
+    json!({
+        "type": "string",
+        "contentEncoding": "base64url",
+        "description": _description,
+        "pattern": "^[A-Za-z0-9_-]*$"
+    })
+}
+
+// a base64url string of exactly `_length` bytes
+fn encoded(_description: &str, _length: usize) -> Value {
+    let _characters = (4 * _length).div_ceil(3);
+    let mut _schema = base64url(_description);
+    _schema["minLength"] = json!(_characters);
+    _schema["maxLength"] = json!(_characters);
+    _schema
+}
+
+fn in_field(_error: RabeError, _name: &str) -> RabeError {
+    RabeError::new(&format!("{} in field {}", _error.details, _name))
+}
+
+/// Encodes bytes as base64url without padding
+pub fn encode_bytes(_bytes: &[u8]) -> Value {
+    Value::String(encode_config(_bytes, URL_SAFE_NO_PAD))
+}
+
+/// Decodes base64url without padding, rejecting non-canonical input
+pub fn decode_bytes(_value: &Value) -> Result<Vec<u8>, RabeError> {
+    let _text = _value.as_str().ok_or_else(|| RabeError::new("json: expected a string"))?;
+    let _bytes = decode_config(_text, URL_SAFE_NO_PAD)?;
+    if encode_config(&_bytes, URL_SAFE_NO_PAD) != _text {
+        return Err(RabeError::new("json: non-canonical base64url"));
+    }
+    Ok(_bytes)
+}
+
+// group elements are represented by their wire encoding
+fn element_to_json<T: Wire>(_value: &T) -> Value {
+    let mut _writer = Writer::new();
+    _writer.write(_value);
+    encode_bytes(&_writer.into_bytes())
+}
+
+fn element_from_json<T: Wire>(_value: &Value) -> Result<T, RabeError> {
+    let _bytes = decode_bytes(_value)?;
+    let mut _reader = Reader::new(&_bytes);
+    let _element = _reader.read()?;
+    _reader.finish()?;
+    Ok(_element)
+}
+
+impl Json for Fr {
+    fn to_json(&self) -> Value {
+        element_to_json(self)
+    }
+
+    fn from_json(_value: &Value) -> Result<Fr, RabeError> {
+        element_from_json(_value)
+    }
+
+    fn schema() -> Value {
+        reference("Fr")
+    }
+}
+
+impl Json for G1 {
+    fn to_json(&self) -> Value {
+        element_to_json(self)
+    }
+
+    fn from_json(_value: &Value) -> Result<G1, RabeError> {
+        element_from_json(_value)
+    }
+
+    fn schema() -> Value {
+        reference("G1")
+    }
+}
+
+impl Json for G2 {
+    fn to_json(&self) -> Value {
+        element_to_json(self)
+    }
+
+    fn from_json(_value: &Value) -> Result<G2, RabeError> {
+        element_from_json(_value)
+    }
+
+    fn schema() -> Value {
+        reference("G2")
+    }
+}
+
+impl Json for Gt {
+    fn to_json(&self) -> Value {
+        element_to_json(self)
+    }
+
+    fn from_json(_value: &Value) -> Result<Gt, RabeError> {
+        element_from_json(_value)
+    }
+
+    fn schema() -> Value {
+        reference("Gt")
+    }
+}
+
+impl Json for String {
+    fn to_json(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn from_json(_value: &Value) -> Result<String, RabeError> {
+        match _value {
+            Value::String(_string) => Ok(_string.clone()),
+            _ => Err(RabeError::new("json: expected a string")),
+        }
+    }
+
+    fn schema() -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl Json for Vec<u8> {
+    fn to_json(&self) -> Value {
+        encode_bytes(self)
+    }
+
+    fn from_json(_value: &Value) -> Result<Vec<u8>, RabeError> {
+        decode_bytes(_value)
+    }
+
+    fn schema() -> Value {
+        reference("Bytes")
+    }
+}
+
+impl<T: Json> Json for Vec<T> {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(|_value| _value.to_json()).collect())
+    }
+
+    fn from_json(_value: &Value) -> Result<Vec<T>, RabeError> {
+        match _value {
+            Value::Array(_values) => _values.iter().map(T::from_json).collect(),
+            _ => Err(RabeError::new("json: expected an array")),
+        }
+    }
+
+    fn schema() -> Value {
+        array(T::schema())
+    }
+}
+
+impl<T: Json> Json for Option<T> {
+    fn to_json(&self) -> Value {
+        match self {
+            None => Value::Null,
+            Some(_value) => _value.to_json(),
+        }
+    }
+
+    fn from_json(_value: &Value) -> Result<Option<T>, RabeError> {
+        match _value {
+            Value::Null => Ok(None),
+            _ => Ok(Some(T::from_json(_value)?)),
+        }
+    }
+
+    fn schema() -> Value {
+        json!({ "anyOf": [T::schema(), { "type": "null" }] })
+    }
+}
+
+impl<A: Json, B: Json> JsonTuple for (A, B) {
+    fn to_json_named(&self, _names: &[&str]) -> Value {
+        let mut _writer = JsonWriter::new();
+        _writer.field(_names[0], &self.0).field(_names[1], &self.1);
+        _writer.into_value()
+    }
+
+    fn from_json_named(_value: &Value, _names: &[&str]) -> Result<(A, B), RabeError> {
+        let mut _reader = JsonReader::new(_value)?;
+        let _tuple = (_reader.field(_names[0])?, _reader.field(_names[1])?);
+        _reader.finish()?;
+        Ok(_tuple)
+    }
+
+    fn schema_named(_names: &[&str]) -> Value {
+        let mut _schema = SchemaWriter::new();
+        _schema.field::<A>(_names[0]).field::<B>(_names[1]);
+        _schema.into_value()
+    }
+}
+
+impl<A: Json, B: Json, C: Json> JsonTuple for (A, B, C) {
+    fn to_json_named(&self, _names: &[&str]) -> Value {
+        let mut _writer = JsonWriter::new();
+        _writer.field(_names[0], &self.0).field(_names[1], &self.1).field(_names[2], &self.2);
+        _writer.into_value()
+    }
+
+    fn from_json_named(_value: &Value, _names: &[&str]) -> Result<(A, B, C), RabeError> {
+        let mut _reader = JsonReader::new(_value)?;
+        let _tuple = (_reader.field(_names[0])?, _reader.field(_names[1])?, _reader.field(_names[2])?);
+        _reader.finish()?;
+        Ok(_tuple)
+    }
+
+    fn schema_named(_names: &[&str]) -> Value {
+        let mut _schema = SchemaWriter::new();
+        _schema.field::<A>(_names[0]).field::<B>(_names[1]).field::<C>(_names[2]);
+        _schema.into_value()
+    }
+}
+
+impl<A: Json, B: Json, C: Json, D: Json> JsonTuple for (A, B, C, D) {
+    fn to_json_named(&self, _names: &[&str]) -> Value {
+        let mut _writer = JsonWriter::new();
+        _writer.field(_names[0], &self.0).field(_names[1], &self.1).field(_names[2], &self.2).field(_names[3], &self.3);
+        _writer.into_value()
+    }
+
+    fn from_json_named(_value: &Value, _names: &[&str]) -> Result<(A, B, C, D), RabeError> {
+        let mut _reader = JsonReader::new(_value)?;
+        let _tuple = (_reader.field(_names[0])?, _reader.field(_names[1])?, _reader.field(_names[2])?, _reader.field(_names[3])?);
+        _reader.finish()?;
+        Ok(_tuple)
+    }
+
+    fn schema_named(_names: &[&str]) -> Value {
+        let mut _schema = SchemaWriter::new();
+        _schema.field::<A>(_names[0]).field::<B>(_names[1]).field::<C>(_names[2]).field::<D>(_names[3]);
+        _schema.into_value()
+    }
+}
+
+impl<A: Json, B: Json, C: Json, D: Json, E: Json, F: Json> JsonTuple for (A, B, C, D, E, F) {
+    fn to_json_named(&self, _names: &[&str]) -> Value {
+        let mut _writer = JsonWriter::new();
+        _writer
+            .field(_names[0], &self.0)
+            .field(_names[1], &self.1)
+            .field(_names[2], &self.2)
+            .field(_names[3], &self.3)
+            .field(_names[4], &self.4)
+            .field(_names[5], &self.5);
+        _writer.into_value()
+    }
+
+    fn from_json_named(_value: &Value, _names: &[&str]) -> Result<(A, B, C, D, E, F), RabeError> {
+        let mut _reader = JsonReader::new(_value)?;
+        let _tuple = (
+            _reader.field(_names[0])?,
+            _reader.field(_names[1])?,
+            _reader.field(_names[2])?,
+            _reader.field(_names[3])?,
+            _reader.field(_names[4])?,
+            _reader.field(_names[5])?,
+        );
+        _reader.finish()?;
+        Ok(_tuple)
+    }
+
+    fn schema_named(_names: &[&str]) -> Value {
+        let mut _schema = SchemaWriter::new();
+        _schema
+            .field::<A>(_names[0])
+            .field::<B>(_names[1])
+            .field::<C>(_names[2])
+            .field::<D>(_names[3])
+            .field::<E>(_names[4])
+            .field::<F>(_names[5]);
+        _schema.into_value()
+    }
+}
+
+/// An enum without fields, represented by the name of its variant
+pub trait JsonVariant: Sized + Copy + PartialEq + 'static {
+    /// The name of the enum in definitions()
+    const NAME: &'static str;
+    /// All variants and their names
+    fn variants() -> Vec<(&'static str, Self)>;
+}
+
+impl<T: JsonVariant> Json for T {
+    fn to_json(&self) -> Value {
+        let _name = T::variants()
+            .into_iter()
+            .find(|(_, _variant)| _variant == self)
+            .map(|(_name, _)| _name)
+            .unwrap();
+        Value::String(_name.to_string())
+    }
+
+    fn from_json(_value: &Value) -> Result<T, RabeError> {
+        let _name = _value.as_str().ok_or_else(|| RabeError::new("json: expected a string"))?;
+        T::variants()
+            .into_iter()
+            .find(|(_variant, _)| *_variant == _name)
+            .map(|(_, _variant)| _variant)
+            .ok_or_else(|| RabeError::new(&format!("json: unknown {} {}", T::NAME, _name)))
+    }
+
+    fn schema() -> Value {
+        reference(T::NAME)
+    }
+}
+
+// the schema listing the names of all variants of an enum
+fn variants<T: JsonVariant>() -> Value {
+    let _names: Vec<&str> = T::variants().into_iter().map(|(_name, _)| _name).collect();
+    json!({ "enum": _names })
+}
+
+impl JsonVariant for PolicyLanguage {
+    const NAME: &'static str = "PolicyLanguage";
+
+    fn variants() -> Vec<(&'static str, PolicyLanguage)> {
+        vec![("JsonPolicy", PolicyLanguage::JsonPolicy), ("HumanPolicy", PolicyLanguage::HumanPolicy)]
+    }
+}
+
+impl JsonVariant for HashVersion {
+    const NAME: &'static str = "HashVersion";
+
+    fn variants() -> Vec<(&'static str, HashVersion)> {
+        vec![("Legacy", HashVersion::Legacy), ("V1", HashVersion::V1), ("V2", HashVersion::V2)]
+    }
+}
+
+impl JsonVariant for KdfVersion {
+    const NAME: &'static str = "KdfVersion";
+
+    fn variants() -> Vec<(&'static str, KdfVersion)> {
+        vec![("Legacy", KdfVersion::Legacy), ("Hkdf", KdfVersion::Hkdf), ("HkdfAad", KdfVersion::HkdfAad)]
+    }
+}
+
+impl JsonVariant for AeadSuite {
+    const NAME: &'static str = "AeadSuite";
+
+    fn variants() -> Vec<(&'static str, AeadSuite)> {
+        vec![
+            ("Aes256Ccm", AeadSuite::Aes256Ccm),
+            ("Aes256Gcm", AeadSuite::Aes256Gcm),
+            ("ChaCha20Poly1305", AeadSuite::ChaCha20Poly1305),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use schemes::{
+        ac17,
+        aw11,
+        bdabe,
+        bsw,
+        lsw,
+        mke08,
+        yct14,
+        CpAbe,
+        KpAbe,
+        MultiAuthorityAbe,
+        ac17::{Ac17Cp, Ac17Kp},
+        aw11::Aw11,
+        bdabe::Bdabe,
+        bsw::Bsw,
+        lsw::Lsw,
+        mke08::Mke08,
+        yct14::Yct14
+    };
+
+    const PLAINTEXT: &[u8] = b"dance like no one's watching, encrypt like everyone is!";
+    const POLICY: &str = r#""A" and ("B" or "C")"#;
+
+    fn attributes(_names: &[&str]) -> Vec<String> {
+        _names.iter().map(|_name| _name.to_string()).collect()
+    }
+
+    // a minimal JSON Schema validator for the keywords the generated schemas use
+    fn conforms(_value: &Value, _schema: &Value, _defs: &Value) -> bool {
+        if let Some(_ref) = _schema["$ref"].as_str() {
+            return conforms(_value, &_defs[_ref.trim_start_matches("#/$defs/")], _defs);
+        }
+        if let Some(_const) = _schema.get("const") {
+            return _value == _const;
+        }
+        if let Some(_enum) = _schema["enum"].as_array() {
+            return _enum.contains(_value);
+        }
+        if let Some(_any) = _schema["anyOf"].as_array() {
+            return _any.iter().any(|_schema| conforms(_value, _schema, _defs));
+        }
+        if let Some(_one) = _schema["oneOf"].as_array() {
+            return _one.iter().filter(|_schema| conforms(_value, _schema, _defs)).count() == 1;
+        }
+        match _schema["type"].as_str() {
+            Some("null") => _value.is_null(),
+            Some("string") => match _value.as_str() {
+                Some(_string) => {
+                    let _length = _string.chars().count() as u64;
+                    _schema["minLength"].as_u64().is_none_or(|_min| _length >= _min)
+                        && _schema["maxLength"].as_u64().is_none_or(|_max| _length <= _max)
+                        && (_schema.get("pattern").is_none()
+                            || _string.chars().all(|_c| _c.is_ascii_alphanumeric() || _c == '-' || _c == '_'))
+                },
+                None => false,
+            },
+            Some("array") => match _value.as_array() {
+                Some(_items) => _items.iter().all(|_item| conforms(_item, &_schema["items"], _defs)),
+                None => false,
+            },
+            Some("object") => match _value.as_object() {
+                Some(_fields) => {
+                    let _properties = _schema["properties"].as_object().unwrap();
+                    _schema["required"].as_array().unwrap().iter().all(|_name| _fields.contains_key(_name.as_str().unwrap()))
+                        && _fields.iter().all(|(_name, _field)| match _properties.get(_name) {
+                            Some(_property) => conforms(_field, _property, _defs),
+                            None => false,
+                        })
+                },
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    // checks that a value conforms to its schema and survives a round trip
+    fn round_trip<T: JsonFormat + PartialEq>(_object: &T) -> T {
+        let _text = _object.to_json_string();
+        let _schema = T::json_schema();
+        assert!(conforms(&serde_json::from_str(&_text).unwrap(), &_schema, &_schema["$defs"]), "{} does not conform to its schema", _text);
+        let _parsed = T::from_json_str(&_text).unwrap();
+        assert!(_parsed == *_object);
+        _parsed
+    }
+
+    fn round_trip_cp<S: CpAbe>(_scheme: S, _attributes: &[String])
+    where S::PublicKey: JsonFormat + PartialEq, S::MasterKey: JsonFormat + PartialEq, S::SecretKey: JsonFormat + PartialEq,
+          S::Ciphertext: JsonFormat + PartialEq, S::Encapsulation: JsonFormat + PartialEq {
+        let (_pk, _msk) = _scheme.setup();
+        let _pk = round_trip(&_pk);
+        let _msk = round_trip(&_msk);
+        let _sk = round_trip(&_scheme.keygen(&_pk, &_msk, _attributes).unwrap());
+        let _ct = round_trip(&_scheme.encrypt(&_pk, POLICY, PolicyLanguage::HumanPolicy, PLAINTEXT).unwrap());
+        assert_eq!(_scheme.decrypt(&_sk, &_ct).unwrap(), PLAINTEXT.to_vec());
+        let (_key, _enc) = _scheme.encapsulate(&_pk, POLICY, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(_scheme.decapsulate(&_sk, &round_trip(&_enc)).unwrap().as_bytes(), _key.as_bytes());
+    }
+
+    fn round_trip_kp<S: KpAbe>(_scheme: S, _attributes: &[String])
+    where S::PublicKey: JsonFormat + PartialEq, S::MasterKey: JsonFormat + PartialEq, S::SecretKey: JsonFormat + PartialEq,
+          S::Ciphertext: JsonFormat + PartialEq, S::Encapsulation: JsonFormat + PartialEq {
+        let (_pk, _msk) = _scheme.setup();
+        let _pk = round_trip(&_pk);
+        let _msk = round_trip(&_msk);
+        let _sk = round_trip(&_scheme.keygen(&_pk, &_msk, POLICY, PolicyLanguage::HumanPolicy).unwrap());
+        let _ct = round_trip(&_scheme.encrypt(&_pk, _attributes, PLAINTEXT).unwrap());
+        assert_eq!(_scheme.decrypt(&_sk, &_ct).unwrap(), PLAINTEXT.to_vec());
+        let (_key, _enc) = _scheme.encapsulate(&_pk, _attributes).unwrap();
+        assert_eq!(_scheme.decapsulate(&_sk, &round_trip(&_enc)).unwrap().as_bytes(), _key.as_bytes());
+    }
+
+    fn round_trip_ma<S: MultiAuthorityAbe>(_scheme: S)
+    where S::GlobalKey: JsonFormat + PartialEq, S::AuthorityKey: JsonFormat + PartialEq, S::PublicAttributeKey: JsonFormat + PartialEq,
+          S::SecretKey: JsonFormat + PartialEq, S::Ciphertext: JsonFormat + PartialEq, S::Encapsulation: JsonFormat + PartialEq {
+        let (_gk, _mk) = _scheme.setup();
+        let _gk = round_trip(&_gk);
+        let _aa1 = _scheme.authgen(&_gk, &_mk, "AA1", &attributes(&["AA1::A", "AA1::B", "AA1::C"])).unwrap();
+        let _aa1 = round_trip(&_aa1);
+        let mut _pks = Vec::new();
+        for _attribute in &["AA1::A", "AA1::B", "AA1::C"] {
+            _pks.push(round_trip(&_scheme.request_attribute_pk(&_gk, &_aa1, _attribute).unwrap()));
+        }
+        let mut _sk = _scheme.keygen(&_gk, &_mk, &_aa1, "bob").unwrap();
+        for _attribute in &["AA1::A", "AA1::B"] {
+            _scheme.request_attribute_sk(&_gk, &_aa1, &mut _sk, _attribute).unwrap();
+        }
+        let _sk = round_trip(&_sk);
+        let _policy = r#""AA1::A" and ("AA1::B" or "AA1::C")"#;
+        let _ct = round_trip(&_scheme.encrypt(&_gk, &_pks, _policy, PolicyLanguage::HumanPolicy, PLAINTEXT).unwrap());
+        assert_eq!(_scheme.decrypt(&_gk, &_sk, &_ct).unwrap(), PLAINTEXT.to_vec());
+        let (_key, _enc) = _scheme.encapsulate(&_gk, &_pks, _policy, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(_scheme.decapsulate(&_gk, &_sk, &round_trip(&_enc)).unwrap().as_bytes(), _key.as_bytes());
+    }
+
+    #[test]
+    fn round_trips() {
+        let _attributes = attributes(&["A", "B"]);
+        round_trip_cp(Ac17Cp, &_attributes);
+        round_trip_kp(Ac17Kp, &_attributes);
+        round_trip_cp(Bsw, &_attributes);
+        round_trip_kp(Lsw, &_attributes);
+        round_trip_kp(Yct14::try_new(attributes(&["A", "B", "C"])).unwrap(), &_attributes);
+        round_trip_ma(Aw11);
+        round_trip_ma(Bdabe);
+        round_trip_ma(Mke08);
+        let (_, _msk) = Bdabe.setup();
+        round_trip(&_msk);
+        let (_, _msk) = Mke08.setup();
+        round_trip(&_msk);
+    }
+
+    #[test]
+    fn named_fields() {
+        let _gk = aw11::setup();
+        let (_pk, _msk) = aw11::authgen(&_gk, &attributes(&["A", "B"])).unwrap();
+        let _ct = aw11::encrypt(&_gk, &vec![_pk], &String::from(r#""A" or "B""#), PolicyLanguage::HumanPolicy, PLAINTEXT).unwrap();
+        let _json = _ct.to_json_value();
+        assert_eq!(_json["scheme"], json!("AW11"));
+        assert_eq!(_json["version"], json!(JSON_VERSION));
+        assert_eq!(_json["kind"], json!("Ciphertext"));
+        assert_eq!(_json["policy"], json!({ "text": r#""A" or "B""#, "language": "HumanPolicy" }));
+        assert_eq!(_json["c"][0]["attribute"], json!("A"));
+        assert_eq!(_json["c"][0]["c_2"].as_str().unwrap().len(), 87);
+        assert_eq!(_json["c_0"].as_str().unwrap().len(), 512);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let (_pk, _msk) = bsw::setup();
+        let _json = _pk.to_json_value();
+        let _parse = |_edit: &dyn Fn(&mut Value)| {
+            let mut _value = _json.clone();
+            _edit(&mut _value);
+            bsw::CpAbePublicKey::from_json_value(&_value)
+        };
+        assert!(_parse(&|_| {}).is_ok());
+        // wrong header
+        assert!(_parse(&|_value| _value["scheme"] = json!("LSW")).is_err());
+        assert!(_parse(&|_value| _value["kind"] = json!("MasterKey")).is_err());
+        assert!(_parse(&|_value| _value["version"] = json!(JSON_VERSION + 1)).is_err());
+        assert!(lsw::KpAbePublicKey::from_json_value(&_json).is_err());
+        assert!(bsw::CpAbeMasterKey::from_json_value(&_json).is_err());
+        // missing and unknown fields
+        assert!(_parse(&|_value| { _value.as_object_mut().unwrap().remove("h"); }).is_err());
+        assert!(_parse(&|_value| _value["_h"] = _value["h"].clone()).is_err());
+        // padded, non-canonical and invalid elements
+        assert!(_parse(&|_value| _value["g1"] = json!(format!("{}=", _value["g1"].as_str().unwrap()))).is_err());
+        assert!(_parse(&|_value| _value["g1"] = _value["g2"].clone()).is_err());
+        let mut _invalid = decode_bytes(&_json["g1"]).unwrap();
+        _invalid[0] = 0x05;
+        assert!(_parse(&|_value| _value["g1"] = encode_bytes(&_invalid)).is_err());
+        assert_eq!(decode_bytes(&json!("AA")).unwrap(), vec![0u8]);
+        assert!(decode_bytes(&json!("AB")).is_err());
+        // parsed objects are validated
+        let _sk = bsw::keygen(&_pk, &_msk, &attributes(&["A", "B"])).unwrap();
+        let mut _value = _sk.to_json_value();
+        _value["d_j"][1] = _value["d_j"][0].clone();
+        assert!(bsw::CpAbeSecretKey::from_json_value(&_value).err().unwrap().validation_error().is_some());
+    }
+
+    fn schema_path(_name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/utils/json/schema").join(_name)
+    }
+
+    // the file name of the schema of an object, e.g. bsw_secret_key.json
+    fn schema<T: JsonFormat>() -> (String, String) {
+        let mut _kind = String::new();
+        for _c in format!("{:?}", T::KIND).chars() {
+            if _c.is_ascii_uppercase() && !_kind.is_empty() {
+                _kind.push('_');
+            }
+            _kind.push(_c.to_ascii_lowercase());
+        }
+        let _name = format!("{}_{}.json", T::SCHEME.name().to_lowercase(), _kind);
+        (_name, serde_json::to_string_pretty(&T::json_schema()).unwrap() + "\n")
+    }
+
+    fn schemas() -> Vec<(String, String)> {
+        vec![
+            schema::<ac17::Ac17PublicKey>(),
+            schema::<ac17::Ac17MasterKey>(),
+            schema::<ac17::Ac17CpSecretKey>(),
+            schema::<ac17::Ac17CpCiphertext>(),
+            schema::<ac17::Ac17CpEncapsulation>(),
+            schema::<ac17::Ac17KpSecretKey>(),
+            schema::<ac17::Ac17KpCiphertext>(),
+            schema::<ac17::Ac17KpEncapsulation>(),
+            schema::<aw11::Aw11GlobalKey>(),
+            schema::<aw11::Aw11PublicKey>(),
+            schema::<aw11::Aw11MasterKey>(),
+            schema::<(aw11::Aw11PublicKey, aw11::Aw11MasterKey)>(),
+            schema::<aw11::Aw11SecretKey>(),
+            schema::<aw11::Aw11Ciphertext>(),
+            schema::<aw11::Aw11Encapsulation>(),
+            schema::<bdabe::BdabePublicKey>(),
+            schema::<bdabe::BdabeMasterKey>(),
+            schema::<bdabe::BdabeSecretAuthorityKey>(),
+            schema::<bdabe::BdabePublicAttributeKey>(),
+            schema::<bdabe::BdabeSecretAttributeKey>(),
+            schema::<bdabe::BdabePublicUserKey>(),
+            schema::<bdabe::BdabeSecretUserKey>(),
+            schema::<bdabe::BdabeUserKey>(),
+            schema::<bdabe::BdabeCiphertext>(),
+            schema::<bdabe::BdabeEncapsulation>(),
+            schema::<bsw::CpAbePublicKey>(),
+            schema::<bsw::CpAbeMasterKey>(),
+            schema::<bsw::CpAbeSecretKey>(),
+            schema::<bsw::CpAbeCiphertext>(),
+            schema::<bsw::CpAbeEncapsulation>(),
+            schema::<lsw::KpAbePublicKey>(),
+            schema::<lsw::KpAbeMasterKey>(),
+            schema::<lsw::KpAbeSecretKey>(),
+            schema::<lsw::KpAbeCiphertext>(),
+            schema::<lsw::KpAbeEncapsulation>(),
+            schema::<mke08::Mke08PublicKey>(),
+            schema::<mke08::Mke08MasterKey>(),
+            schema::<mke08::Mke08SecretAuthorityKey>(),
+            schema::<mke08::Mke08PublicAttributeKey>(),
+            schema::<mke08::Mke08SecretAttributeKey>(),
+            schema::<mke08::Mke08PublicUserKey>(),
+            schema::<mke08::Mke08SecretUserKey>(),
+            schema::<mke08::Mke08UserKey>(),
+            schema::<mke08::Mke08Ciphertext>(),
+            schema::<mke08::Mke08Encapsulation>(),
+            schema::<yct14::Yct14AbePublicKey>(),
+            schema::<yct14::Yct14AbeMasterKey>(),
+            schema::<yct14::Yct14AbeSecretKey>(),
+            schema::<yct14::Yct14AbeCiphertext>(),
+            schema::<yct14::Yct14AbeEncapsulation>(),
+        ]
+    }
+
+    #[test]
+    fn schema_files() {
+        let _schemas = schemas();
+        for (_name, _schema) in _schemas.iter() {
+            assert_eq!(_schemas.iter().filter(|(_other, _)| _other == _name).count(), 1, "{} is not unique", _name);
+            let _file = fs::read_to_string(schema_path(_name)).unwrap();
+            assert!(_file == *_schema, "{} is outdated, run write_schema_files", _name);
+        }
+    }
+
+    // regenerates the schema files, needed whenever the JSON representation changes
+    #[test]
+    #[ignore]
+    fn write_schema_files() {
+        fs::create_dir_all(schema_path("")).unwrap();
+        for (_name, _schema) in schemas() {
+            fs::write(schema_path(&_name), _schema).unwrap();
+        }
+    }
+}
@@ -2,15 +2,30 @@
 //!
 //! Currently those are:
 //! * aes
+//! * armor
+//! * attribute
 //! * hash
 //! * policy
 //! * secretSharing
 //! * tools
+//! * validate
 //! * file
+//! * fingerprint
+//! * json
+//! * wire
 //!
 pub mod aes;
+pub mod armor;
+pub mod attribute;
 pub mod hash;
 pub mod policy;
 pub mod secretsharing;
 pub mod tools;
+#[macro_use]
+pub mod validate;
 pub mod file;
+pub mod fingerprint;
+#[macro_use]
+pub mod json;
+#[macro_use]
+pub mod wire;
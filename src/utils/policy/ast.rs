@@ -0,0 +1,329 @@
+//! An owned policy AST that can be built, stored and transformed in code.
+//!
+//! In contrast to `PolicyValue`, which borrows from the policy text it was parsed from, a `Policy`
+//! owns its attributes. It can be converted from and into a `PolicyValue` and is displayed in the
+//! human or the JSON policy language.
+//!
+//! # Examples
+//!
+//! ```
+//! use rabe::utils::policy::ast::Policy;
+//! use rabe::utils::policy::pest::PolicyLanguage;
+//! # fn main() -> Result<(), rabe::RabeError> {
+//! let policy = Policy::and(vec![Policy::attr("A")?, Policy::or(vec![Policy::attr("B")?, Policy::attr("C")?])]);
+//! assert_eq!(policy.to_string(), r#""A" and ("B" or "C")"#);
+//! assert_eq!(Policy::parse(&policy.to_string(), PolicyLanguage::HumanPolicy)?, policy);
+//! assert!(Policy::attr("dept=fin=ance").is_err());
+//! # Ok(())
+//! # }
+//! ```
+use std::borrow::Cow;
+use std::fmt;
+use std::ops;
+use std::slice;
+use utils::attribute::canonical_attribute;
+use utils::policy::pest::{parse, serialize_policy, PolicyLanguage, PolicyType, PolicyValue};
+use RabeError;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Policy {
+    /// A single attribute
+    Attr(String),
+    And(Vec<Policy>),
+    Or(Vec<Policy>),
+    /// A k-of-n gate: satisfied if at least k of its children are satisfied
+    Threshold(usize, Vec<Policy>),
+    /// Satisfied if its child is not satisfied
+    Not(Box<Policy>),
+}
+
+/// Walks a policy bottom up: every gate is visited with the results of its children.
+pub trait PolicyVisitor {
+    type Output;
+
+    fn visit_attr(&mut self, _attr: &str) -> Self::Output;
+
+    fn visit_gate(&mut self, _type: PolicyType, _children: Vec<Self::Output>) -> Self::Output;
+}
+
+impl Policy {
+    /// Returns a policy consisting of a single attribute, in its canonical encoding
+    ///
+    /// Fails if the attribute is invalid, see `Attribute::validate()`.
+    pub fn attr(_attr: &str) -> Result<Policy, RabeError> {
+        Ok(Policy::Attr(canonical_attribute(_attr)?))
+    }
+
+    /// Returns a policy that is satisfied if all of `_children` are satisfied
+    pub fn and(_children: Vec<Policy>) -> Policy {
+        Policy::And(_children)
+    }
+
+    /// Returns a policy that is satisfied if any of `_children` is satisfied
+    pub fn or(_children: Vec<Policy>) -> Policy {
+        Policy::Or(_children)
+    }
+
+    /// Returns a policy that is satisfied if at least `_k` of `_children` are satisfied
+    pub fn threshold(_k: usize, _children: Vec<Policy>) -> Policy {
+        Policy::Threshold(_k, _children)
+    }
+
+    /// Parses a policy given as String
+    ///
+    /// # Arguments
+    ///
+    ///	* `_policy` - An access policy given as String
+    ///	* `_language` - The language the policy is written in
+    pub fn parse(_policy: &str, _language: PolicyLanguage) -> Result<Policy, RabeError> {
+        Ok(Policy::from(&parse(_policy, _language)?))
+    }
+
+    /// Visits this policy bottom up and returns the result of the root
+    pub fn accept<V: PolicyVisitor>(&self, _visitor: &mut V) -> V::Output {
+        match self {
+            Policy::Attr(_attr) => _visitor.visit_attr(_attr),
+            _ => {
+                let (_type, _children) = self.gate().unwrap();
+                let _results = _children.iter().map(|_child| _child.accept(_visitor)).collect();
+                _visitor.visit_gate(_type, _results)
+            }
+        }
+    }
+
+    /// Returns the type and the children of a gate, or None for an attribute
+    pub fn gate(&self) -> Option<(PolicyType, &[Policy])> {
+        match self {
+            Policy::Attr(_) => None,
+            Policy::Not(_child) => Some((PolicyType::Not, slice::from_ref(_child.as_ref()))),
+            Policy::And(_children) => Some((PolicyType::And, _children)),
+            Policy::Or(_children) => Some((PolicyType::Or, _children)),
+            Policy::Threshold(_k, _children) => Some((PolicyType::Threshold(*_k), _children)),
+        }
+    }
+
+    /// Returns the negation of this policy, with the negations moved down to the attributes
+    pub fn negated(&self) -> Policy {
+        match self {
+            Policy::Attr(_) => !self.clone(),
+            Policy::Not(_child) => _child.as_ref().clone(),
+            Policy::And(_children) => Policy::Or(_children.iter().map(Policy::negated).collect()),
+            Policy::Or(_children) => Policy::And(_children.iter().map(Policy::negated).collect()),
+            // at most k - 1 of n children are satisfied, i.e. at least n - k + 1 are not
+            Policy::Threshold(_k, _children) => Policy::Threshold(
+                (_children.len() + 1).saturating_sub(*_k),
+                _children.iter().map(Policy::negated).collect()
+            ),
+        }
+    }
+
+    /// Returns true if this policy contains no negations
+    pub fn is_monotone(&self) -> bool {
+        match self.gate() {
+            None => true,
+            Some((PolicyType::Not, _)) => false,
+            Some((_, _children)) => _children.iter().all(Policy::is_monotone),
+        }
+    }
+
+    /// Returns all attributes of this policy in order of their first occurrence
+    pub fn attributes(&self) -> Vec<String> {
+        struct Attributes(Vec<String>);
+        impl PolicyVisitor for Attributes {
+            type Output = ();
+            fn visit_attr(&mut self, _attr: &str) {
+                if !self.0.iter().any(|_a| _a == _attr) {
+                    self.0.push(_attr.to_string());
+                }
+            }
+            fn visit_gate(&mut self, _type: PolicyType, _children: Vec<()>) {}
+        }
+        let mut _visitor = Attributes(Vec::new());
+        self.accept(&mut _visitor);
+        _visitor.0
+    }
+
+    /// Returns a copy of this policy with every attribute replaced by `_f(attribute)`
+    pub fn map_attributes<F: FnMut(&str) -> String>(&self, _f: F) -> Policy {
+        struct Map<F>(F);
+        impl<F: FnMut(&str) -> String> PolicyVisitor for Map<F> {
+            type Output = Policy;
+            fn visit_attr(&mut self, _attr: &str) -> Policy {
+                Policy::Attr((self.0)(_attr))
+            }
+            fn visit_gate(&mut self, _type: PolicyType, _children: Vec<Policy>) -> Policy {
+                gate(_type, _children)
+            }
+        }
+        self.accept(&mut Map(_f))
+    }
+
+    /// Returns a value that displays this policy in the given language
+    pub fn display(&self, _language: PolicyLanguage) -> PolicyDisplay<'_> {
+        PolicyDisplay { _policy: self, _language }
+    }
+}
+
+fn gate(_type: PolicyType, _children: Vec<Policy>) -> Policy {
+    match _type {
+        PolicyType::And => Policy::And(_children),
+        PolicyType::Or => Policy::Or(_children),
+        PolicyType::Threshold(_k) => Policy::Threshold(_k, _children),
+        PolicyType::Not => Policy::Not(Box::new(_children.into_iter().next().unwrap())),
+        PolicyType::Leaf => unreachable!(),
+    }
+}
+
+impl<'a> From<&PolicyValue<'a>> for Policy {
+    fn from(_value: &PolicyValue<'a>) -> Policy {
+        match _value {
+            PolicyValue::String(_attr) => Policy::Attr(_attr.to_string()),
+            PolicyValue::Object((PolicyType::Leaf, _child)) => Policy::from(_child.as_ref()),
+            PolicyValue::Object((PolicyType::Not, _child)) => !Policy::from(_child.as_ref()),
+            PolicyValue::Object((_type, _children)) => match _children.as_ref() {
+                PolicyValue::Array(_children) => gate(*_type, _children.iter().map(Policy::from).collect()),
+                _child => gate(*_type, vec![Policy::from(_child)]),
+            },
+            // a bare list of policies, as found below a gate
+            PolicyValue::Array(_children) => Policy::And(_children.iter().map(Policy::from).collect()),
+        }
+    }
+}
+
+impl<'a> From<&'a Policy> for PolicyValue<'a> {
+    fn from(_policy: &'a Policy) -> PolicyValue<'a> {
+        match _policy.gate() {
+            None => match _policy {
+                Policy::Attr(_attr) => PolicyValue::String(Cow::Borrowed(_attr)),
+                _ => unreachable!(),
+            },
+            Some((PolicyType::Not, _children)) => PolicyValue::Object((PolicyType::Not, Box::new(PolicyValue::from(&_children[0])))),
+            Some((_type, _children)) => PolicyValue::Object((
+                _type,
+                Box::new(PolicyValue::Array(_children.iter().map(PolicyValue::from).collect()))
+            )),
+        }
+    }
+}
+
+/// Displays a `Policy` in the human or the JSON policy language, see `Policy::display()`
+pub struct PolicyDisplay<'a> {
+    _policy: &'a Policy,
+    _language: PolicyLanguage,
+}
+
+impl<'a> fmt::Display for PolicyDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self._language {
+            PolicyLanguage::JsonPolicy => write!(f, "{}", serialize_policy(&PolicyValue::from(self._policy), PolicyLanguage::JsonPolicy, None)),
+            PolicyLanguage::HumanPolicy => write_human(f, self._policy, true),
+        }
+    }
+}
+
+fn write_human(f: &mut fmt::Formatter<'_>, _policy: &Policy, _root: bool) -> fmt::Result {
+    let (_separator, _children) = match _policy {
+        Policy::Attr(_attr) => return write!(f, "\"{}\"", _attr),
+        Policy::Not(_child) => {
+            write!(f, "not ")?;
+            return write_human(f, _child, false);
+        },
+        Policy::And(_children) => (" and ", _children),
+        Policy::Or(_children) => (" or ", _children),
+        Policy::Threshold(_k, _children) => {
+            write!(f, "{} of (", _k)?;
+            for (_i, _child) in _children.iter().enumerate() {
+                if _i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_human(f, _child, true)?;
+            }
+            return write!(f, ")");
+        }
+    };
+    if _children.len() == 1 {
+        return write_human(f, &_children[0], _root);
+    }
+    if !_root {
+        write!(f, "(")?;
+    }
+    for (_i, _child) in _children.iter().enumerate() {
+        if _i > 0 {
+            write!(f, "{}", _separator)?;
+        }
+        write_human(f, _child, false)?;
+    }
+    if !_root {
+        write!(f, ")")?;
+    }
+    Ok(())
+}
+
+/// Returns a policy that is satisfied if this policy is not satisfied, e.g. `!Policy::attr("A")?`
+impl ops::Not for Policy {
+    type Output = Policy;
+
+    fn not(self) -> Policy {
+        Policy::Not(Box::new(self))
+    }
+}
+
+/// Displays the policy in the human policy language
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_human(f, self, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::attribute::MAX_ATTRIBUTE_LENGTH;
+
+    #[test]
+    fn test_policy_builder() {
+        let policy = Policy::or(vec![
+            Policy::and(vec![Policy::attr("A").unwrap(), Policy::attr("dept=finance").unwrap()]),
+            Policy::threshold(2, vec![Policy::attr("B").unwrap(), Policy::attr("C").unwrap(), Policy::or(vec![Policy::attr("D").unwrap(), Policy::attr("A").unwrap()])]),
+        ]);
+        assert_eq!(policy.to_string(), r#"("A" and "dept=finance") or 2 of ("B", "C", "D" or "A")"#);
+        assert_eq!(
+            policy.display(PolicyLanguage::JsonPolicy).to_string(),
+            r#"{"name": "or", "children": [{"name": "and", "children": [{"name": "A"}, {"name": "dept=finance"}]}, {"name": "of", "threshold": 2, "children": [{"name": "B"}, {"name": "C"}, {"name": "or", "children": [{"name": "D"}, {"name": "A"}]}]}]}"#
+        );
+        for _language in [PolicyLanguage::HumanPolicy, PolicyLanguage::JsonPolicy] {
+            let _text = policy.display(_language).to_string();
+            assert_eq!(Policy::parse(&_text, _language).unwrap(), policy);
+            assert!(PolicyValue::from(&policy) == parse(&_text, _language).unwrap());
+        }
+        assert_eq!(Policy::attr("A").unwrap().display(PolicyLanguage::JsonPolicy).to_string(), r#"{"name": "A"}"#);
+        let negated = !Policy::and(vec![Policy::attr("A").unwrap(), !Policy::attr("B").unwrap()]);
+        assert_eq!(negated.to_string(), r#"not ("A" and not "B")"#);
+        assert_eq!(negated.negated().to_string(), r#""A" and not "B""#);
+        assert_eq!(Policy::parse(&negated.to_string(), PolicyLanguage::HumanPolicy).unwrap(), Policy::or(vec![!Policy::attr("A").unwrap(), Policy::attr("B").unwrap()]));
+        assert!(!negated.is_monotone());
+        // attributes are validated and canonicalized when the policy is built
+        assert_eq!(Policy::attr(" hr :: dept = finance ").unwrap(), Policy::Attr(String::from("hr::dept=finance")));
+        assert!(Policy::attr("A\" or \"B").is_err());
+        assert!(Policy::attr("a::b::c").is_err());
+        assert!(Policy::attr(&"a".repeat(MAX_ATTRIBUTE_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_policy_visitor() {
+        let policy = Policy::parse(r#""A" and ("B" or "A") and 1 of ("C")"#, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(policy.attributes(), vec!["A", "B", "C"]);
+        let renamed = policy.map_attributes(|_attr| format!("aa1::{}", _attr));
+        assert_eq!(renamed.to_string(), r#""aa1::A" and ("aa1::B" or "aa1::A") and 1 of ("aa1::C")"#);
+        struct Depth;
+        impl PolicyVisitor for Depth {
+            type Output = usize;
+            fn visit_attr(&mut self, _attr: &str) -> usize { 0 }
+            fn visit_gate(&mut self, _type: PolicyType, _children: Vec<usize>) -> usize {
+                1 + _children.into_iter().max().unwrap_or(0)
+            }
+        }
+        assert_eq!(policy.accept(&mut Depth), 2);
+    }
+}
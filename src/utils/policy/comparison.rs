@@ -0,0 +1,238 @@
+//! Numeric comparisons in policies, e.g. `"age" >= 18`.
+//!
+//! A numeric attribute is encoded as a "bag of bits" (Bethencourt, Sahai, Waters: "Ciphertext-Policy
+//! Attribute-Based Encryption", Section 4.3): a user with `age = 42` holds one attribute per bit of
+//! the value, i.e. `age#0=0`, `age#1=1`, `age#2=0`, ... A comparison is compiled into an and/or
+//! tree over these bit attributes, so every scheme that understands and/or policies supports it.
+//!
+//! Comparing an attribute to a string, e.g. `dept == "finance"`, yields the key-value attribute
+//! `dept=finance`.
+use std::borrow::Cow;
+use std::str::FromStr;
+use utils::attribute::{Attribute, canonical_attribute};
+use utils::policy::pest::{PolicyValue, PolicyType};
+use RabeError;
+
+/// The number of bits used to encode a numeric attribute
+pub const COMPARISON_BITS: usize = 32;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+}
+
+impl Comparison {
+    /// Returns the comparison for an operator (`<`, `<=`, `>`, `>=` or `==`)
+    ///
+    /// # Arguments
+    ///
+    ///	* `_operator` - The operator as written in the policy
+    pub fn from_operator(_operator: &str) -> Result<Comparison, RabeError> {
+        match _operator {
+            "<" => Ok(Comparison::Less),
+            "<=" => Ok(Comparison::LessEqual),
+            ">" => Ok(Comparison::Greater),
+            ">=" => Ok(Comparison::GreaterEqual),
+            "==" => Ok(Comparison::Equal),
+            _ => Err(RabeError::new(&format!("unknown comparison operator {}", _operator))),
+        }
+    }
+
+    pub fn operator(&self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterEqual => ">=",
+            Comparison::Equal => "==",
+        }
+    }
+}
+
+/// Returns the name of the attribute stating that bit `_bit` of the numeric attribute `_name` is `_set`
+pub fn bit_attribute(_name: &str, _bit: usize, _set: bool) -> String {
+    format!("{}#{}={}", _name, _bit, _set as u8)
+}
+
+/// Expands a numeric user attribute like `age = 42` into the bit attributes that have to be
+/// given to the user at key generation.
+///
+/// # Arguments
+///
+///	* `_name` - The name of the numeric attribute
+///	* `_value` - The value of the numeric attribute
+pub fn numeric_attribute(_name: &str, _value: u64) -> Result<Vec<String>, RabeError> {
+    check_range(_value)?;
+    let _name = &attribute_name(_name)?;
+    Ok((0..COMPARISON_BITS)
+        .map(|_bit| bit_attribute(_name, _bit, (_value >> _bit) & 1 == 1))
+        .collect())
+}
+
+/// A partially compiled comparison. Constant sub-terms are folded away.
+enum Term<'a> {
+    Const(bool),
+    Policy(PolicyValue<'a>),
+}
+
+fn gate<'a>(_type: PolicyType, _bit: PolicyValue<'a>, _rest: Term<'a>) -> Term<'a> {
+    let _absorbing = _type == PolicyType::Or;
+    match _rest {
+        Term::Const(_c) if _c == _absorbing => Term::Const(_c),
+        Term::Const(_) => Term::Policy(_bit),
+        Term::Policy(PolicyValue::Object((_t, _children))) if _t == _type => {
+            // keep same typed gates flat
+            match *_children {
+                PolicyValue::Array(mut _vec) => {
+                    _vec.insert(0, _bit);
+                    Term::Policy(PolicyValue::Object((_type, Box::new(PolicyValue::Array(_vec)))))
+                },
+                _other => Term::Policy(PolicyValue::Object((_type, Box::new(PolicyValue::Array(vec![_bit, _other]))))),
+            }
+        },
+        Term::Policy(_policy) => Term::Policy(PolicyValue::Object((_type, Box::new(PolicyValue::Array(vec![_bit, _policy]))))),
+    }
+}
+
+/// Compiles the comparison `_name _comparison _value` into an and/or policy over bit attributes.
+///
+/// # Arguments
+///
+///	* `_name` - The name of the numeric attribute
+///	* `_comparison` - The comparison
+///	* `_value` - The constant the attribute is compared to
+pub fn comparison_policy<'a>(_name: &str, _comparison: Comparison, _value: u64) -> Result<PolicyValue<'a>, RabeError> {
+    check_range(_value)?;
+    let _name = &attribute_name(_name)?;
+    let _leaf = |_bit: usize, _set: bool| PolicyValue::String(Cow::Owned(bit_attribute(_name, _bit, _set)));
+    // build the tree from the least significant bit upwards
+    let mut _term = Term::Const(!matches!(_comparison, Comparison::Less | Comparison::Greater));
+    for _bit in 0..COMPARISON_BITS {
+        let _value_bit = (_value >> _bit) & 1 == 1;
+        _term = match _comparison {
+            Comparison::Greater | Comparison::GreaterEqual => {
+                let _type = if _value_bit { PolicyType::And } else { PolicyType::Or };
+                gate(_type, _leaf(_bit, true), _term)
+            },
+            Comparison::Less | Comparison::LessEqual => {
+                let _type = if _value_bit { PolicyType::Or } else { PolicyType::And };
+                gate(_type, _leaf(_bit, false), _term)
+            },
+            Comparison::Equal => gate(PolicyType::And, _leaf(_bit, _value_bit), _term),
+        };
+    }
+    match _term {
+        Term::Policy(_policy) => Ok(_policy),
+        // always satisfied by anyone holding the numeric attribute
+        Term::Const(true) => Ok(PolicyValue::Object((PolicyType::Or, Box::new(PolicyValue::Array(vec![_leaf(0, false), _leaf(0, true)]))))),
+        Term::Const(false) => Err(RabeError::new(&format!("comparison {} {} {} can never be satisfied", _name, _comparison.operator(), _value))),
+    }
+}
+
+/// Compiles the comparison `_name == "_value"` into the key-value attribute `_name=_value`.
+///
+/// # Arguments
+///
+///	* `_name` - The name of the attribute
+///	* `_comparison` - The comparison, only equality is defined for strings
+///	* `_value` - The value the attribute is compared to
+pub fn value_policy<'a>(_name: &str, _comparison: Comparison, _value: &str) -> Result<PolicyValue<'a>, RabeError> {
+    if _comparison != Comparison::Equal {
+        return Err(RabeError::new(&format!("comparison {} {} \"{}\": strings can only be compared using ==", _name, _comparison.operator(), _value)));
+    }
+    let _name = attribute_name(_name)?;
+    Ok(PolicyValue::String(Cow::Owned(canonical_attribute(&format!("{}={}", _name, _value))?)))
+}
+
+// the attribute in a comparison must not have a value itself
+fn attribute_name(_name: &str) -> Result<String, RabeError> {
+    let _attribute = Attribute::from_str(_name)?;
+    match _attribute._value {
+        None => Ok(_attribute.to_string()),
+        Some(_) => Err(RabeError::new(&format!("comparison on attribute {} which already has a value", _name))),
+    }
+}
+
+fn check_range(_value: u64) -> Result<(), RabeError> {
+    if _value >> COMPARISON_BITS != 0 {
+        Err(RabeError::new(&format!("numeric value {} exceeds {} bits", _value, COMPARISON_BITS)))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::tools::traverse_policy;
+    use utils::policy::pest::{parse, serialize_policy, PolicyLanguage};
+
+    #[test]
+    fn test_comparisons() {
+        let _values: Vec<u64> = vec![0, 1, 2, 3, 17, 18, 19, 42, (1 << 32) - 2, (1 << 32) - 1];
+        let _ops = [
+            Comparison::Less, Comparison::LessEqual, Comparison::Greater, Comparison::GreaterEqual, Comparison::Equal
+        ];
+        for _constant in _values.iter() {
+            for _op in _ops.iter() {
+                let _policy = comparison_policy("age", *_op, *_constant);
+                for _user in _values.iter() {
+                    let _expected = match _op {
+                        Comparison::Less => _user < _constant,
+                        Comparison::LessEqual => _user <= _constant,
+                        Comparison::Greater => _user > _constant,
+                        Comparison::GreaterEqual => _user >= _constant,
+                        Comparison::Equal => _user == _constant,
+                    };
+                    match &_policy {
+                        Ok(_p) => {
+                            let _attributes = numeric_attribute("age", *_user).unwrap();
+                            assert_eq!(traverse_policy(&_attributes, _p, PolicyType::Leaf), _expected);
+                        },
+                        // only unsatisfiable comparisons are rejected
+                        Err(_) => assert!(!_expected),
+                    }
+                }
+            }
+        }
+        assert!(comparison_policy("age", Comparison::Less, 0).is_err());
+        assert!(numeric_attribute("age", 1 << 32).is_err());
+    }
+
+    #[test]
+    fn test_comparison_parsing() {
+        let human = parse(r#""age" >= 18 and ("clearance" < 3 or "admin")"#, PolicyLanguage::HumanPolicy).unwrap();
+        let json = parse(
+            r#"{"name": "and", "children": [{"name": "age", "operator": ">=", "value": 18}, {"name": "or", "children": [{"name": "clearance", "operator": "<", "value": 3}, {"name": "admin"}]}]}"#,
+            PolicyLanguage::JsonPolicy
+        ).unwrap();
+        assert!(human == json);
+        let mut _attributes = numeric_attribute("age", 42).unwrap();
+        _attributes.extend(numeric_attribute("clearance", 2).unwrap());
+        assert!(traverse_policy(&_attributes, &human, PolicyType::Leaf));
+        let mut _attributes = numeric_attribute("age", 42).unwrap();
+        _attributes.extend(numeric_attribute("clearance", 3).unwrap());
+        assert!(!traverse_policy(&_attributes, &human, PolicyType::Leaf));
+        assert!(parse(r#""age" < 0"#, PolicyLanguage::HumanPolicy).is_err());
+    }
+
+    #[test]
+    fn test_value_comparison() {
+        let human = parse(r#"dept == "finance" or hr::level == "senior""#, PolicyLanguage::HumanPolicy).unwrap();
+        let json = parse(
+            r#"{"name": "or", "children": [{"name": "dept", "operator": "==", "value": "finance"}, {"name": "hr::level=senior"}]}"#,
+            PolicyLanguage::JsonPolicy
+        ).unwrap();
+        assert!(human == json);
+        assert_eq!(serialize_policy(&human, PolicyLanguage::HumanPolicy, None), "(dept=finance or hr::level=senior)");
+        assert!(traverse_policy(&vec![String::from("dept=finance")], &human, PolicyType::Leaf));
+        assert!(!traverse_policy(&vec![String::from("dept=sales")], &human, PolicyType::Leaf));
+        assert!(parse(r#"dept >= "finance""#, PolicyLanguage::HumanPolicy).is_err());
+        assert!(parse(r#""dept=x" == "finance""#, PolicyLanguage::HumanPolicy).is_err());
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
     },
 };
 use utils::policy::pest::{PolicyLanguage, PolicyValue, parse, PolicyType};
+use utils::policy::{ast::Policy, normalize::to_dnf};
 
 /// A DNF policy for the MKE08 scheme and the BDABE scheme
 pub struct DnfPolicy {
@@ -25,6 +26,14 @@ impl DnfPolicy {
         DnfPolicy { _terms: Vec::new() }
     }
 
+    /// Returns a new DNF policy based on a policy given as String. The policy is converted into DNF
+    /// first, see `utils::policy::normalize`.
+    ///
+    /// # Arguments
+    ///
+    /// * `_policy` - A policy given as String
+    /// * `_pks` - The public attribute keys of the attributes in the policy
+    /// * `_language` - The language the policy is written in
     pub fn from_string<K: PublicAttributeKey>(
         _policy: &String,
         _pks: &Vec<K>,
@@ -33,16 +42,18 @@ impl DnfPolicy {
         return match parse(_policy, _language) {
             Err(e) => Err(e),
             Ok(_pol) => {
-                json_to_dnf(&_pol, _pks)
+                DnfPolicy::from_policy(&_pol, _pks)
             }
         }
     }
 
+    /// Returns a new DNF policy based on a parsed policy, which is converted into DNF first.
     pub fn from_policy<K: PublicAttributeKey>(
         _json: &PolicyValue,
         _pks: &Vec<K>,
     ) -> Result<DnfPolicy, RabeError> {
-        json_to_dnf(_json, _pks)
+        let _dnf = to_dnf(&Policy::from(_json))?.to_policy();
+        json_to_dnf(&PolicyValue::from(&_dnf), _pks)
     }
 }
 
@@ -154,7 +165,7 @@ pub fn dnf<K: PublicAttributeKey>(
         },
         PolicyValue::Object(obj) => {
             return match _parent {
-                None => dnf(_dnfp, _pks, &obj.1, _i, Some(obj.0.clone())),
+                None => dnf(_dnfp, _pks, &obj.1, _i, Some(obj.0)),
                 Some(PolicyType::Leaf) => dnf(_dnfp, _pks, &obj.1, _i, Some(PolicyType::Leaf)),
                 Some(PolicyType::Or) => {
                     match &obj.0 {
@@ -168,6 +179,8 @@ pub fn dnf<K: PublicAttributeKey>(
                         _ => false,
                     }
                 }
+                // threshold gates and negations are not part of a DNF
+                Some(PolicyType::Threshold(_)) | Some(PolicyType::Not) => false,
             }
         }
     }
@@ -195,6 +208,7 @@ pub fn policy_in_dnf(pol: &PolicyValue, conjunction: bool, parent: Option<Policy
                 PolicyType::And=> policy_in_dnf(&obj.1.as_ref(), true, Some(PolicyType::And)),
                 PolicyType::Or => policy_in_dnf(&obj.1.as_ref(), conjunction, Some(PolicyType::Or)),
                 PolicyType::Leaf => policy_in_dnf(&obj.1.as_ref(), conjunction, Some(PolicyType::Leaf)),
+                PolicyType::Threshold(_) | PolicyType::Not => false,
             }
         },
         PolicyValue::String(_str) => true,
@@ -230,6 +244,7 @@ pub fn policy_in_dnf(pol: &PolicyValue, conjunction: bool, parent: Option<Policy
 mod tests {
 
     use super::*;
+    use utils::hash::HashVersion;
 
     #[test]
     fn test_dnf_from() {
@@ -266,6 +281,7 @@ mod tests {
             _a1: G1::one(),
             _a2: G2::one(),
             _a3: Gt::one(),
+            _hash: HashVersion::CURRENT,
         };
 
         let pk_b = BdabePublicAttributeKey {
@@ -273,6 +289,7 @@ mod tests {
             _a1: G1::one(),
             _a2: G2::one(),
             _a3: Gt::one(),
+            _hash: HashVersion::CURRENT,
         };
 
         let pk_c = BdabePublicAttributeKey {
@@ -280,6 +297,7 @@ mod tests {
             _a1: G1::one(),
             _a2: G2::one(),
             _a3: Gt::one(),
+            _hash: HashVersion::CURRENT,
         };
 
         let mut pks: Vec<BdabePublicAttributeKey> = Vec::new();
@@ -293,7 +311,23 @@ mod tests {
 
         assert_eq!(policy1._terms.len(), 2);
         assert_eq!(policy2._terms.len(), 1);
-        assert_eq!(policy3._terms.len(), 3);
+        // ("A" and "C") is absorbed by "C"
+        assert_eq!(policy3._terms.len(), 2);
     }
 
+    #[test]
+    fn test_dnf_single() {
+        let pks = vec![BdabePublicAttributeKey {
+            _str: String::from("admin"),
+            _a1: G1::one(),
+            _a2: G2::one(),
+            _a3: Gt::one(),
+            _hash: HashVersion::CURRENT,
+        }];
+        let single = String::from(r#""admin""#);
+        assert!(policy_in_dnf(&parse(&single, PolicyLanguage::HumanPolicy).unwrap(), false, None));
+        let policy: DnfPolicy = DnfPolicy::from_string(&single, &pks, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(policy._terms.len(), 1);
+        assert_eq!(policy._terms[0].0, vec![String::from("admin")]);
+    }
 }
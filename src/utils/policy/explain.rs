@@ -0,0 +1,131 @@
+//! Explains whether, and why, a set of attributes satisfies a policy.
+//!
+//! The policy is converted into DNF (see `utils::policy::normalize`). Every conjunction of the
+//! DNF is a branch of the policy and a minimal set of attributes satisfying it. For an unsatisfied
+//! policy the explanation lists the attributes missing in each branch, fewest missing first.
+//! A negated attribute `!A` of a branch is missing if the attribute `A` is present.
+//!
+//! # Examples
+//!
+//! ```
+//! use rabe::utils::policy::ast::Policy;
+//! use rabe::utils::policy::explain::explain;
+//! use rabe::utils::policy::pest::PolicyLanguage;
+//! let policy = Policy::parse(r#""A" and ("B" or "C")"#, PolicyLanguage::HumanPolicy).unwrap();
+//! let explanation = explain(&policy, &["B".to_string()]).unwrap();
+//! assert!(!explanation._satisfied);
+//! assert_eq!(explanation._branches[0], (vec!["A".to_string(), "B".to_string()], vec!["A".to_string()]));
+//! ```
+use std::fmt;
+use utils::attribute::canonical_attribute;
+use utils::policy::{ast::Policy, normalize::to_dnf};
+use RabeError;
+
+/// The result of `explain()`
+#[derive(PartialEq, Clone, Debug)]
+pub struct Explanation {
+    pub _satisfied: bool,
+    /// A minimal subset of the attributes that satisfies the policy, empty if it is not satisfied
+    pub _satisfying: Vec<String>,
+    /// All branches of the policy together with the attributes missing to satisfy them, fewest missing first
+    pub _branches: Vec<(Vec<String>, Vec<String>)>,
+}
+
+/// Explains whether the attributes satisfy the policy, which subset of them is sufficient and
+/// which attributes are missing otherwise.
+///
+/// # Arguments
+///
+///	* `_policy` - The policy
+///	* `_attributes` - The attributes, e.g. of a secret key
+pub fn explain(_policy: &Policy, _attributes: &[String]) -> Result<Explanation, RabeError> {
+    let _attributes: Vec<String> = _attributes
+        .iter()
+        .map(|_attr| canonical_attribute(_attr).unwrap_or_else(|_| _attr.clone()))
+        .collect();
+    let mut _branches: Vec<(Vec<String>, Vec<String>)> = to_dnf(_policy)?
+        ._clauses
+        .into_iter()
+        .map(|_clause| {
+            let _missing = _clause.iter().filter(|_literal| !satisfies(&_attributes, _literal)).cloned().collect();
+            (_clause, _missing)
+        })
+        .collect();
+    _branches.sort_by_key(|(_clause, _missing)| (_missing.len(), _clause.len()));
+    let _satisfying = _branches
+        .iter()
+        .filter(|(_, _missing)| _missing.is_empty())
+        .map(|(_clause, _)| _clause.clone())
+        .min_by_key(|_clause| _clause.len());
+    Ok(Explanation {
+        _satisfied: _satisfying.is_some(),
+        _satisfying: _satisfying.unwrap_or_default(),
+        _branches,
+    })
+}
+
+// true if the attributes satisfy an attribute or a negated attribute of a clause
+fn satisfies(_attributes: &[String], _literal: &str) -> bool {
+    match _literal.strip_prefix('!') {
+        Some(_attr) => !_attributes.iter().any(|_a| _a == _attr),
+        None => _attributes.iter().any(|_a| _a == _literal),
+    }
+}
+
+// a clause literal in the human policy language, i.e. "A" or "not A"
+fn human(_literal: &String) -> String {
+    match _literal.strip_prefix('!') {
+        Some(_attr) => format!("not {}", _attr),
+        None => _literal.clone(),
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self._satisfied {
+            let _satisfying: Vec<String> = self._satisfying.iter().map(human).collect();
+            return write!(f, "policy is satisfied by: {}", _satisfying.join(", "));
+        }
+        write!(f, "policy is not satisfied, missing attributes per branch:")?;
+        for (_clause, _missing) in self._branches.iter() {
+            let _clause: Vec<String> = _clause.iter().map(human).collect();
+            let _missing: Vec<String> = _missing.iter().map(human).collect();
+            write!(f, "\n  {}: missing {}", _clause.join(" and "), _missing.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::policy::pest::PolicyLanguage;
+
+    fn attributes(_names: &[&str]) -> Vec<String> {
+        _names.iter().map(|_name| _name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_explain() {
+        let policy = Policy::parse(r#"("A" and "B" and "C") or ("A" and "D") or 2 of ("E", "F", "G")"#, PolicyLanguage::HumanPolicy).unwrap();
+        let satisfied = explain(&policy, &attributes(&["A", "B", "C", "D", "X"])).unwrap();
+        assert!(satisfied._satisfied);
+        assert_eq!(satisfied._satisfying, attributes(&["A", "D"]));
+        let unsatisfied = explain(&policy, &attributes(&["B", "E"])).unwrap();
+        assert!(!unsatisfied._satisfied);
+        assert!(unsatisfied._satisfying.is_empty());
+        assert_eq!(unsatisfied._branches.len(), 5);
+        assert_eq!(unsatisfied._branches[0].1.len(), 1);
+        assert_eq!(unsatisfied._branches[1].1.len(), 1);
+        assert_eq!(unsatisfied._branches[2], (attributes(&["A", "D"]), attributes(&["A", "D"])));
+        assert_eq!(unsatisfied._branches[4], (attributes(&["A", "B", "C"]), attributes(&["A", "C"])));
+        assert_eq!(
+            unsatisfied.to_string().lines().nth(5),
+            Some("  A and B and C: missing A, C")
+        );
+        // attributes are compared in their canonical encoding
+        let policy = Policy::parse(r#"dept == "finance""#, PolicyLanguage::HumanPolicy).unwrap();
+        assert!(explain(&policy, &attributes(&["dept = finance"])).unwrap()._satisfied);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod ast;
 pub mod pest;
+pub mod comparison;
 pub mod dnf;
+pub mod explain;
 pub mod msp;
+pub mod normalize;
@@ -1,14 +1,16 @@
 use std::string::String;
+use rabe_bn::Fr;
 use utils::policy::pest::{PolicyLanguage, PolicyValue, parse, PolicyType};
+use utils::tools::{contains, i64_to_fr};
 use RabeError;
 use std::fmt::{Display, Formatter, Result as FormatResult};
 
-const ZERO: i8 = 0;
-const PLUS: i8 = 1;
-const MINUS: i8 = -1;
+const ZERO: i64 = 0;
+const PLUS: i64 = 1;
+const MINUS: i64 = -1;
 
 pub struct AbePolicy {
-    pub _m: Vec<Vec<i8>>,
+    pub _m: Vec<Vec<i64>>,
     pub _pi: Vec<String>,
     pub _c: usize,
 }
@@ -34,6 +36,62 @@ impl AbePolicy {
     pub fn from_policy(_content: &PolicyValue) -> Result<AbePolicy, RabeError> {
         calculate_msp(_content)
     }
+
+    /// Calculates the reconstruction coefficients for a set of attributes, i.e. a list of
+    /// (row, coefficient) pairs such that the sum of coefficient * row equals (1, 0, ..., 0).
+    /// Returns None if the attributes do not satisfy the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `_attributes` - The attributes used for reconstruction
+    pub fn coefficients(&self, _attributes: &Vec<String>) -> Option<Vec<(usize, Fr)>> {
+        let _rows: Vec<usize> = (0..self._m.len())
+            .filter(|&_i| contains(_attributes, &self._pi[_i]))
+            .collect();
+        // solve M_I^T * w = (1, 0, ..., 0) by gaussian elimination over Fr
+        let mut _a: Vec<Vec<Fr>> = (0..self._c)
+            .map(|_j| {
+                let mut _eq: Vec<Fr> = _rows.iter().map(|&_i| i64_to_fr(self._m[_i][_j])).collect();
+                _eq.push(if _j == 0 { Fr::one() } else { Fr::zero() });
+                _eq
+            })
+            .collect();
+        let mut _pivots: Vec<(usize, usize)> = Vec::new();
+        let mut _r = 0;
+        for _col in 0.._rows.len() {
+            match (_r.._a.len()).find(|&_i| !_a[_i][_col].is_zero()) {
+                None => continue,
+                Some(_p) => {
+                    _a.swap(_r, _p);
+                    let _inv = _a[_r][_col].inverse().unwrap();
+                    for _x in _a[_r][_col..].iter_mut() {
+                        *_x = *_x * _inv;
+                    }
+                    let _pivot = _a[_r].clone();
+                    for (_i, _row) in _a.iter_mut().enumerate() {
+                        if _i != _r && !_row[_col].is_zero() {
+                            let _f = _row[_col];
+                            for (_x, _p) in _row[_col..].iter_mut().zip(&_pivot[_col..]) {
+                                *_x = *_x - *_p * _f;
+                            }
+                        }
+                    }
+                    _pivots.push((_r, _col));
+                    _r += 1;
+                }
+            }
+        }
+        // inconsistent system: the attributes do not satisfy the policy
+        if (_r.._a.len()).any(|_i| !_a[_i][_rows.len()].is_zero()) {
+            return None;
+        }
+        // free variables are set to zero
+        Some(_pivots
+            .into_iter()
+            .map(|(_i, _col)| (_rows[_col], _a[_i][_rows.len()]))
+            .filter(|(_, _w)| !_w.is_zero())
+            .collect())
+    }
 }
 
 impl Display for AbePolicy {
@@ -69,8 +127,8 @@ impl Display for AbePolicy {
 // * BEWARE: policy must be in DNF!
 // */]
 pub fn calculate_msp(p: &PolicyValue) -> Result<AbePolicy, RabeError> {
-    let mut v: Vec<i8> = Vec::new();
-    let mut _values: Vec<Vec<i8>> = Vec::new();
+    let mut v: Vec<i64> = Vec::new();
+    let mut _values: Vec<Vec<i64>> = Vec::new();
     let mut _attributes: Vec<String> = Vec::new();
     let mut msp = AbePolicy {
         _m: _values,
@@ -78,62 +136,86 @@ pub fn calculate_msp(p: &PolicyValue) -> Result<AbePolicy, RabeError> {
         _c: 1,
     };
     v.push(PLUS);
-    if lw(&mut msp, p, &v, None) {
-        for val in &mut msp._m {
-            val.resize(msp._c, ZERO);
-        }
-        // permutate both _pi and _m according to _pi
-        let permutation = permutation::sort(&msp._pi[..]);
-        msp._pi = permutation.apply_slice(&msp._pi[..]);
-        msp._m = permutation.apply_slice(&msp._m[..]);
-        return Ok(msp);
+    lw(&mut msp, p, &v, None)?;
+    for val in &mut msp._m {
+        val.resize(msp._c, ZERO);
     }
-    return Err(RabeError::new("lewko waters algorithm failed =("))
+    // permutate both _pi and _m according to _pi
+    let permutation = permutation::sort(&msp._pi[..]);
+    msp._pi = permutation.apply_slice(&msp._pi[..]);
+    msp._m = permutation.apply_slice(&msp._m[..]);
+    Ok(msp)
 }
 /// Converting from Boolean Formulas to LSSS Matrices
 /// Lewko Waters: "Decentralizing Attribute-Based Encryption" Appendix G
-fn lw(msp: &mut AbePolicy, p: &PolicyValue, v: &Vec<i8>, _parent: Option<PolicyType>) -> bool {
-    let mut v_tmp_left = Vec::new();
-    let mut v_tmp_right = v.clone();
+/// An AND gate with n children is treated as a chain of n-1 binary AND gates.
+/// Threshold gates are converted using a Vandermonde matrix, see Liu, Cao: "On Efficiently Transferring the Linear Secret-Sharing Scheme Matrix in Ciphertext-Policy Attribute-Based Encryption"
+fn lw(msp: &mut AbePolicy, p: &PolicyValue, v: &Vec<i64>, _parent: Option<PolicyType>) -> Result<(), RabeError> {
     return match p {
         PolicyValue::String(attr) => {
-            msp._m.insert(0, v_tmp_right);
+            msp._m.insert(0, v.clone());
             msp._pi.insert(0, attr.to_string());
-            true
+            Ok(())
         },
         PolicyValue::Object(obj) => {
             match obj.0 {
-                PolicyType::And => lw(msp, &obj.1.as_ref(), v, Some(PolicyType::And)),
-                PolicyType::Or => lw(msp, &obj.1.as_ref(), v, Some(PolicyType::Or)),
-                PolicyType::Leaf => lw(msp, &obj.1.as_ref(), v, Some(PolicyType::Leaf)),
+                PolicyType::And => lw(msp, obj.1.as_ref(), v, Some(PolicyType::And)),
+                PolicyType::Or => lw(msp, obj.1.as_ref(), v, Some(PolicyType::Or)),
+                PolicyType::Threshold(k) => lw(msp, obj.1.as_ref(), v, Some(PolicyType::Threshold(k))),
+                PolicyType::Leaf => lw(msp, obj.1.as_ref(), v, Some(PolicyType::Leaf)),
+                PolicyType::Not => Err(RabeError::new("lw: Invalid policy. Negated attributes cannot be expressed by an MSP")),
             }
         },
         PolicyValue::Array(policies) => {
             let len = policies.len();
-            if len < 2 {
-                panic!("lw: policy with just a single attribute is not allowed");
+            if len == 0 {
+                return Err(RabeError::new("lw: Invalid policy. A gate without children is not allowed"));
             }
             return match _parent {
                 Some(PolicyType::Or) => {
-                    let mut _ret = true;
                     for policy in policies {
-                        _ret &= lw(msp, &policy, &v, Some(PolicyType::Or));
+                        lw(msp, policy, v, Some(PolicyType::Or))?;
                     }
-                    return _ret;
+                    Ok(())
                 },
                 Some(PolicyType::And) => {
-                    if len != 2 {
-                        panic!("lw: Invalid policy. Number of arguments under AND != 2");
+                    // child i gets (.., -1 at column c_i-1, 1 at column c_i), so that all vectors sum up to v.
+                    // a single child simply inherits v
+                    let mut _v_rest = v.clone();
+                    for policy in &policies[..len - 1] {
+                        let mut _v_child = _v_rest.clone();
+                        _v_child.resize(msp._c, ZERO);
+                        _v_child.push(PLUS);
+                        _v_rest = Vec::new();
+                        _v_rest.resize(msp._c, ZERO);
+                        _v_rest.push(MINUS);
+                        msp._c += 1;
+                        lw(msp, policy, &_v_child, Some(PolicyType::And))?;
                     }
-                    v_tmp_right.resize(msp._c, ZERO);
-                    v_tmp_right.push(PLUS);
-                    v_tmp_left.resize(msp._c, ZERO);
-                    v_tmp_left.push(MINUS);
-                    msp._c += 1;
-                    lw(msp, &policies[0], &v_tmp_right, Some(PolicyType::And)) && lw(msp, &policies[1], &v_tmp_left, Some(PolicyType::And))
+                    lw(msp, &policies[len - 1], &_v_rest, Some(PolicyType::And))
                 },
-                Some(PolicyType::Leaf) => false,
-                None => false,
+                Some(PolicyType::Threshold(k)) => {
+                    if k < 1 || k > len {
+                        return Err(RabeError::new(&format!("lw: invalid threshold gate: {} of {} children", k, len)));
+                    }
+                    // child x gets the vector v || (x, x^2, ..., x^(k-1)) in k-1 new columns
+                    let _first = msp._c;
+                    msp._c += k - 1;
+                    for (_i, policy) in policies.iter().enumerate() {
+                        let mut _v = v.clone();
+                        _v.resize(_first, ZERO);
+                        let mut _x_pow = PLUS;
+                        for _ in 1..k {
+                            _x_pow = _x_pow
+                                .checked_mul((_i + 1) as i64)
+                                .ok_or(RabeError::new("lw: threshold gate is too large"))?;
+                            _v.push(_x_pow);
+                        }
+                        lw(msp, policy, &_v, Some(PolicyType::Threshold(k)))?;
+                    }
+                    Ok(())
+                },
+                Some(PolicyType::Leaf) | Some(PolicyType::Not) | None => Err(RabeError::new("lw: Invalid policy. An array must be the children of an AND, OR or threshold gate")),
             }
         }
     };
@@ -145,6 +227,35 @@ mod tests {
 
     use super::*;
     use rabe_bn::Fr;
+    use rand::Rng;
+    use utils::tools::traverse_policy;
+
+    const NAMES: [&str; 6] = ["A", "B", "C", "D", "E", "F"];
+
+    fn random_policy<R: Rng>(_rng: &mut R, _depth: usize) -> PolicyValue<'static> {
+        if _depth == 0 || _rng.gen_bool(0.3) {
+            return PolicyValue::String(NAMES[_rng.gen_range(0, NAMES.len())].into());
+        }
+        let _n = _rng.gen_range(2, 5);
+        let _children = (0.._n).map(|_| random_policy(_rng, _depth - 1)).collect();
+        let _type = match _rng.gen_range(0, 3) {
+            0 => PolicyType::And,
+            1 => PolicyType::Or,
+            _ => PolicyType::Threshold(_rng.gen_range(1, _n + 1)),
+        };
+        PolicyValue::Object((_type, Box::new(PolicyValue::Array(_children))))
+    }
+
+    // checks that the coefficients reconstruct the target vector (1, 0, ..., 0)
+    fn assert_reconstructs(msp: &AbePolicy, _coeff: &Vec<(usize, Fr)>) {
+        for _j in 0..msp._c {
+            let mut _sum = Fr::zero();
+            for (_i, _w) in _coeff.iter() {
+                _sum = _sum + (*_w * i64_to_fr(msp._m[*_i][_j]));
+            }
+            assert!(_sum == if _j == 0 { Fr::one() } else { Fr::zero() });
+        }
+    }
 
     #[test]
     fn test_msp_from() {
@@ -190,4 +301,63 @@ mod tests {
         }
 
     }
+
+    #[test]
+    fn test_msp_threshold() {
+        let policy = String::from(r#""X" and 2 of ("A", "B", "C")"#);
+        let msp = AbePolicy::new(&policy, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(msp._c, 3);
+        assert_eq!(msp._pi, vec!["A", "B", "C", "X"]);
+        let attributes = |_a: &[&str]| _a.iter().map(|_s| _s.to_string()).collect::<Vec<String>>();
+        for _set in [vec!["X", "A", "B"], vec!["X", "A", "C"], vec!["X", "B", "C"], vec!["X", "A", "B", "C"]] {
+            let _coeff = msp.coefficients(&attributes(&_set)).expect("attributes should satisfy the policy");
+            assert_reconstructs(&msp, &_coeff);
+        }
+        for _set in [vec!["A", "B", "C"], vec!["X", "A"], vec!["X"]] {
+            assert!(msp.coefficients(&attributes(&_set)).is_none());
+        }
+    }
+
+    #[test]
+    fn test_msp_nary_and() {
+        let policy = String::from(r#""A" and "B" and "C" and "D""#);
+        let msp = AbePolicy::new(&policy, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(msp._c, 4);
+        let all: Vec<String> = NAMES[..4].iter().map(|_s| _s.to_string()).collect();
+        assert_reconstructs(&msp, &msp.coefficients(&all).unwrap());
+        assert!(msp.coefficients(&all[1..].to_vec()).is_none());
+        let invalid = PolicyValue::Object((PolicyType::Leaf, Box::new(PolicyValue::Array(vec![PolicyValue::String("A".into()), PolicyValue::String("B".into())]))));
+        assert!(AbePolicy::from_policy(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_msp_random_policies() {
+        let mut _rng = rand::thread_rng();
+        for _ in 0..200 {
+            let policy = random_policy(&mut _rng, 3);
+            let msp = AbePolicy::from_policy(&policy).unwrap();
+            let _attributes: Vec<String> = NAMES.iter()
+                .filter(|_| _rng.gen_bool(0.5))
+                .map(|_s| _s.to_string())
+                .collect();
+            let _coeff = msp.coefficients(&_attributes);
+            assert_eq!(_coeff.is_some(), traverse_policy(&_attributes, &policy, PolicyType::Leaf));
+            if let Some(_coeff) = _coeff {
+                assert_reconstructs(&msp, &_coeff);
+            }
+        }
+    }
+
+    #[test]
+    fn test_msp_single() {
+        let admin = vec![String::from("admin")];
+        for policy in [r#"{"name": "admin"}"#, r#"{"name": "and", "children": [{"name": "admin"}]}"#, r#"{"name": "of", "threshold": 1, "children": [{"name": "admin"}]}"#] {
+            let msp = AbePolicy::new(&String::from(policy), PolicyLanguage::JsonPolicy).unwrap();
+            assert_eq!(msp._m, vec![vec![PLUS]]);
+            assert_eq!(msp._pi, admin);
+            assert_reconstructs(&msp, &msp.coefficients(&admin).unwrap());
+            assert!(msp.coefficients(&vec![String::from("B")]).is_none());
+        }
+        assert!(AbePolicy::new(&String::from(r#"{"name": "or", "children": []}"#), PolicyLanguage::JsonPolicy).is_err());
+    }
 }
@@ -0,0 +1,358 @@
+//! Normalisation of policies and conversion into disjunctive (DNF) or conjunctive (CNF) normal form.
+//!
+//! `normalize()` simplifies a policy without changing its meaning: nested gates of the same type
+//! are flattened, duplicate children are removed and absorption is applied, e.g.
+//! `"A" or ("A" and "B")` becomes `"A"`.
+//!
+//! `to_dnf()` and `to_cnf()` convert a policy into a normal form. Threshold gates are expanded into
+//! and/or terms, so a normal form may be exponentially larger than the policy it was computed from.
+//! The conversion fails if the normal form would have more than `MAX_CLAUSES` clauses, and the
+//! size of the result is reported by `NormalForm::size()` and `NormalForm::blowup()`.
+//!
+//! Negations are moved down to the attributes first. In a clause a negated attribute is written
+//! as `!A`, and clauses containing both `A` and `!A` are removed: such a conjunction of a DNF is
+//! never satisfied and such a disjunction of a CNF is always satisfied.
+use utils::policy::ast::Policy;
+use RabeError;
+
+/// The maximal number of clauses of a normal form
+pub const MAX_CLAUSES: usize = 4096;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum NormalFormType {
+    /// A disjunction of conjunctions
+    Dnf,
+    /// A conjunction of disjunctions
+    Cnf,
+}
+
+/// A policy in disjunctive or conjunctive normal form
+#[derive(PartialEq, Clone, Debug)]
+pub struct NormalForm {
+    pub _type: NormalFormType,
+    /// The clauses, i.e. the conjunctions of a DNF or the disjunctions of a CNF
+    pub _clauses: Vec<Vec<String>>,
+    /// The number of attributes in the policy the normal form was computed from
+    pub _original_size: usize,
+}
+
+impl NormalForm {
+    /// Returns the number of attributes in the normal form
+    pub fn size(&self) -> usize {
+        self._clauses.iter().map(|_clause| _clause.len()).sum()
+    }
+
+    /// Returns the size of the normal form relative to the size of the original policy
+    pub fn blowup(&self) -> f64 {
+        self.size() as f64 / self._original_size.max(1) as f64
+    }
+
+    /// Returns the normal form as a policy
+    pub fn to_policy(&self) -> Policy {
+        let _clause = |_c: &Vec<String>| -> Policy {
+            let mut _attrs: Vec<Policy> = _c.iter().map(|_a| literal_policy(_a)).collect();
+            match (_attrs.len(), self._type) {
+                (1, _) => _attrs.remove(0),
+                (_, NormalFormType::Dnf) => Policy::and(_attrs),
+                (_, NormalFormType::Cnf) => Policy::or(_attrs),
+            }
+        };
+        let mut _clauses: Vec<Policy> = self._clauses.iter().map(_clause).collect();
+        match (_clauses.len(), self._type) {
+            (1, _) => _clauses.remove(0),
+            (_, NormalFormType::Dnf) => Policy::or(_clauses),
+            (_, NormalFormType::Cnf) => Policy::and(_clauses),
+        }
+    }
+}
+
+// the policy of a clause literal, i.e. of an attribute or a negated attribute
+fn literal_policy(_literal: &str) -> Policy {
+    match _literal.strip_prefix('!') {
+        Some(_attr) => !Policy::Attr(_attr.to_string()),
+        None => Policy::Attr(_literal.to_string()),
+    }
+}
+
+/// Simplifies a policy by flattening nested gates of the same type, removing duplicate children
+/// and applying absorption. Threshold gates with k = 1 or k = n become or and and gates.
+///
+/// # Arguments
+///
+///	* `_policy` - The policy
+pub fn normalize(_policy: &Policy) -> Policy {
+    match _policy {
+        Policy::Attr(_) => _policy.clone(),
+        Policy::Not(_child) => match _child.as_ref() {
+            Policy::Attr(_) => _policy.clone(),
+            _ => normalize(&_child.negated()),
+        },
+        Policy::And(_children) => simplify(true, _children.iter().map(normalize).collect()),
+        Policy::Or(_children) => simplify(false, _children.iter().map(normalize).collect()),
+        Policy::Threshold(_k, _children) => {
+            let _children: Vec<Policy> = _children.iter().map(normalize).collect();
+            if *_k == 1 {
+                simplify(false, _children)
+            } else if *_k == _children.len() {
+                simplify(true, _children)
+            } else {
+                Policy::threshold(*_k, _children)
+            }
+        }
+    }
+}
+
+// simplifies an and (_and = true) or an or gate with normalized children
+fn simplify(_and: bool, _children: Vec<Policy>) -> Policy {
+    // flatten gates of the same type
+    let mut _flat: Vec<Policy> = Vec::new();
+    for _child in _children {
+        match _child {
+            Policy::And(_grandchildren) if _and => _flat.extend(_grandchildren),
+            Policy::Or(_grandchildren) if !_and => _flat.extend(_grandchildren),
+            _ => _flat.push(_child),
+        }
+    }
+    // remove duplicates
+    let mut _unique: Vec<Policy> = Vec::new();
+    for _child in _flat {
+        if !_unique.contains(&_child) {
+            _unique.push(_child);
+        }
+    }
+    // absorption: A and (A or B) = A, A or (A and B) = A
+    let _terms = |_child: &Policy| -> Vec<Policy> {
+        match _child {
+            Policy::Or(_grandchildren) if _and => _grandchildren.clone(),
+            Policy::And(_grandchildren) if !_and => _grandchildren.clone(),
+            _ => vec![_child.clone()],
+        }
+    };
+    let _terms: Vec<Vec<Policy>> = _unique.iter().map(_terms).collect();
+    let mut _children: Vec<Policy> = _unique
+        .into_iter()
+        .enumerate()
+        .filter(|(_i, _)| !absorbed(&_terms, *_i))
+        .map(|(_, _child)| _child)
+        .collect();
+    match (_children.len(), _and) {
+        (1, _) => _children.remove(0),
+        (_, true) => Policy::and(_children),
+        (_, false) => Policy::or(_children),
+    }
+}
+
+// clause _i is absorbed if another clause is a subset of it. Of two equal clauses the first is kept.
+fn absorbed<T: PartialEq>(_clauses: &[Vec<T>], _i: usize) -> bool {
+    _clauses.iter().enumerate().any(|(_j, _other)| {
+        _j != _i
+            && _other.iter().all(|_t| _clauses[_i].contains(_t))
+            && (_other.len() < _clauses[_i].len() || _j < _i)
+    })
+}
+
+/// Converts a policy into disjunctive normal form
+///
+/// # Arguments
+///
+///	* `_policy` - The policy
+pub fn to_dnf(_policy: &Policy) -> Result<NormalForm, RabeError> {
+    normal_form(_policy, NormalFormType::Dnf)
+}
+
+/// Converts a policy into conjunctive normal form
+///
+/// # Arguments
+///
+///	* `_policy` - The policy
+pub fn to_cnf(_policy: &Policy) -> Result<NormalForm, RabeError> {
+    normal_form(_policy, NormalFormType::Cnf)
+}
+
+fn normal_form(_policy: &Policy, _type: NormalFormType) -> Result<NormalForm, RabeError> {
+    let _clauses = clauses(_policy, _type)?;
+    if _clauses.is_empty() || _clauses.iter().any(|_clause| _clause.is_empty()) {
+        return Err(RabeError::new(&format!("policy {} is constant and has no normal form", _policy)));
+    }
+    Ok(NormalForm {
+        _type,
+        _clauses,
+        _original_size: original_size(_policy),
+    })
+}
+
+fn original_size(_policy: &Policy) -> usize {
+    match _policy.gate() {
+        None => 1,
+        Some((_, _children)) => _children.iter().map(original_size).sum(),
+    }
+}
+
+type Clauses = Vec<Vec<String>>;
+
+// the clauses of a constant: in a DNF "true" is a single empty conjunction and "false" has no
+// conjunctions, in a CNF it is the other way around
+fn constant(_value: bool, _type: NormalFormType) -> Clauses {
+    if _value == (_type == NormalFormType::Dnf) {
+        vec![Vec::new()]
+    } else {
+        Vec::new()
+    }
+}
+
+fn clauses(_policy: &Policy, _type: NormalFormType) -> Result<Clauses, RabeError> {
+    match _policy {
+        Policy::Attr(_attr) => Ok(vec![vec![_attr.clone()]]),
+        Policy::Not(_child) => match _child.as_ref() {
+            Policy::Attr(_attr) => Ok(vec![vec![format!("!{}", _attr)]]),
+            _ => clauses(&_child.negated(), _type),
+        },
+        Policy::And(_children) => {
+            let mut _result = constant(true, _type);
+            for _child in _children {
+                _result = combine(true, &_result, &clauses(_child, _type)?, _type)?;
+            }
+            Ok(_result)
+        },
+        Policy::Or(_children) => {
+            let mut _result = constant(false, _type);
+            for _child in _children {
+                _result = combine(false, &_result, &clauses(_child, _type)?, _type)?;
+            }
+            Ok(_result)
+        },
+        Policy::Threshold(_k, _children) => {
+            // _at_least[j] holds the clauses of "at least j of the children seen so far"
+            let mut _at_least: Vec<Clauses> = vec![constant(false, _type); *_k + 1];
+            _at_least[0] = constant(true, _type);
+            for _child in _children {
+                let _child = clauses(_child, _type)?;
+                for _j in (1..*_k + 1).rev() {
+                    let _with_child = combine(true, &_at_least[_j - 1], &_child, _type)?;
+                    _at_least[_j] = combine(false, &_at_least[_j], &_with_child, _type)?;
+                }
+            }
+            Ok(_at_least.remove(*_k))
+        },
+    }
+}
+
+// combines the clauses of two policies using an and (_and = true) or an or
+fn combine(_and: bool, _a: &Clauses, _b: &Clauses, _type: NormalFormType) -> Result<Clauses, RabeError> {
+    let mut _result: Clauses = Vec::new();
+    if _and == (_type == NormalFormType::Dnf) {
+        // distribute: (a1 or a2) and (b1 or b2) = (a1 and b1) or (a1 and b2) or ...
+        for _x in _a {
+            for _y in _b {
+                let mut _clause = _x.clone();
+                for _attr in _y {
+                    if !_clause.contains(_attr) {
+                        _clause.push(_attr.clone());
+                    }
+                }
+                if !contradicts(&_clause) {
+                    _result.push(_clause);
+                }
+            }
+            check_size(_result.len())?;
+        }
+    } else {
+        _result.extend(_a.iter().cloned());
+        _result.extend(_b.iter().cloned());
+    }
+    let _absorbed: Vec<bool> = (0.._result.len()).map(|_i| absorbed(&_result, _i)).collect();
+    let _result: Clauses = _result
+        .into_iter()
+        .zip(_absorbed)
+        .filter(|(_, _absorbed)| !_absorbed)
+        .map(|(_clause, _)| _clause)
+        .collect();
+    check_size(_result.len())?;
+    Ok(_result)
+}
+
+// true if a clause contains an attribute and its negation
+fn contradicts(_clause: &[String]) -> bool {
+    _clause
+        .iter()
+        .filter_map(|_literal| _literal.strip_prefix('!'))
+        .any(|_attr| _clause.iter().any(|_literal| _literal == _attr))
+}
+
+fn check_size(_clauses: usize) -> Result<(), RabeError> {
+    if _clauses > MAX_CLAUSES {
+        Err(RabeError::new(&format!("normal form of policy exceeds {} clauses", MAX_CLAUSES)))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::policy::pest::{PolicyLanguage, PolicyType, PolicyValue};
+    use utils::tools::traverse_policy;
+
+    fn policy(_policy: &str) -> Policy {
+        Policy::parse(_policy, PolicyLanguage::HumanPolicy).unwrap()
+    }
+
+    // checks that two policies over the attributes A to E are satisfied by the same attribute sets
+    fn assert_equivalent(_a: &Policy, _b: &Policy) {
+        let _names = ["A", "B", "C", "D", "E"];
+        for _set in 0..(1 << _names.len()) {
+            let _attributes: Vec<String> = _names
+                .iter()
+                .enumerate()
+                .filter(|(_i, _)| _set & (1 << _i) != 0)
+                .map(|(_, _name)| _name.to_string())
+                .collect();
+            assert_eq!(
+                traverse_policy(&_attributes, &PolicyValue::from(_a), PolicyType::Leaf),
+                traverse_policy(&_attributes, &PolicyValue::from(_b), PolicyType::Leaf),
+                "{} and {} differ on {:?}", _a, _b, _attributes
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(&policy(r#""A" or ("A" and "B")"#)), policy(r#""A""#));
+        assert_eq!(normalize(&policy(r#""A" and ("A" or "B")"#)), policy(r#""A""#));
+        assert_eq!(normalize(&policy(r#""A" and ("B" and ("C" and "A"))"#)), policy(r#""A" and "B" and "C""#));
+        assert_eq!(normalize(&policy(r#"("A" and "B") or ("B" and "A") or ("C" and "B" and "A")"#)), policy(r#""A" and "B""#));
+        assert_eq!(normalize(&policy(r#"1 of ("A", "B" or "C")"#)), policy(r#""A" or "B" or "C""#));
+        assert_eq!(normalize(&policy(r#"2 of ("A", "B", "C")"#)), policy(r#"2 of ("A", "B", "C")"#));
+    }
+
+    #[test]
+    fn test_normal_forms() {
+        let _policies = vec![
+            r#""A" and ("B" or "C") and ("D" or "E")"#,
+            r#"("A" or "B") and ("A" or "C")"#,
+            r#"2 of ("A", "B" and "C", "D" or "E")"#,
+            r#"3 of ("A", "B", "C", "D") or "E""#,
+            r#""A""#,
+            r#"("A" and not ("B" and not "C")) or not 2 of ("A", "D", "E")"#,
+        ];
+        for _p in _policies {
+            let _p = policy(_p);
+            let _dnf = to_dnf(&_p).unwrap();
+            let _cnf = to_cnf(&_p).unwrap();
+            assert_equivalent(&_p, &_dnf.to_policy());
+            assert_equivalent(&_p, &_cnf.to_policy());
+            assert_equivalent(&_p, &normalize(&_p));
+        }
+        let _dnf = to_dnf(&policy(r#""A" and ("B" or "C") and ("D" or "E")"#)).unwrap();
+        assert_eq!(_dnf._clauses.len(), 4);
+        assert_eq!((_dnf._original_size, _dnf.size()), (5, 12));
+        assert_eq!(_dnf.blowup(), 12.0 / 5.0);
+        assert_eq!(to_cnf(&policy(r#"("A" and "B") or ("A" and "C")"#)).unwrap().to_policy(), policy(r#""A" and ("B" or "C")"#));
+        // contradictions are removed
+        assert_eq!(to_dnf(&policy(r#""A" and ("B" or not "A")"#)).unwrap()._clauses, vec![vec!["A", "B"]]);
+        assert!(to_dnf(&policy(r#""A" and not "A""#)).is_err());
+        assert!(to_cnf(&policy(r#""A" or not "A""#)).is_err());
+        assert!(to_dnf(&policy(r#"8 of ("A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P")"#)).is_err());
+    }
+}
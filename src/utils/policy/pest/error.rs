@@ -0,0 +1,263 @@
+//! Errors of the policy parsers, with the position of the error and the expected tokens.
+use pest::error::{Error as PestError, ErrorVariant, InputLocation, LineColLocation};
+use pest::RuleType;
+use std::fmt;
+use utils::policy::pest::PolicyLanguage;
+
+/// The reserved words of the human policy language
+const KEYWORDS: [&str; 4] = ["and", "or", "of", "not"];
+
+/// A malformed policy
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct PolicyParseError {
+    pub _language: PolicyLanguage,
+    /// The line of the error, starting at 1
+    pub _line: usize,
+    /// The column of the error, starting at 1
+    pub _column: usize,
+    /// The byte offsets of the unexpected token in the policy
+    pub _span: (usize, usize),
+    pub _expected: Vec<String>,
+    pub _found: String,
+    /// The line of the policy containing the error
+    pub _source: String,
+    pub _suggestion: Option<String>,
+}
+
+impl PolicyParseError {
+    /// Converts an error of a pest parser
+    ///
+    /// # Arguments
+    ///
+    ///	* `_error` - The error of the pest parser
+    ///	* `_language` - The language of the parser
+    pub fn from_pest<R: RuleType>(_error: PestError<R>, _language: PolicyLanguage) -> PolicyParseError {
+        let _source = _error.line().trim_end_matches(&['\r', '\n'][..]).to_string();
+        let (_line, _column) = match _error.line_col {
+            LineColLocation::Pos(_pos) => _pos,
+            LineColLocation::Span(_start, _) => _start,
+        };
+        let _found = token_at(&_source, _column);
+        let _span = match _error.location {
+            InputLocation::Pos(_pos) => (_pos, _pos + _found.len()),
+            InputLocation::Span(_span) => _span,
+        };
+        let _expected = match &_error.variant {
+            ErrorVariant::ParsingError { positives, .. } => {
+                let mut _expected: Vec<String> = Vec::new();
+                for _rule in positives {
+                    let _token = describe(&format!("{:?}", _rule), _language);
+                    if !_expected.contains(&_token) {
+                        _expected.push(_token);
+                    }
+                }
+                _expected
+            },
+            ErrorVariant::CustomError { message } => vec![message.clone()],
+        };
+        let _suggestion = suggest(&_source, _column, &_found, &_expected, _language);
+        PolicyParseError { _language, _line, _column, _span, _expected, _found, _source, _suggestion }
+    }
+
+    /// Returns the line of the policy containing the error, with the unexpected token marked by carets
+    pub fn snippet(&self) -> String {
+        let _number = self._line.to_string();
+        let _indent = " ".repeat(_number.len());
+        let _offset: String = self._source
+            .chars()
+            .take(self._column - 1)
+            .map(|_c| if _c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let _carets = "^".repeat(self._found.chars().count().max(1));
+        let mut _snippet = format!(
+            "{} --> line {}, column {}\n{} |\n{} | {}\n{} | {}{} {}",
+            _indent, self._line, self._column, _indent, _number, self._source, _indent, _offset, _carets, self.message()
+        );
+        if let Some(_suggestion) = &self._suggestion {
+            _snippet.push_str(&format!("\n{} = help: {}", _indent, _suggestion));
+        }
+        _snippet
+    }
+
+    /// Returns what was expected and what was found
+    pub fn message(&self) -> String {
+        let _found = if self._found.is_empty() {
+            String::from("end of policy")
+        } else if self._found.starts_with('"') {
+            self._found.clone()
+        } else {
+            format!("\"{}\"", self._found)
+        };
+        match self._expected.len() {
+            0 => format!("unexpected {}", _found),
+            1 => format!("expected {}, found {}", self._expected[0], _found),
+            _ => {
+                let (_last, _first) = self._expected.split_last().unwrap();
+                format!("expected {} or {}, found {}", _first.join(", "), _last, _found)
+            }
+        }
+    }
+}
+
+impl fmt::Display for PolicyParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let _language = match self._language {
+            PolicyLanguage::HumanPolicy => "Human",
+            PolicyLanguage::JsonPolicy => "Json",
+        };
+        write!(f, "{} Policy Error in line {}, column {}: {}", _language, self._line, self._column, self.message())
+    }
+}
+
+// the token starting at column _column (1-based) of the line _source
+fn token_at(_source: &str, _column: usize) -> String {
+    let _rest: String = _source.chars().skip(_column - 1).collect();
+    let _rest = _rest.trim_end();
+    let _word: String = _rest
+        .chars()
+        .take_while(|_c| _c.is_alphanumeric() || "_-.:#@/=!&|".contains(*_c))
+        .collect();
+    if !_word.is_empty() {
+        _word
+    } else if let Some(_quoted) = _rest.strip_prefix('"') {
+        // a quoted string up to the closing quote
+        match _quoted.find('"') {
+            Some(_end) => _rest[.._end + 2].to_string(),
+            None => _rest.to_string(),
+        }
+    } else {
+        _rest.chars().take(1).collect()
+    }
+}
+
+// a readable description of a grammar rule
+fn describe(_rule: &str, _language: PolicyLanguage) -> String {
+    match (_rule, _language) {
+        ("string", PolicyLanguage::HumanPolicy) => String::from("a quoted attribute"),
+        ("string", PolicyLanguage::JsonPolicy) => String::from("a quoted string"),
+        ("number", _) | ("count", _) => String::from("a number"),
+        ("identifier", _) => String::from("an attribute name"),
+        ("operator", _) => String::from("a comparison operator"),
+        ("and", _) => String::from("an \"and\" gate"),
+        ("or", _) => String::from("an \"or\" gate"),
+        ("threshold", _) => String::from("a threshold gate"),
+        ("comparison", _) => String::from("a comparison"),
+        ("not", _) | ("notvalue", _) => String::from("\"not\""),
+        ("andinner", _) => String::from("\"and\""),
+        ("orinner", _) => String::from("\"or\""),
+        ("EOI", _) => String::from("end of policy"),
+        ("NAME", _) => String::from("\"name\""),
+        ("CHILDREN", _) => String::from("\"children\""),
+        ("THRESHOLD", _) => String::from("\"threshold\""),
+        ("OPERATOR", _) => String::from("\"operator\""),
+        ("VALUE", _) => String::from("\"value\""),
+        _ => _rule.to_string(),
+    }
+}
+
+fn suggest(_source: &str, _column: usize, _found: &str, _expected: &[String], _language: PolicyLanguage) -> Option<String> {
+    if _language != PolicyLanguage::HumanPolicy {
+        return None;
+    }
+    let _lower = _found.to_lowercase();
+    // "and" and "or" cannot be mixed on one level
+    let _other = match _lower.as_str() {
+        "and" | "&&" => Some("\"or\""),
+        "or" | "||" => Some("\"and\""),
+        _ => None,
+    };
+    if _other.is_some_and(|_other| _expected.iter().any(|_e| _e == _other)) {
+        return Some(String::from("use parentheses to combine \"and\" and \"or\", e.g. (\"A\" and \"B\") or \"C\""));
+    }
+    if !_found.is_empty() && !_found.starts_with('"') && !KEYWORDS.contains(&_lower.as_str()) {
+        if let Some(_keyword) = KEYWORDS.iter().find(|_k| edit_distance(&_lower, _k) == 1) {
+            return Some(format!("did you mean \"{}\"?", _keyword));
+        }
+        if _expected.iter().any(|_e| _e == &describe("string", _language)) {
+            return Some(format!("attributes have to be quoted, e.g. \"{}\"", _found));
+        }
+    }
+    // an unquoted attribute is only allowed on the left side of a comparison
+    let _before: String = _source.chars().take(_column - 1).collect();
+    let _before = _before.trim_end();
+    let _previous: String = _before
+        .chars()
+        .rev()
+        .take_while(|_c| _c.is_alphanumeric() || "_-.:#@/".contains(*_c))
+        .collect::<Vec<char>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let _quoted = _before.len() > _previous.len() && _before[.._before.len() - _previous.len()].ends_with('"');
+    if !_previous.is_empty() && !_quoted && !KEYWORDS.contains(&_previous.to_lowercase().as_str()) {
+        return Some(format!("attributes have to be quoted, e.g. \"{}\"", _previous));
+    }
+    None
+}
+
+// the edit distance of two words, counting the transposition of two neighbouring characters as one edit
+fn edit_distance(_a: &str, _b: &str) -> usize {
+    let _a: Vec<char> = _a.chars().collect();
+    let _b: Vec<char> = _b.chars().collect();
+    let mut _d = vec![vec![0; _b.len() + 1]; _a.len() + 1];
+    for (_i, _row) in _d.iter_mut().enumerate() {
+        _row[0] = _i;
+    }
+    for (_j, _cell) in _d[0].iter_mut().enumerate() {
+        *_cell = _j;
+    }
+    for _i in 1.._a.len() + 1 {
+        for _j in 1.._b.len() + 1 {
+            let _cost = if _a[_i - 1] == _b[_j - 1] { 0 } else { 1 };
+            _d[_i][_j] = (_d[_i - 1][_j] + 1).min(_d[_i][_j - 1] + 1).min(_d[_i - 1][_j - 1] + _cost);
+            if _i > 1 && _j > 1 && _a[_i - 1] == _b[_j - 2] && _a[_i - 2] == _b[_j - 1] {
+                _d[_i][_j] = _d[_i][_j].min(_d[_i - 2][_j - 2] + 1);
+            }
+        }
+    }
+    _d[_a.len()][_b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::policy::pest::parse;
+
+    fn error(_policy: &str, _language: PolicyLanguage) -> PolicyParseError {
+        parse(_policy, _language).err().unwrap().policy_parse_error().unwrap().clone()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let typo = error(r#""A" adn "B""#, PolicyLanguage::HumanPolicy);
+        assert_eq!((typo._line, typo._column, typo._span), (1, 5, (4, 7)));
+        assert_eq!(typo._found, "adn");
+        assert!(typo._expected.contains(&String::from("\"and\"")));
+        assert_eq!(typo._suggestion, Some(String::from("did you mean \"and\"?")));
+        assert_eq!(
+            typo.snippet(),
+            "  --> line 1, column 5\n  |\n1 | \"A\" adn \"B\"\n  |     ^^^ expected end of policy, \"and\", \"or\" or a comparison operator, found \"adn\"\n  = help: did you mean \"and\"?"
+        );
+        let unquoted = error(r#"A and "B""#, PolicyLanguage::HumanPolicy);
+        assert_eq!(unquoted._expected, vec!["a comparison operator"]);
+        assert_eq!(unquoted._suggestion, Some(String::from("attributes have to be quoted, e.g. \"A\"")));
+        let mixed = error("\"A\" and\n  \"B\" or \"C\"", PolicyLanguage::HumanPolicy);
+        assert_eq!((mixed._line, mixed._column, mixed._span), (2, 7, (14, 16)));
+        assert_eq!(mixed._source, "  \"B\" or \"C\"");
+        assert!(mixed._suggestion.unwrap().starts_with("use parentheses"));
+        let unclosed = error(r#""A" and ("B" or "C""#, PolicyLanguage::HumanPolicy);
+        assert_eq!(unclosed._found, "");
+        assert!(unclosed.message().ends_with("found end of policy"));
+        assert_eq!(
+            parse(r#""A" and and "B""#, PolicyLanguage::HumanPolicy).err().unwrap().to_string(),
+            "RabeError: Human Policy Error in line 1, column 9: expected \"not\", a comparison, a quoted attribute or a number, found \"and\""
+        );
+        let json = error(r#"{"name": "age", "operator": "=>", "value": 18}"#, PolicyLanguage::JsonPolicy);
+        assert_eq!(json._language, PolicyLanguage::JsonPolicy);
+        assert_eq!((json._line, json._column, json._span), (1, 30, (29, 30)));
+        assert_eq!(json._expected, vec!["a comparison operator"]);
+        assert!(json.to_string().starts_with("Json Policy Error"));
+        assert_eq!(json._suggestion, None);
+    }
+}
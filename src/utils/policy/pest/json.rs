@@ -1,28 +1,59 @@
-use utils::policy::pest::{PolicyValue, PolicyType};
+use utils::policy::pest::{PolicyValue, PolicyType, attribute_leaf};
+use utils::policy::comparison::{Comparison, comparison_policy, value_policy};
 use pest::iterators::Pair;
+use RabeError;
 
 #[derive(Parser)]
 #[grammar = "json.policy.pest"]
 pub(crate) struct JSONPolicyParser;
 
-pub(crate) fn parse(pair: Pair<Rule>) -> PolicyValue {
-    match pair.as_rule() {
-        Rule::string => PolicyValue::String(pair.into_inner().next().unwrap().as_str()),
-        Rule::number => PolicyValue::String(pair.into_inner().next().unwrap().as_str()),
+pub(crate) fn parse(pair: Pair<Rule>) -> Result<PolicyValue, RabeError> {
+    Ok(match pair.as_rule() {
+        Rule::string => attribute_leaf(pair.into_inner().next().unwrap().as_str())?,
+        Rule::number => attribute_leaf(pair.as_str())?,
         Rule::and => {
             let mut vec = Vec::new();
             for child in pair.into_inner() {
-                vec.push(parse(child));
+                vec.push(parse(child)?);
             }
             PolicyValue::Object((PolicyType::And, Box::new(PolicyValue::Array(vec))))
         },
         Rule::or => {
             let mut vec = Vec::new();
             for child in pair.into_inner() {
-                vec.push(parse(child));
+                vec.push(parse(child)?);
             }
             PolicyValue::Object((PolicyType::Or, Box::new(PolicyValue::Array(vec))))
         },
+        Rule::not => PolicyValue::Object((PolicyType::Not, Box::new(parse(pair.into_inner().next().unwrap())?))),
+        Rule::threshold => {
+            let mut inner = pair.into_inner();
+            let k = inner.next().unwrap().as_str().parse::<usize>().unwrap_or(0);
+            let mut vec = Vec::new();
+            for child in inner {
+                vec.push(parse(child)?);
+            }
+            PolicyValue::Object((PolicyType::Threshold(k), Box::new(PolicyValue::Array(vec))))
+        },
+        Rule::comparison => {
+            let mut inner = pair.into_inner();
+            let name = inner.next().unwrap();
+            let name = match name.as_rule() {
+                Rule::string => name.into_inner().next().unwrap().as_str(),
+                _ => name.as_str(),
+            };
+            let comparison = Comparison::from_operator(inner.next().unwrap().as_str())?;
+            let value = inner.next().unwrap();
+            match value.as_rule() {
+                Rule::string => value_policy(name, comparison, value.into_inner().next().unwrap().as_str())?,
+                _ => {
+                    let count = value.as_str();
+                    let value = count.parse::<u64>()
+                        .map_err(|_| RabeError::new(&format!("Invalid comparison value: {}", count)))?;
+                    comparison_policy(name, comparison, value)?
+                }
+            }
+        },
         Rule::content
         | Rule::EOI
         | Rule::inner
@@ -32,11 +63,20 @@ pub(crate) fn parse(pair: Pair<Rule>) -> PolicyValue {
         | Rule::value
         | Rule::andvalue
         | Rule::orvalue
+        | Rule::ofvalue
+        | Rule::notinner
+        | Rule::notvalue
+        | Rule::count
+        | Rule::operator
+        | Rule::OPERATOR
+        | Rule::VALUE
         | Rule::char
         | Rule::NAME
         | Rule::CHILDREN
+        | Rule::THRESHOLD
+        | Rule::ofinner
         | Rule::COMMENT
         | Rule::QUOTE
         | Rule::WHITESPACE => unreachable!(),
-    }
+    })
 }
\ No newline at end of file
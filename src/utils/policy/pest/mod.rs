@@ -1,22 +1,29 @@
 use pest::Parser;
+use std::borrow::Cow;
 use std::string::String;
+use utils::attribute::canonical_attribute;
 use RabeError;
 
 pub(crate) mod json;
 pub(crate) mod human;
+pub mod error;
 use self::human::HumanPolicyParser;
 use self::json::JSONPolicyParser;
 
-#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
 pub enum PolicyLanguage {
     JsonPolicy,
     HumanPolicy,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
 pub enum PolicyType {
     And,
     Or,
+    /// A k-of-n gate: satisfied if at least k of its children are satisfied
+    Threshold(usize),
+    /// The negation of its child. After parsing, only attributes are negated
+    Not,
     Leaf
 }
 
@@ -24,7 +31,7 @@ pub enum PolicyType {
 pub enum PolicyValue<'a> {
     Object((PolicyType, Box<PolicyValue<'a>>)),
     Array(Vec<PolicyValue<'a>>),
-    String(&'a str),
+    String(Cow<'a, str>),
 }
 
 pub fn parse(policy: &str, language: PolicyLanguage) -> Result<PolicyValue, RabeError> {
@@ -32,20 +39,80 @@ pub fn parse(policy: &str, language: PolicyLanguage) -> Result<PolicyValue, Rabe
         PolicyLanguage::JsonPolicy => {
             use utils::policy::pest::json::Rule;
             match JSONPolicyParser::parse(Rule::content, policy) {
-                Ok(mut result) => Ok(json::parse(result.next().unwrap())),
+                Ok(mut result) => Ok(push_negations(check_thresholds(json::parse(result.next().unwrap())?)?, false)),
                 Err(e) => Err(e.into())
             }
         },
         PolicyLanguage::HumanPolicy => {
             use utils::policy::pest::human::Rule;
             match HumanPolicyParser::parse(Rule::content, policy) {
-                Ok(mut result) => Ok(human::parse(result.next().unwrap())),
+                Ok(mut result) => Ok(push_negations(check_thresholds(human::parse(result.next().unwrap())?)?, false)),
                 Err(e) => Err(e.into())
             }
         }
     }
 }
 
+/// A leaf of a policy, i.e. an attribute in its canonical encoding
+pub(crate) fn attribute_leaf(attribute: &str) -> Result<PolicyValue<'_>, RabeError> {
+    let canonical = canonical_attribute(attribute)?;
+    if canonical == attribute {
+        Ok(PolicyValue::String(Cow::Borrowed(attribute)))
+    } else {
+        Ok(PolicyValue::String(Cow::Owned(canonical)))
+    }
+}
+
+/// Rejects threshold gates that can never (k > n) or always (k = 0) be satisfied
+fn check_thresholds(policy: PolicyValue) -> Result<PolicyValue, RabeError> {
+    fn check(val: &PolicyValue) -> Result<(), RabeError> {
+        match val {
+            PolicyValue::Object((PolicyType::Threshold(k), children)) => {
+                match children.as_ref() {
+                    PolicyValue::Array(a) if *k >= 1 && *k <= a.len() => check(children),
+                    PolicyValue::Array(a) => Err(RabeError::new(&format!("Invalid threshold gate: {} of {} children", k, a.len()))),
+                    _ => check(children)
+                }
+            },
+            PolicyValue::Object(obj) => check(obj.1.as_ref()),
+            PolicyValue::Array(a) => a.iter().try_for_each(check),
+            PolicyValue::String(_) => Ok(())
+        }
+    }
+    check(&policy).map(|_| policy)
+}
+
+/// Moves all negations down to the attributes, e.g. `not ("A" and "B")` becomes
+/// `not "A" or not "B"` and `not 2 of ("A", "B", "C")` becomes `2 of (not "A", not "B", not "C")`
+fn push_negations(policy: PolicyValue, negate: bool) -> PolicyValue {
+    match policy {
+        PolicyValue::Object((PolicyType::Not, child)) => push_negations(*child, !negate),
+        PolicyValue::Object((gate, children)) => {
+            let gate = match (gate, children.as_ref()) {
+                (PolicyType::And, _) if negate => PolicyType::Or,
+                (PolicyType::Or, _) if negate => PolicyType::And,
+                // at most k - 1 of n children are satisfied, i.e. at least n - k + 1 are not
+                (PolicyType::Threshold(k), PolicyValue::Array(a)) if negate => PolicyType::Threshold(a.len() + 1 - k),
+                _ => gate,
+            };
+            PolicyValue::Object((gate, Box::new(push_negations(*children, negate))))
+        },
+        PolicyValue::Array(a) => PolicyValue::Array(a.into_iter().map(|child| push_negations(child, negate)).collect()),
+        PolicyValue::String(s) if negate => PolicyValue::Object((PolicyType::Not, Box::new(PolicyValue::String(s)))),
+        PolicyValue::String(s) => PolicyValue::String(s),
+    }
+}
+
+/// Returns true if a policy contains no negated attributes
+pub fn is_monotone(policy: &PolicyValue) -> bool {
+    match policy {
+        PolicyValue::Object((PolicyType::Not, _)) => false,
+        PolicyValue::Object(obj) => is_monotone(obj.1.as_ref()),
+        PolicyValue::Array(a) => a.iter().all(is_monotone),
+        PolicyValue::String(_) => true,
+    }
+}
+
 pub fn serialize_policy(val: &PolicyValue, language: PolicyLanguage, parent: Option<PolicyType>) -> String {
     use self::PolicyValue::*;
     match language {
@@ -55,6 +122,8 @@ pub fn serialize_policy(val: &PolicyValue, language: PolicyLanguage, parent: Opt
                     match obj.0 {
                         PolicyType::And => format!("{{\"name\": \"and\", {}}}", serialize_policy(obj.1.as_ref(), language, None)),
                         PolicyType::Or => format!("{{\"name\": \"or\", {}}}", serialize_policy(obj.1.as_ref(), language, None)),
+                        PolicyType::Threshold(k) => format!("{{\"name\": \"of\", \"threshold\": {}, {}}}", k, serialize_policy(obj.1.as_ref(), language, None)),
+                        PolicyType::Not => format!("{{\"name\": \"not\", \"children\": [{}]}}", serialize_policy(obj.1.as_ref(), language, None)),
                         PolicyType::Leaf => serialize_policy(&obj.1.as_ref(), language, None)
                     }
                 },
@@ -69,8 +138,10 @@ pub fn serialize_policy(val: &PolicyValue, language: PolicyLanguage, parent: Opt
             match val {
                 Object(obj) => {
                     match obj.0 {
-                        PolicyType::And => format!("{}", serialize_policy(obj.1.as_ref(), language, Some(PolicyType::And))),
-                        PolicyType::Or => format!("{}", serialize_policy(obj.1.as_ref(), language, Some(PolicyType::Or))),
+                        PolicyType::And => serialize_policy(obj.1.as_ref(), language, Some(PolicyType::And)),
+                        PolicyType::Or => serialize_policy(obj.1.as_ref(), language, Some(PolicyType::Or)),
+                        PolicyType::Threshold(k) => serialize_policy(obj.1.as_ref(), language, Some(PolicyType::Threshold(k))),
+                        PolicyType::Not => format!("not {}", serialize_policy(obj.1.as_ref(), language, None)),
                         PolicyType::Leaf => serialize_policy(&obj.1.as_ref(), language, Some(PolicyType::Leaf))
                     }
                 },
@@ -79,6 +150,7 @@ pub fn serialize_policy(val: &PolicyValue, language: PolicyLanguage, parent: Opt
                     match parent {
                         Some(PolicyType::And) => format!("({})", contents.join(" and ")),
                         Some(PolicyType::Or) => format!("({})", contents.join(" or ")),
+                        Some(PolicyType::Threshold(k)) => format!("{} of ({})", k, contents.join(", ")),
                         _ => panic!("children without parent")
                     }
                 }
@@ -124,4 +196,61 @@ mod tests {
         assert_eq!(serialized_json, pol);
         assert_eq!(serialized_human, human);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_threshold_parsing() {
+        let pol = String::from(r#"{"name": "of", "threshold": 2, "children": [{"name": "A"}, {"name": "B"}, {"name": "and", "children": [{"name": "C"}, {"name": "D"}]}]}"#);
+        let human = String::from(r#"2 of (A, B, (C and D))"#);
+        let json: PolicyValue = parse(&pol, PolicyLanguage::JsonPolicy).expect("unsuccessful parse");
+        assert_eq!(serialize_policy(&json, PolicyLanguage::JsonPolicy, None), pol);
+        assert_eq!(serialize_policy(&json, PolicyLanguage::HumanPolicy, None), human);
+        let parsed: PolicyValue = parse(r#"2 of ("A", "B", "C" and "D")"#, PolicyLanguage::HumanPolicy).expect("unsuccessful parse");
+        assert!(parsed == json);
+        let nested: PolicyValue = parse(r#""X" or 1 of {"A", "B"}"#, PolicyLanguage::HumanPolicy).expect("unsuccessful parse");
+        assert_eq!(serialize_policy(&nested, PolicyLanguage::HumanPolicy, None), "(X or 1 of (A, B))");
+        assert!(parse(r#"3 of ("A", "B")"#, PolicyLanguage::HumanPolicy).is_err());
+        assert!(parse(r#"{"name": "of", "threshold": 0, "children": [{"name": "A"}]}"#, PolicyLanguage::JsonPolicy).is_err());
+    }
+
+    #[test]
+    fn test_negation_parsing() {
+        let human: PolicyValue = parse(r#""A" and not ("B" or !"C")"#, PolicyLanguage::HumanPolicy).expect("unsuccessful parse");
+        let json: PolicyValue = parse(
+            r#"{"name": "and", "children": [{"name": "A"}, {"name": "not", "children": [{"name": "or", "children": [{"name": "B"}, {"name": "!", "children": [{"name": "C"}]}]}]}]}"#,
+            PolicyLanguage::JsonPolicy
+        ).expect("unsuccessful parse");
+        assert!(human == json);
+        // negations are moved down to the attributes
+        assert_eq!(serialize_policy(&human, PolicyLanguage::HumanPolicy, None), "(A and (not B and C))");
+        assert_eq!(
+            serialize_policy(&human, PolicyLanguage::JsonPolicy, None),
+            r#"{"name": "and", "children": [{"name": "A"}, {"name": "and", "children": [{"name": "not", "children": [{"name": "B"}]}, {"name": "C"}]}]}"#
+        );
+        assert!(!is_monotone(&human));
+        assert!(is_monotone(&parse(r#"not not "A""#, PolicyLanguage::HumanPolicy).unwrap()));
+        let threshold = parse(r#"not 2 of ("A", "B", "C")"#, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(serialize_policy(&threshold, PolicyLanguage::HumanPolicy, None), "2 of (not A, not B, not C)");
+        // "not" is a keyword, and a literal attribute must not start with "!"
+        assert!(parse(r#"not and "A""#, PolicyLanguage::HumanPolicy).is_err());
+        assert!(parse(r#""!A""#, PolicyLanguage::HumanPolicy).is_err());
+    }
+
+    #[test]
+    fn test_attribute_leaves() {
+        // typed leaves are stored in their canonical encoding, see utils::attribute
+        let canonical = parse(r#""hr :: dept = finance" and 42 and -1.5 and 1e5"#, PolicyLanguage::HumanPolicy).unwrap();
+        assert_eq!(serialize_policy(&canonical, PolicyLanguage::HumanPolicy, None), "(hr::dept=finance and 42 and -1.5 and 1e5)");
+        // plain leaves accept any character, as before typed attributes
+        for _leaf in ["1e+5", r#""A B""#, r#""A&B""#] {
+            assert!(parse(_leaf, PolicyLanguage::HumanPolicy).is_ok(), "{}", _leaf);
+            assert!(parse(&format!(r#"{{"name": {}}}"#, _leaf), PolicyLanguage::JsonPolicy).is_ok(), "{}", _leaf);
+        }
+        assert!(parse(r#""A B""#, PolicyLanguage::HumanPolicy).unwrap() == PolicyValue::String(Cow::Borrowed("A B")));
+        // the stricter rules apply to typed leaves only
+        for _leaf in [r#""dept=fin=ance""#, r#""hr::A B&C""#, r#""""#] {
+            assert!(parse(_leaf, PolicyLanguage::HumanPolicy).is_err(), "{}", _leaf);
+            assert!(parse(&format!(r#"{{"name": {}}}"#, _leaf), PolicyLanguage::JsonPolicy).is_err(), "{}", _leaf);
+        }
+    }
+}
+
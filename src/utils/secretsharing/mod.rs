@@ -1,19 +1,32 @@
 use rabe_bn::*;
 use rand::Rng;
 use utils::{
-    tools::{contains, usize_to_fr, get_value},
+    tools::{contains, usize_to_fr, get_value, traverse_policy},
     policy::pest::{PolicyValue, PolicyLanguage, parse, PolicyType}
 };
 use RabeError;
 
-pub fn calc_coefficients(_json: &PolicyValue, _fr: Option<Fr>, _type: Option<PolicyType>) -> Option<Vec<(String, Fr)>> {
+/// Calculates the lagrange coefficients of all leaves of a policy.
+///
+/// # Arguments
+///
+///	* `_json` - The policy
+///	* `_fr` - The coefficient of the current node (None for the root)
+///	* `_type` - The type of the parent node
+///	* `_attr` - The attributes used for decryption. They select which k children of a threshold gate are used, if None the first k children are used
+pub fn calc_coefficients(_json: &PolicyValue, _fr: Option<Fr>, _type: Option<PolicyType>, _attr: Option<&Vec<String>>) -> Option<Vec<(String, Fr)>> {
     let _coeff = _fr.unwrap_or(Fr::one());
     let mut _result: Vec<(String, Fr)> = Vec::new();
     return match _json {
         PolicyValue::Object(obj) => {
             match obj.0 {
-                PolicyType::And => calc_coefficients(&obj.1.as_ref(), _fr, Some(PolicyType::And)),
-                PolicyType::Or => calc_coefficients(&obj.1.as_ref(), _fr, Some(PolicyType::Or)),
+                PolicyType::And => calc_coefficients(obj.1.as_ref(), _fr, Some(PolicyType::And), _attr),
+                PolicyType::Or => calc_coefficients(obj.1.as_ref(), _fr, Some(PolicyType::Or), _attr),
+                PolicyType::Threshold(k) => calc_coefficients(obj.1.as_ref(), _fr, Some(PolicyType::Threshold(k)), _attr),
+                PolicyType::Not => {
+                    _result.push((negated_label(&obj.1), _coeff));
+                    return Some(_result);
+                },
                 _ => {
                     _result.push((get_value(&obj.1), _coeff));
                     return Some(_result);
@@ -30,7 +43,7 @@ pub fn calc_coefficients(_json: &PolicyValue, _fr: Option<Fr>, _type: Option<Pol
                     }
                     let _this_coeff = recover_coefficients(_vec);
                     for (i, child) in children.iter().enumerate() {
-                        match calc_coefficients(&child, Some(_coeff * _this_coeff[i]), None) {
+                        match calc_coefficients(child, Some(_coeff * _this_coeff[i]), None, _attr) {
                             None => return None,
                             Some(_res) => {
                                 _result.extend(_res.iter().cloned());
@@ -42,7 +55,7 @@ pub fn calc_coefficients(_json: &PolicyValue, _fr: Option<Fr>, _type: Option<Pol
                 PolicyType::Or => {
                     let _this_coeff = recover_coefficients(vec![Fr::one()]);
                     for child in children.iter() {
-                        match calc_coefficients(&child, Some(_coeff * _this_coeff[0]), None) {
+                        match calc_coefficients(child, Some(_coeff * _this_coeff[0]), None, _attr) {
                             None => return None,
                             Some(_res) => {
                                 _result.extend(_res.iter().cloned());
@@ -50,7 +63,30 @@ pub fn calc_coefficients(_json: &PolicyValue, _fr: Option<Fr>, _type: Option<Pol
                         }
                     }
                     Some(_result)
-                }
+                },
+                PolicyType::Threshold(k) => {
+                    // children are shared at x = 1..n, use the first k satisfied ones
+                    let _selected: Vec<usize> = (0..children.len())
+                        .filter(|&_i| match _attr {
+                            Some(_a) => traverse_policy(_a, &children[_i], PolicyType::Leaf),
+                            None => true,
+                        })
+                        .take(k)
+                        .collect();
+                    if _selected.len() < k {
+                        return None;
+                    }
+                    let _this_coeff = recover_coefficients(_selected.iter().map(|&_i| usize_to_fr(_i + 1)).collect());
+                    for (_j, &_i) in _selected.iter().enumerate() {
+                        match calc_coefficients(&children[_i], Some(_coeff * _this_coeff[_j]), None, _attr) {
+                            None => return None,
+                            Some(_res) => {
+                                _result.extend(_res.iter().cloned());
+                            }
+                        }
+                    }
+                    Some(_result)
+                },
                 _ => None
             }
         }
@@ -61,6 +97,11 @@ pub fn calc_coefficients(_json: &PolicyValue, _fr: Option<Fr>, _type: Option<Pol
     };
 }
 
+// the label of a negated attribute, e.g. "!A"
+fn negated_label(_json: &PolicyValue) -> String {
+    format!("!{}", get_value(_json))
+}
+
 // lagrange interpolation
 pub fn recover_coefficients(_list: Vec<Fr>) -> Vec<Fr> {
     let mut _coeff: Vec<Fr> = Vec::new();
@@ -77,6 +118,11 @@ pub fn recover_coefficients(_list: Vec<Fr>) -> Vec<Fr> {
 }
 
 pub fn gen_shares_policy(_secret: Fr, _json: &PolicyValue, _type: Option<PolicyType>) -> Option<Vec<(String, Fr)>> {
+    gen_shares_policy_with(_secret, _json, _type, &mut rand::thread_rng())
+}
+
+/// Like gen_shares_policy(), but draws the polynomial coefficients from the given random number generator.
+pub fn gen_shares_policy_with<R: Rng>(_secret: Fr, _json: &PolicyValue, _type: Option<PolicyType>, _rng: &mut R) -> Option<Vec<(String, Fr)>> {
     let mut _result: Vec<(String, Fr)> = Vec::new();
     let mut _k = 0;
     let mut _n = 0;
@@ -87,26 +133,34 @@ pub fn gen_shares_policy(_secret: Fr, _json: &PolicyValue, _type: Option<PolicyT
         },
         PolicyValue::Object(obj) => {
             match obj.0 {
-                PolicyType::And => gen_shares_policy(_secret, &obj.1.as_ref(), Some(PolicyType::And)),
-                PolicyType::Or => gen_shares_policy(_secret, &obj.1.as_ref(), Some(PolicyType::Or)),
-                _ => gen_shares_policy(_secret, &obj.1.as_ref(), Some(PolicyType::Leaf)),
+                PolicyType::And => gen_shares_policy_with(_secret, obj.1.as_ref(), Some(PolicyType::And), _rng),
+                PolicyType::Or => gen_shares_policy_with(_secret, obj.1.as_ref(), Some(PolicyType::Or), _rng),
+                PolicyType::Threshold(k) => gen_shares_policy_with(_secret, obj.1.as_ref(), Some(PolicyType::Threshold(k)), _rng),
+                PolicyType::Not => Some(vec![(negated_label(&obj.1), _secret)]),
+                _ => gen_shares_policy_with(_secret, obj.1.as_ref(), Some(PolicyType::Leaf), _rng),
             }
         },
         PolicyValue::Array(children) => {
             _n = children.len();
+            if _n == 0 {
+                return None;
+            }
             match _type {
                 Some(PolicyType::And) => {
                     _k = _n;
                 },
                 Some(PolicyType::Or) => {
                     _k = 1;
-                }
-                None => panic!("this should not happen =( Array is always AND or OR."),
-                _ => panic!("this should not happen =( Array is always AND or OR.")
+                },
+                Some(PolicyType::Threshold(k)) => {
+                    _k = k;
+                },
+                None => panic!("this should not happen =( Array is always AND, OR or a threshold."),
+                _ => panic!("this should not happen =( Array is always AND, OR or a threshold.")
             }
-            let shares = gen_shares(_secret, _k, _n);
+            let shares = gen_shares_with(_secret, _k, _n, _rng);
             for _i in 0.._n {
-                match gen_shares_policy(shares[_i + 1], &children[_i], None) {
+                match gen_shares_policy_with(shares[_i + 1], &children[_i], None, _rng) {
                     None => panic!("Error in gen_shares_policy: Returned None."),
                     Some(_items) => {
                         _result.extend(_items.iter().cloned());
@@ -119,10 +173,13 @@ pub fn gen_shares_policy(_secret: Fr, _json: &PolicyValue, _type: Option<PolicyT
 }
 
 pub fn gen_shares(_secret: Fr, _k: usize, _n: usize) -> Vec<Fr> {
+    gen_shares_with(_secret, _k, _n, &mut rand::thread_rng())
+}
+
+/// Like gen_shares(), but draws the polynomial coefficients from the given random number generator.
+pub fn gen_shares_with<R: Rng>(_secret: Fr, _k: usize, _n: usize, _rng: &mut R) -> Vec<Fr> {
     let mut _shares: Vec<Fr> = Vec::new();
     if _k <= _n {
-        // random number generator
-        let mut _rng = rand::thread_rng();
         // polynomial coefficients
         let mut _a: Vec<Fr> = Vec::new();
         for _i in 0.._k {
@@ -145,47 +202,68 @@ pub fn calc_pruned(_attr: &Vec<String>, _json: &PolicyValue, _type: Option<Polic
     match _json {
         PolicyValue::Object(obj) => {
             match obj.0 {
-                PolicyType::And => calc_pruned(_attr, &obj.1.as_ref(), Some(PolicyType::And)),
-                PolicyType::Or => calc_pruned(_attr, &obj.1.as_ref(), Some(PolicyType::Or)),
+                PolicyType::And => calc_pruned(_attr, obj.1.as_ref(), Some(PolicyType::And)),
+                PolicyType::Or => calc_pruned(_attr, obj.1.as_ref(), Some(PolicyType::Or)),
+                PolicyType::Threshold(k) => calc_pruned(_attr, obj.1.as_ref(), Some(PolicyType::Threshold(k))),
+                // a negated attribute is satisfied if the attribute is missing
+                PolicyType::Not => {
+                    if contains(_attr, &get_value(&obj.1)) {
+                        Ok((false, _emtpy_list))
+                    } else {
+                        Ok((true, vec![negated_label(&obj.1)]))
+                    }
+                },
                 _ => calc_pruned(_attr, &obj.1.as_ref(), Some(PolicyType::Leaf)),
             }
         },
         PolicyValue::Array(children) => {
             let len = children.len();
+            if len == 0 {
+                return Err(RabeError::new("Error in calc_pruned: Invalid policy (gate without children)."));
+            }
             match _type {
                 Some(PolicyType::And) => {
                     let mut _match: bool = true;
-                    if len >= 2 {
-                        for _i in 0usize..len {
-                            let (_found, mut _list) = calc_pruned(_attr, &children[_i], None).unwrap();
-                            _match = _match && _found;
-                            if _match {
-                                _emtpy_list.append(&mut _list);
-                            }
+                    for _child in children.iter() {
+                        let (_found, mut _list) = calc_pruned(_attr, _child, None)?;
+                        _match = _match && _found;
+                        if _match {
+                            _emtpy_list.append(&mut _list);
                         }
-                    } else {
-                        panic!("Error: Invalid policy (AND with just a single child).");
                     }
                     if !_match {
                         _emtpy_list = Vec::new();
                     }
-                    return Ok((_match, _emtpy_list));
+                    Ok((_match, _emtpy_list))
                 },
                 Some(PolicyType::Or) => {
                     let mut _match: bool = false;
-                    if len >= 2 {
-                        for _i in 0usize..len {
-                            let (_found, mut _list) = calc_pruned(_attr, &children[_i], None).unwrap();
-                            _match = _match || _found;
-                            if _match {
-                                _emtpy_list.append(&mut _list);
-                                break;
-                            }
+                    for _child in children.iter() {
+                        let (_found, mut _list) = calc_pruned(_attr, _child, None)?;
+                        _match = _match || _found;
+                        if _match {
+                            _emtpy_list.append(&mut _list);
+                            break;
                         }
-                        return Ok((_match, _emtpy_list));
-                    } else {
-                        panic!("Error: Invalid policy (OR with just a single child).")
                     }
+                    Ok((_match, _emtpy_list))
+                },
+                Some(PolicyType::Threshold(k)) => {
+                    let mut _found_count = 0;
+                    for _child in children.iter() {
+                        if _found_count == k {
+                            break;
+                        }
+                        let (_found, mut _list) = calc_pruned(_attr, _child, None)?;
+                        if _found {
+                            _found_count += 1;
+                            _emtpy_list.append(&mut _list);
+                        }
+                    }
+                    if _found_count < k {
+                        _emtpy_list = Vec::new();
+                    }
+                    Ok((_found_count >= k, _emtpy_list))
                 },
                 _ => Err(RabeError::new("Error in calc_pruned: unknown array type!")),
 
@@ -204,7 +282,7 @@ pub fn calc_pruned(_attr: &Vec<String>, _json: &PolicyValue, _type: Option<Polic
 #[allow(dead_code)]
 pub fn recover_secret(_shares: Vec<Fr>, _policy: &String) -> Fr {
     let policy = parse(_policy, PolicyLanguage::JsonPolicy).unwrap();
-    let _coeff = calc_coefficients(&policy, None, None).unwrap();
+    let _coeff = calc_coefficients(&policy, None, None, None).unwrap();
     let mut _secret = Fr::zero();
     for _i in 0usize.._shares.len() {
         _secret = _secret + (_coeff[_i].1 * _shares[_i]);
@@ -252,7 +330,7 @@ mod tests {
         match parse(&_policy, PolicyLanguage::JsonPolicy) {
             Ok(pol) => {
                 let _shares = gen_shares_policy(_secret, &pol, None).unwrap();
-                let _coeff = calc_coefficients(&pol, Some(Fr::one()), None).unwrap();
+                let _coeff = calc_coefficients(&pol, Some(Fr::one()), None, None).unwrap();
                 assert_eq!(_coeff.len(), _shares.len());
             },
             Err(e) => println!("test_gen_shares_json: could not parse policy {}", e)
@@ -281,6 +359,28 @@ mod tests {
         assert!(_k == _reconstruct);
     }
 
+    #[test]
+    fn test_secret_sharing_threshold() {
+        let mut _rng = rand::thread_rng();
+        let _secret:Fr = _rng.gen();
+        let pol = parse(r#"2 of ("A", "B", "C" and "D")"#, PolicyLanguage::HumanPolicy).unwrap();
+        let _shares = gen_shares_policy(_secret, &pol, None).unwrap();
+        let _attributes = vec![String::from("A"), String::from("C"), String::from("D")];
+        let (_match, _list) = calc_pruned(&_attributes, &pol, None).unwrap();
+        assert!(_match);
+        assert!(_list == vec!["A".to_string(), "C".to_string(), "D".to_string()]);
+        let _coeff = calc_coefficients(&pol, None, None, Some(&_attributes)).unwrap();
+        let mut _reconstruct = Fr::zero();
+        for (_attr, _c) in _coeff.iter() {
+            let _share = _shares.iter().find(|(_name, _)| _name == _attr).unwrap().1;
+            _reconstruct = _reconstruct + (*_c * _share);
+        }
+        assert!(_secret == _reconstruct);
+        let (_match, _list) = calc_pruned(&vec![String::from("B"), String::from("C")], &pol, None).unwrap();
+        assert!(!_match);
+        assert!(_list.is_empty());
+    }
+
     #[test]
     fn test_pruning() {
         // a set of two attributes
@@ -320,4 +420,24 @@ mod tests {
         assert_eq!(_match3, false);
         assert_eq!(_list3.is_empty(), true);
     }
+
+    #[test]
+    fn test_secret_sharing_single() {
+        let mut _rng = rand::thread_rng();
+        let _secret:Fr = _rng.gen();
+        let _attributes = vec![String::from("admin")];
+        for _policy in [r#"{"name": "admin"}"#, r#"{"name": "and", "children": [{"name": "admin"}]}"#, r#"{"name": "or", "children": [{"name": "admin"}]}"#] {
+            let pol = parse(_policy, PolicyLanguage::JsonPolicy).unwrap();
+            let _shares = gen_shares_policy(_secret, &pol, None).unwrap();
+            let (_match, _list) = calc_pruned(&_attributes, &pol, None).unwrap();
+            assert!(_match);
+            assert!(_list == _attributes);
+            let _coeff = calc_coefficients(&pol, None, None, Some(&_attributes)).unwrap();
+            assert!(_coeff[0].1 * _shares[0].1 == _secret);
+            assert!(!calc_pruned(&vec![String::from("B")], &pol, None).unwrap().0);
+        }
+        let empty = parse(r#"{"name": "and", "children": []}"#, PolicyLanguage::JsonPolicy).unwrap();
+        assert!(gen_shares_policy(_secret, &empty, None).is_none());
+        assert!(calc_pruned(&_attributes, &empty, None).is_err());
+    }
 }
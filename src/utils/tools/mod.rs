@@ -11,8 +11,13 @@ pub fn usize_to_fr(_i: usize) -> Fr {
     return Fr::from_str(&_i.to_string()).unwrap();
 }
 
-pub fn contains(data: &Vec<String>, value: &String) -> bool {
-    let len = data.into_iter()
+pub fn i64_to_fr(_i: i64) -> Fr {
+    let _abs = Fr::from_str(&_i.abs().to_string()).unwrap();
+    if _i < 0 { -_abs } else { _abs }
+}
+
+pub fn contains(data: &[String], value: &String) -> bool {
+    let len = data.iter()
         .filter(|&i| i == value)
         .collect::<Vec<_>>()
         .len();
@@ -29,18 +34,21 @@ pub fn is_subset(_subset: &Vec<String>, _attr: &Vec<String>) -> bool {
 // used to traverse / check policy tree
 pub fn traverse_policy(_attr: &Vec<String>, _json: &PolicyValue, _type: PolicyType) -> bool {
     return (_attr.len() > 0) && match _json {
-        PolicyValue::String(val) => (&_attr).into_iter().any(|x| x == val),
+        PolicyValue::String(val) => _attr.iter().any(|x| x == val),
         PolicyValue::Object(obj) => {
             return match obj.0 {
-                PolicyType::And => traverse_policy(_attr, &obj.1.as_ref(), PolicyType::And),
-                PolicyType::Or => traverse_policy(_attr, &obj.1.as_ref(), PolicyType::Or),
+                PolicyType::And => traverse_policy(_attr, obj.1.as_ref(), PolicyType::And),
+                PolicyType::Or => traverse_policy(_attr, obj.1.as_ref(), PolicyType::Or),
+                PolicyType::Threshold(k) => traverse_policy(_attr, obj.1.as_ref(), PolicyType::Threshold(k)),
+                PolicyType::Not => !traverse_policy(_attr, obj.1.as_ref(), PolicyType::Leaf),
                 _ => true,
             }
         },
         PolicyValue::Array(arrayref) => {
             return match _type {
                 PolicyType::And => {
-                    let mut ret = true;
+                    // a gate without children is never satisfied
+                    let mut ret = !arrayref.is_empty();
                     for obj in arrayref.iter() {
                         ret &= traverse_policy(_attr, obj, PolicyType::Leaf)
                     }
@@ -53,7 +61,12 @@ pub fn traverse_policy(_attr: &Vec<String>, _json: &PolicyValue, _type: PolicyTy
                     }
                     ret
                 }
-                PolicyType::Leaf => false
+                PolicyType::Threshold(k) => {
+                    arrayref.iter()
+                        .filter(|obj| traverse_policy(_attr, obj, PolicyType::Leaf))
+                        .count() >= k
+                },
+                PolicyType::Leaf | PolicyType::Not => false
             };
         }
     };
@@ -0,0 +1,341 @@
+//! Validation of keys and ciphertexts that were deserialized from untrusted input.
+//!
+//! Neither serde nor the wire format know the invariants of a scheme: a vector may have the
+//! wrong number of elements, a group element may be the identity or, when deserialized with
+//! serde, not even a point of the curve. Every key and ciphertext type implements `Validate`,
+//! which is run automatically when it is deserialized with serde or decoded with
+//! `WireFormat::from_bytes()`, so the algorithms of the schemes can rely on well-formed input
+//! instead of panicking on it.
+//!
+//! A failed validation is reported as a `RabeError` carrying a `ValidationError`, see
+//! `RabeError::validation_error()`.
+use rabe_bn::{Fr, G1, G2, Gt, Group};
+use serde::de;
+use std::fmt;
+use utils::{
+    fingerprint::FINGERPRINT_LENGTH,
+    hash::{g1_is_valid, g2_is_valid, gt_is_valid, fr_is_reduced},
+    policy::pest::{parse, PolicyLanguage}
+};
+use RabeError;
+
+/// A key or ciphertext whose invariants can be checked after it was deserialized
+pub trait Validate {
+    /// Checks the arities of all vectors, that all group elements are valid and not the
+    /// identity, that attribute names are unique and that policies parse
+    fn validate(&self) -> Result<(), RabeError>;
+}
+
+/// The reason a key or ciphertext failed validation. Every variant names the offending field.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub enum ValidationError {
+    /// A vector has the wrong number of elements
+    Arity { _field: String, _expected: usize, _found: usize },
+    /// A group element is the identity, or a scalar that is inverted is zero
+    Identity { _field: String },
+    /// A group element is not on the curve or not in the subgroup of order r, or a scalar is
+    /// not reduced
+    NotInGroup { _field: String },
+    /// An attribute occurs more than once
+    DuplicateAttribute { _field: String, _attribute: String },
+    /// A policy does not parse
+    Policy { _field: String, _reason: String },
+    /// Fields that contradict each other
+    Inconsistent { _field: String, _reason: String },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Arity { _field, _expected, _found } =>
+                write!(f, "Validation Error in {}: expected {} elements, found {}", _field, _expected, _found),
+            ValidationError::Identity { _field } =>
+                write!(f, "Validation Error in {}: unexpected identity element", _field),
+            ValidationError::NotInGroup { _field } =>
+                write!(f, "Validation Error in {}: not an element of the group", _field),
+            ValidationError::DuplicateAttribute { _field, _attribute } =>
+                write!(f, "Validation Error in {}: duplicate attribute {}", _field, _attribute),
+            ValidationError::Policy { _field, _reason } =>
+                write!(f, "Validation Error in {}: invalid policy: {}", _field, _reason),
+            ValidationError::Inconsistent { _field, _reason } =>
+                write!(f, "Validation Error in {}: {}", _field, _reason),
+        }
+    }
+}
+
+/// Validates a value deserialized by the derived serde implementation. Used by the
+/// Deserialize implementations of the keys and ciphertexts, which are derived with
+/// `#[serde(remote = "Self")]` and wrapped.
+///
+/// # Arguments
+///
+///	* `_value` - The deserialized value
+///
+pub fn validated<T: Validate, E: de::Error>(_value: T) -> Result<T, E> {
+    match _value.validate() {
+        Ok(()) => Ok(_value),
+        Err(_error) => Err(E::custom(_error)),
+    }
+}
+
+/// Checks that a point of G1 is valid and not the identity
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_g` - The point to check
+///
+pub fn check_g1(_field: &str, _g: &G1) -> Result<(), ValidationError> {
+    if !g1_is_valid(_g) {
+        return Err(ValidationError::NotInGroup { _field: _field.to_string() });
+    }
+    if _g.is_zero() {
+        return Err(ValidationError::Identity { _field: _field.to_string() });
+    }
+    Ok(())
+}
+
+/// Checks that a point of G2 is valid and not the identity
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_g` - The point to check
+///
+pub fn check_g2(_field: &str, _g: &G2) -> Result<(), ValidationError> {
+    if !g2_is_valid(_g) {
+        return Err(ValidationError::NotInGroup { _field: _field.to_string() });
+    }
+    if _g.is_zero() {
+        return Err(ValidationError::Identity { _field: _field.to_string() });
+    }
+    Ok(())
+}
+
+/// Checks that an element of Gt is valid and not the identity
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_gt` - The element to check
+///
+pub fn check_gt(_field: &str, _gt: &Gt) -> Result<(), ValidationError> {
+    if !gt_is_valid(_gt) {
+        return Err(ValidationError::NotInGroup { _field: _field.to_string() });
+    }
+    if *_gt == Gt::one() {
+        return Err(ValidationError::Identity { _field: _field.to_string() });
+    }
+    Ok(())
+}
+
+/// Checks that an element of Fr is reduced
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_fr` - The element to check
+///
+pub fn check_fr(_field: &str, _fr: &Fr) -> Result<(), ValidationError> {
+    if !fr_is_reduced(_fr) {
+        return Err(ValidationError::NotInGroup { _field: _field.to_string() });
+    }
+    Ok(())
+}
+
+/// Checks that an element of Fr, which a scheme inverts, is reduced and not zero
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_fr` - The element to check
+///
+pub fn check_fr_invertible(_field: &str, _fr: &Fr) -> Result<(), ValidationError> {
+    check_fr(_field, _fr)?;
+    if _fr.is_zero() {
+        return Err(ValidationError::Identity { _field: _field.to_string() });
+    }
+    Ok(())
+}
+
+/// Checks the number of elements of a vector
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_found` - The number of elements of the vector
+///	* `_expected` - The number of elements the vector must have
+///
+pub fn check_arity(_field: &str, _found: usize, _expected: usize) -> Result<(), ValidationError> {
+    if _found != _expected {
+        return Err(ValidationError::Arity { _field: _field.to_string(), _expected, _found });
+    }
+    Ok(())
+}
+
+/// Checks that a recorded issuer is empty (unknown) or a fingerprint
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_issuer` - The recorded issuer
+///
+pub fn check_issuer_length(_field: &str, _issuer: &[u8]) -> Result<(), ValidationError> {
+    if _issuer.is_empty() {
+        return Ok(());
+    }
+    check_arity(_field, _issuer.len(), FINGERPRINT_LENGTH)
+}
+
+/// Checks that no attribute occurs twice
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_attributes` - The attribute names
+///
+pub fn check_unique<'a, I: IntoIterator<Item = &'a String>>(_field: &str, _attributes: I) -> Result<(), ValidationError> {
+    let mut _seen: Vec<&String> = Vec::new();
+    for _attribute in _attributes {
+        if _seen.contains(&_attribute) {
+            return Err(ValidationError::DuplicateAttribute { _field: _field.to_string(), _attribute: _attribute.to_string() });
+        }
+        _seen.push(_attribute);
+    }
+    Ok(())
+}
+
+/// Checks that a policy parses
+///
+/// # Arguments
+///
+///	* `_field` - The name of the field, used in the error
+///	* `_policy` - The policy and its language
+///
+pub fn check_policy(_field: &str, _policy: &(String, PolicyLanguage)) -> Result<(), ValidationError> {
+    match parse(&_policy.0, _policy.1) {
+        Ok(_) => Ok(()),
+        Err(_error) => Err(ValidationError::Policy { _field: _field.to_string(), _reason: _error.to_string() }),
+    }
+}
+
+/// Implements `Validate` for a struct by running the listed checks in order, each one named
+/// after its field. A field may be listed more than once.
+///
+/// * `check_g1` (or any other function taking the name and the field) is called with the field
+/// * `[check_g1]` is called with every element, `[check_g1; n]` also checks that there are n
+/// * `unique(_str)` checks that the `_str` of all elements are unique, see check_unique()
+/// * `each` validates every element, `validate` the field itself
+///
+/// Checks that do not fit a single field follow in a block, with the object bound to the
+/// given name.
+///
+/// ```ignore
+/// impl_validate!(CpAbeSecretKey {
+///     _d: check_g2,
+///     _d_j: unique(_str),
+///     _d_j: each,
+///     _issuer: check_issuer_length,
+/// });
+/// impl_validate!(Ac17KpSecretKey { _policy: check_policy, _sk: validate } |_self| {
+///     check_arity("_k_p", _self._sk._k_p.len(), 0)?;
+/// });
+/// ```
+macro_rules! impl_validate {
+    ($type:ident { $($field:ident: $check:tt $(($($args:tt)*))?),* $(,)? } $(|$this:ident| $extra:block)?) => {
+        impl $crate::utils::validate::Validate for $type {
+            fn validate(&self) -> Result<(), $crate::RabeError> {
+                $(impl_validate!(@check self, $field, $check $(($($args)*))?);)*
+                $(
+                    let $this = self;
+                    $extra
+                )?
+                Ok(())
+            }
+        }
+    };
+    (@check $self:ident, $field:ident, each) => {
+        for _element in $self.$field.iter() {
+            $crate::utils::validate::Validate::validate(_element)?;
+        }
+    };
+    (@check $self:ident, $field:ident, validate) => {
+        $crate::utils::validate::Validate::validate(&$self.$field)?;
+    };
+    (@check $self:ident, $field:ident, unique($key:tt)) => {
+        $crate::utils::validate::check_unique(stringify!($field), $self.$field.iter().map(|_element| &_element.$key))?;
+    };
+    (@check $self:ident, $field:ident, [$check:ident; $arity:expr]) => {
+        $crate::utils::validate::check_arity(stringify!($field), $self.$field.len(), $arity)?;
+        impl_validate!(@check $self, $field, [$check]);
+    };
+    (@check $self:ident, $field:ident, [$check:ident]) => {
+        for _element in $self.$field.iter() {
+            $check(stringify!($field), _element)?;
+        }
+    };
+    (@check $self:ident, $field:ident, $check:ident) => {
+        $check(stringify!($field), &$self.$field)?;
+    };
+}
+
+/// Implements `Serialize` and `Deserialize` for a struct whose serde implementations are derived
+/// with `#[serde(remote = "Self")]`, validating it after it is deserialized, see validated()
+macro_rules! impl_validated_serde {
+    ($type:ident) => {
+        impl ::serde::Serialize for $type {
+            fn serialize<S: ::serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+                $type::serialize(self, _serializer)
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $type {
+            fn deserialize<D: ::serde::Deserializer<'de>>(_deserializer: D) -> Result<$type, D::Error> {
+                $crate::utils::validate::validated($type::deserialize(_deserializer)?)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::hash::hash_to_g1;
+    use serde_json::{from_value, to_value, json};
+
+    #[test]
+    fn group_elements() {
+        let _g = hash_to_g1(b"A");
+        assert!(check_g1("_g", &_g).is_ok());
+        assert_eq!(check_g1("_g", &G1::zero()), Err(ValidationError::Identity { _field: "_g".to_string() }));
+        assert!(check_g2("_h", &G2::one()).is_ok());
+        assert!(check_gt("_e", &Gt::one()).is_err());
+        // serde accepts any coordinates, (x, x) is not on the curve
+        let mut _off = to_value(_g).unwrap();
+        _off["y"] = _off["x"].clone();
+        let _off: G1 = from_value(_off).unwrap();
+        assert_eq!(check_g1("_g", &_off), Err(ValidationError::NotInGroup { _field: "_g".to_string() }));
+        // the largest possible limbs are not a reduced scalar
+        let _unreduced: Fr = from_value(json!([u64::MAX, u64::MAX, u64::MAX, u64::MAX])).unwrap();
+        assert!(check_fr("_a", &_unreduced).is_err());
+        assert!(check_fr_invertible("_a", &Fr::zero()).is_err());
+        assert!(check_fr_invertible("_a", &Fr::one()).is_ok());
+    }
+
+    #[test]
+    fn attributes_and_policies() {
+        let _attributes = vec!["A".to_string(), "B".to_string(), "A".to_string()];
+        assert_eq!(
+            check_unique("_attr", &_attributes[..2]),
+            Ok(())
+        );
+        assert_eq!(
+            check_unique("_attr", &_attributes),
+            Err(ValidationError::DuplicateAttribute { _field: "_attr".to_string(), _attribute: "A".to_string() })
+        );
+        assert!(check_arity("_c_0", 3, 3).is_ok());
+        assert!(check_arity("_c_0", 2, 3).is_err());
+        assert!(check_policy("_policy", &(String::from(r#""A" and "B""#), PolicyLanguage::HumanPolicy)).is_ok());
+        assert!(check_policy("_policy", &(String::from(r#""A" and"#), PolicyLanguage::HumanPolicy)).is_err());
+    }
+}
@@ -0,0 +1,769 @@
+//! A compact, versioned binary encoding for keys and ciphertexts.
+//!
+//! Every object implementing `WireFormat` is encoded as a 7 byte header followed by its fields:
+//!
+//! | bytes | content                                                       |
+//! |-------|---------------------------------------------------------------|
+//! | 4     | the magic `RABE`                                              |
+//! | 1     | the format version, currently `WIRE_VERSION` (2)              |
+//! | 1     | the scheme: AC17CP=1, AC17KP=2, AW11=3, BDABE=4, BSW=5, LSW=6, MKE08=7, YCT14=8 |
+//! | 1     | the kind of object, see `WireKind`                            |
+//!
+//! The fields follow in the order they are declared in their struct, each one encoded as:
+//!
+//! * `Fr`: 32 byte big-endian integer smaller than the group order r
+//! * `G1`: 33 byte compressed point (`0x02`/`0x03` and x), see g1_to_compressed()
+//! * `G2`: 65 byte compressed point (`0x02`/`0x03`, x.c0 and x.c1), see g2_to_compressed()
+//! * `Gt`: 384 bytes, the twelve coefficients of the element, see gt_to_bytes()
+//! * `u8`: a single byte
+//! * `String` and `Vec<T>`: the number of bytes or elements as u32 big-endian, followed by them
+//! * tuples: their elements in order
+//! * `Option<T>`: `0x00` for None, `0x01` followed by the value
+//! * enums: a single byte with the index of the variant, followed by its fields:
+//!   `PolicyLanguage` JsonPolicy=0, HumanPolicy=1; `HashVersion` Legacy=0, V1=1, V2=2;
+//!   `KdfVersion` Legacy=0, Hkdf=1, HkdfAad=2; `AeadSuite` Aes256Ccm=0, Aes256Gcm=1,
+//!   ChaCha20Poly1305=2; `Yct14Type` Public=0, Private=1
+//!
+//! Version 2 appends the fingerprint of the issuer (`_issuer`, a `Vec<u8>`, see the module
+//! `fingerprint`) to every secret key, ciphertext and encapsulation. Objects of version 1 are
+//! still decoded, with an empty issuer, and are encoded as version 2.
+//!
+//! Decoding is strict: unknown versions, schemes, kinds or variants, non-canonical group
+//! elements, invalid UTF-8 and trailing bytes are rejected, so every object has exactly one
+//! encoding. Decoded objects are checked with `Validate`, see the module `validate`. The public
+//! and master keys of AC17 are shared by AC17CP and AC17KP and are tagged as AC17CP.
+//!
+//! # Examples
+//!
+//! ```
+//! use rabe::schemes::bsw::*;
+//! use rabe::utils::wire::WireFormat;
+//! let (pk, _msk) = setup();
+//! let _bytes = pk.to_bytes();
+//! assert!(CpAbePublicKey::from_bytes(&_bytes).unwrap() == pk);
+//! ```
+use rabe_bn::{Fr, G1, G2, Gt};
+use schemes::SchemeId;
+use utils::{
+    aes::{AeadSuite, KdfVersion},
+    hash::{
+        HashVersion,
+        fr_to_bytes,
+        fr_from_bytes,
+        g1_to_compressed,
+        g1_from_compressed,
+        g2_to_compressed,
+        g2_from_compressed,
+        gt_to_bytes,
+        gt_from_bytes
+    },
+    policy::pest::PolicyLanguage,
+    validate::Validate
+};
+use RabeError;
+
+/// The magic bytes every encoded object starts with
+pub const WIRE_MAGIC: &[u8; 4] = b"RABE";
+/// The current version of the wire format
+pub const WIRE_VERSION: u8 = 2;
+/// The oldest version of the wire format that can still be decoded
+pub const WIRE_MIN_VERSION: u8 = 1;
+/// The length of the header of an encoded object
+pub const WIRE_HEADER_LENGTH: usize = 7;
+
+/// The kind of object following the header
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum WireKind {
+    PublicKey = 1,
+    MasterKey = 2,
+    SecretKey = 3,
+    Ciphertext = 4,
+    Encapsulation = 5,
+    GlobalKey = 6,
+    AuthorityKey = 7,
+    PublicAttributeKey = 8,
+    SecretAttributeKey = 9,
+    PublicUserKey = 10,
+    SecretUserKey = 11,
+}
+
+impl WireKind {
+    /// All kinds of objects
+    pub fn all() -> Vec<WireKind> {
+        vec![
+            WireKind::PublicKey,
+            WireKind::MasterKey,
+            WireKind::SecretKey,
+            WireKind::Ciphertext,
+            WireKind::Encapsulation,
+            WireKind::GlobalKey,
+            WireKind::AuthorityKey,
+            WireKind::PublicAttributeKey,
+            WireKind::SecretAttributeKey,
+            WireKind::PublicUserKey,
+            WireKind::SecretUserKey,
+        ]
+    }
+}
+
+/// The header of an encoded object
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct WireHeader {
+    pub _version: u8,
+    pub _scheme: SchemeId,
+    pub _kind: WireKind,
+}
+
+/// Collects the encoding of an object
+#[derive(Default)]
+pub struct Writer {
+    _bytes: Vec<u8>,
+}
+
+impl Writer {
+    /// Creates an empty Writer
+    pub fn new() -> Writer {
+        Writer { _bytes: Vec::new() }
+    }
+
+    /// Appends the encoding of a value
+    pub fn write<T: Wire>(&mut self, _value: &T) -> &mut Writer {
+        _value.encode(self);
+        self
+    }
+
+    /// Appends raw bytes without a length prefix
+    pub fn raw(&mut self, _bytes: &[u8]) -> &mut Writer {
+        self._bytes.extend_from_slice(_bytes);
+        self
+    }
+
+    /// Appends a u32 as 4 big-endian bytes
+    pub fn length(&mut self, _length: usize) -> &mut Writer {
+        let _bytes = (_length as u32).to_be_bytes();
+        self.raw(&_bytes)
+    }
+
+    /// Returns the collected bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self._bytes
+    }
+}
+
+/// Reads the encoding of an object
+pub struct Reader<'a> {
+    _bytes: &'a [u8],
+    _version: u8,
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a Reader over an encoding of the current version
+    pub fn new(_bytes: &'a [u8]) -> Reader<'a> {
+        Reader { _bytes, _version: WIRE_VERSION }
+    }
+
+    /// The format version of the encoding, as recorded in its header
+    pub fn version(&self) -> u8 {
+        self._version
+    }
+
+    /// Decodes the next value
+    pub fn read<T: Wire>(&mut self) -> Result<T, RabeError> {
+        T::decode(self)
+    }
+
+    /// Takes the next `_n` raw bytes
+    pub fn raw(&mut self, _n: usize) -> Result<&'a [u8], RabeError> {
+        if self._bytes.len() < _n {
+            return Err(RabeError::new("wire: unexpected end of input"));
+        }
+        let (_head, _tail) = self._bytes.split_at(_n);
+        self._bytes = _tail;
+        Ok(_head)
+    }
+
+    /// Reads a u32 length prefix and checks that at least that many bytes remain,
+    /// so that no element count is trusted before its data is present
+    pub fn length(&mut self) -> Result<usize, RabeError> {
+        let _raw = self.raw(4)?;
+        let _length = u32::from_be_bytes([_raw[0], _raw[1], _raw[2], _raw[3]]) as usize;
+        if _length > self._bytes.len() {
+            return Err(RabeError::new("wire: length exceeds input"));
+        }
+        Ok(_length)
+    }
+
+    /// Fails if any input is left
+    pub fn finish(&self) -> Result<(), RabeError> {
+        if self._bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(RabeError::new("wire: trailing bytes after object"))
+        }
+    }
+}
+
+/// A value with a canonical binary encoding
+pub trait Wire: Sized {
+    /// Appends the encoding of self
+    fn encode(&self, _writer: &mut Writer);
+    /// Decodes a value, failing on malformed or non-canonical input
+    fn decode(_reader: &mut Reader) -> Result<Self, RabeError>;
+}
+
+/// A top-level object, encoded with a header recording the format version, scheme and kind
+pub trait WireFormat: Wire + Validate {
+    /// The scheme recorded in the header
+    const SCHEME: SchemeId;
+    /// The kind of object recorded in the header
+    const KIND: WireKind;
+
+    /// Encodes self including the header
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut _writer = Writer::new();
+        _writer.raw(&wire_header(Self::SCHEME, Self::KIND)).write(self);
+        _writer.into_bytes()
+    }
+
+    /// Decodes an object encoded by to_bytes(), checking the header, rejecting trailing bytes
+    /// and validating the object
+    ///
+    /// # Arguments
+    ///
+    ///	* `_bytes` - The encoded object
+    ///
+    fn from_bytes(_bytes: &[u8]) -> Result<Self, RabeError> {
+        let _header = read_header(_bytes)?;
+        if _header._scheme != Self::SCHEME {
+            return Err(RabeError::new(&format!("wire: expected a {} object", Self::SCHEME)));
+        }
+        if _header._kind != Self::KIND {
+            return Err(RabeError::new(&format!("wire: expected a {:?}", Self::KIND)));
+        }
+        let mut _reader = Reader::new(&_bytes[WIRE_HEADER_LENGTH..]);
+        _reader._version = _header._version;
+        let _value: Self = _reader.read()?;
+        _reader.finish()?;
+        _value.validate()?;
+        Ok(_value)
+    }
+}
+
+/// Reads the header of an encoded object without decoding the object, e.g. to find out which
+/// scheme produced it
+///
+/// # Arguments
+///
+///	* `_bytes` - The encoded object
+///
+pub fn read_header(_bytes: &[u8]) -> Result<WireHeader, RabeError> {
+    let mut _reader = Reader::new(_bytes);
+    if _reader.raw(4)? != WIRE_MAGIC {
+        return Err(RabeError::new("wire: not a rabe object"));
+    }
+    let _header = _reader.raw(3)?;
+    if _header[0] < WIRE_MIN_VERSION || _header[0] > WIRE_VERSION {
+        return Err(RabeError::new(&format!("wire: unsupported format version {}", _header[0])));
+    }
+    let _scheme = SchemeId::all()
+        .into_iter()
+        .find(|_scheme| scheme_code(*_scheme) == _header[1])
+        .ok_or_else(|| RabeError::new(&format!("wire: unknown scheme {}", _header[1])))?;
+    let _kind = WireKind::all()
+        .into_iter()
+        .find(|_kind| *_kind as u8 == _header[2])
+        .ok_or_else(|| RabeError::new(&format!("wire: unknown kind {}", _header[2])))?;
+    Ok(WireHeader { _version: _header[0], _scheme, _kind })
+}
+
+/// The 7 byte header of an encoded object of a scheme and kind
+///
+/// # Arguments
+///
+///	* `_scheme` - The scheme of the object
+///	* `_kind` - The kind of the object
+///
+pub fn wire_header(_scheme: SchemeId, _kind: WireKind) -> Vec<u8> {
+    let mut _header = WIRE_MAGIC.to_vec();
+    _header.extend_from_slice(&[WIRE_VERSION, scheme_code(_scheme), _kind as u8]);
+    _header
+}
+
+/// The code of a scheme in the header
+fn scheme_code(_scheme: SchemeId) -> u8 {
+    match _scheme {
+        SchemeId::Ac17Cp => 1,
+        SchemeId::Ac17Kp => 2,
+        SchemeId::Aw11 => 3,
+        SchemeId::Bdabe => 4,
+        SchemeId::Bsw => 5,
+        SchemeId::Lsw => 6,
+        SchemeId::Mke08 => 7,
+        SchemeId::Yct14 => 8,
+    }
+}
+
+/// Reads the fingerprint of the issuer of a secret key, ciphertext or encapsulation, which is
+/// empty in objects of version 1
+pub fn read_issuer(_reader: &mut Reader) -> Result<Vec<u8>, RabeError> {
+    if _reader.version() < 2 {
+        return Ok(Vec::new());
+    }
+    _reader.read()
+}
+
+/// Reads a variant index, failing if it is not smaller than `_variants`
+pub fn read_variant(_reader: &mut Reader, _variants: u8) -> Result<u8, RabeError> {
+    let _variant: u8 = _reader.read()?;
+    if _variant >= _variants {
+        return Err(RabeError::new(&format!("wire: unknown variant {}", _variant)));
+    }
+    Ok(_variant)
+}
+
+/// Implements `Wire` for a struct, encoding the listed fields in order, and `WireFormat` if the
+/// scheme and the kind of the header are given. The field after the semicolon is the fingerprint
+/// of the issuer, which is read with read_issuer().
+///
+/// ```ignore
+/// impl_wire!(CpAbeSecretKey: Bsw, SecretKey { _d, _d_j, _hash; _issuer });
+/// impl_wire!(CpAbeAttribute { _str, _g1, _g2 });
+/// ```
+macro_rules! impl_wire {
+    ($type:ident: $scheme:ident, $kind:ident { $($fields:tt)* }) => {
+        impl_wire!($type { $($fields)* });
+
+        impl $crate::utils::wire::WireFormat for $type {
+            const SCHEME: $crate::schemes::SchemeId = $crate::schemes::SchemeId::$scheme;
+            const KIND: $crate::utils::wire::WireKind = $crate::utils::wire::WireKind::$kind;
+        }
+    };
+    ($type:ident { $($field:ident),+ $(; $issuer:ident)? }) => {
+        impl $crate::utils::wire::Wire for $type {
+            fn encode(&self, _writer: &mut $crate::utils::wire::Writer) {
+                $(_writer.write(&self.$field);)+
+                $(_writer.write(&self.$issuer);)?
+            }
+
+            fn decode(_reader: &mut $crate::utils::wire::Reader) -> Result<$type, $crate::RabeError> {
+                Ok($type {
+                    $($field: _reader.read()?,)+
+                    $($issuer: $crate::utils::wire::read_issuer(_reader)?,)?
+                })
+            }
+        }
+    };
+}
+
+impl Wire for u8 {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.raw(&[*self]);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<u8, RabeError> {
+        Ok(_reader.raw(1)?[0])
+    }
+}
+
+impl Wire for Fr {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.raw(&fr_to_bytes(self));
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<Fr, RabeError> {
+        fr_from_bytes(_reader.raw(32)?).ok_or_else(|| RabeError::new("wire: invalid scalar"))
+    }
+}
+
+impl Wire for G1 {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.raw(&g1_to_compressed(self));
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<G1, RabeError> {
+        g1_from_compressed(_reader.raw(33)?).ok_or_else(|| RabeError::new("wire: invalid G1 point"))
+    }
+}
+
+impl Wire for G2 {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.raw(&g2_to_compressed(self));
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<G2, RabeError> {
+        g2_from_compressed(_reader.raw(65)?).ok_or_else(|| RabeError::new("wire: invalid G2 point"))
+    }
+}
+
+impl Wire for Gt {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.raw(&gt_to_bytes(self));
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<Gt, RabeError> {
+        gt_from_bytes(_reader.raw(384)?).ok_or_else(|| RabeError::new("wire: invalid Gt element"))
+    }
+}
+
+impl Wire for String {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.length(self.len()).raw(self.as_bytes());
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<String, RabeError> {
+        let _length = _reader.length()?;
+        String::from_utf8(_reader.raw(_length)?.to_vec()).map_err(|_| RabeError::new("wire: invalid UTF-8 string"))
+    }
+}
+
+impl<T: Wire> Wire for Vec<T> {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.length(self.len());
+        for _value in self {
+            _writer.write(_value);
+        }
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<Vec<T>, RabeError> {
+        // every element takes at least one byte, which length() checked to be present
+        let _length = _reader.length()?;
+        let mut _values = Vec::new();
+        for _ in 0.._length {
+            _values.push(_reader.read()?);
+        }
+        Ok(_values)
+    }
+}
+
+impl<T: Wire> Wire for Option<T> {
+    fn encode(&self, _writer: &mut Writer) {
+        match self {
+            None => {
+                _writer.write(&0u8);
+            },
+            Some(_value) => {
+                _writer.write(&1u8).write(_value);
+            }
+        }
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<Option<T>, RabeError> {
+        match read_variant(_reader, 2)? {
+            0 => Ok(None),
+            _ => Ok(Some(_reader.read()?)),
+        }
+    }
+}
+
+impl<A: Wire, B: Wire> Wire for (A, B) {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.write(&self.0).write(&self.1);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<(A, B), RabeError> {
+        Ok((_reader.read()?, _reader.read()?))
+    }
+}
+
+impl<A: Wire, B: Wire, C: Wire> Wire for (A, B, C) {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.write(&self.0).write(&self.1).write(&self.2);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<(A, B, C), RabeError> {
+        Ok((_reader.read()?, _reader.read()?, _reader.read()?))
+    }
+}
+
+impl<A: Wire, B: Wire, C: Wire, D: Wire> Wire for (A, B, C, D) {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.write(&self.0).write(&self.1).write(&self.2).write(&self.3);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<(A, B, C, D), RabeError> {
+        Ok((_reader.read()?, _reader.read()?, _reader.read()?, _reader.read()?))
+    }
+}
+
+impl<A: Wire, B: Wire, C: Wire, D: Wire, E: Wire, F: Wire> Wire for (A, B, C, D, E, F) {
+    fn encode(&self, _writer: &mut Writer) {
+        _writer.write(&self.0).write(&self.1).write(&self.2).write(&self.3).write(&self.4).write(&self.5);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<(A, B, C, D, E, F), RabeError> {
+        Ok((_reader.read()?, _reader.read()?, _reader.read()?, _reader.read()?, _reader.read()?, _reader.read()?))
+    }
+}
+
+impl Wire for PolicyLanguage {
+    fn encode(&self, _writer: &mut Writer) {
+        let _variant: u8 = match self {
+            PolicyLanguage::JsonPolicy => 0,
+            PolicyLanguage::HumanPolicy => 1,
+        };
+        _writer.write(&_variant);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<PolicyLanguage, RabeError> {
+        match read_variant(_reader, 2)? {
+            0 => Ok(PolicyLanguage::JsonPolicy),
+            _ => Ok(PolicyLanguage::HumanPolicy),
+        }
+    }
+}
+
+impl Wire for HashVersion {
+    fn encode(&self, _writer: &mut Writer) {
+        let _variant: u8 = match self {
+            HashVersion::Legacy => 0,
+            HashVersion::V1 => 1,
+            HashVersion::V2 => 2,
+        };
+        _writer.write(&_variant);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<HashVersion, RabeError> {
+        match read_variant(_reader, 3)? {
+            0 => Ok(HashVersion::Legacy),
+            1 => Ok(HashVersion::V1),
+            _ => Ok(HashVersion::V2),
+        }
+    }
+}
+
+impl Wire for KdfVersion {
+    fn encode(&self, _writer: &mut Writer) {
+        let _variant: u8 = match self {
+            KdfVersion::Legacy => 0,
+            KdfVersion::Hkdf => 1,
+            KdfVersion::HkdfAad => 2,
+        };
+        _writer.write(&_variant);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<KdfVersion, RabeError> {
+        match read_variant(_reader, 3)? {
+            0 => Ok(KdfVersion::Legacy),
+            1 => Ok(KdfVersion::Hkdf),
+            _ => Ok(KdfVersion::HkdfAad),
+        }
+    }
+}
+
+impl Wire for AeadSuite {
+    fn encode(&self, _writer: &mut Writer) {
+        let _variant: u8 = match self {
+            AeadSuite::Aes256Ccm => 0,
+            AeadSuite::Aes256Gcm => 1,
+            AeadSuite::ChaCha20Poly1305 => 2,
+        };
+        _writer.write(&_variant);
+    }
+
+    fn decode(_reader: &mut Reader) -> Result<AeadSuite, RabeError> {
+        match read_variant(_reader, 3)? {
+            0 => Ok(AeadSuite::Aes256Ccm),
+            1 => Ok(AeadSuite::Aes256Gcm),
+            _ => Ok(AeadSuite::ChaCha20Poly1305),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use utils::fingerprint::Fingerprint;
+    use schemes::{
+        ac17,
+        bsw,
+        lsw,
+        yct14,
+        CpAbe,
+        KpAbe,
+        MultiAuthorityAbe,
+        ac17::{Ac17Cp, Ac17Kp},
+        aw11::Aw11,
+        bdabe::Bdabe,
+        bsw::Bsw,
+        lsw::Lsw,
+        mke08::Mke08,
+        yct14::Yct14
+    };
+
+    const GOLDEN_PLAINTEXT: &[u8] = b"dance like no one's watching, encrypt like everyone is!";
+    const GOLDEN_POLICY: &str = r#""A" and ("B" or "C")"#;
+
+    // the files of version 1 are in the golden directory itself, later versions in subdirectories
+    fn golden_path(_version: u8, _name: &str) -> PathBuf {
+        let _dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/utils/wire/golden");
+        match _version {
+            1 => _dir.join(_name),
+            _ => _dir.join(format!("v{}", _version)).join(_name),
+        }
+    }
+
+    fn golden<T: WireFormat>(_version: u8, _name: &str) -> T {
+        let _bytes = fs::read(golden_path(_version, _name)).unwrap();
+        assert_eq!(_bytes[4], _version, "{} is not of version {}", _name, _version);
+        let _value = T::from_bytes(&_bytes).unwrap();
+        // older versions are re-encoded as the current one
+        if _version == WIRE_VERSION {
+            assert_eq!(_value.to_bytes(), _bytes, "{} does not re-encode to the same bytes", _name);
+        }
+        _value
+    }
+
+    fn attributes(_names: &[&str]) -> Vec<String> {
+        _names.iter().map(|_name| _name.to_string()).collect()
+    }
+
+    fn golden_ma<S: MultiAuthorityAbe>(_scheme: S, _version: u8, _prefix: &str)
+    where S::GlobalKey: WireFormat, S::PublicAttributeKey: WireFormat, S::SecretKey: WireFormat, S::Ciphertext: WireFormat {
+        let _gk: S::GlobalKey = golden(_version, &format!("{}_gk.bin", _prefix));
+        let _sk: S::SecretKey = golden(_version, &format!("{}_sk.bin", _prefix));
+        let _ct: S::Ciphertext = golden(_version, &format!("{}_ct.bin", _prefix));
+        golden::<S::PublicAttributeKey>(_version, &format!("{}_pk.bin", _prefix));
+        assert_eq!(_scheme.decrypt(&_gk, &_sk, &_ct).unwrap(), GOLDEN_PLAINTEXT.to_vec());
+    }
+
+    fn golden_files(_version: u8) {
+        let _pt = GOLDEN_PLAINTEXT.to_vec();
+        golden::<ac17::Ac17PublicKey>(_version, "ac17_pk.bin");
+        let _sk: ac17::Ac17CpSecretKey = golden(_version, "ac17cp_sk.bin");
+        let _ct: ac17::Ac17CpCiphertext = golden(_version, "ac17cp_ct.bin");
+        assert_eq!(Ac17Cp.decrypt(&_sk, &_ct).unwrap(), _pt);
+        let _sk: ac17::Ac17KpSecretKey = golden(_version, "ac17kp_sk.bin");
+        let _ct: ac17::Ac17KpCiphertext = golden(_version, "ac17kp_ct.bin");
+        assert_eq!(Ac17Kp.decrypt(&_sk, &_ct).unwrap(), _pt);
+        golden::<bsw::CpAbePublicKey>(_version, "bsw_pk.bin");
+        let _sk: bsw::CpAbeSecretKey = golden(_version, "bsw_sk.bin");
+        let _ct: bsw::CpAbeCiphertext = golden(_version, "bsw_ct.bin");
+        assert_eq!(Bsw.decrypt(&_sk, &_ct).unwrap(), _pt);
+        golden::<lsw::KpAbePublicKey>(_version, "lsw_pk.bin");
+        let _sk: lsw::KpAbeSecretKey = golden(_version, "lsw_sk.bin");
+        let _ct: lsw::KpAbeCiphertext = golden(_version, "lsw_ct.bin");
+        assert_eq!(Lsw.decrypt(&_sk, &_ct).unwrap(), _pt);
+        golden::<yct14::Yct14AbePublicKey>(_version, "yct14_pk.bin");
+        let _sk: yct14::Yct14AbeSecretKey = golden(_version, "yct14_sk.bin");
+        let _ct: yct14::Yct14AbeCiphertext = golden(_version, "yct14_ct.bin");
+        assert_eq!(Yct14::try_new(attributes(&["A", "B", "C"])).unwrap().decrypt(&_sk, &_ct).unwrap(), _pt);
+        golden_ma(Aw11, _version, "aw11");
+        golden_ma(Bdabe, _version, "bdabe");
+        golden_ma(Mke08, _version, "mke08");
+    }
+
+    #[test]
+    fn golden_files_v1() {
+        golden_files(1);
+        // version 1 did not record the issuer
+        let _sk: bsw::CpAbeSecretKey = golden(1, "bsw_sk.bin");
+        let _ct: bsw::CpAbeCiphertext = golden(1, "bsw_ct.bin");
+        assert!(_sk._issuer.is_empty() && _ct._issuer.is_empty());
+    }
+
+    #[test]
+    fn golden_files_v2() {
+        golden_files(2);
+        let _pk: bsw::CpAbePublicKey = golden(2, "bsw_pk.bin");
+        let _sk: bsw::CpAbeSecretKey = golden(2, "bsw_sk.bin");
+        let _ct: bsw::CpAbeCiphertext = golden(2, "bsw_ct.bin");
+        assert_eq!(_sk._issuer, _pk.fingerprint());
+        assert_eq!(_ct._issuer, _pk.fingerprint());
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let _bytes = fs::read(golden_path(WIRE_VERSION, "bsw_ct.bin")).unwrap();
+        // wrong kind, scheme and version
+        assert!(bsw::CpAbeSecretKey::from_bytes(&_bytes).is_err());
+        assert!(lsw::KpAbeCiphertext::from_bytes(&_bytes).is_err());
+        let mut _version = _bytes.clone();
+        _version[4] = WIRE_VERSION + 1;
+        assert!(bsw::CpAbeCiphertext::from_bytes(&_version).is_err());
+        _version[4] = 0;
+        assert!(bsw::CpAbeCiphertext::from_bytes(&_version).is_err());
+        // truncated and trailing input
+        assert!(bsw::CpAbeCiphertext::from_bytes(&_bytes[.._bytes.len() - 1]).is_err());
+        let mut _trailing = _bytes.clone();
+        _trailing.push(0);
+        assert!(bsw::CpAbeCiphertext::from_bytes(&_trailing).is_err());
+        // a huge element count must fail without allocating
+        let mut _count = _bytes[..7].to_vec();
+        _count.extend_from_slice(&[0xff; 4]);
+        assert!(bsw::CpAbeCiphertext::from_bytes(&_count).is_err());
+    }
+
+    #[test]
+    fn header() {
+        let _bytes = fs::read(golden_path(WIRE_MIN_VERSION, "bsw_ct.bin")).unwrap();
+        assert_eq!(read_header(&_bytes).unwrap(), WireHeader { _version: WIRE_MIN_VERSION, _scheme: SchemeId::Bsw, _kind: WireKind::Ciphertext });
+        assert_eq!(read_header(&wire_header(SchemeId::Yct14, WireKind::SecretKey)).unwrap()._scheme, SchemeId::Yct14);
+        // unknown schemes and kinds, and input that is not an encoded object
+        let mut _unknown = _bytes.clone();
+        _unknown[5] = 0;
+        assert!(read_header(&_unknown).is_err());
+        _unknown[5] = _bytes[5];
+        _unknown[6] = 12;
+        assert!(read_header(&_unknown).is_err());
+        assert!(read_header(b"RABE").is_err());
+        assert!(read_header(b"{\"scheme\": \"BSW\"}").is_err());
+    }
+
+    fn write<T: WireFormat>(_name: &str, _value: &T) {
+        let _path = golden_path(WIRE_VERSION, _name);
+        fs::create_dir_all(_path.parent().unwrap()).unwrap();
+        fs::write(_path, _value.to_bytes()).unwrap();
+    }
+
+    fn write_ma<S: MultiAuthorityAbe>(_scheme: S, _prefix: &str)
+    where S::GlobalKey: WireFormat, S::PublicAttributeKey: WireFormat, S::SecretKey: WireFormat, S::Ciphertext: WireFormat {
+        let (_gk, _mk) = _scheme.setup();
+        let _aa1 = _scheme.authgen(&_gk, &_mk, "AA1", &attributes(&["AA1::A", "AA1::B", "AA1::C"])).unwrap();
+        let mut _pks = Vec::new();
+        for _attribute in &["AA1::A", "AA1::B", "AA1::C"] {
+            _pks.push(_scheme.request_attribute_pk(&_gk, &_aa1, _attribute).unwrap());
+        }
+        let mut _sk = _scheme.keygen(&_gk, &_mk, &_aa1, "bob").unwrap();
+        for _attribute in &["AA1::A", "AA1::B"] {
+            _scheme.request_attribute_sk(&_gk, &_aa1, &mut _sk, _attribute).unwrap();
+        }
+        let _policy = r#""AA1::A" and ("AA1::B" or "AA1::C")"#;
+        write(&format!("{}_gk.bin", _prefix), &_gk);
+        write(&format!("{}_pk.bin", _prefix), &_pks[0]);
+        write(&format!("{}_sk.bin", _prefix), &_sk);
+        write(&format!("{}_ct.bin", _prefix), &_scheme.encrypt(&_gk, &_pks, _policy, PolicyLanguage::HumanPolicy, GOLDEN_PLAINTEXT).unwrap());
+    }
+
+    // writes the golden files of the current version, only needed when the wire format version changes
+    #[test]
+    #[ignore]
+    fn write_golden_files() {
+        let _pt = GOLDEN_PLAINTEXT;
+        let _language = PolicyLanguage::HumanPolicy;
+        let _attributes = attributes(&["A", "B"]);
+        let (_pk, _msk) = Ac17Cp.setup();
+        write("ac17_pk.bin", &_pk);
+        write("ac17cp_sk.bin", &Ac17Cp.keygen(&_pk, &_msk, &_attributes).unwrap());
+        write("ac17cp_ct.bin", &Ac17Cp.encrypt(&_pk, GOLDEN_POLICY, _language, _pt).unwrap());
+        write("ac17kp_sk.bin", &Ac17Kp.keygen(&_pk, &_msk, GOLDEN_POLICY, _language).unwrap());
+        write("ac17kp_ct.bin", &Ac17Kp.encrypt(&_pk, &_attributes, _pt).unwrap());
+        let (_pk, _msk) = Bsw.setup();
+        write("bsw_pk.bin", &_pk);
+        write("bsw_sk.bin", &Bsw.keygen(&_pk, &_msk, &_attributes).unwrap());
+        write("bsw_ct.bin", &Bsw.encrypt(&_pk, GOLDEN_POLICY, _language, _pt).unwrap());
+        let (_pk, _msk) = Lsw.setup();
+        write("lsw_pk.bin", &_pk);
+        write("lsw_sk.bin", &Lsw.keygen(&_pk, &_msk, GOLDEN_POLICY, _language).unwrap());
+        write("lsw_ct.bin", &Lsw.encrypt(&_pk, &_attributes, _pt).unwrap());
+        let _yct14 = Yct14::try_new(attributes(&["A", "B", "C"])).unwrap();
+        let (_pk, _msk) = _yct14.setup();
+        write("yct14_pk.bin", &_pk);
+        write("yct14_sk.bin", &_yct14.keygen(&_pk, &_msk, GOLDEN_POLICY, _language).unwrap());
+        write("yct14_ct.bin", &_yct14.encrypt(&_pk, &_attributes, _pt).unwrap());
+        write_ma(Aw11, "aw11");
+        write_ma(Bdabe, "bdabe");
+        write_ma(Mke08, "mke08");
+    }
+}